@@ -0,0 +1,191 @@
+// Room-level throughput on a real rheomesh worker and router, with stand-in actors in place of
+// WebSocket sessions, so refactors of the room bookkeeping and broadcast paths can be compared with
+// numbers. No media flows, but the worker starts for real, so this needs what the server needs to run.
+// Run with `cargo bench --bench room_worker`; set INTEREST_RADIUS=0 to measure without interest filtering.
+
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use actix::{Actor, Addr, Context, Handler, SystemRunner};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use backend::config;
+use backend::streaming::codecs::media_config;
+use backend::streaming::handler::{FacialFeatures, PlayerData, PlayerPosition, Position, SendingMessage};
+use backend::streaming::room::{Room, RoomOwner};
+use backend::streaming::tick::broadcast_pending_moves;
+use backend::streaming::turn_server::IceServerCache;
+
+const ROOM_SIZES: [usize; 3] = [10, 50, 100];
+const PUBLISHERS_PER_ITERATION: usize = 1000;
+
+/// Receives room broadcasts in place of a `StreamingSession`, counting what arrives
+struct BenchPeer {
+    received: Arc<AtomicUsize>,
+}
+
+impl Actor for BenchPeer {
+    type Context = Context<Self>;
+}
+
+impl Handler<SendingMessage> for BenchPeer {
+    type Result = ();
+
+    fn handle(&mut self, msg: SendingMessage, _ctx: &mut Self::Context) -> Self::Result {
+        black_box(msg);
+        self.received.fetch_add(1, Ordering::Release);
+    }
+}
+
+fn bench_player(index: usize) -> PlayerData {
+    PlayerData {
+        id: String::new(),
+        name: format!("player-{}", index),
+        color: "#4488ff".to_string(),
+        activity: "benchmarking".to_string(),
+        facial_features: FacialFeatures::default(),
+        position: Position::default(),
+        rotation: 0.0,
+        is_moving: false,
+        language: "en".to_string(),
+        handle: None,
+        role: Default::default(),
+        status: Default::default(),
+        moderation_identity: None,
+    }
+}
+
+/// An empty room on a real router; media isn't exercised, but `Room` needs one to exist.
+/// The owner is returned too so its worker outlives the benchmark
+fn bench_room(system: &SystemRunner, room_id: &str) -> (RoomOwner<BenchPeer>, Arc<Room<BenchPeer>>) {
+    config::init().expect("default config");
+    system.block_on(async {
+        let worker = rheomesh::worker::Worker::new(rheomesh::config::WorkerConfig::default())
+            .await
+            .expect("rheomesh worker");
+        let mut owner = RoomOwner::<BenchPeer>::new(vec![worker], rheomesh::config::WorkerConfig::default(), IceServerCache::default());
+        let room = owner
+            .create_new_room(room_id.to_string(), "Bench Room".to_string(), media_config(room_id))
+            .await
+            .expect("bench room");
+        (owner, room)
+    })
+}
+
+/// Fill a room with `count` peers sharing one receive counter
+fn populate(system: &SystemRunner, room: &Room<BenchPeer>, count: usize, received: &Arc<AtomicUsize>) -> Vec<String> {
+    system.block_on(async {
+        (0..count)
+            .map(|index| {
+                let addr = BenchPeer { received: received.clone() }.start();
+                room.add_player(addr, bench_player(index))
+            })
+            .collect()
+    })
+}
+
+/// Wait until the peers have handled `expected` messages in total
+async fn wait_for(received: &AtomicUsize, expected: usize) {
+    while received.load(Ordering::Acquire) < expected {
+        tokio::task::yield_now().await;
+    }
+}
+
+/// Joining an occupied room: register the player, announce them and gather everyone for `RoomState`
+fn bench_joins(c: &mut Criterion) {
+    let system = actix::System::new();
+    let mut group = c.benchmark_group("join");
+    group.throughput(Throughput::Elements(1));
+    for size in ROOM_SIZES {
+        let (_owner, room) = bench_room(&system, &format!("join-{}", size));
+        let received = Arc::new(AtomicUsize::new(0));
+        populate(&system, &room, size, &received);
+
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, _| {
+            b.iter_custom(|iters| {
+                system.block_on(async {
+                    let mut elapsed = Duration::ZERO;
+                    for index in 0..iters {
+                        let start = Instant::now();
+                        let addr: Addr<BenchPeer> = BenchPeer { received: received.clone() }.start();
+                        let player_id = room.add_player(addr, bench_player(size + index as usize));
+                        let player = room.get_player_data(&player_id).expect("player just joined");
+                        for peer in room.get_peers(&player_id) {
+                            peer.do_send(SendingMessage::PlayerJoined { player: player.clone() });
+                        }
+                        black_box(room.get_peers_data(&player_id));
+                        elapsed += start.elapsed();
+                        room.remove_player(&player_id);
+                    }
+                    elapsed
+                })
+            });
+        });
+    }
+    group.finish();
+}
+
+/// One movement tick where every player moved: time until every peer has its `PlayersMoved`
+fn bench_movement_broadcast(c: &mut Criterion) {
+    let system = actix::System::new();
+    let mut group = c.benchmark_group("movement_broadcast");
+    for size in ROOM_SIZES {
+        let (_owner, room) = bench_room(&system, &format!("movement-{}", size));
+        let received = Arc::new(AtomicUsize::new(0));
+        let player_ids = populate(&system, &room, size, &received);
+
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, _| {
+            b.iter_custom(|iters| {
+                system.block_on(async {
+                    let mut elapsed = Duration::ZERO;
+                    for tick in 0..iters {
+                        let start = Instant::now();
+                        let expected = received.load(Ordering::Acquire) + size;
+                        for (index, player_id) in player_ids.iter().enumerate() {
+                            room.queue_move(PlayerPosition {
+                                player_id: player_id.clone(),
+                                position: Position { x: (index % 10) as f32, y: 0.0, z: (tick % 10) as f32 },
+                                rotation: 0.0,
+                                is_moving: true,
+                            });
+                        }
+                        broadcast_pending_moves(&room);
+                        wait_for(&received, expected).await;
+                        elapsed += start.elapsed();
+                    }
+                    elapsed
+                })
+            });
+        });
+    }
+    group.finish();
+}
+
+/// Publisher bookkeeping as tracks are published and unpublished
+fn bench_publisher_registration(c: &mut Criterion) {
+    let system = actix::System::new();
+    let (_owner, room) = bench_room(&system, "publishers");
+    let received = Arc::new(AtomicUsize::new(0));
+    let player_ids = populate(&system, &room, 50, &received);
+    let publisher_ids: Vec<String> = (0..PUBLISHERS_PER_ITERATION).map(|index| format!("publisher-{}", index)).collect();
+
+    let mut group = c.benchmark_group("publisher_registration");
+    group.throughput(Throughput::Elements(PUBLISHERS_PER_ITERATION as u64));
+    group.bench_function("register_unregister", |b| {
+        b.iter(|| {
+            for (index, publisher_id) in publisher_ids.iter().enumerate() {
+                room.register_publisher(publisher_id.clone(), player_ids[index % player_ids.len()].clone());
+            }
+            black_box(room.get_all_publishers());
+            for publisher_id in &publisher_ids {
+                room.unregister_publisher(publisher_id);
+            }
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_joins, bench_movement_broadcast, bench_publisher_registration);
+criterion_main!(benches);
@@ -0,0 +1,565 @@
+use std::sync::RwLock;
+use std::time::Duration;
+use actix_web::web::{self, Data};
+use actix_web::{HttpRequest, HttpResponse};
+use actix_web_actors::ws;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::api_keys::{ApiAuth, ApiKeyRecord, ApiKeyStore, Scope};
+use crate::bans::BanList;
+use crate::config::{RoomRoute, RoomRouting};
+use crate::revocations::RevocationList;
+use crate::routing::RoutingTable;
+use crate::streaming::chaos::{self, ChaosSettings};
+use crate::streaming::roles::{issue_role_token, Role, DEFAULT_ROLE_TOKEN_TTL};
+use crate::streaming::handler::{InspectTransports, Position, RevokeSession, SendingMessage};
+use crate::streaming::{BandwidthProfile, FacialFeatures, PlayerData, RoomOwner, StreamingSession};
+
+#[derive(Deserialize)]
+struct CreateKeyRequest {
+    name: String,
+    scopes: Vec<Scope>,
+}
+
+#[derive(Serialize)]
+struct CreatedKey {
+    #[serde(flatten)]
+    record: ApiKeyRecord,
+    /// Plaintext secret, only ever returned here
+    key: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AnnounceRequest {
+    message: String,
+    /// Limit the announcement to one room, otherwise every room receives it
+    room_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RoleTokenRequest {
+    role: Role,
+    /// Defaults to a day
+    ttl_secs: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RoleToken {
+    /// Join with `roleToken=<token>` to take on the role
+    token: String,
+    expires_at: i64,
+}
+
+#[derive(Deserialize)]
+struct SetRoleRequest {
+    role: Role,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BanRequest {
+    ip: Option<std::net::IpAddr>,
+    /// A join token's `sub` or PIN-verified profile ID; only matched against verified joins
+    profile_id: Option<String>,
+    reason: Option<String>,
+    /// Permanent when unset
+    duration_secs: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct KickRequest {
+    /// Shown to the players removed
+    reason: Option<String>,
+}
+
+/// A room as listed by `GET /api/admin/rooms`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RoomSummary {
+    id: String,
+    theme: String,
+    language: String,
+    player_count: usize,
+    observer_count: usize,
+    publisher_count: usize,
+    host_id: Option<String>,
+    locked: bool,
+    worker: Option<usize>,
+    worker_healthy: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PlayerSummary {
+    id: String,
+    name: String,
+    role: Role,
+    position: Position,
+    rotation: f32,
+    is_moving: bool,
+    publisher_count: usize,
+    is_host: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RevokeRequest {
+    /// Sign the whole account out
+    profile_id: Option<String>,
+    /// Or just the token with this `jti`
+    token_id: Option<String>,
+    reason: Option<String>,
+}
+
+/// Register REST endpoints for integrations and administration
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/api/admin/keys", web::get().to(list_keys))
+        .route("/api/admin/keys", web::post().to(create_key))
+        .route("/api/admin/keys/{key_id}", web::delete().to(revoke_key))
+        .route("/api/admin/routes", web::get().to(get_routes))
+        .route("/api/admin/routes", web::put().to(replace_routes))
+        .route("/api/admin/routes/{route_id}", web::put().to(upsert_route))
+        .route("/api/admin/routes/{route_id}", web::delete().to(delete_route))
+        .route("/api/admin/revoke", web::get().to(list_revocations))
+        .route("/api/admin/revoke", web::post().to(revoke_profile))
+        .route("/api/admin/revoke/{profile_id}", web::delete().to(restore_profile))
+        .route("/api/admin/revoke/tokens/{token_id}", web::delete().to(restore_token))
+        .route("/api/admin/bans", web::get().to(list_bans))
+        .route("/api/admin/bans", web::post().to(add_ban))
+        .route("/api/admin/bans/{ban_id}", web::delete().to(remove_ban))
+        .route("/api/admin/rooms", web::get().to(list_rooms))
+        .route("/api/admin/rooms/{room_id}", web::delete().to(close_room))
+        .route("/api/admin/rooms/{room_id}/kick/{player_id}", web::post().to(kick_player))
+        .route("/api/admin/rooms/{room_id}/players", web::get().to(list_room_players))
+        .route("/api/admin/rooms/{room_id}/players/{player_id}/transports", web::get().to(inspect_transports))
+        .route("/api/admin/rooms/{room_id}/observe", web::get().to(observe_room))
+        .route("/api/admin/rooms/{room_id}/players/{player_id}/role", web::put().to(set_player_role))
+        .route("/api/admin/role-tokens", web::post().to(create_role_token))
+        .route("/api/admin/chaos", web::get().to(get_chaos))
+        .route("/api/admin/chaos", web::put().to(set_chaos))
+        .route("/api/announce", web::post().to(announce));
+}
+
+async fn list_keys(auth: ApiAuth, store: Data<RwLock<ApiKeyStore>>) -> actix_web::Result<HttpResponse> {
+    auth.require_admin()?;
+    Ok(HttpResponse::Ok().json(store.read().unwrap().list()))
+}
+
+async fn create_key(
+    auth: ApiAuth,
+    store: Data<RwLock<ApiKeyStore>>,
+    body: web::Json<CreateKeyRequest>,
+) -> actix_web::Result<HttpResponse> {
+    auth.require_admin()?;
+    let CreateKeyRequest { name, scopes } = body.into_inner();
+    if scopes.is_empty() {
+        return Ok(HttpResponse::BadRequest().body("at least one scope is required"));
+    }
+
+    let (record, key) = store.write().unwrap().create(name, scopes);
+    Ok(HttpResponse::Created().json(CreatedKey {
+        record: record.redacted(),
+        key,
+    }))
+}
+
+async fn revoke_key(
+    auth: ApiAuth,
+    store: Data<RwLock<ApiKeyStore>>,
+    key_id: web::Path<String>,
+) -> actix_web::Result<HttpResponse> {
+    auth.require_admin()?;
+    if store.write().unwrap().revoke(&key_id) {
+        Ok(HttpResponse::NoContent().finish())
+    } else {
+        Ok(HttpResponse::NotFound().finish())
+    }
+}
+
+async fn get_routes(auth: ApiAuth, routing: Data<RwLock<RoutingTable>>) -> actix_web::Result<HttpResponse> {
+    auth.require_admin()?;
+    Ok(HttpResponse::Ok().json(routing.read().unwrap().routing()))
+}
+
+/// Swap the whole activity routing table; takes effect for the next join
+async fn replace_routes(
+    auth: ApiAuth,
+    routing: Data<RwLock<RoutingTable>>,
+    body: web::Json<RoomRouting>,
+) -> actix_web::Result<HttpResponse> {
+    auth.require_admin()?;
+    apply_routes(&mut routing.write().unwrap(), body.into_inner())
+}
+
+/// Add a route, or replace the one with the same ID in place
+async fn upsert_route(
+    auth: ApiAuth,
+    routing: Data<RwLock<RoutingTable>>,
+    route_id: web::Path<String>,
+    body: web::Json<RoomRoute>,
+) -> actix_web::Result<HttpResponse> {
+    auth.require_admin()?;
+    let mut route = body.into_inner();
+    route.id = route_id.into_inner();
+    let mut routing = routing.write().unwrap();
+    let mut updated = routing.routing().clone();
+    match updated.routes.iter_mut().find(|existing| existing.id == route.id) {
+        Some(existing) => *existing = route,
+        None => updated.routes.push(route),
+    }
+    apply_routes(&mut routing, updated)
+}
+
+async fn delete_route(
+    auth: ApiAuth,
+    routing: Data<RwLock<RoutingTable>>,
+    route_id: web::Path<String>,
+) -> actix_web::Result<HttpResponse> {
+    auth.require_admin()?;
+    let mut routing = routing.write().unwrap();
+    let mut updated = routing.routing().clone();
+    let before = updated.routes.len();
+    updated.routes.retain(|route| route.id != *route_id);
+    if updated.routes.len() == before {
+        return Ok(HttpResponse::NotFound().finish());
+    }
+    apply_routes(&mut routing, updated)
+}
+
+fn apply_routes(routing: &mut RoutingTable, updated: RoomRouting) -> actix_web::Result<HttpResponse> {
+    match routing.replace(updated) {
+        Ok(()) => {
+            tracing::info!("🧭 Room routing updated ({} routes)", routing.routing().routes.len());
+            Ok(HttpResponse::Ok().json(routing.routing()))
+        }
+        Err(e) => Ok(HttpResponse::BadRequest().body(e)),
+    }
+}
+
+async fn list_revocations(auth: ApiAuth, revocations: Data<RwLock<RevocationList>>) -> actix_web::Result<HttpResponse> {
+    auth.require_admin()?;
+    Ok(HttpResponse::Ok().json(revocations.read().unwrap().list()))
+}
+
+/// Revoke a compromised account or token: new joins are refused and its live sessions in every
+/// room are closed now. Other nodes sharing the revocations file close theirs on their next periodic check
+async fn revoke_profile(
+    auth: ApiAuth,
+    revocations: Data<RwLock<RevocationList>>,
+    room_owner: Data<Mutex<RoomOwner<StreamingSession>>>,
+    body: web::Json<RevokeRequest>,
+) -> actix_web::Result<HttpResponse> {
+    auth.require_admin()?;
+    let RevokeRequest { profile_id, token_id, reason } = body.into_inner();
+    let revoke = match (profile_id.filter(|id| !id.is_empty()), token_id.filter(|id| !id.is_empty())) {
+        (Some(profile_id), None) => {
+            revocations.write().unwrap().revoke(&profile_id, reason);
+            RevokeSession::Account(profile_id)
+        }
+        (None, Some(token_id)) => {
+            revocations.write().unwrap().revoke_token(&token_id, reason);
+            RevokeSession::Token(token_id)
+        }
+        _ => return Ok(HttpResponse::BadRequest().body("exactly one of profileId or tokenId is required")),
+    };
+
+    // Sessions only know their own identity, so each one decides whether it's affected
+    let rooms = room_owner.lock().await.list_rooms();
+    for room in &rooms {
+        for addr in room.get_all_addrs() {
+            addr.do_send(revoke.clone());
+        }
+    }
+    tracing::info!("🚫 Revoked a profile or token, checked sessions in {} room(s)", rooms.len());
+    Ok(HttpResponse::NoContent().finish())
+}
+
+async fn restore_profile(
+    auth: ApiAuth,
+    revocations: Data<RwLock<RevocationList>>,
+    profile_id: web::Path<String>,
+) -> actix_web::Result<HttpResponse> {
+    auth.require_admin()?;
+    if revocations.write().unwrap().restore(&profile_id) {
+        Ok(HttpResponse::NoContent().finish())
+    } else {
+        Ok(HttpResponse::NotFound().finish())
+    }
+}
+
+async fn restore_token(
+    auth: ApiAuth,
+    revocations: Data<RwLock<RevocationList>>,
+    token_id: web::Path<String>,
+) -> actix_web::Result<HttpResponse> {
+    auth.require_admin()?;
+    if revocations.write().unwrap().restore_token(&token_id) {
+        Ok(HttpResponse::NoContent().finish())
+    } else {
+        Ok(HttpResponse::NotFound().finish())
+    }
+}
+
+async fn list_bans(auth: ApiAuth, bans: Data<RwLock<BanList>>) -> actix_web::Result<HttpResponse> {
+    auth.require(Scope::ManageBans)?;
+    Ok(HttpResponse::Ok().json(bans.read().unwrap().list()))
+}
+
+/// Ban an address and/or player identity from joining; players already connected stay until kicked
+async fn add_ban(auth: ApiAuth, bans: Data<RwLock<BanList>>, body: web::Json<BanRequest>) -> actix_web::Result<HttpResponse> {
+    auth.require(Scope::ManageBans)?;
+    let BanRequest { ip, profile_id, reason, duration_secs } = body.into_inner();
+    let profile_id = profile_id.filter(|profile_id| !profile_id.is_empty());
+    if ip.is_none() && profile_id.is_none() {
+        return Ok(HttpResponse::BadRequest().body("ip or profileId is required"));
+    }
+    let ban = bans.write().unwrap().add(ip, profile_id, reason, duration_secs.map(Duration::from_secs));
+    Ok(HttpResponse::Created().json(ban))
+}
+
+async fn remove_ban(auth: ApiAuth, bans: Data<RwLock<BanList>>, ban_id: web::Path<String>) -> actix_web::Result<HttpResponse> {
+    auth.require(Scope::ManageBans)?;
+    if bans.write().unwrap().remove(&ban_id) {
+        Ok(HttpResponse::NoContent().finish())
+    } else {
+        Ok(HttpResponse::NotFound().finish())
+    }
+}
+
+async fn list_rooms(auth: ApiAuth, room_owner: Data<Mutex<RoomOwner<StreamingSession>>>) -> actix_web::Result<HttpResponse> {
+    auth.require(Scope::ReadRooms)?;
+    let owner = room_owner.lock().await;
+    let mut rooms: Vec<RoomSummary> = owner
+        .list_rooms()
+        .iter()
+        .map(|room| {
+            let worker = owner.room_worker(&room.id);
+            RoomSummary {
+                id: room.id.clone(),
+                theme: room.theme.clone(),
+                language: room.language.clone(),
+                player_count: room.player_count(),
+                observer_count: room.get_observers().len(),
+                publisher_count: room.get_all_publishers().len(),
+                host_id: room.get_host_id(),
+                locked: room.is_locked(),
+                worker: worker.map(|(index, _)| index),
+                worker_healthy: worker.is_none_or(|(_, healthy)| healthy),
+            }
+        })
+        .collect();
+    rooms.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(HttpResponse::Ok().json(rooms))
+}
+
+async fn list_room_players(
+    auth: ApiAuth,
+    room_owner: Data<Mutex<RoomOwner<StreamingSession>>>,
+    room_id: web::Path<String>,
+) -> actix_web::Result<HttpResponse> {
+    auth.require(Scope::ReadRooms)?;
+    let Some(room) = room_owner.lock().await.find_by_id(room_id.into_inner()) else {
+        return Ok(HttpResponse::NotFound().body("no such room"));
+    };
+    let publishers = room.get_all_publishers();
+    let players: Vec<PlayerSummary> = room
+        .get_all_players()
+        .into_iter()
+        .map(|player| PlayerSummary {
+            publisher_count: publishers.iter().filter(|(_, owner)| *owner == player.id).count(),
+            is_host: room.is_host(&player.id),
+            id: player.id,
+            name: player.name,
+            role: player.role,
+            position: player.position,
+            rotation: player.rotation,
+            is_moving: player.is_moving,
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(players))
+}
+
+/// Publishers, subscribers and connection health as the player's session sees them
+async fn inspect_transports(
+    auth: ApiAuth,
+    room_owner: Data<Mutex<RoomOwner<StreamingSession>>>,
+    path: web::Path<(String, String)>,
+) -> actix_web::Result<HttpResponse> {
+    auth.require(Scope::ReadRooms)?;
+    let (room_id, player_id) = path.into_inner();
+    let Some(addr) = room_owner.lock().await.find_by_id(room_id).and_then(|room| room.get_addr(&player_id)) else {
+        return Ok(HttpResponse::NotFound().body("no such player"));
+    };
+    match addr.send(InspectTransports).await {
+        Ok(state) => Ok(HttpResponse::Ok().json(state)),
+        // The session stopped between the lookup and the request
+        Err(_) => Ok(HttpResponse::NotFound().body("no such player")),
+    }
+}
+
+/// Disconnect everyone in a room and take it out of service; the next join starts a fresh one
+async fn close_room(
+    auth: ApiAuth,
+    room_owner: Data<Mutex<RoomOwner<StreamingSession>>>,
+    room_id: web::Path<String>,
+    body: Option<web::Json<KickRequest>>,
+) -> actix_web::Result<HttpResponse> {
+    auth.require_admin()?;
+    let Some(room) = room_owner.lock().await.close_room(&room_id) else {
+        return Ok(HttpResponse::NotFound().body("no such room"));
+    };
+    let reason = body
+        .and_then(|body| body.into_inner().reason)
+        .unwrap_or_else(|| "This room was closed by an administrator".to_string());
+    let sessions = room.get_all_addrs();
+    for addr in &sessions {
+        addr.do_send(SendingMessage::Kicked { reason: reason.clone() });
+    }
+    tracing::warn!("🚪 Admin closed room {} ({} sessions disconnected)", room.id, sessions.len());
+    Ok(HttpResponse::NoContent().finish())
+}
+
+async fn kick_player(
+    auth: ApiAuth,
+    room_owner: Data<Mutex<RoomOwner<StreamingSession>>>,
+    path: web::Path<(String, String)>,
+    body: Option<web::Json<KickRequest>>,
+) -> actix_web::Result<HttpResponse> {
+    auth.require_admin()?;
+    let (room_id, player_id) = path.into_inner();
+    let Some(addr) = room_owner.lock().await.find_by_id(room_id).and_then(|room| room.get_addr(&player_id)) else {
+        return Ok(HttpResponse::NotFound().body("no such player"));
+    };
+    let reason = body
+        .and_then(|body| body.into_inner().reason)
+        .unwrap_or_else(|| "Removed by an administrator".to_string());
+    addr.do_send(SendingMessage::Kicked { reason });
+    tracing::warn!("🚪 Admin kicked player {} from room {}", player_id, room_id);
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Attach to a room over WebSocket in ghost mode: every room event arrives as it does for players
+/// and media can be subscribed to, but the observer is never shown to anyone in the room
+async fn observe_room(
+    auth: ApiAuth,
+    req: HttpRequest,
+    stream: web::Payload,
+    room_owner: Data<Mutex<RoomOwner<StreamingSession>>>,
+    room_id: web::Path<String>,
+) -> actix_web::Result<HttpResponse> {
+    auth.require_admin()?;
+    let Some(room) = room_owner.lock().await.find_by_id(room_id.into_inner()) else {
+        return Ok(HttpResponse::NotFound().body("no such room"));
+    };
+    tracing::info!("👻 Admin observing room {}", room.id);
+    let observer = PlayerData {
+        id: String::new(),
+        name: "Observer".to_string(),
+        color: "#808080".to_string(),
+        activity: String::new(),
+        facial_features: FacialFeatures::default(),
+        position: Default::default(),
+        rotation: 0.0,
+        is_moving: false,
+        language: room.language.clone(),
+        handle: None,
+        role: Role::Player,
+        status: Default::default(),
+        moderation_identity: None,
+    };
+    let ice_servers = RoomOwner::session_ice_servers(&room_owner).await;
+    let session = StreamingSession::new(room, room_owner.clone(), observer, ice_servers, BandwidthProfile::default())
+        .await
+        .as_ghost();
+    ws::start(session, &req, stream)
+}
+
+/// Sign a token that grants a role to whoever joins with it
+async fn create_role_token(auth: ApiAuth, body: web::Json<RoleTokenRequest>) -> actix_web::Result<HttpResponse> {
+    auth.require_admin()?;
+    let RoleTokenRequest { role, ttl_secs } = body.into_inner();
+    let ttl = ttl_secs.map(Duration::from_secs).unwrap_or(DEFAULT_ROLE_TOKEN_TTL);
+    let (token, expires_at) = issue_role_token(role, ttl);
+    Ok(HttpResponse::Created().json(RoleToken { token, expires_at }))
+}
+
+/// Change the role of a player who is already in a room; lasts until they leave
+async fn set_player_role(
+    auth: ApiAuth,
+    room_owner: Data<Mutex<RoomOwner<StreamingSession>>>,
+    path: web::Path<(String, String)>,
+    body: web::Json<SetRoleRequest>,
+) -> actix_web::Result<HttpResponse> {
+    auth.require_admin()?;
+    let (room_id, player_id) = path.into_inner();
+    let Some(room) = room_owner.lock().await.find_by_id(room_id) else {
+        return Ok(HttpResponse::NotFound().body("no such room"));
+    };
+    let role = body.into_inner().role;
+    if !room.set_player_role(&player_id, role) {
+        return Ok(HttpResponse::NotFound().body("no such player"));
+    }
+    tracing::info!("🛡️ Player {} in room {} is now {:?}", player_id, room.id, role);
+    for addr in room.get_all_addrs() {
+        addr.do_send(SendingMessage::RoleChanged {
+            player_id: player_id.clone(),
+            role,
+        });
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+async fn get_chaos(auth: ApiAuth) -> actix_web::Result<HttpResponse> {
+    auth.require_admin()?;
+    if !chaos::is_enabled() {
+        return Ok(HttpResponse::NotFound().body("chaos mode is off (CHAOS_MODE)"));
+    }
+    Ok(HttpResponse::Ok().json(chaos::settings()))
+}
+
+/// Set the faults chaos mode injects; all zero turns injection off again
+async fn set_chaos(auth: ApiAuth, body: web::Json<ChaosSettings>) -> actix_web::Result<HttpResponse> {
+    auth.require_admin()?;
+    if !chaos::is_enabled() {
+        return Ok(HttpResponse::NotFound().body("chaos mode is off (CHAOS_MODE)"));
+    }
+    let settings = body.into_inner();
+    if let Err(e) = settings.validate() {
+        return Ok(HttpResponse::BadRequest().body(e));
+    }
+    chaos::configure(settings.clone());
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+/// Broadcast a system message into rooms
+async fn announce(
+    auth: ApiAuth,
+    room_owner: Data<Mutex<RoomOwner<StreamingSession>>>,
+    body: web::Json<AnnounceRequest>,
+) -> actix_web::Result<HttpResponse> {
+    auth.require(Scope::Announce)?;
+    let AnnounceRequest { message, room_id } = body.into_inner();
+
+    let rooms = {
+        let owner = room_owner.lock().await;
+        match room_id {
+            Some(room_id) => owner.find_by_id(room_id).into_iter().collect(),
+            None => owner.list_rooms(),
+        }
+    };
+    if rooms.is_empty() {
+        return Ok(HttpResponse::NotFound().body("no matching rooms"));
+    }
+
+    for room in &rooms {
+        for addr in room.get_all_addrs() {
+            addr.do_send(SendingMessage::SystemMessage { message: message.clone() });
+        }
+    }
+    tracing::info!("📢 Announcement sent to {} room(s)", rooms.len());
+    Ok(HttpResponse::NoContent().finish())
+}
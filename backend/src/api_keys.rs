@@ -0,0 +1,195 @@
+use std::future::{ready, Ready};
+use std::path::PathBuf;
+use std::sync::RwLock;
+use actix_web::dev::Payload;
+use actix_web::error::{ErrorForbidden, ErrorUnauthorized};
+use actix_web::web::Data;
+use actix_web::{FromRequest, HttpRequest};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::file_writer;
+
+/// Permissions an integration key can be granted
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Scope {
+    ReadRooms,
+    Announce,
+    ManageBans,
+    Ingest,
+    Search,
+}
+
+/// A stored integration key; only the SHA-256 of the secret is kept
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyRecord {
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "String::is_empty", default)]
+    key_hash: String,
+    pub scopes: Vec<Scope>,
+    pub created_at: String,
+}
+
+impl ApiKeyRecord {
+    /// Copy without the hash, for API responses
+    pub fn redacted(&self) -> Self {
+        Self {
+            key_hash: String::new(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Who is calling a REST endpoint
+#[derive(Debug, Clone)]
+pub enum Principal {
+    /// Holder of the root `ADMIN_API_KEY`, implicitly has every scope
+    Admin,
+    Integration { key_id: String, scopes: Vec<Scope> },
+}
+
+impl Principal {
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        match self {
+            Principal::Admin => true,
+            Principal::Integration { scopes, .. } => scopes.contains(&scope),
+        }
+    }
+}
+
+/// Integration keys persisted as JSON (hashes only), plus the root admin key from env
+pub struct ApiKeyStore {
+    keys: Vec<ApiKeyRecord>,
+    admin_key_hash: Option<String>,
+    path: PathBuf,
+}
+
+fn hash_key(key: &str) -> String {
+    format!("{:x}", Sha256::digest(key.as_bytes()))
+}
+
+impl ApiKeyStore {
+    /// Load keys from `API_KEYS_FILE` (default `api_keys.json`) and the root key from `ADMIN_API_KEY`
+    pub fn load() -> Self {
+        let path = PathBuf::from(std::env::var("API_KEYS_FILE").unwrap_or_else(|_| "api_keys.json".to_string()));
+        let keys = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                tracing::error!("Failed to parse {}: {}", path.display(), e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+
+        let admin_key_hash = std::env::var("ADMIN_API_KEY")
+            .ok()
+            .filter(|key| !key.is_empty())
+            .map(|key| hash_key(&key));
+        if admin_key_hash.is_none() {
+            tracing::warn!("ADMIN_API_KEY not set, admin endpoints are disabled");
+        }
+
+        tracing::info!("Loaded {} integration API keys", keys.len());
+        Self { keys, admin_key_hash, path }
+    }
+
+    /// Create a key, returns the record and the plaintext secret (shown only once)
+    pub fn create(&mut self, name: String, scopes: Vec<Scope>) -> (ApiKeyRecord, String) {
+        let secret = format!("whk_{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+        let record = ApiKeyRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            name,
+            key_hash: hash_key(&secret),
+            scopes,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        };
+        self.keys.push(record.clone());
+        self.save();
+        tracing::info!("Created API key {} ({}) with scopes {:?}", record.id, record.name, record.scopes);
+        (record, secret)
+    }
+
+    /// Revoke a key by ID, returns false if it didn't exist
+    pub fn revoke(&mut self, key_id: &str) -> bool {
+        let before = self.keys.len();
+        self.keys.retain(|record| record.id != key_id);
+        if self.keys.len() == before {
+            return false;
+        }
+        self.save();
+        tracing::info!("Revoked API key {}", key_id);
+        true
+    }
+
+    pub fn list(&self) -> Vec<ApiKeyRecord> {
+        self.keys.iter().map(ApiKeyRecord::redacted).collect()
+    }
+
+    fn authenticate(&self, key: &str) -> Option<Principal> {
+        let hash = hash_key(key);
+        if self.admin_key_hash.as_deref() == Some(hash.as_str()) {
+            return Some(Principal::Admin);
+        }
+        self.keys
+            .iter()
+            .find(|record| record.key_hash == hash)
+            .map(|record| Principal::Integration {
+                key_id: record.id.clone(),
+                scopes: record.scopes.clone(),
+            })
+    }
+
+    /// Queue a snapshot for the writer thread, so the key lock isn't held across disk writes
+    fn save(&self) {
+        match serde_json::to_string_pretty(&self.keys) {
+            Ok(json) => file_writer::replace(self.path.clone(), json),
+            Err(e) => tracing::error!("Failed to serialize API keys: {}", e),
+        }
+    }
+}
+
+/// Extractor for authenticated REST callers (`Authorization: Bearer <key>` or `X-Api-Key`)
+pub struct ApiAuth(pub Principal);
+
+impl ApiAuth {
+    pub fn require(&self, scope: Scope) -> actix_web::Result<()> {
+        if self.0.has_scope(scope) {
+            Ok(())
+        } else {
+            Err(ErrorForbidden(format!("API key lacks the {:?} scope", scope)))
+        }
+    }
+
+    pub fn require_admin(&self) -> actix_web::Result<()> {
+        match self.0 {
+            Principal::Admin => Ok(()),
+            Principal::Integration { .. } => Err(ErrorForbidden("admin key required")),
+        }
+    }
+}
+
+impl FromRequest for ApiAuth {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let headers = req.headers();
+        let key = headers
+            .get("Authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .or_else(|| headers.get("X-Api-Key").and_then(|value| value.to_str().ok()));
+        let Some(key) = key else {
+            return ready(Err(ErrorUnauthorized("missing API key")));
+        };
+
+        let Some(store) = req.app_data::<Data<RwLock<ApiKeyStore>>>() else {
+            tracing::error!("ApiKeyStore not registered as app data");
+            return ready(Err(ErrorUnauthorized("API keys not configured")));
+        };
+        let principal = store.read().unwrap().authenticate(key.trim());
+        ready(principal.map(ApiAuth).ok_or_else(|| ErrorUnauthorized("invalid API key")))
+    }
+}
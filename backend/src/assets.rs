@@ -0,0 +1,82 @@
+use actix_web::web;
+
+/// Serve the Next.js static export: from `../frontend/out` on disk, or from the binary itself
+/// when built with the `embedded-assets` feature
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    #[cfg(feature = "embedded-assets")]
+    cfg.default_service(web::get().to(embedded::serve));
+
+    #[cfg(not(feature = "embedded-assets"))]
+    cfg.service(
+        actix_files::Files::new("/", "../frontend/out")
+            .index_file("index.html")
+            .use_last_modified(true),
+    );
+}
+
+#[cfg(feature = "embedded-assets")]
+mod embedded {
+    use actix_web::http::header;
+    use actix_web::{HttpRequest, HttpResponse};
+    use rust_embed::RustEmbed;
+
+    /// Run `npm run build` in frontend/ before `cargo build --features embedded-assets`
+    #[derive(RustEmbed)]
+    #[folder = "../frontend/out"]
+    struct FrontendAssets;
+
+    /// Resolve a request path the way the static export lays files out (`trailingSlash: true`)
+    fn lookup(path: &str) -> Option<(String, rust_embed::EmbeddedFile)> {
+        let path = path.trim_start_matches('/');
+        let candidates = if path.is_empty() || path.ends_with('/') {
+            vec![format!("{}index.html", path)]
+        } else {
+            vec![path.to_string(), format!("{}/index.html", path), format!("{}.html", path)]
+        };
+        candidates
+            .into_iter()
+            .find_map(|candidate| FrontendAssets::get(&candidate).map(|file| (candidate, file)))
+    }
+
+    pub async fn serve(req: HttpRequest) -> HttpResponse {
+        let Some((path, file)) = lookup(req.path()) else {
+            return match FrontendAssets::get("404.html") {
+                Some(file) => HttpResponse::NotFound()
+                    .content_type("text/html; charset=utf-8")
+                    .body(file.data.into_owned()),
+                None => HttpResponse::NotFound().finish(),
+            };
+        };
+
+        let etag = format!("\"{}\"", hex_digest(&file.metadata.sha256_hash()));
+        let not_modified = req
+            .headers()
+            .get(header::IF_NONE_MATCH)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value == etag);
+        // Next.js build output under _next/static is content-hashed and never changes
+        let cache_control = if path.starts_with("_next/static/") {
+            "public, max-age=31536000, immutable"
+        } else {
+            "no-cache"
+        };
+
+        let mut response = if not_modified {
+            HttpResponse::NotModified()
+        } else {
+            HttpResponse::Ok()
+        };
+        response
+            .insert_header((header::ETAG, etag))
+            .insert_header((header::CACHE_CONTROL, cache_control));
+        if not_modified {
+            return response.finish();
+        }
+        let content_type = mime_guess::from_path(&path).first_or_octet_stream();
+        response.content_type(content_type.as_ref()).body(file.data.into_owned())
+    }
+
+    fn hex_digest(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
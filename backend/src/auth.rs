@@ -0,0 +1,80 @@
+use std::sync::LazyLock;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+
+use crate::config::AuthSettings;
+use crate::streaming::roles::Role;
+
+/// Who a `/stream` join token says the player is
+#[derive(Deserialize, Debug, Clone)]
+pub struct StreamClaims {
+    /// Stable player ID, used in place of the `profileId` query param
+    pub sub: String,
+    /// Display name, used in place of the `name` query param
+    pub name: Option<String>,
+    #[serde(default)]
+    pub role: Role,
+    /// Token ID, so one leaked token can be revoked without signing the whole account out
+    pub jti: Option<String>,
+}
+
+/// What a join proved about the player, as opposed to what its query params claim
+#[derive(Debug, Clone, Default)]
+pub struct VerifiedIdentity {
+    /// The token's subject, or a profile ID whose PIN was given; handles, revocations and identity
+    /// bans only apply to IDs proven this way
+    pub profile_id: Option<String>,
+    /// The token's `jti`
+    pub token_id: Option<String>,
+}
+
+/// Checks join tokens against the configured HMAC secret or RSA public key
+pub struct TokenVerifier {
+    key: DecodingKey,
+    validation: Validation,
+}
+
+impl TokenVerifier {
+    /// `None` when no key is configured, so joins aren't token-checked
+    pub fn from_settings(settings: &AuthSettings) -> Result<Option<Self>, String> {
+        let (key, algorithm) = match (&settings.jwt_secret, &settings.jwt_public_key_path) {
+            (Some(_), Some(_)) => return Err("set either jwt_secret or jwt_public_key_path, not both".to_string()),
+            (Some(secret), None) => (DecodingKey::from_secret(secret.as_bytes()), Algorithm::HS256),
+            (None, Some(path)) => {
+                let pem = std::fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+                let key = DecodingKey::from_rsa_pem(&pem).map_err(|e| format!("{}: {}", path.display(), e))?;
+                (key, Algorithm::RS256)
+            }
+            (None, None) if settings.require_token => return Err("require_token needs a jwt_secret or jwt_public_key_path".to_string()),
+            (None, None) => return Ok(None),
+        };
+        let mut validation = Validation::new(algorithm);
+        if let Some(issuer) = &settings.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        match &settings.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+        Ok(Some(Self { key, validation }))
+    }
+
+    pub fn verify(&self, token: &str) -> Result<StreamClaims, String> {
+        let claims = jsonwebtoken::decode::<StreamClaims>(token, &self.key, &self.validation)
+            .map_err(|e| e.to_string())?
+            .claims;
+        if claims.sub.trim().is_empty() {
+            return Err("token has no subject".to_string());
+        }
+        Ok(claims)
+    }
+}
+
+// Settings were validated when the config was loaded
+static VERIFIER: LazyLock<Option<TokenVerifier>> =
+    LazyLock::new(|| TokenVerifier::from_settings(&crate::config::get().auth).ok().flatten());
+
+/// The verifier built from the `[auth]` config, if tokens are enabled
+pub fn verifier() -> Option<&'static TokenVerifier> {
+    VERIFIER.as_ref()
+}
@@ -0,0 +1,146 @@
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use actix_web::web::{self, Data};
+use serde::{Deserialize, Serialize};
+
+use crate::file_writer;
+use crate::watched_file::{modified_time, read_json, read_json_if_changed};
+
+/// How often the bans file is checked for entries written by other nodes
+const RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|since| since.as_secs()).unwrap_or(0)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Ban {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip: Option<IpAddr>,
+    /// A join token's `sub` or a PIN-verified profile ID; unverified ones are trivially swapped, so aren't matched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile_id: Option<String>,
+    pub reason: Option<String>,
+    /// Unix seconds
+    pub created_at: u64,
+    /// Unix seconds; permanent when unset
+    pub expires_at: Option<u64>,
+}
+
+impl Ban {
+    fn is_active(&self, now: u64) -> bool {
+        self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+
+    fn matches(&self, ip: Option<IpAddr>, profile_id: Option<&str>) -> bool {
+        (self.ip.is_some() && self.ip == ip) || (self.profile_id.is_some() && self.profile_id.as_deref() == profile_id)
+    }
+}
+
+/// Addresses and player identities turned away before the WebSocket upgrade. Persisted to
+/// `BANS_FILE` (default `bans.json`); nodes sharing that file pick up each other's bans on reload
+pub struct BanList {
+    bans: Vec<Ban>,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    /// Bumped on every save, so the watcher can tell its read went stale while it was off the lock
+    saves: u64,
+}
+
+impl BanList {
+    /// Load from `BANS_FILE` (default `bans.json`)
+    pub fn load() -> Self {
+        let path = PathBuf::from(std::env::var("BANS_FILE").unwrap_or_else(|_| "bans.json".to_string()));
+        Self {
+            bans: read_json(&path),
+            modified: modified_time(&path),
+            path,
+            saves: 0,
+        }
+    }
+
+    /// The active ban covering this address or verified identity, if any
+    pub fn find(&self, ip: Option<IpAddr>, profile_id: Option<&str>) -> Option<&Ban> {
+        let now = now_secs();
+        self.bans.iter().find(|ban| ban.is_active(now) && ban.matches(ip, profile_id))
+    }
+
+    /// Active bans, newest first
+    pub fn list(&self) -> Vec<Ban> {
+        let now = now_secs();
+        let mut bans: Vec<Ban> = self.bans.iter().filter(|ban| ban.is_active(now)).cloned().collect();
+        bans.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        bans
+    }
+
+    pub fn add(&mut self, ip: Option<IpAddr>, profile_id: Option<String>, reason: Option<String>, duration: Option<Duration>) -> Ban {
+        let created_at = now_secs();
+        let ban = Ban {
+            id: uuid::Uuid::new_v4().to_string(),
+            ip,
+            profile_id,
+            reason,
+            created_at,
+            expires_at: duration.map(|duration| created_at + duration.as_secs()),
+        };
+        // Expired bans are dropped whenever the file is rewritten
+        self.bans.retain(|ban| ban.is_active(created_at));
+        self.bans.push(ban.clone());
+        self.save();
+        tracing::info!("Added ban {} (ip: {:?}, profile: {:?})", ban.id, ban.ip, ban.profile_id);
+        ban
+    }
+
+    /// Lift a ban by ID; returns false if it didn't exist
+    pub fn remove(&mut self, ban_id: &str) -> bool {
+        let before = self.bans.len();
+        self.bans.retain(|ban| ban.id != ban_id);
+        if self.bans.len() == before {
+            return false;
+        }
+        self.save();
+        tracing::info!("Lifted ban {}", ban_id);
+        true
+    }
+
+    /// Swap in what was read from the file, unless this node saved since; its queued write will
+    /// change the file again and the next check picks that up
+    fn replace(&mut self, saves: u64, bans: Vec<Ban>, modified: Option<SystemTime>) {
+        if self.saves == saves {
+            self.bans = bans;
+            self.modified = modified;
+        }
+    }
+
+    /// Queue a snapshot for the writer thread, so every join waiting on the list isn't stuck behind the disk
+    fn save(&mut self) {
+        self.saves += 1;
+        match serde_json::to_string_pretty(&self.bans) {
+            Ok(json) => file_writer::replace(self.path.clone(), json),
+            Err(e) => tracing::error!("Failed to serialize bans: {}", e),
+        }
+    }
+}
+
+/// Poll the bans file so entries from other nodes apply without a restart
+pub fn spawn_bans_watcher(list: Data<RwLock<BanList>>) {
+    actix::spawn(async move {
+        let mut interval = tokio::time::interval(RELOAD_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let (path, known, saves) = {
+                let list = list.read().unwrap();
+                (list.path.clone(), list.modified, list.saves)
+            };
+            // Every join checks the list, so it's only locked to swap in what was already read
+            let Ok(Some((bans, modified))) = web::block(move || read_json_if_changed(&path, known)).await else {
+                continue;
+            };
+            list.write().unwrap().replace(saves, bans, modified);
+        }
+    });
+}
@@ -0,0 +1,117 @@
+use std::net::IpAddr;
+use actix_web::HttpRequest;
+
+/// An IP network like `10.0.0.0/8`; a bare address is a single-host network
+#[derive(Debug, Clone, Copy)]
+struct IpNetwork {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpNetwork {
+    fn parse(value: &str) -> Option<Self> {
+        let (addr, prefix) = match value.split_once('/') {
+            Some((addr, prefix)) => (addr.parse::<IpAddr>().ok()?, Some(prefix.parse::<u8>().ok()?)),
+            None => (value.parse::<IpAddr>().ok()?, None),
+        };
+        let max_prefix = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = prefix.unwrap_or(max_prefix);
+        (prefix <= max_prefix).then_some(Self { addr, prefix })
+    }
+
+    fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Proxies allowed to tell us the client's address (`TRUSTED_PROXIES`, comma-separated IPs/CIDRs)
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    networks: Vec<IpNetwork>,
+}
+
+impl TrustedProxies {
+    pub fn from_env() -> Self {
+        let networks: Vec<IpNetwork> = std::env::var("TRUSTED_PROXIES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .filter_map(|entry| {
+                let network = IpNetwork::parse(entry);
+                if network.is_none() {
+                    tracing::warn!("Ignoring invalid TRUSTED_PROXIES entry: {}", entry);
+                }
+                network
+            })
+            .collect();
+        if !networks.is_empty() {
+            tracing::info!("Trusting forwarded headers from {} proxy network(s)", networks.len());
+        }
+        Self { networks }
+    }
+
+    fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.networks.iter().any(|network| network.contains(ip))
+    }
+
+    /// The real client address behind any trusted proxies.
+    ///
+    /// Forwarded headers are only believed when the direct peer is a trusted proxy (or the
+    /// connection came over the local Unix socket, which only a local proxy can reach).
+    /// The chain is walked right to left so a client can't spoof an address by sending
+    /// its own `X-Forwarded-For`.
+    pub fn client_ip(&self, req: &HttpRequest) -> Option<IpAddr> {
+        let peer = req.peer_addr().map(|addr| addr.ip());
+        if peer.is_some_and(|peer| !self.is_trusted(peer)) {
+            return peer;
+        }
+
+        let headers = req.headers();
+        let forwarded_for: Vec<IpAddr> = if let Some(forwarded) = headers.get("Forwarded").and_then(|value| value.to_str().ok()) {
+            forwarded
+                .split(',')
+                .filter_map(|element| {
+                    element
+                        .split(';')
+                        .find_map(|pair| pair.trim().strip_prefix("for=").or_else(|| pair.trim().strip_prefix("For=")))
+                })
+                .filter_map(parse_forwarded_node)
+                .collect()
+        } else {
+            headers
+                .get_all("X-Forwarded-For")
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(','))
+                .filter_map(|entry| entry.trim().parse::<IpAddr>().ok())
+                .collect()
+        };
+
+        forwarded_for
+            .into_iter()
+            .rev()
+            .find(|ip| !self.is_trusted(*ip))
+            .or(peer)
+    }
+}
+
+/// `for=` node from RFC 7239: `192.0.2.1`, `"192.0.2.1:4711"` or `"[2001:db8::1]:4711"`
+fn parse_forwarded_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+    if let Some(rest) = node.strip_prefix('[') {
+        return rest.split(']').next()?.parse().ok();
+    }
+    node.parse()
+        .ok()
+        .or_else(|| node.rsplit_once(':').and_then(|(host, _)| host.parse().ok()))
+}
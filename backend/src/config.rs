@@ -0,0 +1,624 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+use rheomesh::config::WorkerConfig;
+use serde::{Deserialize, Serialize};
+use webrtc::peer_connection::policy::ice_transport_policy::RTCIceTransportPolicy;
+use webrtc_ice::network_type::NetworkType;
+
+use crate::streaming::codecs::VideoCodec;
+use crate::streaming::theme::{builtin_themes, ThemeInfo};
+use crate::streaming::{ECHO_TEST_ROOM_ID, ECHO_TEST_ROOM_THEME, HUB_ROOM_ID, HUB_ROOM_THEME};
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Server settings from `config.toml` (path in `CONFIG_FILE`), with environment variables taking precedence
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub media: MediaSettings,
+    /// Activity keyword -> room routing, first match wins
+    pub rooms: RoomRouting,
+    /// Room themes keyed by base room ID, added to (or replacing) the built-in ones; `default` is
+    /// used for rooms without a theme of their own
+    pub themes: HashMap<String, ThemeInfo>,
+    /// Chat filters applied before messages reach the room
+    pub moderation: ModerationSettings,
+    /// Signed tokens that identify players joining `/stream`
+    pub auth: AuthSettings,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// Public listeners (`BIND_ADDRS`, comma-separated)
+    pub bind_addrs: Vec<String>,
+    /// Serve admin endpoints only on this address (`ADMIN_BIND_ADDR`)
+    pub admin_bind_addr: Option<String>,
+    /// Plain-HTTP listener redirecting to HTTPS (`HTTP_REDIRECT_ADDR`)
+    pub http_redirect_addr: Option<String>,
+    /// Port HTTPS redirects point at (`HTTPS_PORT`)
+    pub https_port: u16,
+    /// Extra Unix socket listener (`UNIX_SOCKET_PATH`)
+    pub unix_socket_path: Option<PathBuf>,
+    /// Rheomesh workers, defaults to one per core group (`RHEOMESH_WORKERS`)
+    pub workers: Option<usize>,
+    /// Release workers after this long without sessions, 0 = never (`IDLE_SHUTDOWN_SECS`)
+    pub idle_shutdown_secs: u64,
+    /// Ports rheomesh relays media between servers on, rheomesh's defaults when unset
+    /// (`RELAY_SENDER_PORT`, `RELAY_SERVER_UDP_PORT`, `RELAY_SERVER_TCP_PORT`)
+    pub relay_sender_port: Option<u16>,
+    pub relay_server_udp_port: Option<u16>,
+    pub relay_server_tcp_port: Option<u16>,
+    /// Keep a dropped connection's player and media this long for it to resume, 0 = off (`RESUME_GRACE_SECS`)
+    pub resume_grace_secs: u64,
+    /// Ping every connection this often, 0 = off (`HEARTBEAT_INTERVAL_SECS`)
+    pub heartbeat_interval_secs: u64,
+    /// Drop connections that leave this many pings in a row unanswered (`HEARTBEAT_MAX_MISSED`)
+    pub heartbeat_max_missed: u32,
+    /// Disconnect sessions after this long, 0 = unlimited (`MAX_SESSION_SECS`)
+    pub max_session_secs: u64,
+    /// Per-room overrides keyed by base room ID, 0 = unlimited in that room
+    pub room_max_session_secs: HashMap<String, u64>,
+    /// Players per room before joins overflow into `<room>-2`, `-3`, ...; 0 = unlimited (`MAX_PLAYERS_PER_ROOM`)
+    pub max_players_per_room: usize,
+    /// Per-room overrides keyed by base room ID, 0 = unlimited in that room
+    pub room_max_players: HashMap<String, usize>,
+    /// Keep a room this long after its last player leaves, 0 = remove right away (`EMPTY_ROOM_TTL_SECS`)
+    pub empty_room_ttl_secs: u64,
+    /// Create every routed room at startup and keep them open while empty (`PRECREATE_ROOMS`);
+    /// skipped with idle shutdown on, since standing rooms would keep the workers awake
+    pub precreate_rooms: bool,
+    /// Mark players idle after this long without moving, chatting or reacting, 0 = never (`AFK_AFTER_SECS`)
+    pub afk_after_secs: u64,
+    /// Disconnect players idle this long to free their media and TURN bandwidth, 0 = never (`AFK_DISCONNECT_SECS`)
+    pub afk_disconnect_secs: u64,
+    /// Allow fault injection through `/api/admin/chaos`; development only (`CHAOS_MODE`)
+    pub chaos_mode: bool,
+    /// Keep each room's chat in `<dir>/<room_id>.jsonl` across restarts (`CHAT_HISTORY_DIR`)
+    pub chat_history_dir: Option<PathBuf>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind_addrs: vec!["0.0.0.0:3001".to_string()],
+            admin_bind_addr: None,
+            http_redirect_addr: None,
+            https_port: 443,
+            unix_socket_path: None,
+            workers: None,
+            idle_shutdown_secs: 0,
+            relay_sender_port: None,
+            relay_server_udp_port: None,
+            relay_server_tcp_port: None,
+            resume_grace_secs: 30,
+            heartbeat_interval_secs: 10,
+            heartbeat_max_missed: 3,
+            max_session_secs: 0,
+            room_max_session_secs: HashMap::new(),
+            max_players_per_room: 0,
+            room_max_players: HashMap::new(),
+            empty_room_ttl_secs: 60,
+            precreate_rooms: true,
+            afk_after_secs: 300,
+            afk_disconnect_secs: 0,
+            chaos_mode: false,
+            chat_history_dir: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Longest a session may last in a room, falling back to the deployment-wide limit
+    pub fn max_session_for(&self, base_room_id: &str) -> Option<Duration> {
+        let secs = self.room_max_session_secs.get(base_room_id).copied().unwrap_or(self.max_session_secs);
+        (secs > 0).then(|| Duration::from_secs(secs))
+    }
+
+    pub fn empty_room_ttl(&self) -> Duration {
+        Duration::from_secs(self.empty_room_ttl_secs)
+    }
+
+    pub fn afk_after(&self) -> Option<Duration> {
+        (self.afk_after_secs > 0).then(|| Duration::from_secs(self.afk_after_secs))
+    }
+
+    pub fn afk_disconnect(&self) -> Option<Duration> {
+        (self.afk_disconnect_secs > 0).then(|| Duration::from_secs(self.afk_disconnect_secs))
+    }
+
+    /// Capacity of each instance of a room, falling back to the deployment-wide limit
+    pub fn max_players_for(&self, base_room_id: &str) -> Option<usize> {
+        let max = self.room_max_players.get(base_room_id).copied().unwrap_or(self.max_players_per_room);
+        (max > 0).then_some(max)
+    }
+
+    /// What every rheomesh worker is started with, at boot and when waking from idle shutdown
+    pub fn worker_config(&self) -> WorkerConfig {
+        let defaults = WorkerConfig::default();
+        WorkerConfig {
+            relay_sender_port: self.relay_sender_port.unwrap_or(defaults.relay_sender_port),
+            relay_server_udp_port: self.relay_server_udp_port.unwrap_or(defaults.relay_server_udp_port),
+            relay_server_tcp_port: self.relay_server_tcp_port.unwrap_or(defaults.relay_server_tcp_port),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct MediaSettings {
+    /// Put AV1 ahead of every room's video codecs (`ENABLE_AV1`)
+    pub enable_av1: bool,
+    /// Replace every theme's video codec preference, e.g. `["vp8", "h264"]`
+    pub video_codecs: Option<Vec<VideoCodec>>,
+    pub ice_disconnected_timeout_secs: u64,
+    pub ice_failed_timeout_secs: u64,
+    pub ice_keep_alive_interval_secs: u64,
+    /// `relay` sends everything through TURN (works around webrtc-rs DTLS issues behind NAT),
+    /// `all` lets LAN and direct paths be used (`ICE_TRANSPORT_POLICY`)
+    pub ice_transport_policy: IcePolicy,
+    /// Candidate network types (`ICE_NETWORK_TYPES`, comma-separated)
+    pub network_types: Vec<IceNetworkType>,
+    /// Per-room policy overrides keyed by base room ID, e.g. `focus-den = "all"`
+    pub room_ice_policies: HashMap<String, IcePolicy>,
+    /// Per-room video bitrate caps keyed by base room ID, overriding the theme's; `0` is uncapped
+    /// (`ROOM_MAX_VIDEO_KBPS`, e.g. `focus-den=200,city=1000`)
+    pub room_max_video_kbps: HashMap<String, u32>,
+    /// Inclusive UDP ports for media, so a firewall or Docker only needs this range open
+    /// (`UDP_PORT_RANGE`, e.g. `40000-40100`)
+    pub udp_port_range: Option<UdpPortRange>,
+    /// Public addresses advertised in host candidates when behind 1:1 NAT (`PUBLIC_IPS`, comma-separated)
+    pub public_ips: Vec<IpAddr>,
+    /// Listen to every audio publisher server-side and broadcast who is speaking (`SPEAKING_DETECTION`)
+    pub speaking_detection: bool,
+    /// Auto-subscribed sessions only receive publishers within the room's speaking cutoff
+    /// (`PROXIMITY_SUBSCRIBE`)
+    pub proximity_subscribe: bool,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UdpPortRange {
+    pub min: u16,
+    pub max: u16,
+}
+
+impl std::str::FromStr for UdpPortRange {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, ()> {
+        let (min, max) = value.split_once('-').ok_or(())?;
+        Ok(Self {
+            min: min.trim().parse().map_err(|_| ())?,
+            max: max.trim().parse().map_err(|_| ())?,
+        })
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IcePolicy {
+    All,
+    Relay,
+}
+
+impl From<IcePolicy> for RTCIceTransportPolicy {
+    fn from(policy: IcePolicy) -> Self {
+        match policy {
+            IcePolicy::All => RTCIceTransportPolicy::All,
+            IcePolicy::Relay => RTCIceTransportPolicy::Relay,
+        }
+    }
+}
+
+impl std::str::FromStr for IcePolicy {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, ()> {
+        match value {
+            "all" => Ok(IcePolicy::All),
+            "relay" => Ok(IcePolicy::Relay),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IceNetworkType {
+    Udp4,
+    Udp6,
+    Tcp4,
+    Tcp6,
+}
+
+impl From<IceNetworkType> for NetworkType {
+    fn from(network_type: IceNetworkType) -> Self {
+        match network_type {
+            IceNetworkType::Udp4 => NetworkType::Udp4,
+            IceNetworkType::Udp6 => NetworkType::Udp6,
+            IceNetworkType::Tcp4 => NetworkType::Tcp4,
+            IceNetworkType::Tcp6 => NetworkType::Tcp6,
+        }
+    }
+}
+
+impl std::str::FromStr for IceNetworkType {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, ()> {
+        match value {
+            "udp4" => Ok(IceNetworkType::Udp4),
+            "udp6" => Ok(IceNetworkType::Udp6),
+            "tcp4" => Ok(IceNetworkType::Tcp4),
+            "tcp6" => Ok(IceNetworkType::Tcp6),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Default for MediaSettings {
+    fn default() -> Self {
+        Self {
+            enable_av1: false,
+            video_codecs: None,
+            ice_disconnected_timeout_secs: 30,
+            ice_failed_timeout_secs: 60,
+            ice_keep_alive_interval_secs: 2,
+            ice_transport_policy: IcePolicy::Relay,
+            // IPv4 only - IPv6 causes Windows binding errors (os error 10049)
+            network_types: vec![IceNetworkType::Udp4, IceNetworkType::Tcp4],
+            room_ice_policies: HashMap::new(),
+            room_max_video_kbps: HashMap::new(),
+            udp_port_range: None,
+            public_ips: Vec::new(),
+            speaking_detection: true,
+            proximity_subscribe: false,
+        }
+    }
+}
+
+impl MediaSettings {
+    pub fn ice_disconnected_timeout(&self) -> Duration {
+        Duration::from_secs(self.ice_disconnected_timeout_secs)
+    }
+
+    pub fn ice_failed_timeout(&self) -> Duration {
+        Duration::from_secs(self.ice_failed_timeout_secs)
+    }
+
+    pub fn ice_keep_alive_interval(&self) -> Duration {
+        Duration::from_secs(self.ice_keep_alive_interval_secs)
+    }
+
+    /// Policy for a room, falling back to the deployment-wide one
+    pub fn ice_policy_for(&self, base_room_id: &str) -> IcePolicy {
+        self.room_ice_policies.get(base_room_id).copied().unwrap_or(self.ice_transport_policy)
+    }
+}
+
+/// What a chat filter does with a message it matches
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterAction {
+    /// Replace the matched text with `*`
+    #[default]
+    Mask,
+    /// Don't deliver the message
+    Drop,
+    /// Don't deliver it and mute the sender's chat for `mute_secs`
+    Mute,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ModerationRule {
+    /// Regular expression matched against the message
+    pub pattern: String,
+    #[serde(default)]
+    pub action: FilterAction,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ModerationSettings {
+    /// Words matched case-insensitively as whole words
+    pub blocked_words: Vec<String>,
+    pub blocked_words_action: FilterAction,
+    pub rules: Vec<ModerationRule>,
+    /// External moderation service (`MODERATION_ENDPOINT`), asked after the local filters
+    pub endpoint: Option<String>,
+    /// Messages go through unreviewed when the endpoint takes longer than this
+    pub endpoint_timeout_ms: u64,
+    /// How long the `mute` action silences a sender
+    pub mute_secs: u64,
+}
+
+impl Default for ModerationSettings {
+    fn default() -> Self {
+        Self {
+            blocked_words: Vec::new(),
+            blocked_words_action: FilterAction::Mask,
+            rules: Vec::new(),
+            endpoint: None,
+            endpoint_timeout_ms: 1500,
+            mute_secs: 5 * 60,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct AuthSettings {
+    /// HS256 shared secret for join tokens (`JWT_SECRET`)
+    pub jwt_secret: Option<String>,
+    /// PEM RSA public key for RS256 join tokens, used instead of the secret (`JWT_PUBLIC_KEY_PATH`)
+    pub jwt_public_key_path: Option<PathBuf>,
+    /// Checked against the token's `iss` when set
+    pub issuer: Option<String>,
+    /// Checked against the token's `aud` when set
+    pub audience: Option<String>,
+    /// Turn away joins that don't carry a token (`REQUIRE_AUTH_TOKEN`)
+    pub require_token: bool,
+    /// Turn away joins without a verified identity, since revocations can't follow them (`ENFORCE_REVOCATIONS`)
+    pub enforce_revocations: bool,
+    /// Signs the tokens that bring players back to their room after a restart; share it across nodes
+    /// (`RECONNECT_SECRET`, random per process when unset)
+    pub reconnect_secret: Option<String>,
+    /// Signs moderator role tokens handed out by the admin API; share it across nodes
+    /// (`ROLE_SECRET`, random per process when unset)
+    pub role_secret: Option<String>,
+}
+
+/// A themed room players are routed to by their activity
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RoomRoute {
+    pub id: String,
+    pub name: String,
+    /// Case-insensitive substrings of the activity that send a player here
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// Case-insensitive regular expressions matched against the activity
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// Higher priorities are tried first; ties keep their listed order
+    #[serde(default)]
+    pub priority: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct RoomRouting {
+    pub routes: Vec<RoomRoute>,
+    /// Where players go when no keyword matches
+    pub fallback_id: String,
+    pub fallback_name: String,
+}
+
+fn route(id: &str, name: &str, keywords: &[&str]) -> RoomRoute {
+    RoomRoute {
+        id: id.to_string(),
+        name: name.to_string(),
+        keywords: keywords.iter().map(|keyword| keyword.to_string()).collect(),
+        patterns: Vec::new(),
+        priority: 0,
+    }
+}
+
+impl Default for RoomRouting {
+    fn default() -> Self {
+        Self {
+            routes: vec![
+                route("music-lounge", "Music Lounge", &["music", "guitar", "piano"]),
+                route("art-studio", "Art Studio", &["art", "draw", "paint"]),
+                route("focus-den", "Focus Den", &["code", "program", "study"]),
+                route("gaming-corner", "Gaming Corner", &["game", "gaming"]),
+                route("cinema", "Cinema", &["watching", "movie", "judge", "judging"]),
+                route("city", "City", &["party", "city", "walking"]),
+                route(ECHO_TEST_ROOM_ID, ECHO_TEST_ROOM_THEME, &["echo", "mic test"]),
+                route(HUB_ROOM_ID, HUB_ROOM_THEME, &["explore", "hub", "teleport"]),
+            ],
+            fallback_id: "hangout-hub".to_string(),
+            fallback_name: "Hangout Hub".to_string(),
+        }
+    }
+}
+
+fn env_parse<T: std::str::FromStr>(name: &str) -> Option<T> {
+    std::env::var(name).ok().and_then(|value| value.parse().ok())
+}
+
+/// `config.toml`, or wherever `CONFIG_FILE` points
+pub fn config_path() -> PathBuf {
+    PathBuf::from(std::env::var("CONFIG_FILE").unwrap_or_else(|_| "config.toml".to_string()))
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RoomsSection {
+    rooms: RoomRouting,
+}
+
+/// Re-read just the `[rooms]` section, for picking up routing edits without a restart
+pub fn read_room_routing() -> Result<RoomRouting, String> {
+    let path = config_path();
+    let contents = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let section: RoomsSection = toml::from_str(&contents).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(section.rooms)
+}
+
+impl Config {
+    fn load() -> std::io::Result<Self> {
+        let path = config_path();
+        let mut config: Config = match std::fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents).map_err(|e| {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Config::default(),
+            Err(e) => return Err(e),
+        };
+        config.apply_env_overrides();
+        let configured_themes = std::mem::take(&mut config.themes);
+        config.themes = builtin_themes();
+        config.themes.extend(configured_themes);
+        for (theme_id, theme) in &config.themes {
+            if let Err(e) = theme.validate() {
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("invalid theme {}: {}", theme_id, e)));
+            }
+        }
+        if let Some(rule) = config.moderation.rules.iter().find(|rule| regex::Regex::new(&rule.pattern).is_err()) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("invalid moderation rule pattern {}", rule.pattern),
+            ));
+        }
+        if let Err(e) = crate::auth::TokenVerifier::from_settings(&config.auth) {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("invalid auth settings: {}", e)));
+        }
+        if let Some(range) = config.media.udp_port_range {
+            if range.min == 0 || range.min > range.max {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("invalid UDP port range {}-{}", range.min, range.max),
+                ));
+            }
+        }
+        Ok(config)
+    }
+
+    fn apply_env_overrides(&mut self) {
+        let server = &mut self.server;
+        if let Ok(addrs) = std::env::var("BIND_ADDRS") {
+            server.bind_addrs = addrs
+                .split(',')
+                .map(|addr| addr.trim().to_string())
+                .filter(|addr| !addr.is_empty())
+                .collect();
+        }
+        if let Ok(addr) = std::env::var("ADMIN_BIND_ADDR") {
+            server.admin_bind_addr = Some(addr);
+        }
+        if let Ok(addr) = std::env::var("HTTP_REDIRECT_ADDR") {
+            server.http_redirect_addr = Some(addr);
+        }
+        if let Some(port) = env_parse("HTTPS_PORT") {
+            server.https_port = port;
+        }
+        if let Ok(path) = std::env::var("UNIX_SOCKET_PATH") {
+            server.unix_socket_path = Some(PathBuf::from(path));
+        }
+        if let Some(workers) = env_parse("RHEOMESH_WORKERS") {
+            server.workers = Some(workers);
+        }
+        if let Some(secs) = env_parse("IDLE_SHUTDOWN_SECS") {
+            server.idle_shutdown_secs = secs;
+        }
+        if let Some(port) = env_parse("RELAY_SENDER_PORT") {
+            server.relay_sender_port = Some(port);
+        }
+        if let Some(port) = env_parse("RELAY_SERVER_UDP_PORT") {
+            server.relay_server_udp_port = Some(port);
+        }
+        if let Some(port) = env_parse("RELAY_SERVER_TCP_PORT") {
+            server.relay_server_tcp_port = Some(port);
+        }
+        if let Some(secs) = env_parse("RESUME_GRACE_SECS") {
+            server.resume_grace_secs = secs;
+        }
+        if let Some(secs) = env_parse("HEARTBEAT_INTERVAL_SECS") {
+            server.heartbeat_interval_secs = secs;
+        }
+        if let Some(missed) = env_parse("HEARTBEAT_MAX_MISSED") {
+            server.heartbeat_max_missed = missed;
+        }
+        if let Some(secs) = env_parse("MAX_SESSION_SECS") {
+            server.max_session_secs = secs;
+        }
+        if let Some(max) = env_parse("MAX_PLAYERS_PER_ROOM") {
+            server.max_players_per_room = max;
+        }
+        if let Some(secs) = env_parse("EMPTY_ROOM_TTL_SECS") {
+            server.empty_room_ttl_secs = secs;
+        }
+        if let Ok(value) = std::env::var("PRECREATE_ROOMS") {
+            server.precreate_rooms = value == "true" || value == "1";
+        }
+        if let Some(secs) = env_parse("AFK_AFTER_SECS") {
+            server.afk_after_secs = secs;
+        }
+        if let Some(secs) = env_parse("AFK_DISCONNECT_SECS") {
+            server.afk_disconnect_secs = secs;
+        }
+        if let Ok(value) = std::env::var("CHAOS_MODE") {
+            server.chaos_mode = value == "true" || value == "1";
+        }
+        if let Ok(dir) = std::env::var("CHAT_HISTORY_DIR") {
+            server.chat_history_dir = Some(PathBuf::from(dir));
+        }
+        if let Some(policy) = env_parse("ICE_TRANSPORT_POLICY") {
+            self.media.ice_transport_policy = policy;
+        }
+        if let Ok(types) = std::env::var("ICE_NETWORK_TYPES") {
+            let types: Vec<IceNetworkType> = types.split(',').filter_map(|t| t.trim().parse().ok()).collect();
+            if !types.is_empty() {
+                self.media.network_types = types;
+            }
+        }
+        if let Some(range) = env_parse("UDP_PORT_RANGE") {
+            self.media.udp_port_range = Some(range);
+        }
+        if let Ok(ips) = std::env::var("PUBLIC_IPS") {
+            self.media.public_ips = ips.split(',').filter_map(|ip| ip.trim().parse().ok()).collect();
+        }
+        if let Ok(secret) = std::env::var("JWT_SECRET") {
+            self.auth.jwt_secret = Some(secret);
+        }
+        if let Ok(path) = std::env::var("JWT_PUBLIC_KEY_PATH") {
+            self.auth.jwt_public_key_path = Some(PathBuf::from(path));
+        }
+        if let Ok(value) = std::env::var("REQUIRE_AUTH_TOKEN") {
+            self.auth.require_token = value == "true" || value == "1";
+        }
+        if let Ok(value) = std::env::var("ENFORCE_REVOCATIONS") {
+            self.auth.enforce_revocations = value == "true" || value == "1";
+        }
+        if let Ok(secret) = std::env::var("RECONNECT_SECRET") {
+            self.auth.reconnect_secret = Some(secret);
+        }
+        if let Ok(secret) = std::env::var("ROLE_SECRET") {
+            self.auth.role_secret = Some(secret);
+        }
+        if let Ok(endpoint) = std::env::var("MODERATION_ENDPOINT") {
+            self.moderation.endpoint = Some(endpoint);
+        }
+        if let Ok(caps) = std::env::var("ROOM_MAX_VIDEO_KBPS") {
+            let caps = caps.split(',').filter_map(|cap| {
+                let (room_id, kbps) = cap.split_once('=')?;
+                Some((room_id.trim().to_string(), kbps.trim().parse().ok()?))
+            });
+            self.media.room_max_video_kbps.extend(caps);
+        }
+        if let Ok(value) = std::env::var("ENABLE_AV1") {
+            self.media.enable_av1 = value == "true" || value == "1";
+        }
+        if let Ok(value) = std::env::var("SPEAKING_DETECTION") {
+            self.media.speaking_detection = value == "true" || value == "1";
+        }
+        if let Ok(value) = std::env::var("PROXIMITY_SUBSCRIBE") {
+            self.media.proximity_subscribe = value == "true" || value == "1";
+        }
+    }
+}
+
+/// Load the configuration once at startup
+pub fn init() -> std::io::Result<&'static Config> {
+    let config = Config::load()?;
+    Ok(CONFIG.get_or_init(|| config))
+}
+
+/// The loaded configuration; `init` must have run
+pub fn get() -> &'static Config {
+    CONFIG.get().expect("config::init must be called at startup")
+}
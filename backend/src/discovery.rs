@@ -0,0 +1,62 @@
+use actix_web::web::{self, Data};
+use actix_web::HttpResponse;
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use crate::streaming::echo::is_echo_room;
+use crate::streaming::instances::base_room_id;
+use crate::streaming::room::{is_waiting_room, Room, RoomOwner};
+use crate::streaming::StreamingSession;
+
+/// An active room as the lobby sees it
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RoomListing {
+    pub room_id: String,
+    /// Routed room this is an instance of, shared by every language and overflow instance
+    pub base_room_id: String,
+    pub theme: String,
+    pub language: String,
+    pub player_count: usize,
+    /// Players each instance holds; unlimited when unset
+    pub capacity: Option<usize>,
+    pub locked: bool,
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/api/rooms", web::get().to(list_rooms));
+}
+
+/// Echo tests, waiting rooms and password rooms aren't something to browse into
+fn is_private(room: &Room<StreamingSession>) -> bool {
+    is_echo_room(&room.id) || is_waiting_room(&room.id) || room.has_password()
+}
+
+/// Public rooms sorted by ID, shared by `GET /api/rooms` and the `/lobby` feed
+pub fn room_listings(owner: &RoomOwner<StreamingSession>) -> Vec<RoomListing> {
+    let mut rooms: Vec<RoomListing> = owner
+        .list_rooms()
+        .into_iter()
+        // Rooms on a failed worker are about to move, so they'd only flicker in the list
+        .filter(|room| !is_private(room) && owner.room_worker(&room.id).is_none_or(|(_, healthy)| healthy))
+        .map(|room| {
+            let base = base_room_id(&room.id).to_string();
+            RoomListing {
+                capacity: crate::config::get().server.max_players_for(&base),
+                room_id: room.id.clone(),
+                base_room_id: base,
+                theme: room.theme.clone(),
+                language: room.language.clone(),
+                player_count: room.player_count(),
+                locked: room.is_locked(),
+            }
+        })
+        .collect();
+    rooms.sort_by(|a, b| a.room_id.cmp(&b.room_id));
+    rooms
+}
+
+async fn list_rooms(owner: Data<Mutex<RoomOwner<StreamingSession>>>) -> HttpResponse {
+    let rooms = room_listings(&*owner.lock().await);
+    HttpResponse::Ok().json(rooms)
+}
@@ -0,0 +1,57 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::sync::LazyLock;
+
+enum FileWrite {
+    Replace(PathBuf, String),
+    Append(PathBuf, String),
+}
+
+/// One thread doing every persisted store's disk writes in the order they were queued, so request
+/// handlers and session actors never wait on the filesystem
+static WRITER: LazyLock<Sender<FileWrite>> = LazyLock::new(|| {
+    let (tx, rx) = mpsc::channel();
+    std::thread::Builder::new()
+        .name("file-writer".to_string())
+        .spawn(move || {
+            for write in rx {
+                let (path, result) = match write {
+                    FileWrite::Replace(path, contents) => {
+                        let result = write_atomically(&path, &contents);
+                        (path, result)
+                    }
+                    FileWrite::Append(path, contents) => {
+                        let result = std::fs::OpenOptions::new()
+                            .create(true)
+                            .append(true)
+                            .open(&path)
+                            .and_then(|mut file| std::io::Write::write_all(&mut file, contents.as_bytes()));
+                        (path, result)
+                    }
+                };
+                if let Err(e) = result {
+                    tracing::error!("Failed to write {}: {}", path.display(), e);
+                }
+            }
+        })
+        .expect("failed to spawn the file writer thread");
+    tx
+});
+
+/// Write next to the file and rename over it, so watchers polling it never read it half-written
+fn write_atomically(path: &Path, contents: &str) -> std::io::Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    std::fs::write(&temp, contents)?;
+    std::fs::rename(&temp, path)
+}
+
+/// Queue replacing the file's contents
+pub fn replace(path: PathBuf, contents: String) {
+    let _ = WRITER.send(FileWrite::Replace(path, contents));
+}
+
+/// Queue adding to the end of the file, creating it if needed
+pub fn append(path: PathBuf, contents: String) {
+    let _ = WRITER.send(FileWrite::Append(path, contents));
+}
@@ -0,0 +1,96 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+/// Sliding window over which join attempts are counted
+const JOIN_WINDOW: Duration = Duration::from_secs(10);
+/// Joins allowed per IP inside one window before a cooldown kicks in
+const MAX_JOINS_PER_WINDOW: usize = 5;
+/// First cooldown; doubles with every repeated offence
+const BASE_COOLDOWN: Duration = Duration::from_secs(5);
+const MAX_COOLDOWN: Duration = Duration::from_secs(300);
+/// How often stale IP entries are swept out
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+struct JoinHistory {
+    recent: VecDeque<Instant>,
+    strikes: u32,
+    blocked_until: Option<Instant>,
+    last_seen: Instant,
+}
+
+/// Tracks `/stream` join attempts per IP so reconnect loops can't hammer room creation
+pub struct JoinGuard {
+    history: HashMap<IpAddr, JoinHistory>,
+    last_prune: Instant,
+}
+
+impl Default for JoinGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JoinGuard {
+    pub fn new() -> Self {
+        Self {
+            history: HashMap::new(),
+            last_prune: Instant::now(),
+        }
+    }
+
+    /// Record a join attempt. Returns `Err(retry_after)` if the IP is cooling down
+    pub fn check(&mut self, ip: IpAddr) -> Result<(), Duration> {
+        let now = Instant::now();
+        self.prune(now);
+
+        let entry = self.history.entry(ip).or_insert_with(|| JoinHistory {
+            recent: VecDeque::new(),
+            strikes: 0,
+            blocked_until: None,
+            last_seen: now,
+        });
+
+        // Forgive old offences once the IP has behaved for a full max cooldown
+        if now.duration_since(entry.last_seen) > MAX_COOLDOWN {
+            entry.strikes = 0;
+        }
+        entry.last_seen = now;
+
+        if let Some(blocked_until) = entry.blocked_until {
+            if blocked_until > now {
+                return Err(blocked_until - now);
+            }
+            entry.blocked_until = None;
+        }
+
+        while entry.recent.front().is_some_and(|t| now.duration_since(*t) > JOIN_WINDOW) {
+            entry.recent.pop_front();
+        }
+
+        if entry.recent.len() >= MAX_JOINS_PER_WINDOW {
+            entry.strikes += 1;
+            let cooldown = BASE_COOLDOWN
+                .saturating_mul(1 << (entry.strikes - 1).min(16))
+                .min(MAX_COOLDOWN);
+            entry.blocked_until = Some(now + cooldown);
+            entry.recent.clear();
+            tracing::warn!("Join flood from {}: cooling down for {:?} (strike {})", ip, cooldown, entry.strikes);
+            return Err(cooldown);
+        }
+
+        entry.recent.push_back(now);
+        Ok(())
+    }
+
+    fn prune(&mut self, now: Instant) {
+        if now.duration_since(self.last_prune) < PRUNE_INTERVAL {
+            return;
+        }
+        self.last_prune = now;
+        self.history.retain(|_, entry| {
+            now.duration_since(entry.last_seen) <= MAX_COOLDOWN
+                || entry.blocked_until.is_some_and(|until| until > now)
+        });
+    }
+}
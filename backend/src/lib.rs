@@ -0,0 +1,23 @@
+pub mod admin;
+pub mod api_keys;
+pub mod assets;
+pub mod auth;
+pub mod bans;
+pub mod client_ip;
+pub mod config;
+pub mod discovery;
+pub mod file_writer;
+pub mod join_guard;
+pub mod listeners;
+pub mod lobby;
+pub mod profiles;
+pub mod redirect;
+pub mod revocations;
+pub mod routing;
+pub mod search;
+pub mod signed_token;
+pub mod storage;
+pub mod streaming;
+pub mod time_limits;
+pub mod uploads;
+pub mod watched_file;
@@ -0,0 +1,62 @@
+use std::net::TcpListener;
+#[cfg(unix)]
+use std::os::unix::net::UnixListener;
+
+/// A socket handed to us instead of one we bind ourselves
+pub enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+/// First file descriptor passed by systemd (`SD_LISTEN_FDS_START`)
+#[cfg(unix)]
+const LISTEN_FDS_START: i32 = 3;
+
+/// Sockets passed via systemd socket activation (`LISTEN_PID`/`LISTEN_FDS`), empty when not activated
+#[cfg(unix)]
+pub fn systemd_listeners() -> Vec<Listener> {
+    use std::os::fd::{FromRawFd, IntoRawFd};
+
+    let for_us = std::env::var("LISTEN_PID")
+        .ok()
+        .and_then(|pid| pid.parse::<u32>().ok())
+        .is_some_and(|pid| pid == std::process::id());
+    let count = std::env::var("LISTEN_FDS")
+        .ok()
+        .and_then(|count| count.parse::<i32>().ok())
+        .unwrap_or(0);
+    if !for_us || count <= 0 {
+        return Vec::new();
+    }
+    // Children (e.g. spawned tools) must not think the sockets are theirs
+    // SAFETY: nothing else in the process reads or writes these variables
+    unsafe {
+        std::env::remove_var("LISTEN_PID");
+        std::env::remove_var("LISTEN_FDS");
+        std::env::remove_var("LISTEN_FDNAMES");
+    }
+
+    (LISTEN_FDS_START..LISTEN_FDS_START + count)
+        .map(|fd| {
+            // SAFETY: LISTEN_PID matched our pid, so systemd passed LISTEN_FDS open listening sockets starting at
+            // fd 3; each is wrapped exactly once here and nothing else in the process refers to them
+            let tcp = unsafe { TcpListener::from_raw_fd(fd) };
+            // getsockname only yields an inet address for TCP sockets
+            if tcp.local_addr().is_ok() {
+                tcp.set_nonblocking(true).ok();
+                Listener::Tcp(tcp)
+            } else {
+                // SAFETY: `into_raw_fd` hands over sole ownership of the descriptor, which is still a listening socket
+                let unix = unsafe { UnixListener::from_raw_fd(tcp.into_raw_fd()) };
+                unix.set_nonblocking(true).ok();
+                Listener::Unix(unix)
+            }
+        })
+        .collect()
+}
+
+#[cfg(not(unix))]
+pub fn systemd_listeners() -> Vec<Listener> {
+    Vec::new()
+}
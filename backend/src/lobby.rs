@@ -0,0 +1,139 @@
+use std::time::Duration;
+use actix::{Actor, Addr, AsyncContext, Handler, Message, StreamHandler};
+use actix_web::web::{self, Data};
+use actix_web::{HttpRequest, HttpResponse};
+use actix_web_actors::ws;
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use crate::discovery::{room_listings, RoomListing};
+use crate::streaming::{RoomOwner, StreamingSession};
+
+/// How often room occupancy is compared against what lobby viewers last saw
+const LOBBY_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Pushed to `/lobby` clients; a snapshot on connect, then only what changed
+#[derive(Serialize, Message, Debug, Clone)]
+#[serde(tag = "action")]
+#[rtype(result = "()")]
+pub enum LobbyEvent {
+    #[serde(rename_all = "camelCase")]
+    LobbySnapshot { rooms: Vec<RoomListing> },
+    #[serde(rename_all = "camelCase")]
+    RoomCreated { room: RoomListing },
+    /// Anything but the player count changed, e.g. the room was locked
+    #[serde(rename_all = "camelCase")]
+    RoomUpdated { room: RoomListing },
+    #[serde(rename_all = "camelCase")]
+    RoomRemoved { room_id: String },
+    #[serde(rename_all = "camelCase")]
+    PlayerCountChanged { room_id: String, player_count: usize },
+}
+
+/// Landing-page connections and the room listings they were last sent
+#[derive(Default)]
+pub struct Lobby {
+    viewers: std::sync::Mutex<Vec<Addr<LobbySession>>>,
+    rooms: std::sync::Mutex<Vec<RoomListing>>,
+}
+
+/// What to tell viewers to get from `previous` to `current`
+fn diff(previous: &[RoomListing], current: &[RoomListing]) -> Vec<LobbyEvent> {
+    let mut events: Vec<LobbyEvent> = previous
+        .iter()
+        .filter(|room| !current.iter().any(|other| other.room_id == room.room_id))
+        .map(|room| LobbyEvent::RoomRemoved { room_id: room.room_id.clone() })
+        .collect();
+    for room in current {
+        let Some(before) = previous.iter().find(|other| other.room_id == room.room_id) else {
+            events.push(LobbyEvent::RoomCreated { room: room.clone() });
+            continue;
+        };
+        if before.player_count != room.player_count {
+            events.push(LobbyEvent::PlayerCountChanged {
+                room_id: room.room_id.clone(),
+                player_count: room.player_count,
+            });
+        }
+        let unchanged = RoomListing { player_count: before.player_count, ..room.clone() };
+        if unchanged != *before {
+            events.push(LobbyEvent::RoomUpdated { room: room.clone() });
+        }
+    }
+    events
+}
+
+/// Watch room occupancy and push changes to everyone on the landing page
+pub fn spawn_lobby_updater(owner: Data<Mutex<RoomOwner<StreamingSession>>>, lobby: Data<Lobby>) {
+    actix::spawn(async move {
+        let mut interval = tokio::time::interval(LOBBY_REFRESH_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let current = room_listings(&*owner.lock().await);
+            // Viewers join under the rooms lock, so each gets either the old snapshot plus these
+            // events or the new snapshot, never a gap
+            let mut rooms = lobby.rooms.lock().unwrap();
+            let events = diff(&rooms, &current);
+            if events.is_empty() {
+                continue;
+            }
+            *rooms = current;
+            let mut viewers = lobby.viewers.lock().unwrap();
+            viewers.retain(|viewer| viewer.connected());
+            for viewer in viewers.iter() {
+                for event in &events {
+                    viewer.do_send(event.clone());
+                }
+            }
+        }
+    });
+}
+
+/// Read-only WebSocket for the landing page; nothing the client sends is acted on
+pub struct LobbySession {
+    lobby: Data<Lobby>,
+}
+
+impl Actor for LobbySession {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        let rooms = self.lobby.rooms.lock().unwrap();
+        self.lobby.viewers.lock().unwrap().push(ctx.address());
+        ctx.address().do_send(LobbyEvent::LobbySnapshot { rooms: rooms.clone() });
+    }
+
+    fn stopped(&mut self, ctx: &mut Self::Context) {
+        let address = ctx.address();
+        self.lobby.viewers.lock().unwrap().retain(|viewer| *viewer != address);
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for LobbySession {
+    fn handle(&mut self, item: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        match item {
+            Ok(ws::Message::Ping(msg)) => ctx.pong(&msg),
+            Ok(ws::Message::Close(reason)) => ctx.close(reason),
+            _ => (),
+        }
+    }
+}
+
+impl Handler<LobbyEvent> for LobbySession {
+    type Result = ();
+
+    fn handle(&mut self, msg: LobbyEvent, ctx: &mut Self::Context) -> Self::Result {
+        if let Ok(json) = serde_json::to_string(&msg) {
+            ctx.text(json);
+        }
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/lobby", web::get().to(lobby_handler));
+}
+
+async fn lobby_handler(req: HttpRequest, stream: web::Payload, lobby: Data<Lobby>) -> Result<HttpResponse, actix_web::Error> {
+    ws::start(LobbySession { lobby }, &req, stream)
+}
@@ -1,20 +1,41 @@
-mod streaming;
+use backend::{admin, api_keys, assets, auth, bans, client_ip, config, discovery, join_guard, listeners, lobby, profiles, redirect, revocations, routing, search, storage, streaming, time_limits, uploads};
 
 use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer, Responder};
+use actix_web::http::StatusCode;
 use actix_web::web::{Data, Query};
 use actix_web_actors::ws;
 use actix_cors::Cors;
-use actix_files as fs;
-use rheomesh::config::{CodecConfig, MediaConfig};
 use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 use tracing_actix_web::TracingLogger;
 use tracing_subscriber::prelude::*;
-use webrtc::api::media_engine;
-use webrtc::rtp_transceiver::rtp_codec::{RTCRtpCodecCapability, RTCRtpCodecParameters};
-use webrtc::rtp_transceiver::RTCPFeedback;
 
-use streaming::{RoomOwner, StreamingSession, PlayerData, FacialFeatures, fetch_xirsys_ice_servers};
+use api_keys::ApiKeyStore;
+use auth::VerifiedIdentity;
+use bans::BanList;
+use client_ip::TrustedProxies;
+use join_guard::JoinGuard;
+use listeners::Listener;
+use lobby::Lobby;
+use profiles::HandleStore;
+use revocations::RevocationList;
+use search::SearchLimiter;
+use routing::RoutingTable;
+use storage::{BlobStorage, LocalDiskStorage};
+use time_limits::TimeLimitStore;
+use uploads::UploadLimiter;
+use streaming::codecs::media_config;
+use streaming::echo::is_echo_room;
+use streaming::room::{is_waiting_room, waiting_room_id};
+use streaming::reconnect::{broadcast_shutdown, verify_reconnect_token};
+use streaming::roles::verify_role_token;
+use streaming::instances;
+use streaming::mutes::moderation_identity;
+use streaming::language::{localized_room_id, normalize_language, DEFAULT_LANGUAGE};
+use streaming::{spawn_audio_gain_loop, spawn_movement_tick_loop, spawn_publish_quality_loop, spawn_speaking_monitor, BandwidthProfile, SessionFeatures, WireProtocol, RoomOwner, StreamingSession, PlayerData, FacialFeatures, fetch_ice_servers, PublisherRegistry, spawn_hub_updater, ECHO_TEST_ROOM_ID, HUB_ROOM_ID};
+
+/// CPU cores assigned to each rheomesh worker by default
+const CORES_PER_WORKER: usize = 4;
 
 /// Query parameters for joining a room
 #[derive(Deserialize)]
@@ -28,30 +49,94 @@ struct PlayerJoinQuery {
     mouth_style: String,
     #[serde(default = "default_character_type")]
     character_type: String,
+    /// Join a specific active room directly (used by hub portals)
+    room: Option<String>,
+    #[serde(default)]
+    bandwidth: BandwidthProfile,
+    /// ISO 639-1 code; themed rooms are split per language (`music-lounge-es`)
+    language: Option<String>,
+    /// Code from another device's TransferSession, to take over that player
+    transfer_code: Option<String>,
+    /// Token from `RoomState`/`ServerShutdown`/`SessionExpired`, returns the player to the room they dropped out of
+    reconnect_token: Option<String>,
+    /// Stable per-browser ID, used for time limits that persist across reconnects
+    profile_id: Option<String>,
+    /// The PIN the profile set, proving it's this player's without a token (needed for its handle)
+    profile_pin: Option<String>,
+    /// Token from `RoomState`, picks a dropped session back up inside its grace window
+    resume_token: Option<String>,
+    /// Per-tab ID that survives reloads, so publishers lost in a server crash can be restored
+    session_key: Option<String>,
+    /// `msgpack` to receive movement and other game state as binary frames
+    #[serde(default)]
+    protocol: WireProtocol,
+    /// Wait in `<room>-waiting` instead of being turned away when the room is locked
+    #[serde(default)]
+    waiting_room: bool,
+    /// Signed token from the admin API granting a role such as moderator
+    role_token: Option<String>,
+    /// JWT from the deployment's auth service (see `[auth]`); its claims replace `name`, `profileId` and the role
+    token: Option<String>,
+    /// Passphrase for a password-protected room; can also be sent later with `Authenticate`
+    password: Option<String>,
 }
 
-fn default_character_type() -> String {
-    "cat".to_string()
+/// Per-connection settings every join applies, whichever room it lands in
+fn configure_session(
+    session: StreamingSession,
+    query: &PlayerJoinQuery,
+    identity: &VerifiedIdentity,
+    time_limits: &Data<std::sync::Mutex<TimeLimitStore>>,
+    revocations: &Data<std::sync::RwLock<RevocationList>>,
+    publisher_registry: &PublisherRegistry,
+) -> StreamingSession {
+    session
+        .with_time_limits(query.profile_id.clone(), identity, time_limits.clone())
+        .with_revocations(identity, revocations.clone())
+        .with_publisher_registry(query.session_key.clone(), publisher_registry)
+        .with_features(SessionFeatures::legacy(query.protocol))
 }
 
-/// Map activity to themed room
-fn activity_to_room(activity: &str) -> (&'static str, &'static str) {
-    let activity_lower = activity.to_lowercase();
-    if activity_lower.contains("music") || activity_lower.contains("guitar") || activity_lower.contains("piano") {
-        ("music-lounge", "Music Lounge")
-    } else if activity_lower.contains("art") || activity_lower.contains("draw") || activity_lower.contains("paint") {
-        ("art-studio", "Art Studio")
-    } else if activity_lower.contains("code") || activity_lower.contains("program") || activity_lower.contains("study") {
-        ("focus-den", "Focus Den")
-    } else if activity_lower.contains("game") || activity_lower.contains("gaming") {
-        ("gaming-corner", "Gaming Corner")
-    } else if activity_lower.contains("watching") || activity_lower.contains("movie") || activity_lower.contains("judge") || activity_lower.contains("judging") {
-        ("cinema", "Cinema")
-    } else if activity_lower.contains("party") || activity_lower.contains("city") || activity_lower.contains("walking") {
-        ("city", "City")
-    } else {
-        ("hangout-hub", "Hangout Hub")
+/// Per-room background work every newly created room needs
+fn spawn_room_loops(room: &std::sync::Arc<streaming::room::Room<StreamingSession>>) {
+    spawn_audio_gain_loop(room);
+    spawn_movement_tick_loop(room);
+    spawn_publish_quality_loop(room);
+    spawn_speaking_monitor(room);
+}
+
+/// Open every routed room up front so the first players don't wait on router setup
+async fn precreate_rooms(room_data: &Data<Mutex<RoomOwner<StreamingSession>>>, routing: &config::RoomRouting) -> usize {
+    let mut rooms: Vec<(String, String)> = routing.routes.iter().map(|route| (route.id.clone(), route.name.clone())).collect();
+    rooms.push((routing.fallback_id.clone(), routing.fallback_name.clone()));
+    // Echo tests get a private room per join, so there's nothing to pre-create
+    rooms.retain(|(room_id, _)| room_id != ECHO_TEST_ROOM_ID);
+    rooms.sort();
+    rooms.dedup_by(|a, b| a.0 == b.0);
+
+    let mut owner = room_data.lock().await;
+    let mut created = 0;
+    for (room_id, theme) in &rooms {
+        match owner.create_new_room(room_id.clone(), theme.clone(), media_config(room_id)).await {
+            Ok(room) => {
+                owner.mark_standing(room_id);
+                spawn_room_loops(&room);
+                created += 1;
+            }
+            Err(e) => tracing::error!("Couldn't pre-create room {}: {}", room_id, e),
+        }
     }
+    created
+}
+
+/// Joins that would need a new room while no worker can host one
+fn no_media_workers(error: String) -> HttpResponse {
+    tracing::error!("Refusing join, can't create a room: {}", error);
+    HttpResponse::ServiceUnavailable().body("The server can't host calls right now, try again shortly")
+}
+
+fn default_character_type() -> String {
+    "cat".to_string()
 }
 
 #[derive(Deserialize)]
@@ -69,6 +154,15 @@ async fn index() -> impl Responder {
     HttpResponse::Ok().body("WebHangin Server - Ready to stream!")
 }
 
+/// Load balancer health check: fails while none of the media workers respond to probes
+async fn health(room_owner: Data<Mutex<RoomOwner<StreamingSession>>>) -> HttpResponse {
+    if room_owner.lock().await.is_healthy() {
+        HttpResponse::Ok().body("ok")
+    } else {
+        HttpResponse::ServiceUnavailable().body("no media workers are responding")
+    }
+}
+
 async fn handle_click(payload: web::Json<ClickRequest>) -> web::Json<ClickResponse> {
     println!("🍩 Backend received click! Message: {}", payload.message);
     println!("🎉 Processing donut click at {}", chrono::Local::now().format("%Y-%m-%d %H:%M:%S"));
@@ -81,9 +175,81 @@ async fn handle_click(payload: web::Json<ClickRequest>) -> web::Json<ClickRespon
 async fn websocket_handler(
     req: HttpRequest,
     room_owner: Data<Mutex<RoomOwner<StreamingSession>>>,
+    join_guard: Data<std::sync::Mutex<JoinGuard>>,
+    time_limits: Data<std::sync::Mutex<TimeLimitStore>>,
+    handles: Data<std::sync::Mutex<HandleStore>>,
+    revocations: Data<std::sync::RwLock<RevocationList>>,
+    bans: Data<std::sync::RwLock<BanList>>,
+    publisher_registry: Data<PublisherRegistry>,
+    routing: Data<std::sync::RwLock<RoutingTable>>,
+    trusted_proxies: Data<TrustedProxies>,
     stream: web::Payload,
     query: Query<PlayerJoinQuery>,
-) -> impl Responder {
+) -> Result<HttpResponse, actix_web::Error> {
+    // Reject reconnect loops before they trigger room creation or ICE work
+    let client_ip = trusted_proxies.client_ip(&req);
+    if let Some(client_ip) = client_ip {
+        if let Err(retry_after) = join_guard.lock().unwrap().check(client_ip) {
+            return Ok(HttpResponse::TooManyRequests()
+                .insert_header(("Retry-After", retry_after.as_secs().max(1).to_string()))
+                .body("Too many join attempts, slow down"));
+        }
+    }
+
+    // A valid token decides who the player is; the name/profileId params are only trusted without one
+    let mut query = query.into_inner();
+    let mut role = query.role_token.as_deref().and_then(verify_role_token).unwrap_or_default();
+    let mut identity = VerifiedIdentity::default();
+    if let Some(verifier) = auth::verifier() {
+        match query.token.as_deref().map(|token| verifier.verify(token)) {
+            Some(Ok(claims)) => {
+                if let Some(name) = claims.name {
+                    query.name = name;
+                }
+                query.profile_id = Some(claims.sub.clone());
+                identity = VerifiedIdentity { profile_id: Some(claims.sub), token_id: claims.jti };
+                role = claims.role;
+            }
+            Some(Err(e)) => {
+                tracing::info!("Rejected join token from {:?}: {}", client_ip, e);
+                return Ok(HttpResponse::Unauthorized().body("Invalid or expired token"));
+            }
+            None if config::get().auth.require_token => {
+                return Ok(HttpResponse::Unauthorized().body("A token is required to join"));
+            }
+            None => {}
+        }
+    }
+    // Without a token, a profile is only taken at its word once it gives the PIN it set
+    if identity.profile_id.is_none() {
+        if let (Some(profile_id), Some(pin)) = (&query.profile_id, &query.profile_pin) {
+            if time_limits.lock().unwrap().verify_pin(profile_id, pin) {
+                identity.profile_id = Some(profile_id.clone());
+            }
+        }
+    }
+
+    // Checked after the token so a banned identity can't come back under a new name
+    if let Some(ban) = bans.read().unwrap().find(client_ip, identity.profile_id.as_deref()) {
+        tracing::info!("Turned away banned join from {:?} (ban {})", client_ip, ban.id);
+        return Ok(HttpResponse::Forbidden().body("You are banned from this server"));
+    }
+
+    // Revoked accounts and tokens are turned away on every node that shares the revocations file. Only a
+    // verified identity is checked: an unverified profileId could simply be swapped for another
+    let revoked = identity
+        .profile_id
+        .as_deref()
+        .is_some_and(|profile_id| revocations.read().unwrap().is_revoked(profile_id, identity.token_id.as_deref()));
+    if revoked {
+        return Ok(HttpResponse::Forbidden().body("This account has been signed out"));
+    }
+    if identity.profile_id.is_none() && config::get().auth.enforce_revocations {
+        return Ok(HttpResponse::Unauthorized().body("Sign in to join this server"));
+    }
+
+    let language = normalize_language(query.language.as_deref().unwrap_or(DEFAULT_LANGUAGE));
+
     // Extract player data from query params
     let player_data = PlayerData {
         id: String::new(), // Will be set by Room::add_player
@@ -99,42 +265,183 @@ async fn websocket_handler(
         position: Default::default(),
         rotation: 0.0,
         is_moving: false,
+        language: language.clone(),
+        // Anyone can send someone else's profileId, so only a verified profile shows its handle
+        handle: identity.profile_id.as_deref().and_then(|profile_id| handles.lock().unwrap().handle_for(profile_id)),
+        role,
+        status: Default::default(),
+        moderation_identity: moderation_identity(identity.profile_id.as_deref(), client_ip),
+    };
+
+    // A dropped connection coming back inside its grace window keeps its player, publishers and subscriptions
+    if let Some(resume_token) = &query.resume_token {
+        let mut owner = room_owner.lock().await;
+        if let Some(parked) = owner.resume_session(resume_token) {
+            let room = owner.find_by_id(parked.room_id.clone());
+            drop(owner);
+            match room.and_then(|room| room.get_player_data(&parked.player_id).map(|player_data| (room, player_data))) {
+                Some((room, player_data)) => {
+                    tracing::info!("Player {} resumed in room {}", &parked.player_id[..8.min(parked.player_id.len())], room.id);
+                    let ice_servers = RoomOwner::session_ice_servers(&room_owner).await;
+                    let server = StreamingSession::resume(room, room_owner.clone(), parked, player_data, ice_servers, query.bandwidth);
+                    let server = configure_session(server, &query, &identity, &time_limits, &revocations, &publisher_registry);
+                    return ws::start(server, &req, stream);
+                }
+                // The room went away underneath the parked session, so join fresh below
+                None => {
+                    actix::spawn(parked.media.close());
+                }
+            }
+        }
+    }
+
+    // Device handoff: rejoin the other device's room as the same player
+    if let Some(transfer_code) = &query.transfer_code {
+        let (room, player_id) = {
+            let mut owner = room_owner.lock().await;
+            let transfer = owner.redeem_transfer_code(transfer_code);
+            let room = transfer.as_ref().and_then(|transfer| owner.find_by_id(transfer.room_id.clone()));
+            (room, transfer.map(|transfer| transfer.player_id))
+        };
+        let (Some(room), Some(player_id)) = (room, player_id) else {
+            return Ok(HttpResponse::Gone().body("Transfer code is invalid or expired"));
+        };
+        tracing::info!("Player {} continuing on another device in room {}", &player_id[..8.min(player_id.len())], room.id);
+        let ice_servers = RoomOwner::session_ice_servers(&room_owner).await;
+        let server = StreamingSession::new(room, room_owner.clone(), player_data, ice_servers, query.bandwidth)
+            .await
+            .with_transfer(player_id);
+        let server = configure_session(server, &query, &identity, &time_limits, &revocations, &publisher_registry);
+        return ws::start(server, &req, stream);
+    }
+
+    // Route to themed room based on activity, split by language
+    let (base_room_id, room_theme) = {
+        let routing = routing.read().unwrap();
+        let (room_id, theme) = routing.route(&query.activity);
+        (room_id.to_string(), theme.to_string())
+    };
+    let room_id = match base_room_id.as_str() {
+        // Utility rooms are shared across languages
+        ECHO_TEST_ROOM_ID | HUB_ROOM_ID => base_room_id,
+        _ => match config::get().server.max_players_for(&base_room_id) {
+            Some(max_players) => room_owner.lock().await.instance_with_space(&base_room_id, &language, max_players),
+            None => localized_room_id(&base_room_id, &language),
+        },
     };
+    tracing::info!("Player {} joining room {} (activity: {}, ip: {:?})", query.name, room_id, query.activity, client_ip);
 
-    // Route to themed room based on activity
-    let (room_id, room_theme) = activity_to_room(&query.activity);
-    tracing::info!("Player {} joining room {} (activity: {})", query.name, room_id, query.activity);
+    // Fresh credentials per connection, so long-running servers survive provider rotations
+    let ice_servers = RoomOwner::session_ice_servers(&room_owner).await;
+
+    // Hub portals send players straight to an existing room, bypassing activity routing; reconnecting
+    // players go back to the room they dropped out of
+    let requested_id = query
+        .room
+        .clone()
+        .or_else(|| query.reconnect_token.as_deref().and_then(verify_reconnect_token))
+        // Echo tests are private to the player who opened them, and waiting rooms are only entered through a lock
+        .filter(|requested_id| !is_echo_room(requested_id) && !is_waiting_room(requested_id));
+    let requested = match requested_id {
+        // Rooms on a failed worker only keep the players already in them
+        Some(requested_id) => {
+            let owner = room_owner.lock().await;
+            owner.find_by_id(requested_id).filter(|room| owner.is_room_worker_healthy(&room.id))
+        }
+        None => None,
+    };
+    // A full room can't be joined directly either; activity routing picks an instance with space
+    let requested = requested.filter(|room| {
+        config::get()
+            .server
+            .max_players_for(instances::base_room_id(&room.id))
+            .is_none_or(|max_players| room.player_count() < max_players)
+    });
 
-    // Get ICE servers from the owner
-    let ice_servers = {
-        let owner = room_owner.lock().await;
-        owner.get_ice_servers()
+    let find = match requested {
+        Some(room) => Some(room),
+        None => room_owner
+            .as_ref()
+            .lock()
+            .await
+            .find_by_id(room_id.clone()),
     };
 
-    let find = room_owner
-        .as_ref()
-        .lock()
-        .await
-        .find_by_id(room_id.to_string());
+    let config = media_config(&room_id);
+
+    // Echo tests are private: every join gets its own throwaway room
+    if room_id == ECHO_TEST_ROOM_ID {
+        let echo_room_id = format!("{}-{}", ECHO_TEST_ROOM_ID, &uuid::Uuid::new_v4().to_string()[..8]);
+        let room = match room_owner.lock().await.create_new_room(echo_room_id, room_theme.to_string(), config).await {
+            Ok(room) => room,
+            Err(e) => return Ok(no_media_workers(e)),
+        };
+        let server = StreamingSession::new(room, room_owner.clone(), player_data, ice_servers, query.bandwidth)
+            .await;
+        let server = configure_session(server, &query, &identity, &time_limits, &revocations, &publisher_registry);
+        return ws::start(server, &req, stream);
+    }
 
-    let mut config = MediaConfig::default();
-    config.codec = CodecConfig {
-        audio: audio_codecs(),
-        video: video_codecs(),
+    // Locked rooms keep their members but turn new joins away, or into a waiting room on request
+    let find = match find {
+        Some(room) if room.is_locked() => {
+            if !query.waiting_room {
+                return Ok(HttpResponse::build(StatusCode::LOCKED).body("This room is locked by its host, try again later"));
+            }
+            let waiting_id = waiting_room_id(&room.id);
+            let mut owner = room_owner.lock().await;
+            let waiting_room = match owner.find_by_id(waiting_id.clone()) {
+                Some(waiting_room) => waiting_room,
+                None => {
+                    let waiting_room = match owner.create_new_room(waiting_id.clone(), room.theme.clone(), media_config(&room.id)).await {
+                        Ok(waiting_room) => waiting_room,
+                        Err(e) => return Ok(no_media_workers(e)),
+                    };
+                    spawn_room_loops(&waiting_room);
+                    waiting_room
+                }
+            };
+            drop(owner);
+            tracing::info!("Room {} is locked, sending player to {}", room.id, waiting_id);
+            let server = StreamingSession::new(waiting_room, room_owner.clone(), player_data, ice_servers, query.bandwidth)
+                .await;
+            let server = configure_session(server, &query, &identity, &time_limits, &revocations, &publisher_registry);
+            return ws::start(server, &req, stream);
+        }
+        other => other,
     };
 
     match find {
+        Some(room) if !room_owner.lock().await.is_room_worker_healthy(&room.id) => {
+            tracing::warn!("Room {} is on an unhealthy worker, not routing new joins to it", room.id);
+            Ok(HttpResponse::ServiceUnavailable().body("This room's server isn't responding, try again shortly"))
+        }
         Some(room) => {
             tracing::info!("Room found, so joining it: {}", room_id);
-            let server = StreamingSession::new(room, room_owner.clone(), player_data, ice_servers).await;
+            let server = StreamingSession::new(room, room_owner.clone(), player_data, ice_servers, query.bandwidth)
+                .await
+                .with_password(query.password.clone());
+            let server = configure_session(server, &query, &identity, &time_limits, &revocations, &publisher_registry);
             ws::start(server, &req, stream)
         }
         None => {
-            let owner = room_owner.clone();
-            let mut owner = owner.lock().await;
-            let room = owner.create_new_room(room_id.to_string(), room_theme.to_string(), config).await;
+            let mut owner = room_owner.lock().await;
+            // Another join may have opened it since the lookup above
+            let room = match owner.find_by_id(room_id.clone()) {
+                Some(room) => room,
+                None => {
+                    let room = match owner.create_new_room(room_id.clone(), room_theme.to_string(), config).await {
+                        Ok(room) => room,
+                        Err(e) => return Ok(no_media_workers(e)),
+                    };
+                    spawn_room_loops(&room);
+                    room
+                }
+            };
             drop(owner); // Release lock before creating session
-            let server = StreamingSession::new(room, room_owner.clone(), player_data, ice_servers).await;
+            let server = StreamingSession::new(room, room_owner.clone(), player_data, ice_servers, query.bandwidth)
+                .await;
+            let server = configure_session(server, &query, &identity, &time_limits, &revocations, &publisher_registry);
             ws::start(server, &req, stream)
         }
     }
@@ -159,24 +466,91 @@ async fn main() -> std::io::Result<()> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    // Fetch TURN servers from Xirsys
-    println!("🔄 Fetching TURN servers from Xirsys...");
-    let ice_servers = fetch_xirsys_ice_servers().await;
-    println!("✅ Configured {} ICE server groups", ice_servers.len());
+    let config = config::init()?;
+    let routing = Data::new(std::sync::RwLock::new(RoutingTable::load(&config.rooms)));
+    println!("📝 Routing activities to {} themed room(s)", routing.read().unwrap().routing().routes.len());
 
-    // Initialize Rheomesh worker
-    let worker = rheomesh::worker::Worker::new(rheomesh::config::WorkerConfig::default())
-        .await
-        .expect("Failed to create worker");
-    let room_owner: RoomOwner<StreamingSession> = RoomOwner::new(worker, ice_servers);
+    // TURN servers from the configured provider (refreshed in the background)
+    println!("🔄 Fetching TURN servers...");
+    let ice_servers = fetch_ice_servers().await;
+    println!("✅ Configured {} ICE server groups", ice_servers.current().len());
+
+    // Initialize Rheomesh workers (one per core group unless configured)
+    let worker_count = config
+        .server
+        .workers
+        .unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|cores| cores.get() / CORES_PER_WORKER)
+                .unwrap_or(1)
+        })
+        .max(1);
+    let worker_config = config.server.worker_config();
+    let mut workers = Vec::with_capacity(worker_count);
+    for _ in 0..worker_count {
+        let worker = rheomesh::worker::Worker::new(worker_config.clone())
+            .await
+            .expect("Failed to create worker");
+        workers.push(worker);
+    }
+    println!("⚙️  Started {} rheomesh worker(s)", worker_count);
+    let room_owner: RoomOwner<StreamingSession> = RoomOwner::new(workers, worker_config, ice_servers);
     let room_data = Data::new(Mutex::new(room_owner));
+    RoomOwner::spawn_worker_health_monitor(room_data.clone());
+    RoomOwner::spawn_ice_refresh(room_data.clone());
+    RoomOwner::spawn_turn_health_monitor(room_data.clone());
+    // Small deployments can release workers while nobody is connected (off by default)
+    let idle_shutdown = Some(config.server.idle_shutdown_secs)
+        .filter(|secs| *secs > 0)
+        .map(std::time::Duration::from_secs);
+    if let Some(idle_timeout) = idle_shutdown {
+        println!("💤 Idle shutdown after {:?} without sessions", idle_timeout);
+        RoomOwner::spawn_idle_monitor(room_data.clone(), idle_timeout);
+    }
+    if config.server.precreate_rooms && idle_shutdown.is_none() {
+        let room_routing = routing.read().unwrap().routing().clone();
+        let count = precreate_rooms(&room_data, &room_routing).await;
+        println!("🏠 Pre-created {} room(s)", count);
+    }
+    spawn_hub_updater(room_data.clone());
+    let lobby = Data::new(Lobby::default());
+    lobby::spawn_lobby_updater(room_data.clone(), lobby.clone());
+    let join_guard = Data::new(std::sync::Mutex::new(JoinGuard::new()));
+    let search_limiter = Data::new(std::sync::Mutex::new(SearchLimiter::default()));
+    let upload_limiter = Data::new(std::sync::Mutex::new(UploadLimiter::default()));
+    let api_keys = Data::new(std::sync::RwLock::new(ApiKeyStore::load()));
+    let time_limits = Data::new(std::sync::Mutex::new(TimeLimitStore::load()));
+    let handles = Data::new(std::sync::Mutex::new(HandleStore::load()));
+    let revocations = Data::new(std::sync::RwLock::new(RevocationList::load()));
+    revocations::spawn_revocations_watcher(revocations.clone());
+    let bans = Data::new(std::sync::RwLock::new(BanList::load()));
+    bans::spawn_bans_watcher(bans.clone());
+    let publisher_registry = Data::new(PublisherRegistry::connect().await);
+    routing::spawn_routes_watcher(routing.clone());
+    let trusted_proxies = Data::new(TrustedProxies::from_env());
+    let storage: Data<dyn BlobStorage> = Data::from(std::sync::Arc::new(LocalDiskStorage::from_env()?) as std::sync::Arc<dyn BlobStorage>);
+
+    // With an admin address set (e.g. `127.0.0.1:3002`), admin endpoints are only reachable there
+    let bind_addrs = &config.server.bind_addrs;
+    let admin_bind_addr = config.server.admin_bind_addr.as_deref();
+    let public_admin = admin_bind_addr.is_none();
 
-    println!("🚀 WebHangin server starting on http://0.0.0.0:3001");
-    println!("📡 WebSocket: ws://0.0.0.0:3001/stream");
-    println!("🌐 Frontend: http://0.0.0.0:3001/");
+    for addr in bind_addrs {
+        println!("🚀 WebHangin server starting on http://{}", addr);
+    }
+    println!("📡 WebSocket: /stream");
+    println!("📡 Lobby feed: /lobby");
     println!("💡 Run 'npm run build' in frontend/ to update the static files");
 
-    HttpServer::new(move || {
+    let admin_room_data = room_data.clone();
+    let admin_api_keys = api_keys.clone();
+    let admin_routing = routing.clone();
+    let admin_revocations = revocations.clone();
+    let admin_bans = bans.clone();
+    let admin_search_limiter = search_limiter.clone();
+    let shutdown_room_data = room_data.clone();
+
+    let server = HttpServer::new(move || {
         let cors = Cors::default()
             .allow_any_origin()
             .allow_any_method()
@@ -187,68 +561,116 @@ async fn main() -> std::io::Result<()> {
             .wrap(cors)
             // API routes first (these take precedence over static files)
             .route("/api/click", web::post().to(handle_click))
+            .route("/api/health", web::get().to(health))
             .route("/stream", web::get().to(websocket_handler))
-            // Serve Next.js static export from frontend/out
-            .service(
-                fs::Files::new("/", "../frontend/out")
-                    .index_file("index.html")
-                    .use_last_modified(true)
-            )
+            .configure(|cfg| {
+                if public_admin {
+                    admin::configure(cfg);
+                    search::configure(cfg);
+                }
+            })
+            .configure(uploads::configure)
+            .configure(profiles::configure)
+            .configure(discovery::configure)
+            .configure(lobby::configure)
+            // Serve Next.js static export (disk or embedded)
+            .configure(assets::configure)
             .app_data(room_data.clone())
+            .app_data(join_guard.clone())
+            .app_data(search_limiter.clone())
+            .app_data(upload_limiter.clone())
+            .app_data(api_keys.clone())
+            .app_data(storage.clone())
+            .app_data(time_limits.clone())
+            .app_data(handles.clone())
+            .app_data(revocations.clone())
+            .app_data(bans.clone())
+            .app_data(publisher_registry.clone())
+            .app_data(routing.clone())
+            .app_data(trusted_proxies.clone())
+            .app_data(lobby.clone())
     })
-    .bind("0.0.0.0:3001")?
-    .run()
-    .await
-}
+    // Signals are handled below so clients can be told to back off before we go away
+    .disable_signals();
 
-fn audio_codecs() -> Vec<RTCRtpCodecParameters> {
-    vec![
-        RTCRtpCodecParameters {
-            capability: RTCRtpCodecCapability {
-                mime_type: media_engine::MIME_TYPE_OPUS.to_owned(),
-                clock_rate: 48000,
-                channels: 2,
-                sdp_fmtp_line: "minptime=10;useinbandfec=1".to_owned(),
-                rtcp_feedback: vec![],
-            },
-            payload_type: 111,
-            ..Default::default()
-        },
-    ]
+    // systemd socket activation replaces the default TCP bind
+    let activated = listeners::systemd_listeners();
+    let mut server = if activated.is_empty() {
+        bind_addrs.iter().try_fold(server, |server, addr| server.bind(addr))?
+    } else {
+        println!("🔌 Using {} socket(s) from systemd", activated.len());
+        activated.into_iter().try_fold(server, |server, listener| match listener {
+            Listener::Tcp(listener) => server.listen(listener),
+            #[cfg(unix)]
+            Listener::Unix(listener) => server.listen_uds(listener),
+        })?
+    };
+
+    // Optional Unix socket for a reverse proxy on the same host
+    #[cfg(unix)]
+    if let Some(path) = &config.server.unix_socket_path {
+        // A socket file left over from a previous run would make bind fail
+        let _ = std::fs::remove_file(path);
+        server = server.bind_uds(path)?;
+        println!("🔌 Listening on unix:{}", path.display());
+    }
+
+    let mut servers = vec![server.run()];
+
+    if let Some(addr) = admin_bind_addr {
+        let admin_server = HttpServer::new(move || {
+            App::new()
+                .wrap(TracingLogger::default())
+                .configure(admin::configure)
+                .configure(search::configure)
+                .app_data(admin_room_data.clone())
+                .app_data(admin_api_keys.clone())
+                .app_data(admin_routing.clone())
+                .app_data(admin_revocations.clone())
+                .app_data(admin_bans.clone())
+                .app_data(admin_search_limiter.clone())
+        })
+        .workers(1)
+        .disable_signals()
+        .bind(addr)?
+        .run();
+        println!("🔐 Admin API on http://{}", addr);
+        servers.push(admin_server);
+    }
+
+    // Plain HTTP (usually :80) redirecting to HTTPS on HTTPS_PORT, for deployments terminating TLS in front of us
+    if let Some(addr) = &config.server.http_redirect_addr {
+        let https_port = config.server.https_port;
+        servers.push(redirect::https_redirect_server(addr, https_port)?);
+        println!("↪️  Redirecting http://{} to HTTPS port {}", addr, https_port);
+    }
+
+    let handles = servers.iter().map(|server| server.handle()).collect();
+    actix::spawn(shutdown_on_signal(handles, shutdown_room_data));
+    futures_util::future::try_join_all(servers).await?;
+    Ok(())
 }
 
-fn video_codecs() -> Vec<RTCRtpCodecParameters> {
-    let video_rtcp_feedback = vec![
-        RTCPFeedback {
-            typ: "goog-remb".to_owned(),
-            parameter: "".to_owned(),
-        },
-        RTCPFeedback {
-            typ: "ccm".to_owned(),
-            parameter: "fir".to_owned(),
-        },
-        RTCPFeedback {
-            typ: "nack".to_owned(),
-            parameter: "".to_owned(),
-        },
-        RTCPFeedback {
-            typ: "nack".to_owned(),
-            parameter: "pli".to_owned(),
-        },
-    ];
-    vec![
-        RTCRtpCodecParameters {
-            capability: RTCRtpCodecCapability {
-                mime_type: media_engine::MIME_TYPE_H264.to_owned(),
-                clock_rate: 90000,
-                channels: 0,
-                sdp_fmtp_line:
-                    "level-asymmetry-allowed=1;packetization-mode=1;profile-level-id=42001f"
-                        .to_owned(),
-                rtcp_feedback: video_rtcp_feedback.clone(),
-            },
-            payload_type: 102,
-            ..Default::default()
-        },
-    ]
+/// On SIGINT/SIGTERM, hand every client a reconnect policy before stopping, so they don't all
+/// retry the instant the server comes back
+async fn shutdown_on_signal(handles: Vec<actix_web::dev::ServerHandle>, room_data: Data<Mutex<RoomOwner<StreamingSession>>>) {
+    #[cfg(unix)]
+    {
+        let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+
+    println!("👋 Shutting down, sending reconnect hints");
+    broadcast_shutdown(&room_data).await;
+    // Give the messages a moment to flush before connections close
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    for handle in handles {
+        handle.stop(true).await;
+    }
 }
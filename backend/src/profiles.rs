@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use actix_web::web::{self, Data, Json};
+use actix_web::{HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::auth;
+use crate::file_writer;
+use crate::streaming::chat::EVERYONE_MENTION;
+use crate::time_limits::TimeLimitStore;
+
+const MIN_HANDLE_CHARS: usize = 3;
+const MAX_HANDLE_CHARS: usize = 20;
+
+/// Short public names (`@handle`) claimed by profiles; profile IDs stay the private key
+pub struct HandleStore {
+    /// profile_id -> handle
+    handles: HashMap<String, String>,
+    path: PathBuf,
+}
+
+pub enum ClaimError {
+    Invalid,
+    Taken,
+}
+
+/// Handles are lowercase ASCII letters, digits and underscores so mentions parse unambiguously
+pub fn normalize_handle(handle: &str) -> Option<String> {
+    let handle = handle.trim().trim_start_matches('@').to_ascii_lowercase();
+    let valid = (MIN_HANDLE_CHARS..=MAX_HANDLE_CHARS).contains(&handle.len())
+        && handle.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && handle != EVERYONE_MENTION;
+    valid.then_some(handle)
+}
+
+impl HandleStore {
+    /// Load from `HANDLES_FILE` (default `handles.json`)
+    pub fn load() -> Self {
+        let path = PathBuf::from(std::env::var("HANDLES_FILE").unwrap_or_else(|_| "handles.json".to_string()));
+        let handles = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                tracing::error!("Failed to parse {}: {}", path.display(), e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        Self { handles, path }
+    }
+
+    pub fn handle_for(&self, profile_id: &str) -> Option<String> {
+        self.handles.get(profile_id).cloned()
+    }
+
+    /// Claim a handle for a profile, replacing its previous one; handles are unique across profiles
+    pub fn claim(&mut self, profile_id: &str, handle: &str) -> Result<String, ClaimError> {
+        let handle = normalize_handle(handle).ok_or(ClaimError::Invalid)?;
+        if self.handles.iter().any(|(owner, taken)| *taken == handle && owner != profile_id) {
+            return Err(ClaimError::Taken);
+        }
+        self.handles.insert(profile_id.to_string(), handle.clone());
+        self.save();
+        Ok(handle)
+    }
+
+    /// Queue a snapshot for the writer thread, so claims don't hold the store's lock across disk writes
+    fn save(&self) {
+        match serde_json::to_string_pretty(&self.handles) {
+            Ok(json) => file_writer::replace(self.path.clone(), json),
+            Err(e) => tracing::error!("Failed to serialize handles: {}", e),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfileResponse {
+    profile_id: String,
+    handle: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ClaimHandleRequest {
+    handle: String,
+    /// The profile's PIN, when the caller has no join token for it
+    pin: Option<String>,
+}
+
+/// Register profile endpoints
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/api/profiles/{profile_id}", web::get().to(get_profile))
+        .route("/api/profiles/{profile_id}/handle", web::put().to(claim_handle));
+}
+
+async fn get_profile(profile_id: web::Path<String>, handles: Data<std::sync::Mutex<HandleStore>>) -> HttpResponse {
+    let profile_id = profile_id.into_inner();
+    let handle = handles.lock().unwrap().handle_for(&profile_id);
+    HttpResponse::Ok().json(ProfileResponse { profile_id, handle })
+}
+
+/// Whether the caller proved they own the profile: a join token (`Authorization: Bearer`) for it, or its PIN
+fn owns_profile(req: &HttpRequest, profile_id: &str, pin: Option<&str>, time_limits: &std::sync::Mutex<TimeLimitStore>) -> bool {
+    let token = req
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let token_subject = token
+        .zip(auth::verifier())
+        .and_then(|(token, verifier)| verifier.verify(token.trim()).ok())
+        .map(|claims| claims.sub);
+    token_subject.as_deref() == Some(profile_id) || pin.is_some_and(|pin| time_limits.lock().unwrap().verify_pin(profile_id, pin))
+}
+
+async fn claim_handle(
+    req: HttpRequest,
+    profile_id: web::Path<String>,
+    body: Json<ClaimHandleRequest>,
+    handles: Data<std::sync::Mutex<HandleStore>>,
+    time_limits: Data<std::sync::Mutex<TimeLimitStore>>,
+) -> HttpResponse {
+    let profile_id = profile_id.into_inner();
+    if !owns_profile(&req, &profile_id, body.pin.as_deref(), &time_limits) {
+        return HttpResponse::Unauthorized().body("sign in or give the profile's PIN to claim a handle");
+    }
+    let result = handles.lock().unwrap().claim(&profile_id, &body.handle);
+    match result {
+        Ok(handle) => {
+            tracing::info!("Profile claimed handle @{}", handle);
+            HttpResponse::Ok().json(ProfileResponse { profile_id, handle: Some(handle) })
+        }
+        Err(ClaimError::Taken) => HttpResponse::Conflict().body("handle is already taken"),
+        Err(ClaimError::Invalid) => HttpResponse::BadRequest().body("handles are 3-20 letters, digits or underscores"),
+    }
+}
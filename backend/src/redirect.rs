@@ -0,0 +1,34 @@
+use actix_web::dev::Server;
+use actix_web::http::header;
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+
+/// Plain-HTTP listener (usually `:80`) that sends every request to the same path over HTTPS
+pub fn https_redirect_server(addr: &str, https_port: u16) -> std::io::Result<Server> {
+    let server = HttpServer::new(move || {
+        App::new().default_service(web::to(move |req: HttpRequest| async move { redirect(&req, https_port) }))
+    })
+    .workers(1)
+    .disable_signals()
+    .bind(addr)?
+    .run();
+    Ok(server)
+}
+
+fn redirect(req: &HttpRequest, https_port: u16) -> HttpResponse {
+    let host = req.connection_info().host().to_string();
+    // Drop any port the client used for plain HTTP
+    let hostname = match host.rsplit_once(':') {
+        // `[::1]` alone has colons but no port
+        Some((hostname, _)) if !host.ends_with(']') => hostname,
+        _ => host.as_str(),
+    };
+    let authority = if https_port == 443 {
+        hostname.to_string()
+    } else {
+        format!("{}:{}", hostname, https_port)
+    };
+    let path = req.uri().path_and_query().map(|pq| pq.as_str()).unwrap_or("/");
+    HttpResponse::PermanentRedirect()
+        .insert_header((header::LOCATION, format!("https://{}{}", authority, path)))
+        .finish()
+}
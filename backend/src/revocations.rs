@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use actix_web::web::{self, Data};
+use serde::{Deserialize, Serialize};
+
+use crate::file_writer;
+use crate::watched_file::{modified_time, read_json, read_json_if_changed};
+
+/// How often the revocations file is checked for entries written by other nodes
+const RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Revocation {
+    /// Unix seconds
+    pub revoked_at: u64,
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Revocations {
+    /// Verified profile ID (a token's `sub`) -> revocation
+    #[serde(default)]
+    pub accounts: HashMap<String, Revocation>,
+    /// Token `jti` -> revocation, for a leaked token when the rest of the account is fine
+    #[serde(default)]
+    pub tokens: HashMap<String, Revocation>,
+}
+
+/// Accounts and individual tokens that may not join and get disconnected wherever they're connected.
+/// Persisted to `REVOCATIONS_FILE` (default `revocations.json`); nodes sharing that file pick up
+/// each other's revocations on their next reload, and live sessions re-check periodically
+pub struct RevocationList {
+    revoked: Revocations,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    /// Bumped on every save, so the watcher can tell its read went stale while it was off the lock
+    saves: u64,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|since| since.as_secs()).unwrap_or(0)
+}
+
+impl RevocationList {
+    /// Load from `REVOCATIONS_FILE` (default `revocations.json`)
+    pub fn load() -> Self {
+        let path = PathBuf::from(std::env::var("REVOCATIONS_FILE").unwrap_or_else(|_| "revocations.json".to_string()));
+        Self {
+            revoked: read_json(&path),
+            modified: modified_time(&path),
+            path,
+            saves: 0,
+        }
+    }
+
+    /// Whether the account or the token it joined with was revoked
+    pub fn is_revoked(&self, profile_id: &str, token_id: Option<&str>) -> bool {
+        self.revoked.accounts.contains_key(profile_id) || token_id.is_some_and(|token_id| self.revoked.tokens.contains_key(token_id))
+    }
+
+    pub fn list(&self) -> &Revocations {
+        &self.revoked
+    }
+
+    pub fn revoke(&mut self, profile_id: &str, reason: Option<String>) {
+        let revocation = Revocation { revoked_at: now_secs(), reason };
+        self.revoked.accounts.insert(profile_id.to_string(), revocation);
+        self.save();
+    }
+
+    pub fn revoke_token(&mut self, token_id: &str, reason: Option<String>) {
+        let revocation = Revocation { revoked_at: now_secs(), reason };
+        self.revoked.tokens.insert(token_id.to_string(), revocation);
+        self.save();
+    }
+
+    /// Lift a revocation; returns false if the account wasn't revoked
+    pub fn restore(&mut self, profile_id: &str) -> bool {
+        let removed = self.revoked.accounts.remove(profile_id).is_some();
+        if removed {
+            self.save();
+        }
+        removed
+    }
+
+    /// Lift a token revocation; returns false if the token wasn't revoked
+    pub fn restore_token(&mut self, token_id: &str) -> bool {
+        let removed = self.revoked.tokens.remove(token_id).is_some();
+        if removed {
+            self.save();
+        }
+        removed
+    }
+
+    /// Swap in what was read from the file, unless this node saved since; its queued write will
+    /// change the file again and the next check picks that up
+    fn replace(&mut self, saves: u64, revoked: Revocations, modified: Option<SystemTime>) {
+        if self.saves == saves {
+            self.revoked = revoked;
+            self.modified = modified;
+        }
+    }
+
+    /// Queue a snapshot for the writer thread; live sessions re-check the list and shouldn't wait on the disk
+    fn save(&mut self) {
+        self.saves += 1;
+        match serde_json::to_string_pretty(&self.revoked) {
+            Ok(json) => file_writer::replace(self.path.clone(), json),
+            Err(e) => tracing::error!("Failed to serialize revocations: {}", e),
+        }
+    }
+}
+
+/// Poll the revocations file so entries from other nodes apply without a restart
+pub fn spawn_revocations_watcher(list: Data<RwLock<RevocationList>>) {
+    actix::spawn(async move {
+        let mut interval = tokio::time::interval(RELOAD_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let (path, known, saves) = {
+                let list = list.read().unwrap();
+                (list.path.clone(), list.modified, list.saves)
+            };
+            // Joins check the list, so it's only locked to swap in what was already read
+            let Ok(Some((revoked, modified))) = web::block(move || read_json_if_changed(&path, known)).await else {
+                continue;
+            };
+            list.write().unwrap().replace(saves, revoked, modified);
+        }
+    });
+}
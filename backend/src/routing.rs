@@ -0,0 +1,206 @@
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime};
+use actix_web::web::{self, Data};
+use regex::{Regex, RegexBuilder};
+
+use crate::config::{self, RoomRouting};
+use crate::file_writer;
+use crate::watched_file::modified_time;
+
+/// How often the routes file is checked for edits made outside the admin API
+const RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// Keep admin-supplied patterns from compiling into huge automata
+const PATTERN_SIZE_LIMIT: usize = 1 << 20;
+
+/// Activity -> room routing, editable at runtime through the admin API; starts from `config.toml`'s
+/// `[rooms]` unless `ROOM_ROUTES_FILE` (default `room_routes.json`) holds a saved table. Edits to
+/// whichever of the two is in charge apply without a restart
+pub struct RoutingTable {
+    routing: RoomRouting,
+    /// Route indices in match order with their compiled patterns
+    order: Vec<(usize, Vec<Regex>)>,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    /// `config.toml` as of the last `[rooms]` load, watched while there's no routes file
+    config_modified: Option<SystemTime>,
+    /// Bumped on every save, so the watcher can tell its read went stale while it was off the lock
+    saves: u64,
+}
+
+fn compile(routing: &RoomRouting) -> Result<Vec<(usize, Vec<Regex>)>, String> {
+    if routing.fallback_id.is_empty() {
+        return Err("fallback_id is required".to_string());
+    }
+    let mut order = Vec::with_capacity(routing.routes.len());
+    for (index, route) in routing.routes.iter().enumerate() {
+        if route.id.is_empty() || route.name.is_empty() {
+            return Err(format!("route {} needs an id and a name", index));
+        }
+        if routing.routes[..index].iter().any(|other| other.id == route.id) {
+            return Err(format!("duplicate route id '{}'", route.id));
+        }
+        let patterns = route
+            .patterns
+            .iter()
+            .map(|pattern| {
+                RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .size_limit(PATTERN_SIZE_LIMIT)
+                    .build()
+                    .map_err(|e| format!("route '{}': invalid pattern '{}': {}", route.id, pattern, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        order.push((index, patterns));
+    }
+    // Stable sort keeps listed order within a priority
+    order.sort_by_key(|(index, _)| std::cmp::Reverse(routing.routes[*index].priority));
+    Ok(order)
+}
+
+impl RoutingTable {
+    pub fn load(defaults: &RoomRouting) -> Self {
+        let path = PathBuf::from(std::env::var("ROOM_ROUTES_FILE").unwrap_or_else(|_| "room_routes.json".to_string()));
+        let saved = std::fs::read_to_string(&path).ok().and_then(|contents| {
+            serde_json::from_str::<RoomRouting>(&contents)
+                .map_err(|e| tracing::error!("Failed to parse {}: {}", path.display(), e))
+                .ok()
+        });
+        let mut table = Self {
+            routing: defaults.clone(),
+            order: Vec::new(),
+            modified: modified_time(&path),
+            path,
+            config_modified: modified_time(&config::config_path()),
+            saves: 0,
+        };
+        if let Err(e) = table.apply(defaults.clone()) {
+            tracing::error!("Invalid [rooms] routing in config: {}", e);
+        }
+        if let Some(saved) = saved {
+            if let Err(e) = table.apply(saved) {
+                tracing::error!("Ignoring saved room routes in {}: {}", table.path.display(), e);
+            }
+        }
+        table
+    }
+
+    pub fn routing(&self) -> &RoomRouting {
+        &self.routing
+    }
+
+    /// (room id, display name) for an activity
+    pub fn route(&self, activity: &str) -> (&str, &str) {
+        let lowered = activity.to_lowercase();
+        self.order
+            .iter()
+            .map(|(index, patterns)| (&self.routing.routes[*index], patterns))
+            .find(|(route, patterns)| {
+                route.keywords.iter().any(|keyword| lowered.contains(&keyword.to_lowercase()))
+                    || patterns.iter().any(|pattern| pattern.is_match(activity))
+            })
+            .map(|(route, _)| (route.id.as_str(), route.name.as_str()))
+            .unwrap_or((self.routing.fallback_id.as_str(), self.routing.fallback_name.as_str()))
+    }
+
+    fn apply(&mut self, routing: RoomRouting) -> Result<(), String> {
+        self.order = compile(&routing)?;
+        self.routing = routing;
+        Ok(())
+    }
+
+    /// Validate and switch to a new table, queueing it for the writer thread so it survives restarts
+    pub fn replace(&mut self, routing: RoomRouting) -> Result<(), String> {
+        self.apply(routing)?;
+        self.saves += 1;
+        match serde_json::to_string_pretty(&self.routing) {
+            Ok(json) => file_writer::replace(self.path.clone(), json),
+            Err(e) => tracing::error!("Failed to serialize room routes: {}", e),
+        }
+        Ok(())
+    }
+
+    /// Swap in routes the watcher read and compiled, unless the table was saved since it looked;
+    /// invalid edits are logged and the current table kept
+    fn apply_reload(&mut self, saves: u64, reload: RoutesReload) {
+        if self.saves != saves {
+            return;
+        }
+        let source = match reload.source {
+            ReloadSource::RoutesFile(modified) => {
+                self.modified = modified;
+                self.path.display().to_string()
+            }
+            ReloadSource::Config(modified) => {
+                self.config_modified = modified;
+                "[rooms] in the config file".to_string()
+            }
+        };
+        match reload.compiled {
+            Ok((routing, order)) => {
+                self.routing = routing;
+                self.order = order;
+                tracing::info!("🔁 Reloaded {} room route(s) from {}", self.routing.routes.len(), source);
+            }
+            Err(e) => tracing::error!("Not reloading {}: {}", source, e),
+        }
+    }
+}
+
+enum ReloadSource {
+    RoutesFile(Option<SystemTime>),
+    Config(Option<SystemTime>),
+}
+
+/// Routes read and compiled off the table's lock
+struct RoutesReload {
+    source: ReloadSource,
+    compiled: Result<(RoomRouting, Vec<(usize, Vec<Regex>)>), String>,
+}
+
+/// Edits to the routes file, or to `[rooms]` while there isn't one, since the given modification
+/// times. Blocking, so the watcher runs it through `web::block`
+fn read_routes_if_changed(path: &Path, known: Option<SystemTime>, config_known: Option<SystemTime>) -> Option<RoutesReload> {
+    let compile_routing = |routing: RoomRouting| compile(&routing).map(|order| (routing, order));
+    let modified = modified_time(path);
+    if modified.is_none() {
+        let config_modified = modified_time(&config::config_path());
+        if config_modified.is_none() || config_modified == config_known {
+            return None;
+        }
+        return Some(RoutesReload {
+            source: ReloadSource::Config(config_modified),
+            compiled: config::read_room_routing().and_then(compile_routing),
+        });
+    }
+    if modified == known {
+        return None;
+    }
+    let compiled = std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| serde_json::from_str::<RoomRouting>(&contents).map_err(|e| e.to_string()))
+        .and_then(compile_routing);
+    Some(RoutesReload {
+        source: ReloadSource::RoutesFile(modified),
+        compiled,
+    })
+}
+
+/// Poll the routes and config files so hand edits apply without a restart
+pub fn spawn_routes_watcher(table: Data<RwLock<RoutingTable>>) {
+    actix::spawn(async move {
+        let mut interval = tokio::time::interval(RELOAD_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let (path, known, config_known, saves) = {
+                let table = table.read().unwrap();
+                (table.path.clone(), table.modified, table.config_modified, table.saves)
+            };
+            // Every join routes through the table, so it's only locked to swap in what was already compiled
+            let Ok(Some(reload)) = web::block(move || read_routes_if_changed(&path, known, config_known)).await else {
+                continue;
+            };
+            table.write().unwrap().apply_reload(saves, reload);
+        }
+    });
+}
@@ -0,0 +1,213 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use actix_web::web::{self, Data, Query};
+use actix_web::HttpResponse;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::api_keys::{ApiAuth, Principal, Scope};
+use crate::streaming::{RoomOwner, StreamingSession};
+
+/// Window searches are counted over, per API key
+const SEARCH_WINDOW: Duration = Duration::from_secs(60);
+const MAX_SEARCHES_PER_WINDOW: usize = 30;
+const MIN_QUERY_CHARS: usize = 2;
+const MAX_QUERY_CHARS: usize = 100;
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 100;
+
+/// Recent searches per API key, so an integration can't turn search into a full scrape
+#[derive(Default)]
+pub struct SearchLimiter {
+    recent: HashMap<String, VecDeque<Instant>>,
+}
+
+impl SearchLimiter {
+    /// Record a search, or return how long until the key may search again
+    fn check(&mut self, key: &str) -> Result<(), Duration> {
+        let now = Instant::now();
+        self.recent.retain(|_, searches| searches.back().is_some_and(|last| now.duration_since(*last) < SEARCH_WINDOW));
+        let searches = self.recent.entry(key.to_string()).or_default();
+        while searches.front().is_some_and(|t| now.duration_since(*t) >= SEARCH_WINDOW) {
+            searches.pop_front();
+        }
+        if searches.len() >= MAX_SEARCHES_PER_WINDOW {
+            let oldest = *searches.front().unwrap();
+            return Err(SEARCH_WINDOW - now.duration_since(oldest));
+        }
+        searches.push_back(now);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResultType {
+    Rooms,
+    Players,
+    Chat,
+}
+
+impl std::str::FromStr for ResultType {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, ()> {
+        match value {
+            "rooms" => Ok(ResultType::Rooms),
+            "players" => Ok(ResultType::Players),
+            "chat" => Ok(ResultType::Chat),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchQuery {
+    q: String,
+    /// Comma-separated result types to include, all by default
+    types: Option<String>,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RoomHit {
+    room_id: String,
+    theme: String,
+    player_count: usize,
+    locked: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PlayerHit {
+    player_id: String,
+    name: String,
+    handle: Option<String>,
+    room_id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ChatHit {
+    room_id: String,
+    message_id: String,
+    sender: String,
+    sender_handle: Option<String>,
+    message: String,
+    /// Unix timestamp in milliseconds
+    sent_at: i64,
+}
+
+/// Total matches per result type, before `limit` is applied
+#[derive(Serialize, Default)]
+struct Facets {
+    rooms: usize,
+    players: usize,
+    chat: usize,
+}
+
+#[derive(Serialize)]
+struct SearchResponse {
+    query: String,
+    facets: Facets,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rooms: Option<Vec<RoomHit>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    players: Option<Vec<PlayerHit>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chat: Option<Vec<ChatHit>>,
+}
+
+/// Register the search endpoint; it shares the admin listener
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/api/search", web::get().to(search));
+}
+
+/// Find where something is happening: rooms by ID or theme, players by name or `@handle`, and, when
+/// chat history is kept on disk, what open rooms have been saying
+async fn search(
+    auth: ApiAuth,
+    limiter: Data<std::sync::Mutex<SearchLimiter>>,
+    room_owner: Data<Mutex<RoomOwner<StreamingSession>>>,
+    query: Query<SearchQuery>,
+) -> actix_web::Result<HttpResponse> {
+    auth.require(Scope::Search)?;
+    let limiter_key = match &auth.0 {
+        Principal::Admin => "admin",
+        Principal::Integration { key_id, .. } => key_id.as_str(),
+    };
+    if let Err(retry_after) = limiter.lock().unwrap().check(limiter_key) {
+        return Ok(HttpResponse::TooManyRequests()
+            .insert_header(("Retry-After", retry_after.as_secs().max(1).to_string()))
+            .body("Too many searches, slow down"));
+    }
+
+    let needle = query.q.trim().trim_start_matches('@').to_lowercase();
+    if !(MIN_QUERY_CHARS..=MAX_QUERY_CHARS).contains(&needle.chars().count()) {
+        return Ok(HttpResponse::BadRequest().body("q must be 2-100 characters"));
+    }
+    let types: Vec<ResultType> = match &query.types {
+        Some(types) => types.split(',').filter_map(|t| t.trim().parse().ok()).collect(),
+        None => vec![ResultType::Rooms, ResultType::Players, ResultType::Chat],
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let matches = |text: &str| text.to_lowercase().contains(&needle);
+
+    // Chat is only searchable where the deployment already keeps it
+    let search_chat = crate::config::get().server.chat_history_dir.is_some();
+
+    let rooms = room_owner.lock().await.list_rooms();
+    let mut facets = Facets::default();
+    let mut room_hits = Vec::new();
+    let mut player_hits = Vec::new();
+    let mut chat_hits = Vec::new();
+    for room in &rooms {
+        if matches(&room.id) || matches(&room.theme) {
+            facets.rooms += 1;
+            room_hits.push(RoomHit {
+                room_id: room.id.clone(),
+                theme: room.theme.clone(),
+                player_count: room.player_count(),
+                locked: room.is_locked(),
+            });
+        }
+        for player in room.get_all_players() {
+            if matches(&player.name) || player.handle.as_deref().is_some_and(matches) {
+                facets.players += 1;
+                player_hits.push(PlayerHit {
+                    player_id: player.id,
+                    name: player.name,
+                    handle: player.handle,
+                    room_id: room.id.clone(),
+                });
+            }
+        }
+        if search_chat {
+            for record in room.search_chat(&needle) {
+                facets.chat += 1;
+                chat_hits.push(ChatHit {
+                    room_id: room.id.clone(),
+                    message_id: record.message_id,
+                    sender: record.sender,
+                    sender_handle: record.sender_handle,
+                    message: record.message,
+                    sent_at: record.sent_at,
+                });
+            }
+        }
+    }
+    // Busiest rooms first, they're the likeliest answer to "where is this happening"
+    room_hits.sort_by(|a, b| b.player_count.cmp(&a.player_count));
+    room_hits.truncate(limit);
+    player_hits.truncate(limit);
+    chat_hits.sort_by(|a, b| b.sent_at.cmp(&a.sent_at));
+    chat_hits.truncate(limit);
+
+    Ok(HttpResponse::Ok().json(SearchResponse {
+        query: needle,
+        facets,
+        rooms: types.contains(&ResultType::Rooms).then_some(room_hits),
+        players: types.contains(&ResultType::Players).then_some(player_hits),
+        chat: (search_chat && types.contains(&ResultType::Chat)).then_some(chat_hits),
+    }))
+}
@@ -0,0 +1,44 @@
+use std::io;
+use std::path::PathBuf;
+
+/// Where uploaded blobs live; swap the implementation to move off local disk
+pub trait BlobStorage: Send + Sync {
+    fn put(&self, key: &str, data: &[u8]) -> io::Result<()>;
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+}
+
+/// Stores blobs as files under `UPLOAD_DIR` (default `uploads/`)
+pub struct LocalDiskStorage {
+    root: PathBuf,
+}
+
+impl LocalDiskStorage {
+    pub fn from_env() -> io::Result<Self> {
+        let root = PathBuf::from(std::env::var("UPLOAD_DIR").unwrap_or_else(|_| "uploads".to_string()));
+        std::fs::create_dir_all(&root)?;
+        tracing::info!("Storing uploads in {}", root.display());
+        Ok(Self { root })
+    }
+
+    fn path_for(&self, key: &str) -> io::Result<PathBuf> {
+        // Keys are generated server-side, but never let one escape the root
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.') || key.contains("..") {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid storage key"));
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+impl BlobStorage for LocalDiskStorage {
+    fn put(&self, key: &str, data: &[u8]) -> io::Result<()> {
+        std::fs::write(self.path_for(key)?, data)
+    }
+
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        match std::fs::read(self.path_for(key)?) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
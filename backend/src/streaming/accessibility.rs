@@ -0,0 +1,80 @@
+use std::collections::{HashMap, HashSet};
+use serde::Serialize;
+
+use super::handler::{PlayerData, Position};
+
+/// Players closer than this are announced as nearby
+const NEAR_RADIUS: f32 = 4.0;
+/// ...and must get this far away before "moved away" is announced, so hovering at the edge doesn't spam
+const FAR_RADIUS: f32 = 6.0;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AccessibilityEventKind {
+    RoomEntered,
+    PlayerJoined,
+    PlayerLeft,
+    PlayerApproached,
+    PlayerMovedAway,
+    StartedSpeaking,
+    HostChanged,
+}
+
+/// Which players are near the listener, for turning spatial updates into text
+#[derive(Default)]
+pub struct AccessibilityTracker {
+    names: HashMap<String, String>,
+    nearby: HashSet<String>,
+}
+
+impl AccessibilityTracker {
+    /// Start tracking with the room's current occupants (excluding the listener)
+    pub fn new(own: &Position, others: &[PlayerData]) -> Self {
+        let mut tracker = Self::default();
+        for player in others {
+            tracker.remember(&player.id, &player.name);
+            if horizontal_distance(own, &player.position) <= NEAR_RADIUS {
+                tracker.nearby.insert(player.id.clone());
+            }
+        }
+        tracker
+    }
+
+    pub fn remember(&mut self, player_id: &str, name: &str) {
+        self.names.insert(player_id.to_string(), name.to_string());
+    }
+
+    /// Stop tracking a player, returns their name if known
+    pub fn forget(&mut self, player_id: &str) -> Option<String> {
+        self.nearby.remove(player_id);
+        self.names.remove(player_id)
+    }
+
+    pub fn name(&self, player_id: &str) -> Option<&str> {
+        self.names.get(player_id).map(String::as_str)
+    }
+
+    pub fn is_nearby(&self, player_id: &str) -> bool {
+        self.nearby.contains(player_id)
+    }
+
+    pub fn nearby_names(&self) -> Vec<&str> {
+        self.nearby.iter().filter_map(|id| self.name(id)).collect()
+    }
+
+    /// Returns `Some(true)` when the player just came into range, `Some(false)` when they just left it
+    pub fn update_distance(&mut self, player_id: &str, own: &Position, other: &Position) -> Option<bool> {
+        let distance = horizontal_distance(own, other);
+        if distance <= NEAR_RADIUS && self.nearby.insert(player_id.to_string()) {
+            Some(true)
+        } else if distance > FAR_RADIUS && self.nearby.remove(player_id) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+fn horizontal_distance(a: &Position, b: &Position) -> f32 {
+    ((a.x - b.x).powi(2) + (a.z - b.z).powi(2)).sqrt()
+}
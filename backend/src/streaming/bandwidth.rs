@@ -0,0 +1,51 @@
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+
+/// Join-time bandwidth preset chosen by the client
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BandwidthProfile {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+/// Limits the server enforces for a profile
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct BandwidthLimits {
+    /// Concurrent media subscriptions the client may hold
+    pub max_subscriptions: usize,
+    /// Encoder target the client should publish at
+    pub target_bitrate_kbps: u32,
+    /// Minimum gap between movement updates forwarded to this client, per remote player
+    #[serde(rename = "movementUpdateIntervalMs", serialize_with = "serialize_millis")]
+    pub movement_update_interval: Duration,
+}
+
+fn serialize_millis<S: serde::Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_millis() as u64)
+}
+
+impl BandwidthProfile {
+    pub fn limits(self) -> BandwidthLimits {
+        match self {
+            BandwidthProfile::Low => BandwidthLimits {
+                max_subscriptions: 4,
+                target_bitrate_kbps: 300,
+                movement_update_interval: Duration::from_millis(200),
+            },
+            BandwidthProfile::Medium => BandwidthLimits {
+                max_subscriptions: 12,
+                target_bitrate_kbps: 1000,
+                movement_update_interval: Duration::from_millis(100),
+            },
+            BandwidthProfile::High => BandwidthLimits {
+                max_subscriptions: 32,
+                target_bitrate_kbps: 2500,
+                movement_update_interval: Duration::from_millis(33),
+            },
+        }
+    }
+}
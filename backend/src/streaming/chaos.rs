@@ -0,0 +1,119 @@
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+
+/// How often each session rolls for having its transports killed
+pub const TRANSPORT_KILL_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Faults to inject while chaos mode is on; every rate is a probability 0.0 - 1.0
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ChaosSettings {
+    /// Chance an incoming signaling message is held back (and so reordered)
+    pub signaling_delay_rate: f64,
+    /// Delayed messages wait a random time up to this
+    pub max_signaling_delay_ms: u64,
+    /// Chance a room broadcast (movement, chat, joins/leaves, publishers) never reaches a client
+    pub broadcast_drop_rate: f64,
+    /// Chance a `subscribe()` fails as if rheomesh had errored
+    pub subscribe_failure_rate: f64,
+    /// Chance per session every `TRANSPORT_KILL_CHECK_INTERVAL` that its transports are closed
+    pub transport_kill_rate: f64,
+    /// Same seed, same sequence of faults; random when unset
+    pub seed: Option<u64>,
+}
+
+impl ChaosSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        let rates = [
+            ("signalingDelayRate", self.signaling_delay_rate),
+            ("broadcastDropRate", self.broadcast_drop_rate),
+            ("subscribeFailureRate", self.subscribe_failure_rate),
+            ("transportKillRate", self.transport_kill_rate),
+        ];
+        match rates.iter().find(|(_, rate)| !(0.0..=1.0).contains(rate)) {
+            Some((name, _)) => Err(format!("{} must be between 0 and 1", name)),
+            None => Ok(()),
+        }
+    }
+}
+
+struct ChaosState {
+    settings: ChaosSettings,
+    /// SplitMix64 state, so a seed reproduces the same faults
+    rng: u64,
+}
+
+impl ChaosState {
+    fn next_u64(&mut self) -> u64 {
+        self.rng = self.rng.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in 0.0..1.0
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+fn random_seed() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|since| since.as_nanos() as u64).unwrap_or(0)
+}
+
+static CHAOS: LazyLock<Mutex<ChaosState>> = LazyLock::new(|| {
+    Mutex::new(ChaosState {
+        settings: ChaosSettings::default(),
+        rng: random_seed(),
+    })
+});
+
+/// Fault injection is for development only and has to be switched on in config (`CHAOS_MODE`)
+pub fn is_enabled() -> bool {
+    crate::config::get().server.chaos_mode
+}
+
+pub fn settings() -> ChaosSettings {
+    CHAOS.lock().unwrap().settings.clone()
+}
+
+/// Replace the active faults; restarts the fault sequence from the seed
+pub fn configure(settings: ChaosSettings) {
+    tracing::warn!("Chaos mode faults set to {:?}", settings);
+    let mut chaos = CHAOS.lock().unwrap();
+    chaos.rng = settings.seed.unwrap_or_else(random_seed);
+    chaos.settings = settings;
+}
+
+fn roll(rate: impl Fn(&ChaosSettings) -> f64) -> bool {
+    if !is_enabled() {
+        return false;
+    }
+    let mut chaos = CHAOS.lock().unwrap();
+    let rate = rate(&chaos.settings);
+    rate > 0.0 && chaos.next_f64() < rate
+}
+
+/// How long to hold back an incoming signaling message, if at all
+pub fn signaling_delay() -> Option<Duration> {
+    if !roll(|settings| settings.signaling_delay_rate) {
+        return None;
+    }
+    let mut chaos = CHAOS.lock().unwrap();
+    let max_ms = chaos.settings.max_signaling_delay_ms;
+    Some(Duration::from_millis((chaos.next_f64() * max_ms as f64) as u64))
+}
+
+pub fn drop_broadcast() -> bool {
+    roll(|settings| settings.broadcast_drop_rate)
+}
+
+pub fn fail_subscribe() -> bool {
+    roll(|settings| settings.subscribe_failure_rate)
+}
+
+pub fn kill_transports() -> bool {
+    roll(|settings| settings.transport_kill_rate)
+}
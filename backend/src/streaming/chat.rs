@@ -0,0 +1,315 @@
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+
+use crate::file_writer;
+
+/// Most messages a room can have pinned at once
+pub const MAX_PINNED_MESSAGES: usize = 5;
+
+/// Host-only mention notifying the whole room
+pub const EVERYONE_MENTION: &str = "everyone";
+/// How often the host can use `@everyone`
+pub const EVERYONE_MENTION_COOLDOWN: Duration = Duration::from_secs(60);
+/// A typing indicator clears itself when the client doesn't refresh it within this
+pub const TYPING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Most `@handle` mentions in one message that trigger notifications
+const MAX_MENTIONS: usize = 5;
+
+/// Distinct `@handle`s in a message, lowercased, in order of appearance
+pub fn parse_mentions(message: &str) -> Vec<String> {
+    let mut mentions: Vec<String> = Vec::new();
+    for word in message.split_whitespace() {
+        let Some(handle) = word.strip_prefix('@') else {
+            continue;
+        };
+        // Trailing punctuation ("@sam," / "@sam!") isn't part of the handle
+        let handle: String = handle
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+            .collect::<String>()
+            .to_ascii_lowercase();
+        if !handle.is_empty() && !mentions.contains(&handle) {
+            mentions.push(handle);
+        }
+        if mentions.len() == MAX_MENTIONS {
+            break;
+        }
+    }
+    mentions
+}
+
+/// A chat message the host pinned to the room bulletin, copied from the room's chat history
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PinnedMessage {
+    pub pin_id: String,
+    #[serde(flatten)]
+    pub record: ChatRecord,
+    pub pinned_by: String,
+    /// Unix timestamp in milliseconds
+    pub pinned_at: i64,
+}
+
+/// Window chat bursts are counted over
+const FLOOD_WINDOW: Duration = Duration::from_secs(10);
+/// Most messages a player can send per window
+const MAX_MESSAGES_PER_WINDOW: usize = 5;
+/// The same text this many times in a row counts as spam
+const MAX_REPEATS: usize = 3;
+/// First cooldown; each further strike doubles it
+const BASE_COOLDOWN: Duration = Duration::from_secs(5);
+const MAX_COOLDOWN: Duration = Duration::from_secs(5 * 60);
+/// Strikes are forgiven after this long without another one
+const STRIKE_DECAY: Duration = Duration::from_secs(2 * 60);
+
+/// Why a chat message was refused, and when the player may chat again
+#[derive(Debug, Clone)]
+pub struct ChatRejection {
+    pub reason: String,
+    pub retry_after: Duration,
+}
+
+/// Per-player flood protection: bursts and repeated messages earn escalating cooldowns
+#[derive(Default)]
+pub struct ChatFloodGuard {
+    recent: std::collections::VecDeque<Instant>,
+    last_message: Option<String>,
+    repeats: usize,
+    strikes: u32,
+    last_strike: Option<Instant>,
+    cooldown_until: Option<Instant>,
+}
+
+impl ChatFloodGuard {
+    pub fn check(&mut self, message: &str) -> Result<(), ChatRejection> {
+        let now = Instant::now();
+        if let Some(until) = self.cooldown_until.filter(|until| *until > now) {
+            return Err(ChatRejection {
+                reason: "You're on a chat cooldown".to_string(),
+                retry_after: until - now,
+            });
+        }
+        if self.last_strike.is_some_and(|last| now.duration_since(last) >= STRIKE_DECAY) {
+            self.strikes = 0;
+            self.last_strike = None;
+        }
+
+        let normalized = message.trim().to_lowercase();
+        if self.last_message.as_ref() == Some(&normalized) {
+            self.repeats += 1;
+        } else {
+            self.last_message = Some(normalized);
+            self.repeats = 1;
+        }
+        if self.repeats > MAX_REPEATS {
+            return Err(self.strike(now, "Stop repeating the same message"));
+        }
+
+        while self.recent.front().is_some_and(|sent| now.duration_since(*sent) >= FLOOD_WINDOW) {
+            self.recent.pop_front();
+        }
+        if self.recent.len() >= MAX_MESSAGES_PER_WINDOW {
+            return Err(self.strike(now, "You're sending messages too fast"));
+        }
+        self.recent.push_back(now);
+        Ok(())
+    }
+
+    fn strike(&mut self, now: Instant, reason: &str) -> ChatRejection {
+        self.strikes += 1;
+        self.last_strike = Some(now);
+        let cooldown = BASE_COOLDOWN.saturating_mul(1 << (self.strikes - 1).min(16)).min(MAX_COOLDOWN);
+        self.cooldown_until = Some(now + cooldown);
+        self.recent.clear();
+        self.repeats = 0;
+        ChatRejection {
+            reason: reason.to_string(),
+            retry_after: cooldown,
+        }
+    }
+}
+
+/// Messages kept per room
+const CHAT_HISTORY_LIMIT: usize = 200;
+/// Messages sent in `RoomState` so newcomers get context
+pub const JOIN_HISTORY_MESSAGES: usize = 50;
+/// Most messages one `FetchChatHistory` page returns
+pub const MAX_HISTORY_PAGE: usize = 100;
+
+/// A chat message as kept in room history
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatRecord {
+    pub message_id: String,
+    /// Player ID of the author when it was sent
+    #[serde(default)]
+    pub sender_id: String,
+    /// Who may edit or delete it, see `StreamingSession::author_identity`; kept on disk, never sent to clients
+    #[serde(skip)]
+    pub author_identity: String,
+    pub sender: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sender_handle: Option<String>,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upload_id: Option<String>,
+    /// Unix timestamp in milliseconds
+    pub sent_at: i64,
+    /// Unix timestamp in milliseconds of the last edit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edited_at: Option<i64>,
+}
+
+/// How a `ChatRecord` is saved to disk: with the author identity clients never see
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredChatRecord<'a> {
+    #[serde(flatten)]
+    record: &'a ChatRecord,
+    author_identity: &'a str,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LoadedChatRecord {
+    #[serde(flatten)]
+    record: ChatRecord,
+    #[serde(default)]
+    author_identity: Option<String>,
+}
+
+fn stored_line(record: &ChatRecord) -> Option<String> {
+    serde_json::to_string(&StoredChatRecord {
+        record,
+        author_identity: &record.author_identity,
+    })
+    .ok()
+}
+
+fn parse_stored_line(line: &str) -> Option<ChatRecord> {
+    let LoadedChatRecord { mut record, author_identity } = serde_json::from_str(line).ok()?;
+    // Lines saved before authors were tracked belong to the connection that sent them
+    record.author_identity = author_identity.unwrap_or_else(|| format!("player:{}", record.sender_id));
+    Some(record)
+}
+
+/// The latest chat messages in a room, oldest first. With `chat_history_dir` configured they're also
+/// appended to `<dir>/<room_id>.jsonl`, so history survives the room closing and restarts
+pub struct ChatHistory {
+    messages: std::collections::VecDeque<ChatRecord>,
+    path: Option<PathBuf>,
+    /// Lines appended since the file was last rewritten from the buffer
+    appended: usize,
+}
+
+impl ChatHistory {
+    pub fn load(room_id: &str) -> Self {
+        let path = crate::config::get().server.chat_history_dir.as_ref().and_then(|dir| {
+            // Room IDs come from routes and portal joins; keep them from escaping the directory
+            let safe = !room_id.is_empty() && room_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            safe.then(|| dir.join(format!("{}.jsonl", room_id)))
+        });
+        let mut messages: std::collections::VecDeque<ChatRecord> = path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .map(|contents| contents.lines().filter_map(parse_stored_line).collect())
+            .unwrap_or_default();
+        while messages.len() > CHAT_HISTORY_LIMIT {
+            messages.pop_front();
+        }
+        Self { messages, path, appended: 0 }
+    }
+
+    pub fn push(&mut self, record: ChatRecord) {
+        if self.messages.len() == CHAT_HISTORY_LIMIT {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(record);
+        self.persist();
+    }
+
+    /// Up to `limit` messages, oldest first
+    pub fn recent(&self, limit: usize) -> Vec<ChatRecord> {
+        self.messages.iter().skip(self.messages.len().saturating_sub(limit)).cloned().collect()
+    }
+
+    /// Up to `limit` messages older than `before` (a message ID; the newest when `None`), oldest
+    /// first, and whether there are older ones still. A `before` that's no longer in the buffer
+    /// (deleted, or aged out) is an error rather than an empty page the client would take as the end
+    pub fn page(&self, before: Option<&str>, limit: usize) -> Result<(Vec<ChatRecord>, bool), String> {
+        let end = match before {
+            Some(before) => self
+                .messages
+                .iter()
+                .position(|record| record.message_id == before)
+                .ok_or_else(|| "that message is no longer in the chat history".to_string())?,
+            None => self.messages.len(),
+        };
+        let start = end.saturating_sub(limit);
+        Ok((self.messages.range(start..end).cloned().collect(), start > 0))
+    }
+
+    /// Messages whose text, sender or handle contains `needle` (already lowercased), newest first
+    pub fn search(&self, needle: &str) -> Vec<ChatRecord> {
+        let matches = |text: &str| text.to_lowercase().contains(needle);
+        self.messages
+            .iter()
+            .rev()
+            .filter(|record| matches(&record.message) || matches(&record.sender) || record.sender_handle.as_deref().is_some_and(matches))
+            .cloned()
+            .collect()
+    }
+
+    pub fn get(&self, message_id: &str) -> Option<&ChatRecord> {
+        self.messages.iter().find(|record| record.message_id == message_id)
+    }
+
+    /// Replace a message's text; returns the updated record
+    pub fn edit(&mut self, message_id: &str, message: String) -> Option<ChatRecord> {
+        let record = self.messages.iter_mut().find(|record| record.message_id == message_id)?;
+        record.message = message;
+        record.edited_at = Some(chrono::Utc::now().timestamp_millis());
+        let record = record.clone();
+        self.rewrite();
+        Some(record)
+    }
+
+    pub fn delete(&mut self, message_id: &str) -> Option<ChatRecord> {
+        let index = self.messages.iter().position(|record| record.message_id == message_id)?;
+        let record = self.messages.remove(index);
+        self.rewrite();
+        record
+    }
+
+    /// Append the newest message; once the file holds a full buffer's worth of extra lines,
+    /// rewrite it from the buffer so it doesn't grow forever. Writes are queued on the file writer,
+    /// never done on the session's thread
+    fn persist(&mut self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        self.appended += 1;
+        if self.appended >= CHAT_HISTORY_LIMIT {
+            self.appended = 0;
+            self.write_all();
+        } else if let Some(line) = self.messages.back().and_then(stored_line) {
+            file_writer::append(path.clone(), format!("{}\n", line));
+        }
+    }
+
+    /// Edits and deletes change earlier lines, so the whole file is written again
+    fn rewrite(&mut self) {
+        self.appended = 0;
+        self.write_all();
+    }
+
+    fn write_all(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let lines: Vec<String> = self.messages.iter().filter_map(stored_line).collect();
+        file_writer::replace(path.clone(), lines.join("\n") + "\n");
+    }
+}
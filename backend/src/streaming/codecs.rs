@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use rheomesh::config::{CodecConfig, MediaConfig};
+use serde::Deserialize;
+use webrtc::api::media_engine;
+use webrtc::rtp_transceiver::rtp_codec::{RTCRtpCodecCapability, RTCRtpCodecParameters, RTPCodecType};
+use webrtc::rtp_transceiver::RTCPFeedback;
+
+use crate::config;
+
+use super::instances::base_room_id;
+use super::theme::theme_for_room;
+
+/// Video codecs a room can offer; browsers differ in which they decode in hardware
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum VideoCodec {
+    H264,
+    Vp8,
+    Vp9,
+    /// Best quality per bit, but CPU-heavy to encode; only offered when `media.enable_av1` is set
+    Av1,
+}
+
+/// Per-theme codec parameter overrides, applied when a room's `MediaConfig` is built
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CodecSettings {
+    /// Negotiate stereo Opus (music) instead of mono voice
+    pub opus_stereo: bool,
+    /// Opus `maxaveragebitrate` in bits/s; the browser default (~32kbps mono) when unset
+    pub opus_max_average_bitrate: Option<u32>,
+    /// Opus discontinuous transmission; `Some(false)` keeps quiet passages (sustain, reverb tails) intact
+    pub opus_dtx: Option<bool>,
+    /// H.264 `profile-level-id`
+    pub h264_profile_level_id: String,
+    /// Video codecs offered, in order of preference
+    pub video: Vec<VideoCodec>,
+    /// Ceiling on each video publisher's bitrate, so one room can't use up the server's relay bandwidth
+    pub max_video_kbps: Option<u32>,
+}
+
+impl Default for CodecSettings {
+    fn default() -> Self {
+        Self {
+            opus_stereo: false,
+            opus_max_average_bitrate: None,
+            opus_dtx: None,
+            // Baseline 3.1, decodable everywhere
+            h264_profile_level_id: "42001f".to_string(),
+            // H.264 first for Safari's hardware decoder, VP8 as the universal fallback
+            video: vec![VideoCodec::H264, VideoCodec::Vp8, VideoCodec::Vp9],
+            max_video_kbps: None,
+        }
+    }
+}
+
+/// Video bitrate cap for a room: `media.room_max_video_kbps`, else its theme's (`0` lifts the theme's cap)
+pub fn max_video_kbps(room_id: &str) -> Option<u32> {
+    config::get()
+        .media
+        .room_max_video_kbps
+        .get(base_room_id(room_id))
+        .copied()
+        .or(theme_for_room(room_id).codecs.max_video_kbps)
+        .filter(|kbps| *kbps > 0)
+}
+
+/// Set `b=AS` on every video section of an SDP answer, which browsers honor as a send limit
+pub fn cap_video_bandwidth(sdp: &str, kbps: u32) -> String {
+    let bandwidth = format!("b=AS:{}\r\n", kbps);
+    let mut capped = String::with_capacity(sdp.len() + 64);
+    let mut in_video = false;
+    // In a video section whose `b=AS` hasn't been written yet
+    let mut pending = false;
+    for line in sdp.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        let is_media = trimmed.starts_with("m=");
+        // `b=` lines go after the section's `m=`, `i=` and `c=` lines, ahead of everything else
+        if pending && (is_media || !(trimmed.starts_with("i=") || trimmed.starts_with("c="))) {
+            capped.push_str(&bandwidth);
+            pending = false;
+        }
+        if is_media {
+            in_video = trimmed.starts_with("m=video");
+            pending = in_video;
+        } else if in_video && (trimmed.starts_with("b=AS:") || trimmed.starts_with("b=TIAS:")) {
+            continue;
+        }
+        capped.push_str(line);
+    }
+    if pending {
+        if !capped.ends_with('\n') {
+            capped.push_str("\r\n");
+        }
+        capped.push_str(&bandwidth);
+    }
+    capped
+}
+
+/// Track ID -> media kind for every track an offer sends, from each section's `m=` line and
+/// `a=msid:<stream> <track>`; what the SFU actually receives, whatever the client labels it
+pub fn track_kinds(sdp: &str) -> HashMap<String, RTPCodecType> {
+    let mut kinds = HashMap::new();
+    let mut kind = RTPCodecType::Unspecified;
+    for line in sdp.lines() {
+        if let Some(media) = line.strip_prefix("m=") {
+            kind = match media.split(' ').next() {
+                Some("audio") => RTPCodecType::Audio,
+                Some("video") => RTPCodecType::Video,
+                _ => RTPCodecType::Unspecified,
+            };
+        } else if let Some(msid) = line.strip_prefix("a=msid:") {
+            if let Some(track_id) = msid.split_whitespace().nth(1) {
+                kinds.insert(track_id.to_string(), kind);
+            }
+        }
+    }
+    kinds
+}
+
+/// Codec configuration for a room, tuned by its theme
+pub fn media_config(room_id: &str) -> MediaConfig {
+    let settings = &theme_for_room(room_id).codecs;
+    let mut config = MediaConfig::default();
+    config.codec = CodecConfig {
+        audio: audio_codecs(settings),
+        video: video_codecs(settings),
+    };
+    config
+}
+
+fn audio_codecs(settings: &CodecSettings) -> Vec<RTCRtpCodecParameters> {
+    let mut fmtp = "minptime=10;useinbandfec=1".to_string();
+    if settings.opus_stereo {
+        fmtp.push_str(";stereo=1;sprop-stereo=1");
+    }
+    if let Some(bitrate) = settings.opus_max_average_bitrate {
+        fmtp.push_str(&format!(";maxaveragebitrate={}", bitrate));
+    }
+    if let Some(dtx) = settings.opus_dtx {
+        fmtp.push_str(if dtx { ";usedtx=1" } else { ";usedtx=0" });
+    }
+    vec![
+        RTCRtpCodecParameters {
+            capability: RTCRtpCodecCapability {
+                mime_type: media_engine::MIME_TYPE_OPUS.to_owned(),
+                clock_rate: 48000,
+                channels: 2,
+                sdp_fmtp_line: fmtp,
+                rtcp_feedback: vec![],
+            },
+            payload_type: 111,
+            ..Default::default()
+        },
+    ]
+}
+
+fn video_codecs(settings: &CodecSettings) -> Vec<RTCRtpCodecParameters> {
+    let video_rtcp_feedback = vec![
+        RTCPFeedback {
+            typ: "goog-remb".to_owned(),
+            parameter: "".to_owned(),
+        },
+        RTCPFeedback {
+            typ: "ccm".to_owned(),
+            parameter: "fir".to_owned(),
+        },
+        RTCPFeedback {
+            typ: "nack".to_owned(),
+            parameter: "".to_owned(),
+        },
+        RTCPFeedback {
+            typ: "nack".to_owned(),
+            parameter: "pli".to_owned(),
+        },
+    ];
+    let media = &config::get().media;
+    let preference = media.video_codecs.as_deref().unwrap_or(&settings.video);
+    let av1 = media.enable_av1.then_some(&VideoCodec::Av1);
+    av1.into_iter()
+        .chain(preference.iter().filter(|codec| **codec != VideoCodec::Av1))
+        .map(|codec| {
+            let (mime_type, sdp_fmtp_line, payload_type) = match codec {
+                VideoCodec::H264 => (
+                    media_engine::MIME_TYPE_H264,
+                    format!(
+                        "level-asymmetry-allowed=1;packetization-mode=1;profile-level-id={}",
+                        settings.h264_profile_level_id
+                    ),
+                    102,
+                ),
+                VideoCodec::Vp8 => (media_engine::MIME_TYPE_VP8, String::new(), 96),
+                VideoCodec::Vp9 => (media_engine::MIME_TYPE_VP9, "profile-id=0".to_owned(), 98),
+                VideoCodec::Av1 => (media_engine::MIME_TYPE_AV1, "level-idx=5;profile=0;tier=0".to_owned(), 45),
+            };
+            RTCRtpCodecParameters {
+                capability: RTCRtpCodecCapability {
+                    mime_type: mime_type.to_owned(),
+                    clock_rate: 90000,
+                    channels: 0,
+                    sdp_fmtp_line,
+                    rtcp_feedback: video_rtcp_feedback.clone(),
+                },
+                payload_type,
+                ..Default::default()
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+use super::handler::Position;
+
+/// Players are treated as circles of this radius on the floor plane
+const PLAYER_RADIUS: f32 = 0.4;
+/// Gap left between a player and the surface they walked into
+const SKIN: f32 = 0.01;
+
+/// Solid box on the x/z plane (wall, screen, furniture), full height
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all(serialize = "camelCase"))]
+pub struct Collider {
+    pub min_x: f32,
+    pub max_x: f32,
+    pub min_z: f32,
+    pub max_z: f32,
+}
+
+#[derive(Clone, Copy)]
+enum Axis {
+    X,
+    Z,
+}
+
+/// Entry and exit along a segment for one axis, or `None` if it never overlaps on that axis
+fn slab(start: f32, delta: f32, min: f32, max: f32) -> Option<(f32, f32)> {
+    if delta == 0.0 {
+        return (min..=max).contains(&start).then_some((f32::NEG_INFINITY, f32::INFINITY));
+    }
+    let (t1, t2) = ((min - start) / delta, (max - start) / delta);
+    Some((t1.min(t2), t1.max(t2)))
+}
+
+impl Collider {
+    pub const fn new(min_x: f32, min_z: f32, max_x: f32, max_z: f32) -> Self {
+        Self { min_x, max_x, min_z, max_z }
+    }
+
+    fn contains(&self, position: &Position) -> bool {
+        (self.min_x - PLAYER_RADIUS..=self.max_x + PLAYER_RADIUS).contains(&position.x)
+            && (self.min_z - PLAYER_RADIUS..=self.max_z + PLAYER_RADIUS).contains(&position.z)
+    }
+
+    /// How far along `from -> to` (0.0 - 1.0) a player would touch this box, and which face they hit
+    fn entry(&self, from: &Position, to: &Position) -> Option<(f32, Axis)> {
+        let (x_enter, x_exit) = slab(from.x, to.x - from.x, self.min_x - PLAYER_RADIUS, self.max_x + PLAYER_RADIUS)?;
+        let (z_enter, z_exit) = slab(from.z, to.z - from.z, self.min_z - PLAYER_RADIUS, self.max_z + PLAYER_RADIUS)?;
+        let enter = x_enter.max(z_enter);
+        if enter > x_exit.min(z_exit) || !(0.0..=1.0).contains(&enter) {
+            return None;
+        }
+        Some((enter, if x_enter > z_enter { Axis::X } else { Axis::Z }))
+    }
+}
+
+/// The first box a move runs into; boxes the player is already inside are ignored so they can walk out
+fn first_hit(colliders: &[Collider], from: &Position, to: &Position) -> Option<(f32, Axis)> {
+    colliders
+        .iter()
+        .filter(|collider| !collider.contains(from))
+        .filter_map(|collider| collider.entry(from, to))
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+}
+
+/// Where a move from `from` to `to` actually ends up: stopped at the first solid surface, then slid
+/// along it with whatever movement was parallel to that surface
+pub fn resolve_move(colliders: &[Collider], from: &Position, to: Position) -> Position {
+    let Some((t, axis)) = first_hit(colliders, from, &to) else {
+        return to;
+    };
+    let length = (to.x - from.x).hypot(to.z - from.z);
+    let t = (t - SKIN / length).max(0.0);
+    let stop = Position {
+        x: from.x + (to.x - from.x) * t,
+        y: to.y,
+        z: from.z + (to.z - from.z) * t,
+    };
+    let slide = match axis {
+        Axis::X => Position { z: to.z, ..stop.clone() },
+        Axis::Z => Position { x: to.x, ..stop.clone() },
+    };
+    if first_hit(colliders, &stop, &slide).is_some() { stop } else { slide }
+}
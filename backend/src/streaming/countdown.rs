@@ -0,0 +1,57 @@
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use serde::Serialize;
+
+use super::handler::{SendingMessage, StreamingSession};
+use super::room::Room;
+
+/// Longest countdown a host can start
+pub const MAX_COUNTDOWN_SECS: u64 = 3 * 60 * 60;
+/// Countdowns that can run at once in a room
+pub const MAX_ACTIVE_COUNTDOWNS: usize = 5;
+pub const MAX_COUNTDOWN_LABEL_CHARS: usize = 60;
+
+/// Server-timed countdown shared by pomodoro timers, game rounds and event starts; clients render
+/// from `ends_at` rather than counting locally so everyone hits zero together
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Countdown {
+    pub countdown_id: String,
+    pub label: String,
+    pub started_by: String,
+    /// Unix milliseconds when the countdown reaches zero
+    pub ends_at: i64,
+}
+
+/// Start a countdown in the room and broadcast its completion when it ends, unless cancelled first
+pub fn start_countdown(room: &Arc<Room<StreamingSession>>, countdown: Countdown, duration: Duration) -> bool {
+    if !room.add_countdown(countdown.clone()) {
+        return false;
+    }
+    let server_time = chrono::Utc::now().timestamp_millis();
+    for peer in room.get_all_addrs() {
+        peer.do_send(SendingMessage::CountdownStarted {
+            countdown: countdown.clone(),
+            server_time,
+        });
+    }
+
+    let room: Weak<Room<StreamingSession>> = Arc::downgrade(room);
+    actix::spawn(async move {
+        tokio::time::sleep(duration).await;
+        let Some(room) = room.upgrade() else {
+            return;
+        };
+        // Already gone if it was cancelled
+        let Some(countdown) = room.remove_countdown(&countdown.countdown_id) else {
+            return;
+        };
+        for peer in room.get_all_addrs() {
+            peer.do_send(SendingMessage::CountdownFinished {
+                countdown_id: countdown.countdown_id.clone(),
+                label: countdown.label.clone(),
+            });
+        }
+    });
+    true
+}
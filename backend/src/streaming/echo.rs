@@ -0,0 +1,53 @@
+use std::time::Duration;
+use serde::Serialize;
+
+/// Activity routing target; every join gets its own private `echo-test-<id>` instance
+pub const ECHO_TEST_ROOM_ID: &str = "echo-test";
+pub const ECHO_TEST_ROOM_THEME: &str = "Echo Test";
+
+/// How often echo-test clients get a report; browsers send receiver reports about once a second
+pub const ECHO_REPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+pub fn is_echo_room(room_id: &str) -> bool {
+    room_id.starts_with(ECHO_TEST_ROOM_ID)
+}
+
+/// Connectivity summary sent to echo-test clients, from the RTCP receiver reports the client sends
+/// for its loopback tracks; with several tracks the worst one is reported
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct EchoReport {
+    /// Media round trip between the SFU and the client, from RTCP report timestamps
+    pub rtt_ms: Option<u32>,
+    pub loss_percent: f32,
+    pub jitter_ms: f32,
+    /// Loopback tracks the client has reported on so far
+    pub tracks_reported: usize,
+}
+
+/// What one loopback subscription's latest receiver report says
+#[derive(Debug, Clone, Copy)]
+pub struct LoopbackStats {
+    /// Fraction of packets lost since the previous report, 0.0 - 1.0
+    pub fraction_lost: f32,
+    pub jitter: Duration,
+    pub round_trip_time: Option<Duration>,
+}
+
+impl EchoReport {
+    /// None until the client has sent a receiver report for any loopback track
+    pub fn from_loopback(stats: &[LoopbackStats]) -> Option<Self> {
+        if stats.is_empty() {
+            return None;
+        }
+        let worst_loss = stats.iter().map(|track| track.fraction_lost.clamp(0.0, 1.0)).fold(0.0, f32::max);
+        let worst_jitter = stats.iter().map(|track| track.jitter).max().unwrap_or_default();
+        let worst_rtt = stats.iter().filter_map(|track| track.round_trip_time).max();
+        Some(Self {
+            rtt_ms: worst_rtt.map(|rtt| rtt.as_millis() as u32),
+            loss_percent: worst_loss * 100.0,
+            jitter_ms: worst_jitter.as_secs_f32() * 1000.0,
+            tracks_reported: stats.len(),
+        })
+    }
+}
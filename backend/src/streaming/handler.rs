@@ -1,6 +1,6 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use actix::{Actor, ActorFutureExt, AsyncContext, Handler, Message, StreamHandler, WrapFuture};
+use actix::{Actor, ActorFutureExt, AsyncContext, Handler, Message, Running, SpawnHandle, StreamHandler, WrapFuture};
 use actix_web::web::Data;
 use actix_web_actors::ws;
 use rheomesh::publisher::Publisher;
@@ -11,11 +11,65 @@ use tokio::sync::Mutex;
 use webrtc::ice_transport::ice_candidate::RTCIceCandidateInit;
 use webrtc::ice_transport::ice_server::RTCIceServer;
 use webrtc::peer_connection::configuration::RTCConfiguration;
-use webrtc::peer_connection::policy::ice_transport_policy::RTCIceTransportPolicy;
 use webrtc::peer_connection::sdp::session_description::RTCSessionDescription;
+use webrtc::rtp_transceiver::rtp_codec::RTPCodecType;
 use webrtc_ice::network_type::NetworkType;
 
-use super::room::{Room, RoomOwner};
+use super::accessibility::{AccessibilityEventKind, AccessibilityTracker};
+use super::bandwidth::{BandwidthLimits, BandwidthProfile};
+use super::chaos;
+use super::codecs::{cap_video_bandwidth, max_video_kbps, track_kinds};
+use super::chat::{parse_mentions, ChatFloodGuard, ChatRecord, PinnedMessage, JOIN_HISTORY_MESSAGES, MAX_HISTORY_PAGE, EVERYONE_MENTION, EVERYONE_MENTION_COOLDOWN, TYPING_TIMEOUT};
+use super::countdown::{start_countdown, Countdown, MAX_COUNTDOWN_LABEL_CHARS, MAX_COUNTDOWN_SECS};
+use super::echo::{is_echo_room, EchoReport, LoopbackStats, ECHO_REPORT_INTERVAL};
+use super::hub::{build_portals, Portal, HUB_ROOM_ID};
+use crate::auth::VerifiedIdentity;
+use crate::revocations::RevocationList;
+use crate::time_limits::{TimeLimitSettings, TimeLimitStatus, TimeLimitStore};
+use crate::uploads::{is_valid_upload_id, issue_upload_token};
+use super::link_preview::{extract_url, fetch_link_preview, LinkPreview};
+use super::instances::base_room_id;
+use super::interest::{interest_radius, within_interest, FAR_PLAYER_SYNC_INTERVAL};
+use super::proximity::{wants_subscription, PROXIMITY_CHECK_INTERVAL};
+use super::ice_batch::{IceBatch, IceTarget, QueueIceCandidate, ICE_BATCH_WINDOW, ICE_GATHERING_QUIET_PERIOD};
+use super::motion::{MotionEvent, MotionTracker, MovementEffects};
+use super::movement::{MoveCheck, MovementValidator};
+use super::moderation::{self, Verdict};
+use super::protocol::{SessionFeatures, WireProtocol};
+use super::publish_quality::{PublishQuality, ReceiverReport};
+use super::reactions::is_allowed_reaction;
+use super::publisher_registry::{is_valid_session_key, PublisherRegistry, LEASE_RENEW_INTERVAL};
+use super::rate_limit::{MessageClass, MessageRateLimiter, RateDecision};
+use super::reconnect::{issue_reconnect_token, ReconnectPolicy};
+use super::relay::{link_audience, relay_publisher, stop_relaying, watchable_publishers};
+use super::resume::{issue_resume_token, resume_grace, ParkedSession, SessionMedia};
+use super::roles::Role;
+use super::room::{is_waiting_room, waiting_room_id, Room, RoomOwner};
+use super::room_password::{MAX_ROOM_PASSWORD_LEN, PASSWORD_PROMPT_TIMEOUT, WRONG_PASSWORD_CLOSE_CODE};
+use super::simulcast::{max_layer_for, simulcast_layer};
+use super::spatial_audio::SpeakingDistance;
+use super::theme::{theme_for_room, AmbientEmitter, WorldBounds};
+use super::transfer::TRANSFER_CODE_TTL;
+use super::tts::{TtsNarrator, TTS_PLAYER_ID};
+
+/// Lead time before a cutscene starts so every client receives the broadcast in time
+const CUTSCENE_LEAD_TIME_MS: i64 = 3000;
+/// How often connected time is added to a profile's daily usage
+const TIME_LIMIT_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+/// Warn this long before a time limit disconnects the player
+const TIME_LIMIT_WARNING: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+/// How often live sessions re-check the revocation list, so revocations from other nodes apply
+const REVOCATION_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15);
+/// Warn this long before the session length limit disconnects the player
+const SESSION_EXPIRY_WARNING: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+/// How often players are checked for having gone idle
+const AFK_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+/// Keyframes are expensive for the publisher, so each subscription can ask for one this often at most
+const KEYFRAME_REQUEST_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+/// How often each subscription's RTCP feedback is sampled into its publisher's reception stats
+const RECEIVER_STATS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+/// Longest slow-mode interval a host can set
+const MAX_SLOW_MODE_SECS: u64 = 600;
 
 /// ICE server configuration for WebRTC (serializable version for frontend)
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -38,19 +92,67 @@ impl From<&RTCIceServer> for IceServerConfig {
 }
 
 /// 3D position in the game world
-#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct Position {
     pub x: f32,
     pub y: f32,
     pub z: f32,
 }
 
+/// Longest publisher label kept; the rest is cut off
+const MAX_PUBLISHER_LABEL_CHARS: usize = 64;
+/// Longest `E2eeKey` payload relayed; wrapped keys are far smaller
+const MAX_E2EE_KEY_CHARS: usize = 2048;
+
+/// What a publisher's track carries, so screens can be rendered differently from faces
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PublisherSource {
+    Mic,
+    Camera,
+    Screen,
+}
+
+/// Description of a publisher: source and label as the client declared them in `Publish`, and the
+/// track's kind as negotiated with the SFU
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PublisherMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<PublisherSource>,
+    /// e.g. the shared window's title
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// From the publish offer's media section, so unlike `source` the client can't misstate it
+    #[serde(skip)]
+    pub kind: Option<RTPCodecType>,
+    /// Published from an E2EE session, so only E2EE sessions can subscribe
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub encrypted: bool,
+}
+
+impl PublisherMetadata {
+    /// Anything not negotiated as video might be a mic, so a forced mute covers it
+    fn may_carry_voice(&self) -> bool {
+        self.kind != Some(RTPCodecType::Video)
+    }
+
+    fn new(source: Option<PublisherSource>, label: Option<String>, kind: Option<RTPCodecType>, encrypted: bool) -> Self {
+        let label = label
+            .map(|label| label.trim().chars().take(MAX_PUBLISHER_LABEL_CHARS).collect::<String>())
+            .filter(|label| !label.is_empty());
+        Self { source, label, kind, encrypted }
+    }
+}
+
 /// Publisher info for sync/polling
 #[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct PublisherInfo {
     pub publisher_id: String,
     pub player_id: String,
+    #[serde(flatten)]
+    pub metadata: PublisherMetadata,
 }
 
 /// Facial feature customization options
@@ -80,6 +182,50 @@ pub struct PlayerData {
     pub position: Position,
     pub rotation: f32,
     pub is_moving: bool,
+    /// ISO 639-1 code the player asked to be matched on
+    pub language: String,
+    /// Public `@handle` claimed by the player's profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub handle: Option<String>,
+    /// From a signed `roleToken` at join, or set live through the admin API
+    #[serde(default)]
+    pub role: Role,
+    #[serde(default)]
+    pub status: PlayerStatus,
+    /// Verified profile or address mutes are placed on, see `moderation_identity`; never sent to clients
+    #[serde(skip)]
+    pub moderation_identity: Option<String>,
+}
+
+/// Whether a player is at their keyboard, see `check_afk`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PlayerStatus {
+    #[default]
+    Active,
+    /// Hasn't moved, chatted or reacted for `afk_after_secs`
+    Idle,
+}
+
+/// A player's movement state, used in batched position messages
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerPosition {
+    pub player_id: String,
+    pub position: Position,
+    pub rotation: f32,
+    pub is_moving: bool,
+}
+
+impl From<PlayerData> for PlayerPosition {
+    fn from(player: PlayerData) -> Self {
+        Self {
+            player_id: player.id,
+            position: player.position,
+            rotation: player.rotation,
+            is_moving: player.is_moving,
+        }
+    }
 }
 
 /// WebSocket actor for handling streaming sessions
@@ -91,48 +237,106 @@ pub struct StreamingSession {
     publish_transport: Arc<rheomesh::publish_transport::PublishTransport>,
     subscribe_transport: Arc<rheomesh::subscribe_transport::SubscribeTransport>,
     publishers: Arc<Mutex<HashMap<String, Arc<Mutex<Publisher>>>>>,
+    /// Track ID -> kind from the latest publish offer, see `track_kinds`
+    offered_track_kinds: HashMap<String, RTPCodecType>,
     subscribers: Arc<Mutex<HashMap<String, Arc<Mutex<Subscriber>>>>>,
+    /// publisher_id -> subscriber_id (`None` while still subscribing), for auto-subscribe to tear
+    /// down when a publisher goes away or out of range
+    subscriptions: Arc<std::sync::Mutex<HashMap<String, Option<String>>>>,
+    /// Subscriptions the client paused itself, which unmuting their publisher mustn't resume
+    client_paused: HashSet<String>,
+    /// Sent `SubscriberInit`, so auto-subscribe offers have somewhere to go
+    subscriber_ready: bool,
     ice_servers: Vec<IceServerConfig>,
+    motion: MotionTracker,
+    movement: MovementValidator,
+    publisher_ice: IceBatch,
+    subscriber_ice: IceBatch,
+    transport_config: rheomesh::config::WebRTCTransportConfig,
+    bandwidth_profile: BandwidthProfile,
+    /// Last move forwarded to this client per remote player (profile rate limiting)
+    last_movement_sent: HashMap<String, std::time::Instant>,
+    /// Last `RequestKeyFrame` passed on per subscriber_id
+    last_keyframe_request: HashMap<String, std::time::Instant>,
+    /// When this player's last chat message was accepted (slow mode)
+    last_chat_at: Option<std::time::Instant>,
+    /// Burst and repeat limits with escalating cooldowns
+    chat_flood: ChatFloodGuard,
+    /// Text summaries of spatial events, only when the client opted in
+    accessibility: Option<AccessibilityTracker>,
+    /// Player ID this connection takes over from another device
+    transfer_from: Option<String>,
+    /// Set once another device took over, so leaving doesn't remove the player
+    transferred_away: bool,
+    /// Held for the resume grace window when the connection dropped, see `park`
+    parked: bool,
+    /// Lets a new connection take this session over if this one drops
+    resume_token: String,
+    /// This connection picked up a parked session
+    resumed: bool,
+    /// The client said goodbye or the server closed on purpose, so there's nothing to resume
+    leaving: bool,
+    /// Admin observing invisibly: not a player, can only watch and subscribe
+    ghost: bool,
+    /// Profile ID, whether the join verified it, and the store for daily time limits, when the client sent a profile
+    time_limits: Option<(String, bool, Data<std::sync::Mutex<TimeLimitStore>>)>,
+    /// Connected time not yet added to the profile's usage is counted from here
+    usage_since: std::time::Instant,
+    time_limit_warned: bool,
+    /// Verified profile ID and token ID checked against the revocation list, when the join proved an identity
+    revocations: Option<(String, Option<String>, Data<std::sync::RwLock<RevocationList>>)>,
+    /// Client session key and the registry its publishers are recorded in, for crash recovery
+    publisher_registry: Option<(String, PublisherRegistry)>,
+    /// Far-away positions last sent in a correction, so unchanged players are skipped
+    far_positions_sent: HashMap<String, Position>,
+    /// Optional features this client handles, negotiated by `Hello`
+    features: SessionFeatures,
+    /// Features are settled once per connection; a second `Hello` only gets them repeated
+    said_hello: bool,
+    /// Heartbeat pings sent since the client was last heard from
+    missed_heartbeats: u32,
+    /// When this session first joined; carried over on resume for the session length limit
+    session_started: std::time::Instant,
+    /// Budgets for incoming movement, chat and signaling messages
+    rate_limiter: MessageRateLimiter,
+    /// Clears this player's typing indicator if the client never says it stopped
+    typing_expiry: Option<SpawnHandle>,
+    /// Last time the player moved, chatted or reacted
+    last_activity: std::time::Instant,
+    /// Whether the room has been told this player is idle
+    afk: bool,
+    /// Room password from the join query, checked once the session starts
+    password_attempt: Option<String>,
+    /// Told the client `PasswordRequired` and waiting on `Authenticate`; not in the room yet
+    awaiting_password: bool,
 }
 
 impl StreamingSession {
-    pub async fn new(room: Arc<Room<Self>>, owner: Data<Mutex<RoomOwner<Self>>>, player_data: PlayerData, ice_servers: Vec<RTCIceServer>) -> Self {
-        let publish_transport;
-        let subscribe_transport;
-        {
-            let router = room.router.lock().await;
-
-            // Transport config - FORCE RELAY MODE to work around webrtc-rs DTLS issues
-            // webrtc-rs has bugs in both active and passive DTLS modes that cause
-            // intermittent handshake failures. By forcing all connections through TURN
-            // relay, we get a more reliable network path.
-            let mut config = rheomesh::config::WebRTCTransportConfig::default();
-            config.configuration = RTCConfiguration {
-                ice_servers: ice_servers.clone(),
-                // CRITICAL: Force relay-only mode to bypass DTLS/NAT issues
-                ice_transport_policy: RTCIceTransportPolicy::Relay,
-                ..Default::default()
-            };
-            // IPv4 only - IPv6 causes Windows binding errors (os error 10049)
-            config.network_types = vec![
-                NetworkType::Udp4,
-                NetworkType::Tcp4,
-            ];
-            // ICE timeouts
-            config.ice_disconnected_timeout = Some(std::time::Duration::from_secs(30));
-            config.ice_failed_timeout = Some(std::time::Duration::from_secs(60));
-            config.ice_keep_alive_interval = Some(std::time::Duration::from_secs(2));
-
-            tracing::info!("[SESSION] Using RELAY-ONLY mode (ice_transport_policy=Relay)");
-
-            publish_transport = router.create_publish_transport(config.clone()).await;
-            subscribe_transport = router.create_subscribe_transport(config).await;
+    pub async fn new(room: Arc<Room<Self>>, owner: Data<Mutex<RoomOwner<Self>>>, player_data: PlayerData, ice_servers: Vec<RTCIceServer>, bandwidth_profile: BandwidthProfile) -> Self {
+        let config = transport_config(&ice_servers, &room.id);
+        let media = open_session_media(&room, &config, &player_data.name).await;
+        Self::with_media(room, owner, player_data, &ice_servers, bandwidth_profile, config, media)
+    }
 
-            // DIAGNOSTIC: Log transport IDs for correlation
-            tracing::info!("[SESSION] player={} pub={} sub={}",
-                player_data.name, &publish_transport.id[..8], &subscribe_transport.id[..8]);
-        }
+    /// Pick up a parked session: same player, same transports and publishers, new connection
+    pub fn resume(room: Arc<Room<Self>>, owner: Data<Mutex<RoomOwner<Self>>>, parked: ParkedSession, player_data: PlayerData, ice_servers: Vec<RTCIceServer>, bandwidth_profile: BandwidthProfile) -> Self {
+        let config = transport_config(&ice_servers, &room.id);
+        let mut session = Self::with_media(room, owner, player_data, &ice_servers, bandwidth_profile, config, parked.media);
+        session.transfer_from = Some(parked.player_id);
+        session.session_started = parked.session_started;
+        session.resumed = true;
+        session
+    }
 
+    fn with_media(
+        room: Arc<Room<Self>>,
+        owner: Data<Mutex<RoomOwner<Self>>>,
+        player_data: PlayerData,
+        ice_servers: &[RTCIceServer],
+        bandwidth_profile: BandwidthProfile,
+        config: rheomesh::config::WebRTCTransportConfig,
+        media: SessionMedia,
+    ) -> Self {
         // Convert RTCIceServer to serializable IceServerConfig
         let ice_server_configs: Vec<IceServerConfig> = ice_servers.iter().map(|s| s.into()).collect();
 
@@ -141,261 +345,1850 @@ impl StreamingSession {
             room,
             player_id: String::new(), // Set in started()
             player_data,
-            publish_transport: Arc::new(publish_transport),
-            subscribe_transport: Arc::new(subscribe_transport),
-            publishers: Arc::new(Mutex::new(HashMap::new())),
-            subscribers: Arc::new(Mutex::new(HashMap::new())),
+            publish_transport: media.publish_transport,
+            subscribe_transport: media.subscribe_transport,
+            publishers: media.publishers,
+            offered_track_kinds: HashMap::new(),
+            subscribers: media.subscribers,
+            subscriptions: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            client_paused: HashSet::new(),
+            subscriber_ready: false,
             ice_servers: ice_server_configs,
+            motion: MotionTracker::new(),
+            movement: MovementValidator::new(),
+            publisher_ice: IceBatch::default(),
+            subscriber_ice: IceBatch::default(),
+            transport_config: config,
+            bandwidth_profile,
+            last_movement_sent: HashMap::new(),
+            last_keyframe_request: HashMap::new(),
+            last_chat_at: None,
+            chat_flood: ChatFloodGuard::default(),
+            accessibility: None,
+            transfer_from: None,
+            transferred_away: false,
+            parked: false,
+            resume_token: issue_resume_token(),
+            resumed: false,
+            leaving: false,
+            ghost: false,
+            time_limits: None,
+            usage_since: std::time::Instant::now(),
+            time_limit_warned: false,
+            revocations: None,
+            publisher_registry: None,
+            far_positions_sent: HashMap::new(),
+            features: SessionFeatures::default(),
+            said_hello: false,
+            missed_heartbeats: 0,
+            session_started: std::time::Instant::now(),
+            rate_limiter: MessageRateLimiter::default(),
+            typing_expiry: None,
+            last_activity: std::time::Instant::now(),
+            afk: false,
+            password_attempt: None,
+            awaiting_password: false,
         }
     }
-}
 
-impl Actor for StreamingSession {
-    type Context = ws::WebsocketContext<Self>;
+    /// Take over an existing player's identity and position instead of joining fresh
+    pub fn with_transfer(mut self, player_id: String) -> Self {
+        self.transfer_from = Some(player_id);
+        self
+    }
 
-    fn started(&mut self, ctx: &mut Self::Context) {
-        let address = ctx.address();
-        
-        // CRITICAL: Set up ALL callbacks BEFORE processing any messages
-        // Using ctx.wait() ensures the actor won't process ANY messages until this completes
-        let publish_transport = self.publish_transport.clone();
-        let subscribe_transport = self.subscribe_transport.clone();
-        let addr = address.clone();
+    /// Room password the client joined with (`password` query param)
+    pub fn with_password(mut self, password: Option<String>) -> Self {
+        self.password_attempt = password;
+        self
+    }
 
-        let setup_fut = async move {
-            // Publish transport: ICE candidate callback
-            let addr_clone = addr.clone();
-            publish_transport.on_ice_candidate(Box::new(move |candidate| {
-                if let Ok(json) = candidate.to_json() {
-                    tracing::debug!("[ICE] Publisher candidate generated");
-                    addr_clone.do_send(SendingMessage::PublisherIce { candidate: json });
-                }
-            })).await;
-            
-            // Subscribe transport: ICE candidate callback
-            let addr_clone = addr.clone();
-            subscribe_transport.on_ice_candidate(Box::new(move |candidate| {
-                if let Ok(json) = candidate.to_json() {
-                    tracing::debug!("[ICE] Subscriber candidate generated");
-                    addr_clone.do_send(SendingMessage::SubscriberIce { candidate: json });
-                }
-            })).await;
-            
-            // Subscribe transport: Negotiation needed callback (triggers Offer when tracks are added)
-            let addr_clone = addr.clone();
-            subscribe_transport.on_negotiation_needed(Box::new(move |offer| {
-                tracing::debug!("[SUBSCRIBE] Negotiation needed, sending Offer");
-                addr_clone.do_send(SendingMessage::Offer { sdp: offer });
-            })).await;
-            
-            tracing::info!("[SESSION] All callbacks registered");
-        };
-        
-        // Block message processing until callbacks are set up
-        ctx.wait(setup_fut.into_actor(self));
-        
-        // Now do player/room setup
-        self.player_id = self.room.add_player(address.clone(), self.player_data.clone());
+    /// Features until the client says `Hello`, from the join's `?protocol=`
+    pub fn with_features(mut self, features: SessionFeatures) -> Self {
+        self.features = features;
+        self
+    }
 
-        tracing::info!("[JOINED] player={} id={}", self.player_data.name, &self.player_id[..8]);
+    /// Write a message to the socket; game state goes out as MessagePack when the client asked for it
+    fn send(&self, ctx: &mut ws::WebsocketContext<Self>, msg: &SendingMessage) {
+        if self.features.protocol() == WireProtocol::Msgpack && msg.is_game_state() {
+            match rmp_serde::to_vec_named(msg) {
+                Ok(bytes) => ctx.binary(bytes),
+                Err(e) => tracing::error!("Failed to encode MessagePack: {}", e),
+            }
+            return;
+        }
+        ctx.text(serde_json::to_string(msg).expect("failed to serialize SendingMessage"));
+    }
 
-        let players = self.room.get_all_players();
-        address.do_send(SendingMessage::RoomState {
-            your_player_id: self.player_id.clone(),
-            players,
-            room_theme: self.room.theme.clone(),
-            ice_servers: self.ice_servers.clone(),
+    /// Track this connection against a profile's time limits
+    pub fn with_time_limits(
+        mut self,
+        profile_id: Option<String>,
+        identity: &VerifiedIdentity,
+        store: Data<std::sync::Mutex<TimeLimitStore>>,
+    ) -> Self {
+        self.time_limits = profile_id.map(|profile_id| {
+            let verified = identity.profile_id.as_ref() == Some(&profile_id);
+            (profile_id, verified, store)
         });
+        self
+    }
 
-        if let Some(new_player_data) = self.room.get_player_data(&self.player_id) {
-            for peer in self.room.get_peers(&self.player_id) {
-                peer.do_send(SendingMessage::PlayerJoined { player: new_player_data.clone() });
-            }
+    fn media(&self) -> SessionMedia {
+        SessionMedia {
+            publish_transport: self.publish_transport.clone(),
+            subscribe_transport: self.subscribe_transport.clone(),
+            publishers: self.publishers.clone(),
+            subscribers: self.subscribers.clone(),
         }
     }
 
-    fn stopped(&mut self, ctx: &mut Self::Context) {
-        tracing::info!("[LEFT] player={} id={}", self.player_data.name, &self.player_id[..8]);
-
-        let address = ctx.address();
-        let subscribe_transport = self.subscribe_transport.clone();
-        let publish_transport = self.publish_transport.clone();
-        let publishers = self.publishers.clone();
+    /// Keep the player in the room with their media for the resume grace window; if nobody resumes
+    /// by then, they leave as usual. Parked before the actor finishes stopping, so a reconnect that
+    /// comes straight back finds the session instead of joining as a second avatar
+    fn park(&mut self) {
+        tracing::info!("[{}] Connection dropped, holding the session for {:?}", self.player_data.name, resume_grace());
+        self.parked = true;
+        let resume_token = self.resume_token.clone();
+        self.room.park_session(resume_token.clone(), self.player_id.clone(), self.media(), self.session_started);
+        let owner = self.owner.clone();
         let room = self.room.clone();
-        let player_id = self.player_id.clone();
-
         actix::spawn(async move {
-            let publisher_ids: Vec<String> = publishers.lock().await.keys().cloned().collect();
-            for publisher_id in publisher_ids {
-                if let Some(publisher) = publishers.lock().await.remove(&publisher_id) {
-                    publisher.lock().await.close().await;
-                    room.unregister_publisher(&publisher_id);
-                    room.get_peers(&player_id).iter().for_each(|peer| {
-                        peer.do_send(SendingMessage::Unpublished { publisher_id: publisher_id.clone() });
-                    });
-                }
+            tokio::time::sleep(resume_grace()).await;
+            let expired = owner.lock().await.expire_parked_session(&resume_token);
+            if let Some(parked) = expired {
+                tracing::info!("Player {} did not resume in time, removing", &parked.player_id[..8.min(parked.player_id.len())]);
+                close_session_media(owner.clone(), room.clone(), parked.player_id.clone(), parked.media);
+                remove_from_room(&owner, &room, &parked.player_id);
             }
-            let _ = subscribe_transport.close().await;
-            let _ = publish_transport.close().await;
         });
+    }
+
+    /// Watch the room invisibly instead of joining it as a player (admin moderation)
+    pub fn as_ghost(mut self) -> Self {
+        self.ghost = true;
+        self
+    }
 
-        for peer in self.room.get_peers(&self.player_id) {
-            peer.do_send(SendingMessage::PlayerLeft { player_id: self.player_id.clone() });
+    /// Disconnect this connection if its verified account or token gets revoked
+    pub fn with_revocations(mut self, identity: &VerifiedIdentity, list: Data<std::sync::RwLock<RevocationList>>) -> Self {
+        self.revocations = identity.profile_id.clone().map(|profile_id| (profile_id, identity.token_id.clone(), list));
+        self
+    }
+
+    /// Record this connection's publishers under the client's session key
+    pub fn with_publisher_registry(mut self, session_key: Option<String>, registry: &PublisherRegistry) -> Self {
+        self.publisher_registry = session_key
+            .filter(|session_key| registry.is_enabled() && is_valid_session_key(session_key))
+            .map(|session_key| (session_key, registry.clone()));
+        self
+    }
+
+    /// Ping the client, or drop the connection once too many pings went unanswered. Not a
+    /// deliberate leave, so the session is parked and can still be resumed
+    fn heartbeat(&mut self, ctx: &mut ws::WebsocketContext<Self>) {
+        let max_missed = crate::config::get().server.heartbeat_max_missed;
+        if self.missed_heartbeats >= max_missed {
+            tracing::info!("[{}] Missed {} heartbeats, dropping connection", self.player_data.name, self.missed_heartbeats);
+            ctx.stop();
+            return;
         }
+        self.missed_heartbeats += 1;
+        ctx.ping(b"");
+    }
 
-        if let Some((_, remaining)) = self.room.remove_player_by_addr(&address) {
-            if remaining == 0 {
-                let owner = self.owner.clone();
-                let room_id = self.room.id.clone();
-                actix::spawn(async move {
-                    owner.lock().await.remove_room(room_id);
+    /// Warn ahead of the room's session length limit, then disconnect with a token to rejoin
+    fn schedule_session_expiry(&mut self, ctx: &mut ws::WebsocketContext<Self>) {
+        let Some(max_session) = crate::config::get().server.max_session_for(base_room_id(&self.room.id)) else {
+            return;
+        };
+        let remaining = max_session.saturating_sub(self.session_started.elapsed());
+        if remaining > SESSION_EXPIRY_WARNING {
+            ctx.run_later(remaining - SESSION_EXPIRY_WARNING, |_act, ctx| {
+                ctx.address().do_send(SendingMessage::SystemMessage {
+                    message: format!("This session ends in {} minutes", SESSION_EXPIRY_WARNING.as_secs() / 60),
                 });
-            }
+            });
         }
+        ctx.run_later(remaining, |act, ctx| {
+            tracing::info!("[{}] Session length limit reached, disconnecting", act.player_data.name);
+            act.leaving = true;
+            let message = SendingMessage::SessionExpired {
+                reconnect: ReconnectPolicy::DEFAULT,
+                reconnect_token: issue_reconnect_token(&act.room.id),
+            };
+            act.send(ctx, &message);
+            ctx.close(Some(ws::CloseReason {
+                code: ws::CloseCode::Policy,
+                description: Some("session length limit reached".to_string()),
+            }));
+            ctx.stop();
+        });
     }
-}
 
-impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for StreamingSession {
-    fn handle(&mut self, item: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
-        match item {
-            Ok(ws::Message::Ping(msg)) => ctx.pong(&msg),
-            Ok(ws::Message::Pong(_)) => {},
-            Ok(ws::Message::Text(text)) => {
-                if let Ok(message) = serde_json::from_str::<ReceivedMessage>(&text) {
-                    ctx.address().do_send(message);
-                }
-            },
-            Ok(ws::Message::Binary(bin)) => ctx.binary(bin),
-            Ok(ws::Message::Close(reason)) => ctx.close(reason),
-            _ => (),
+    fn broadcast_status(&self, status: PlayerStatus) {
+        self.room.set_player_status(&self.player_id, status);
+        let message = SendingMessage::PlayerStatus {
+            player_id: self.player_id.clone(),
+            status,
+        };
+        for addr in self.room.get_all_addrs() {
+            addr.do_send(message.clone());
         }
     }
-}
 
-impl Handler<ReceivedMessage> for StreamingSession {
-    type Result = ();
+    /// Anything the player does themselves (not signaling their client does on its own) counts as activity
+    fn mark_active(&mut self) {
+        self.last_activity = std::time::Instant::now();
+        if self.afk {
+            self.afk = false;
+            self.broadcast_status(PlayerStatus::Active);
+        }
+    }
 
-    fn handle(&mut self, msg: ReceivedMessage, ctx: &mut Self::Context) -> Self::Result {
-        let address = ctx.address();
-        let player_name = self.player_data.name.clone();
+    /// Tell the room once the player goes idle, and disconnect them if they stay idle past the limit
+    fn check_afk(&mut self, ctx: &mut ws::WebsocketContext<Self>) {
+        let server = &crate::config::get().server;
+        let idle = self.last_activity.elapsed();
+        if server.afk_disconnect().is_some_and(|limit| idle >= limit) {
+            tracing::info!("[{}] Idle for {}s, disconnecting", self.player_data.name, idle.as_secs());
+            self.leaving = true;
+            let message = SendingMessage::SystemMessage {
+                message: "You were disconnected for being idle".to_string(),
+            };
+            self.send(ctx, &message);
+            ctx.close(Some(ws::CloseReason {
+                code: ws::CloseCode::Policy,
+                description: Some("idle for too long".to_string()),
+            }));
+            ctx.stop();
+            return;
+        }
+        if !self.afk && server.afk_after().is_some_and(|after| idle >= after) {
+            self.afk = true;
+            self.broadcast_status(PlayerStatus::Idle);
+        }
+    }
 
-        match msg {
-            ReceivedMessage::Ping => {
-                address.do_send(SendingMessage::Pong);
+    /// Mutes and flood limits apply to room chat and direct messages alike
+    fn chat_allowed(&mut self, message: &str, ctx: &mut ws::WebsocketContext<Self>) -> bool {
+        if let Some(remaining) = self.room.chat_muted_for(&self.player_id) {
+            ctx.address().do_send(SendingMessage::ChatRejected {
+                reason: "You're muted".to_string(),
+                retry_after_ms: remaining.as_millis() as u64,
+            });
+            return false;
+        }
+        if let Err(rejection) = self.chat_flood.check(message) {
+            ctx.address().do_send(SendingMessage::ChatRejected {
+                reason: rejection.reason,
+                retry_after_ms: rejection.retry_after.as_millis() as u64,
+            });
+            return false;
+        }
+        true
+    }
+
+    /// Room slow mode: whether the player may post again yet. The host isn't held to it
+    fn slow_mode_allows(&mut self, ctx: &mut ws::WebsocketContext<Self>) -> bool {
+        let slow_mode = self.room.get_slow_mode();
+        if !slow_mode.is_zero() && !self.room.is_host(&self.player_id) {
+            if let Some(last_chat_at) = self.last_chat_at {
+                let elapsed = last_chat_at.elapsed();
+                if elapsed < slow_mode {
+                    ctx.address().do_send(SendingMessage::SlowModeActive {
+                        retry_after: (slow_mode - elapsed).as_secs_f32().ceil() as u64,
+                    });
+                    return false;
+                }
             }
-            ReceivedMessage::PublisherInit => {
-                // Callbacks are set up in started(), this just logs
-                tracing::info!("[{}] PublisherInit (callbacks already registered)", player_name);
+        }
+        self.last_chat_at = Some(std::time::Instant::now());
+        true
+    }
+
+    /// Who owns this player's chat messages: their verified profile, so edits and deletes still work
+    /// after a reconnect, else just this connection. Addresses are shared behind NAT, so they don't count
+    fn author_identity(&self) -> String {
+        match &self.player_data.moderation_identity {
+            Some(identity) if identity.starts_with("profile:") => identity.clone(),
+            _ => format!("player:{}", self.player_id),
+        }
+    }
+
+    /// Run a message through the moderation pipeline, then deliver whatever survives
+    fn moderate_and_deliver(&mut self, message: String, delivery: ChatDelivery, ctx: &mut ws::WebsocketContext<Self>) {
+        let moderation = moderation::pipeline();
+        if moderation.is_empty() {
+            self.deliver(message, delivery, ctx);
+            return;
+        }
+        let sender = self.player_data.name.clone();
+        let room_id = self.room.id.clone();
+        let review = async move {
+            let verdict = moderation.review(message.clone(), &sender, &room_id).await;
+            (message, verdict)
+        };
+        ctx.spawn(review.into_actor(self).map(move |(message, verdict), act, ctx| {
+            act.apply_moderation(message, delivery, verdict, ctx);
+        }));
+    }
+
+    fn deliver(&mut self, message: String, delivery: ChatDelivery, ctx: &mut ws::WebsocketContext<Self>) {
+        match delivery {
+            ChatDelivery::Room { upload_id } => self.deliver_chat(message, upload_id, ctx),
+            ChatDelivery::Direct { to_player_id } => self.deliver_direct(to_player_id, message, ctx),
+            ChatDelivery::Edit { message_id } => self.deliver_edit(message_id, message, ctx),
+        }
+    }
+
+    fn deliver_edit(&mut self, message_id: String, message: String, ctx: &mut ws::WebsocketContext<Self>) {
+        let Some(record) = self.room.edit_chat(&message_id, message) else {
+            ctx.address().do_send(SendingMessage::MessageActionFailed {
+                message_id,
+                reason: "That message is gone".to_string(),
+            });
+            return;
+        };
+        let edited = SendingMessage::MessageEdited {
+            message_id: record.message_id,
+            message: record.message,
+            edited_at: record.edited_at.unwrap_or_default(),
+        };
+        for peer in self.room.get_all_addrs() {
+            peer.do_send(edited.clone());
+        }
+    }
+
+    /// Act on the moderation pipeline's verdict for a chat message
+    fn apply_moderation(&mut self, message: String, delivery: ChatDelivery, verdict: Verdict, ctx: &mut ws::WebsocketContext<Self>) {
+        match verdict {
+            Verdict::Allow => self.deliver(message, delivery, ctx),
+            Verdict::Mask(masked) => self.deliver(masked, delivery, ctx),
+            Verdict::Drop { reason } => ctx.address().do_send(SendingMessage::ChatRejected { reason, retry_after_ms: 0 }),
+            Verdict::Mute { reason } => {
+                let duration = moderation::pipeline().mute_duration();
+                self.room.mute_chat(&self.player_id, duration);
+                ctx.address().do_send(SendingMessage::ChatRejected {
+                    reason,
+                    retry_after_ms: duration.as_millis() as u64,
+                });
             }
-            ReceivedMessage::SubscriberInit => {
-                // Callbacks are set up in started()
-                // Just send existing publishers to this client
-                tracing::info!("[{}] SubscriberInit (callbacks already registered)", player_name);
-                let room = self.room.clone();
-                
-                // Send existing publishers grouped by player
-                let all_publishers = room.get_all_publishers();
-                let mut publishers_by_player: std::collections::HashMap<String, Vec<String>> = std::collections::HashMap::new();
-                for (publisher_id, player_id) in all_publishers {
-                    publishers_by_player.entry(player_id).or_insert_with(Vec::new).push(publisher_id);
-                }
-                for (player_id, publisher_ids) in publishers_by_player {
-                    address.do_send(SendingMessage::Published { publisher_ids, player_id });
-                }
+        }
+    }
+
+    /// Send a private message to one player in the room; the sender gets a receipt once the
+    /// recipient's connection has it, or an error if they've left
+    fn deliver_direct(&mut self, to_player_id: String, message: String, ctx: &mut ws::WebsocketContext<Self>) {
+        let address = ctx.address();
+        let message_id = uuid::Uuid::new_v4().to_string();
+        let Some(recipient) = self.room.get_addr(&to_player_id) else {
+            address.do_send(SendingMessage::DirectMessageFailed {
+                message_id,
+                to_player_id,
+                reason: "That player isn't in the room anymore".to_string(),
+            });
+            return;
+        };
+        let direct = SendingMessage::DirectMessage {
+            message_id: message_id.clone(),
+            from_player_id: self.player_id.clone(),
+            sender: self.player_data.name.clone(),
+            sender_handle: self.player_data.handle.clone(),
+            message,
+        };
+        actix::spawn(async move {
+            match recipient.send(direct).await {
+                Ok(()) => address.do_send(SendingMessage::DirectMessageDelivered { message_id, to_player_id }),
+                Err(_) => address.do_send(SendingMessage::DirectMessageFailed {
+                    message_id,
+                    to_player_id,
+                    reason: "That player left before it arrived".to_string(),
+                }),
             }
-            ReceivedMessage::GetPublishers => {
-                // Return all active publishers for polling-based discovery
-                tracing::info!("[{}] GetPublishers", player_name);
-                let room = self.room.clone();
-                let all_publishers = room.get_all_publishers();
-                
-                let publishers: Vec<PublisherInfo> = all_publishers
-                    .into_iter()
-                    .map(|(publisher_id, player_id)| PublisherInfo { publisher_id, player_id })
-                    .collect();
-                
-                address.do_send(SendingMessage::PublisherList { publishers });
+        });
+    }
+
+    /// Send an accepted chat message to the room, with mentions, text-to-speech and link previews
+    fn deliver_chat(&mut self, message: String, upload_id: Option<String>, ctx: &mut ws::WebsocketContext<Self>) {
+        let address = ctx.address();
+        let room = self.room.clone();
+        let sender = self.player_data.name.clone();
+        let sender_handle = self.player_data.handle.clone();
+        let message_id = uuid::Uuid::new_v4().to_string();
+        let url = extract_url(&message);
+        if let Some(tts) = room.get_tts() {
+            if !message.trim().is_empty() {
+                tts.speak(&sender, &message);
             }
-            ReceivedMessage::PublisherIce { candidate } => {
-                tracing::info!("[{}] PublisherIce received: {}", player_name, candidate.candidate.chars().take(60).collect::<String>());
-                let publish_transport = self.publish_transport.clone();
-                actix::spawn(async move {
-                    let _ = publish_transport.add_ice_candidate(candidate).await;
+        }
+        room.record_chat(ChatRecord {
+            message_id: message_id.clone(),
+            sender_id: self.player_id.clone(),
+            author_identity: self.author_identity(),
+            sender: sender.clone(),
+            sender_handle: sender_handle.clone(),
+            message: message.clone(),
+            upload_id: upload_id.clone(),
+            sent_at: chrono::Utc::now().timestamp_millis(),
+            edited_at: None,
+        });
+        room.get_all_addrs().iter().for_each(|peer| {
+            peer.do_send(SendingMessage::ChatMessage {
+                message_id: message_id.clone(),
+                sender: sender.clone(),
+                sender_handle: sender_handle.clone(),
+                message: message.clone(),
+                upload_id: upload_id.clone(),
+            });
+        });
+
+        // `@handle` and `@everyone` mentions also notify players directly, wherever they are in linked rooms
+        let mut handles = parse_mentions(&message);
+        let mentioned = SendingMessage::Mentioned {
+            message_id: message_id.clone(),
+            by: sender.clone(),
+            by_handle: sender_handle.clone(),
+            room_id: room.id.clone(),
+            message: message.clone(),
+        };
+        let mut mention_everyone = false;
+        if handles.iter().any(|handle| handle == EVERYONE_MENTION) {
+            handles.retain(|handle| handle != EVERYONE_MENTION);
+            if !room.is_host(&self.player_id) {
+                address.do_send(SendingMessage::SystemMessage {
+                    message: "Only the host can mention @everyone".to_string(),
                 });
-            }
-            ReceivedMessage::SubscriberIce { candidate } => {
-                tracing::info!("[{}] SubscriberIce received", player_name);
-                let subscribe_transport = self.subscribe_transport.clone();
-                actix::spawn(async move {
-                    let _ = subscribe_transport.add_ice_candidate(candidate).await;
+            } else if let Err(retry_after) = room.try_mention_everyone(EVERYONE_MENTION_COOLDOWN) {
+                address.do_send(SendingMessage::SystemMessage {
+                    message: format!("@everyone was used recently, try again in {}s", retry_after.as_secs_f32().ceil() as u64),
                 });
+            } else {
+                mention_everyone = true;
+                // Everyone hears about it once, however many `@handle`s the message also has
+                handles.clear();
             }
-            ReceivedMessage::Offer { sdp } => {
-                tracing::info!("[{}] Offer len={}", player_name, sdp.sdp.len());
-                let publish_transport = self.publish_transport.clone();
-                let player = player_name.clone();
-                actix::spawn(async move {
-                    match publish_transport.get_answer(sdp).await {
-                        Ok(answer) => {
-                            tracing::info!("[{}] Answer sent", player);
-                            address.do_send(SendingMessage::Answer { sdp: answer });
-                        }
-                        Err(e) => {
-                            tracing::error!("[{}] Answer error: {}", player, e);
-                        }
+        }
+        handles.retain(|handle| sender_handle.as_deref() != Some(handle.as_str()));
+        if mention_everyone || !handles.is_empty() {
+            let owner = self.owner.clone();
+            let room = room.clone();
+            let player_id = self.player_id.clone();
+            actix::spawn(async move {
+                let mut rooms = vec![room.clone()];
+                rooms.extend(owner.lock().await.linked_rooms(&room.id));
+                // Rooms linked to a stage share its chat, so `@everyone` reaches their players too
+                if mention_everyone {
+                    for peer in rooms.iter().flat_map(|room| room.get_peers(&player_id)) {
+                        peer.do_send(mentioned.clone());
+                    }
+                }
+                for handle in handles {
+                    if let Some(peer) = rooms.iter().find_map(|room| room.get_addr_by_handle(&handle)) {
+                        peer.do_send(mentioned.clone());
                     }
+                }
+            });
+        }
+
+        // Fetch once on the server so every client shows the same preview
+        if let Some(url) = url {
+            actix::spawn(async move {
+                let Some(preview) = fetch_link_preview(&url).await else {
+                    return;
+                };
+                room.get_all_addrs().iter().for_each(|peer| {
+                    peer.do_send(SendingMessage::LinkPreview {
+                        message_id: message_id.clone(),
+                        preview: preview.clone(),
+                    });
                 });
-            }
-            ReceivedMessage::Subscribe { publisher_id } => {
-                tracing::info!("[{}] Subscribe to {}", player_name, &publisher_id[..8.min(publisher_id.len())]);
-                let subscribe_transport = self.subscribe_transport.clone();
-                let subscribers = self.subscribers.clone();
-                let player = player_name.clone();
-                let pub_id = publisher_id.clone();
+            });
+        }
+    }
 
-                actix::spawn(async move {
-                    let max_retries = 5;
-                    let mut last_error = String::new();
+    /// The session a moderator command targets, or why it can't be used on that player
+    fn moderation_target(&self, player_id: &str) -> Result<actix::Addr<Self>, &'static str> {
+        if !self.player_data.role.is_moderator() {
+            return Err("Only moderators can do that");
+        }
+        if player_id == self.player_id {
+            return Err("You can't do that to yourself");
+        }
+        let target = self.room.get_player_data(player_id).ok_or("That player isn't in the room anymore")?;
+        if target.role.is_moderator() {
+            return Err("Moderators can't kick or mute each other");
+        }
+        self.room.get_addr(player_id).ok_or("That player isn't in the room anymore")
+    }
 
-                    for attempt in 0..max_retries {
-                        if attempt > 0 {
-                            tokio::time::sleep(tokio::time::Duration::from_millis(100 * (1 << (attempt - 1)))).await;
-                        }
+    /// Subscribe to publishers one after another, retrying each while it finishes setting up. Every
+    /// subscribe renegotiates the same peer connection, so only the last offer goes to the client
+    fn subscribe(&self, publisher_ids: Vec<String>, address: &actix::Addr<Self>) {
+        // Encrypted media is noise to a client without the keys
+        let (publisher_ids, encrypted): (Vec<String>, Vec<String>) = publisher_ids
+            .into_iter()
+            .partition(|publisher_id| self.features.e2ee || !self.room.get_publisher_metadata(publisher_id).encrypted);
+        for publisher_id in encrypted {
+            address.do_send(SendingMessage::SubscribeFailed {
+                publisher_id,
+                error: "that publisher is end-to-end encrypted".to_string(),
+            });
+        }
+        let publisher_ids: Vec<String> = {
+            let mut subscriptions = self.subscriptions.lock().unwrap();
+            publisher_ids
+                .into_iter()
+                .filter(|publisher_id| !subscriptions.get(publisher_id).is_some_and(Option::is_none))
+                .inspect(|publisher_id| {
+                    subscriptions.entry(publisher_id.clone()).or_insert(None);
+                })
+                .collect()
+        };
+        if publisher_ids.is_empty() {
+            return;
+        }
+        let short_ids: Vec<&str> = publisher_ids.iter().map(|id| &id[..8.min(id.len())]).collect();
+        tracing::info!("[{}] Subscribe to {}", self.player_data.name, short_ids.join(", "));
+        let subscribe_transport = self.subscribe_transport.clone();
+        let subscribers = self.subscribers.clone();
+        let subscriptions = self.subscriptions.clone();
+        let room = self.room.clone();
+        let player = self.player_data.name.clone();
+        let max_subscriptions = self.bandwidth_profile.limits().max_subscriptions;
+        let address = address.clone();
 
-                        match subscribe_transport.subscribe(pub_id.clone()).await {
-                            Ok((subscriber, offer)) => {
-                                let id = subscriber.lock().await.id.clone();
-                                subscribers.lock().await.insert(id.clone(), subscriber);
-                                address.do_send(SendingMessage::Offer { sdp: offer });
-                                address.do_send(SendingMessage::Subscribed { subscriber_id: id });
-                                return;
-                            }
-                            Err(e) => {
-                                last_error = e.to_string();
+        actix::spawn(async move {
+            let mut offer = None;
+            let mut subscribed_ids = Vec::new();
+            for publisher_id in publisher_ids {
+                if subscribers.lock().await.len() >= max_subscriptions {
+                    tracing::info!("[{}] Subscribe refused: bandwidth profile allows {}", player, max_subscriptions);
+                    subscriptions.lock().unwrap().remove(&publisher_id);
+                    address.do_send(SendingMessage::SubscribeFailed {
+                        publisher_id,
+                        error: "subscription limit reached for bandwidth profile".to_string(),
+                    });
+                    continue;
+                }
+                match subscribe_with_retry(&subscribe_transport, &publisher_id).await {
+                    Ok((subscriber, latest_offer)) => {
+                        let id = subscriber.lock().await.id.clone();
+                        // Nothing is forwarded from a publisher its owner has muted
+                        if room.is_publisher_muted(&publisher_id) {
+                            if let Err(e) = subscriber.lock().await.pause().await {
+                                tracing::warn!("[{}] Couldn't pause subscription to muted publisher: {}", player, e);
                             }
                         }
+                        // The room's cap holds at the SFU too, whatever the publisher's encoder does with `b=AS`;
+                        // non-simulcast publishers have no lower layer and rheomesh reports that as an error
+                        let is_video = room.get_publisher_metadata(&publisher_id).kind == Some(RTPCodecType::Video);
+                        if let Some(kbps) = max_video_kbps(&room.id).filter(|_| is_video) {
+                            let _ = subscriber.lock().await.set_preferred_layer(max_layer_for(kbps), None).await;
+                        }
+                        subscribers.lock().await.insert(id.clone(), subscriber);
+                        subscriptions.lock().unwrap().insert(publisher_id, Some(id.clone()));
+                        subscribed_ids.push(id);
+                        offer = Some(latest_offer);
                     }
-
-                    tracing::error!("[{}] Subscribe failed: {}", player, last_error);
-                    address.do_send(SendingMessage::SubscribeFailed { publisher_id: pub_id, error: last_error });
-                });
+                    Err(e) => {
+                        tracing::error!("[{}] Subscribe failed: {}", player, e);
+                        subscriptions.lock().unwrap().remove(&publisher_id);
+                        address.do_send(SendingMessage::SubscribeFailed { publisher_id, error: e });
+                    }
+                }
             }
-            ReceivedMessage::Answer { sdp } => {
-                let subscribe_transport = self.subscribe_transport.clone();
-                actix::spawn(async move {
-                    let _ = subscribe_transport.set_answer(sdp).await;
-                });
+            if let Some(offer) = offer {
+                address.do_send(SendingMessage::Offer { sdp: offer });
             }
-            ReceivedMessage::Publish { publisher_id } => {
-                let start = std::time::Instant::now();
-                let pub_id_short = &publisher_id[..8.min(publisher_id.len())];
-                tracing::info!("[{}] Publish track={}", player_name, pub_id_short);
+            for subscriber_id in subscribed_ids {
+                address.do_send(SendingMessage::Subscribed { subscriber_id });
+            }
+        });
+    }
 
-                let room = self.room.clone();
-                let player_id = self.player_id.clone();
-                let publish_transport = self.publish_transport.clone();
-                let publishers = self.publishers.clone();
+    /// Close this session's subscription to a publisher, returning its subscriber ID if it had finished subscribing
+    fn unsubscribe(&self, publisher_id: &str) -> Option<String> {
+        let subscriber_id = self.subscriptions.lock().unwrap().remove(publisher_id).flatten()?;
+        let subscribers = self.subscribers.clone();
+        let id = subscriber_id.clone();
+        actix::spawn(async move {
+            if let Some(subscriber) = subscribers.lock().await.remove(&id) {
+                subscriber.lock().await.close().await;
+            }
+        });
+        Some(subscriber_id)
+    }
+
+    /// Auto-subscribed sessions in rooms with proximity subscribe only receive publishers within earshot
+    fn proximity_subscribe(&self) -> bool {
+        self.features.auto_subscribe && crate::config::get().media.proximity_subscribe
+    }
+
+    /// Auto-subscribe: follow the room's publishers without the client asking
+    fn auto_subscribe(&mut self, msg: &SendingMessage, ctx: &mut ws::WebsocketContext<Self>) {
+        if !self.features.auto_subscribe || !self.subscriber_ready {
+            return;
+        }
+        match msg {
+            SendingMessage::Published { player_id, .. } if *player_id != self.player_id && self.proximity_subscribe() => {
+                self.reconcile_proximity(ctx);
+            }
+            SendingMessage::Published { publisher_ids, player_id, .. } if *player_id != self.player_id => {
+                let new_ids: Vec<String> = {
+                    let subscriptions = self.subscriptions.lock().unwrap();
+                    publisher_ids.iter().filter(|id| !subscriptions.contains_key(*id)).cloned().collect()
+                };
+                self.subscribe(new_ids, &ctx.address());
+            }
+            SendingMessage::Unpublished { publisher_id } => {
+                self.unsubscribe(publisher_id);
+            }
+            _ => {}
+        }
+    }
+
+    /// Subscribe to publishers whose owners came within earshot and drop those that wandered off
+    fn reconcile_proximity(&mut self, ctx: &mut ws::WebsocketContext<Self>) {
+        if !self.subscriber_ready {
+            return;
+        }
+        let Some(own) = self.room.get_player_data(&self.player_id) else {
+            return;
+        };
+        let distance = theme_for_room(&self.room.id).speaking_distance;
+        let positions: HashMap<String, Position> = self
+            .room
+            .get_peers_data(&self.player_id)
+            .into_iter()
+            .map(|player| (player.id, player.position))
+            .collect();
+        let address = ctx.address();
+        let mut in_range = Vec::new();
+        for info in self.room.get_publisher_infos() {
+            if info.player_id == self.player_id {
+                continue;
+            }
+            let subscribed = self.subscriptions.lock().unwrap().contains_key(&info.publisher_id);
+            // Publishers without an avatar (the narrator) have no position, so they're always in range
+            let wanted = positions
+                .get(&info.player_id)
+                .is_none_or(|position| wants_subscription(&own.position, position, &distance, subscribed));
+            if wanted && !subscribed {
+                in_range.push(info.publisher_id);
+            } else if !wanted && subscribed {
+                if let Some(subscriber_id) = self.unsubscribe(&info.publisher_id) {
+                    address.do_send(SendingMessage::Unsubscribed {
+                        subscriber_id,
+                        publisher_id: info.publisher_id,
+                    });
+                }
+            }
+        }
+        self.subscribe(in_range, &address);
+    }
+
+    /// `PauseSubscriber` / `ResumeSubscriber`: the SFU stops forwarding to the subscription but keeps
+    /// its transceiver, so resuming is instant. Resuming a muted publisher only takes effect once it's unmuted
+    fn set_subscriber_paused(&mut self, subscriber_id: String, paused: bool, address: &actix::Addr<Self>) {
+        if paused {
+            self.client_paused.insert(subscriber_id.clone());
+        } else {
+            self.client_paused.remove(&subscriber_id);
+        }
+        let publisher_muted = self
+            .subscriptions
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(_, id)| id.as_deref() == Some(subscriber_id.as_str()))
+            .is_some_and(|(publisher_id, _)| self.room.is_publisher_muted(publisher_id));
+        let subscribers = self.subscribers.clone();
+        let address = address.clone();
+        actix::spawn(async move {
+            let Some(subscriber) = subscribers.lock().await.get(&subscriber_id).cloned() else {
+                address.do_send(SendingMessage::PauseSubscriberFailed {
+                    subscriber_id,
+                    error: "no such subscription".to_string(),
+                });
+                return;
+            };
+            let subscriber = subscriber.lock().await;
+            let result = match (paused, publisher_muted) {
+                (true, _) => subscriber.pause().await,
+                (false, true) => Ok(()),
+                (false, false) => subscriber.resume().await,
+            };
+            match result {
+                Ok(()) => address.do_send(SendingMessage::SubscriberPaused { subscriber_id, paused }),
+                Err(e) => address.do_send(SendingMessage::PauseSubscriberFailed { subscriber_id, error: e.to_string() }),
+            }
+        });
+    }
+
+    /// Stop or restart the SFU forwarding a publisher its owner muted, so muted media costs viewers no
+    /// bandwidth; subscriptions the client paused itself stay paused
+    fn set_publisher_forwarding(&self, publisher_id: &str, forwarding: bool) {
+        let Some(subscriber_id) = self.subscriptions.lock().unwrap().get(publisher_id).cloned().flatten() else {
+            return;
+        };
+        if forwarding && self.client_paused.contains(&subscriber_id) {
+            return;
+        }
+        let subscribers = self.subscribers.clone();
+        let player = self.player_data.name.clone();
+        actix::spawn(async move {
+            let Some(subscriber) = subscribers.lock().await.get(&subscriber_id).cloned() else {
+                return;
+            };
+            let subscriber = subscriber.lock().await;
+            let result = if forwarding { subscriber.resume().await } else { subscriber.pause().await };
+            if let Err(e) = result {
+                tracing::warn!("[{}] Couldn't {} muted publisher: {}", player, if forwarding { "resume" } else { "pause" }, e);
+            }
+        });
+    }
+
+    /// Pass on the RTCP this client sends back for each subscription (receiver reports and REMB) as
+    /// its reception of that publisher, for `spawn_publish_quality_loop` to aggregate
+    fn sample_receiver_stats(&self) {
+        let subscriptions: Vec<(String, String)> = self
+            .subscriptions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|(publisher_id, subscriber_id)| Some((publisher_id.clone(), subscriber_id.clone()?)))
+            .collect();
+        if subscriptions.is_empty() {
+            return;
+        }
+        let subscribers = self.subscribers.clone();
+        let room = self.room.clone();
+        let player_id = self.player_id.clone();
+        actix::spawn(async move {
+            for (publisher_id, subscriber_id) in subscriptions {
+                let Some(subscriber) = subscribers.lock().await.get(&subscriber_id).cloned() else {
+                    continue;
+                };
+                // Nothing until the client's first receiver report for this track
+                let Some(stats) = subscriber.lock().await.receiver_stats().await else {
+                    continue;
+                };
+                let report = ReceiverReport {
+                    fraction_lost: stats.fraction_lost.clamp(0.0, 1.0),
+                    jitter_ms: stats.jitter.as_secs_f32() * 1000.0,
+                    remb_kbps: stats.remb_bitrate.map(|bps| (bps / 1000) as u32),
+                    received_at: stats.received_at,
+                };
+                room.record_receiver_report(&publisher_id, &player_id, report);
+            }
+        });
+    }
+
+    /// Echo test: summarize the RTCP the client sends back for its own looped-back media
+    fn report_echo_stats(&self, ctx: &mut ws::WebsocketContext<Self>) {
+        let subscribers = self.subscribers.clone();
+        let address = ctx.address();
+        actix::spawn(async move {
+            let loopbacks: Vec<_> = subscribers.lock().await.values().cloned().collect();
+            let mut stats = Vec::new();
+            for subscriber in loopbacks {
+                if let Some(report) = subscriber.lock().await.receiver_stats().await {
+                    stats.push(LoopbackStats {
+                        fraction_lost: report.fraction_lost,
+                        jitter: report.jitter,
+                        round_trip_time: report.round_trip_time,
+                    });
+                }
+            }
+            if let Some(report) = EchoReport::from_loopback(&stats) {
+                address.do_send(SendingMessage::EchoReport { report });
+            }
+        });
+    }
+
+    /// `ForceMute` / `LiftForceMute`: the target's own session closes its voice publishers when it
+    /// hears about it, and `Publish` refuses new ones until the mute is lifted
+    fn set_force_muted(&self, player_id: String, muted: bool, address: &actix::Addr<Self>) {
+        if let Err(reason) = self.moderation_target(&player_id) {
+            address.do_send(SendingMessage::ModerationFailed {
+                player_id,
+                reason: reason.to_string(),
+            });
+            return;
+        }
+        if !self.room.set_force_muted(&player_id, muted) {
+            return;
+        }
+        tracing::info!(
+            "[{}] {} player {}",
+            self.player_data.name,
+            if muted { "Force-muted" } else { "Lifted force mute on" },
+            &player_id[..8.min(player_id.len())]
+        );
+        let message = SendingMessage::PlayerForceMuted {
+            player_id,
+            muted_by: self.player_data.name.clone(),
+            muted,
+        };
+        for peer in self.room.get_all_addrs() {
+            peer.do_send(message.clone());
+        }
+    }
+
+    /// Close every publisher this session has (or only those that may carry voice), telling the
+    /// room they're gone
+    fn close_publishers(&self, voice_only: bool) {
+        let owner = self.owner.clone();
+        let room = self.room.clone();
+        let player_id = self.player_id.clone();
+        let publishers = self.publishers.clone();
+        let publisher_registry = self.publisher_registry.clone();
+        actix::spawn(async move {
+            let closed: Vec<_> = {
+                let mut publishers = publishers.lock().await;
+                let ids: Vec<String> = publishers
+                    .keys()
+                    .filter(|id| !voice_only || room.get_publisher_metadata(id).may_carry_voice())
+                    .cloned()
+                    .collect();
+                ids.into_iter().filter_map(|id| publishers.remove_entry(&id)).collect()
+            };
+            for (publisher_id, publisher) in closed {
+                publisher.lock().await.close().await;
+                if room.unregister_publisher(&publisher_id) {
+                    stop_relaying(&owner, &room.id, &publisher_id).await;
+                }
+                if let Some((session_key, registry)) = &publisher_registry {
+                    registry.unregister(session_key, &publisher_id).await;
+                }
+                room.get_peers(&player_id).iter().for_each(|peer| {
+                    peer.do_send(SendingMessage::Unpublished { publisher_id: publisher_id.clone() });
+                });
+            }
+        });
+    }
+
+    /// Tell everyone else when this player starts or stops typing; repeated starts only push the expiry back
+    fn set_typing(&mut self, typing: bool, ctx: &mut ws::WebsocketContext<Self>) {
+        let was_typing = match self.typing_expiry.take() {
+            Some(handle) => ctx.cancel_future(handle),
+            None => false,
+        };
+        if typing {
+            self.typing_expiry = Some(ctx.run_later(TYPING_TIMEOUT, |act, ctx| {
+                act.typing_expiry = None;
+                act.broadcast_typing(false, ctx);
+            }));
+        }
+        if typing != was_typing {
+            self.broadcast_typing(typing, ctx);
+        }
+    }
+
+    fn broadcast_typing(&self, typing: bool, ctx: &mut ws::WebsocketContext<Self>) {
+        let address = ctx.address();
+        let indicator = SendingMessage::PlayerTyping {
+            player_id: self.player_id.clone(),
+            typing,
+        };
+        for peer in self.room.get_all_addrs().into_iter().filter(|peer| *peer != address) {
+            peer.do_send(indicator.clone());
+        }
+    }
+
+    /// Add this connection to the room and start its timers, once any room password checks out
+    fn join_room(&mut self, ctx: &mut ws::WebsocketContext<Self>) {
+        self.enter_room(ctx);
+        let address = ctx.address();
+
+        // Time limits follow the profile across reconnects, so check before anything else happens
+        if self.time_limits.is_some() {
+            self.check_time_limit(ctx);
+            ctx.run_interval(TIME_LIMIT_CHECK_INTERVAL, |act, ctx| act.check_time_limit(ctx));
+        }
+
+        // Publishers registered under this session key by a process that died have to be set up again
+        if let Some((session_key, registry)) = self.publisher_registry.clone() {
+            let address = address.clone();
+            actix::spawn(async move {
+                let stale = registry.take_stale(&session_key).await;
+                if !stale.is_empty() {
+                    tracing::info!("Client lost {} publisher(s) in a previous server process", stale.len());
+                    address.do_send(SendingMessage::RepublishRequired {
+                        publisher_ids: stale.into_iter().map(|entry| entry.publisher_id).collect(),
+                    });
+                }
+            });
+            ctx.run_interval(LEASE_RENEW_INTERVAL, |act, _ctx| {
+                if let Some((session_key, registry)) = act.publisher_registry.clone() {
+                    actix::spawn(async move { registry.renew(&session_key).await });
+                }
+            });
+        }
+
+        // Observers are admins, the limit is for players
+        if !self.ghost {
+            self.schedule_session_expiry(ctx);
+        }
+
+        let server = &crate::config::get().server;
+        if !self.ghost && (server.afk_after().is_some() || server.afk_disconnect().is_some()) {
+            ctx.run_interval(AFK_CHECK_INTERVAL, |act, ctx| act.check_afk(ctx));
+        }
+
+        let heartbeat_interval = crate::config::get().server.heartbeat_interval_secs;
+        if heartbeat_interval > 0 {
+            ctx.run_interval(std::time::Duration::from_secs(heartbeat_interval), |act, ctx| act.heartbeat(ctx));
+        }
+
+        if self.revocations.is_some() {
+            ctx.run_interval(REVOCATION_CHECK_INTERVAL, |act, ctx| act.check_revoked(ctx));
+        }
+
+        if interest_radius().is_some() && !self.ghost {
+            ctx.run_interval(FAR_PLAYER_SYNC_INTERVAL, |act, ctx| act.sync_far_players(ctx));
+        }
+
+        if self.proximity_subscribe() {
+            ctx.run_interval(PROXIMITY_CHECK_INTERVAL, |act, ctx| act.reconcile_proximity(ctx));
+        }
+
+        ctx.run_interval(RECEIVER_STATS_INTERVAL, |act, _ctx| act.sample_receiver_stats());
+
+        // Chaos mode may pull the transports out from under the session to test recovery
+        if chaos::is_enabled() {
+            ctx.run_interval(chaos::TRANSPORT_KILL_CHECK_INTERVAL, |act, _ctx| {
+                if !chaos::kill_transports() {
+                    return;
+                }
+                tracing::warn!("[{}] Chaos mode: killing transports", act.player_data.name);
+                let publish_transport = act.publish_transport.clone();
+                let subscribe_transport = act.subscribe_transport.clone();
+                actix::spawn(async move {
+                    let _ = publish_transport.close().await;
+                    let _ = subscribe_transport.close().await;
+                });
+            });
+        }
+
+        // Echo-test sessions get a periodic connectivity report
+        if is_echo_room(&self.room.id) {
+            ctx.run_interval(ECHO_REPORT_INTERVAL, |act, ctx| act.report_echo_stats(ctx));
+        }
+    }
+
+    /// Become a player (or observer) in `self.room` and get the client and peers up to date
+    fn enter_room(&mut self, ctx: &mut ws::WebsocketContext<Self>) {
+        let address = ctx.address();
+        let adopted = self
+            .transfer_from
+            .take()
+            .and_then(|player_id| self.room.adopt_player(&player_id, address.clone()).map(|adopted| (player_id, adopted)));
+        let transferred = adopted.is_some();
+        self.player_id = match adopted {
+            Some((player_id, (previous, player_data))) => {
+                previous.do_send(SendingMessage::SessionTransferred);
+                // A handoff leaves the old device's streams behind (a resume keeps them), so peers drop
+                // those tiles now rather than once the old connection finishes closing
+                if !self.resumed {
+                    let handed_off: Vec<String> = self
+                        .room
+                        .get_all_publishers()
+                        .into_iter()
+                        .filter(|(_, owner_id)| *owner_id == player_id)
+                        .map(|(publisher_id, _)| publisher_id)
+                        .collect();
+                    let (owner, room, previous_id) = (self.owner.clone(), self.room.clone(), player_id.clone());
+                    actix::spawn(async move { unpublish(&owner, &room, &previous_id, handed_off).await });
+                }
+                self.player_data = player_data;
+                player_id
+            }
+            None if self.ghost => self.room.add_observer(address.clone()),
+            None => self.room.add_player(address.clone(), self.player_data.clone()),
+        };
+        // A device handoff keeps the player's idle status until they do something
+        self.afk = transferred && self.player_data.status == PlayerStatus::Idle;
+
+        tracing::info!("[JOINED] player={} id={}", self.player_data.name, &self.player_id[..8.min(self.player_id.len())]);
+
+        let players = self.room.get_all_players();
+        address.do_send(SendingMessage::RoomState {
+            your_player_id: self.player_id.clone(),
+            players,
+            room_theme: self.room.theme.clone(),
+            ice_servers: self.ice_servers.clone(),
+            host_id: self.room.get_host_id(),
+            movement_effects: self.room.get_movement_effects(),
+            ambient_sounds: theme_for_room(&self.room.id).ambient_sounds.clone(),
+            speaking_distance: theme_for_room(&self.room.id).speaking_distance,
+            world_bounds: theme_for_room(&self.room.id).bounds,
+            bandwidth_profile: self.bandwidth_profile,
+            bandwidth_limits: self.bandwidth_profile.limits(),
+            slow_mode_secs: self.room.get_slow_mode().as_secs(),
+            pinned_messages: self.room.get_pinned_messages(),
+            chat_history: self.room.recent_chat(JOIN_HISTORY_MESSAGES),
+            locked: self.room.is_locked(),
+            password_protected: self.room.has_password(),
+            countdowns: self.room.get_countdowns(),
+            server_time: chrono::Utc::now().timestamp_millis(),
+            reconnect: ReconnectPolicy::DEFAULT,
+            reconnect_token: issue_reconnect_token(&self.room.id),
+            resume_token: self.resume_token.clone(),
+            resume_grace_secs: resume_grace().as_secs(),
+            resumed: self.resumed,
+            upload_token: issue_upload_token(&self.room.id, &self.player_id),
+            features: self.features,
+        });
+
+        // Peers already see this player when they just switched devices
+        if let Some(new_player_data) = self.room.get_player_data(&self.player_id).filter(|_| !transferred) {
+            for peer in self.room.get_peers(&self.player_id) {
+                peer.do_send(SendingMessage::PlayerJoined { player: new_player_data.clone() });
+            }
+        }
+
+        // Audience rooms tell newcomers what the linked stage is currently relaying
+        if let Some(source_room_id) = self.room.get_relay_source() {
+            let owner = self.owner.clone();
+            let room = self.room.clone();
+            let address = address.clone();
+            actix::spawn(async move {
+                let source = owner.lock().await.find_by_id(source_room_id.clone());
+                let publishers = source.map(|source| watchable_publishers(&source, &room)).unwrap_or_default();
+                address.do_send(SendingMessage::RoomLinked { source_room_id: Some(source_room_id), publishers });
+            });
+        }
+
+        // Hub visitors need the portal layout right away instead of waiting for the next refresh
+        if self.room.id == HUB_ROOM_ID {
+            let owner = self.owner.clone();
+            actix::spawn(async move {
+                let portals = build_portals(&*owner.lock().await);
+                address.do_send(SendingMessage::HubPortals { portals });
+            });
+        }
+    }
+
+    /// Move to another room over this connection: leave the current one, then join the target
+    /// with fresh transports on its router. The client gets `RoomSwitched`, then the new `RoomState`
+    fn switch_room(&mut self, room_id: String, password: Option<String>, ctx: &mut ws::WebsocketContext<Self>) {
+        let address = ctx.address();
+        let reject = |room_id: &str, reason: &str| SendingMessage::SwitchRoomFailed {
+            room_id: room_id.to_string(),
+            reason: reason.to_string(),
+        };
+        if room_id == self.room.id {
+            address.do_send(reject(&room_id, "already in that room"));
+            return;
+        }
+        // Echo tests get a room of their own and waiting rooms are entered by joining a locked room
+        if is_echo_room(&self.room.id) || is_echo_room(&room_id) || is_waiting_room(&room_id) {
+            address.do_send(reject(&room_id, "can't switch to or from that room"));
+            return;
+        }
+
+        let owner = self.owner.clone();
+        let player_name = self.player_data.name.clone();
+        let addr = address.clone();
+        let requested_id = room_id.clone();
+        let setup_fut = async move {
+            let target = {
+                let owner = owner.lock().await;
+                let target = owner.find_by_id(room_id).ok_or("that room doesn't exist")?;
+                if !owner.is_room_worker_healthy(&target.id) {
+                    return Err("that room's server isn't responding");
+                }
+                target
+            };
+            if target.is_locked() {
+                return Err("that room is locked");
+            }
+            let full = crate::config::get()
+                .server
+                .max_players_for(base_room_id(&target.id))
+                .is_some_and(|max_players| target.player_count() >= max_players);
+            if full {
+                return Err("that room is full");
+            }
+            if target.has_password() && !password.is_some_and(|password| target.check_password(&password)) {
+                return Err("wrong room password");
+            }
+
+            // Fresh credentials, like any other join
+            let ice_servers = RoomOwner::session_ice_servers(&owner).await;
+            let config = transport_config(&ice_servers, &target.id);
+            let media = open_session_media(&target, &config, &player_name).await;
+            register_transport_callbacks(media.publish_transport.clone(), media.subscribe_transport.clone(), addr).await;
+            Ok((target, ice_servers, config, media))
+        };
+        // Hold other messages back so none land on the old transports mid-switch
+        ctx.wait(setup_fut.into_actor(self).map(move |result, act, ctx| match result {
+            Ok((target, ice_servers, config, media)) => act.finish_switch(target, &ice_servers, config, media, ctx),
+            Err(reason) => ctx.address().do_send(reject(&requested_id, reason)),
+        }));
+    }
+
+    fn finish_switch(
+        &mut self,
+        target: Arc<Room<Self>>,
+        ice_servers: &[RTCIceServer],
+        config: rheomesh::config::WebRTCTransportConfig,
+        media: SessionMedia,
+        ctx: &mut ws::WebsocketContext<Self>,
+    ) {
+        tracing::info!("[{}] Switching from room {} to {}", self.player_data.name, self.room.id, target.id);
+        self.set_typing(false, ctx);
+
+        // Leave the old room as if disconnecting, except the connection stays open
+        let previous_room = std::mem::replace(&mut self.room, target);
+        let previous_media = SessionMedia {
+            publish_transport: std::mem::replace(&mut self.publish_transport, media.publish_transport),
+            subscribe_transport: std::mem::replace(&mut self.subscribe_transport, media.subscribe_transport),
+            publishers: std::mem::replace(&mut self.publishers, media.publishers),
+            subscribers: std::mem::replace(&mut self.subscribers, media.subscribers),
+        };
+        close_session_media(self.owner.clone(), previous_room.clone(), self.player_id.clone(), previous_media);
+        if let Some((session_key, registry)) = self.publisher_registry.clone() {
+            actix::spawn(async move { registry.clear(&session_key).await });
+        }
+        remove_from_room(&self.owner, &previous_room, &self.player_id);
+
+        self.ice_servers = ice_servers.iter().map(|s| s.into()).collect();
+        self.transport_config = config;
+        self.publisher_ice = IceBatch::default();
+        self.subscriber_ice = IceBatch::default();
+        self.motion = MotionTracker::new();
+        self.movement = MovementValidator::new();
+        self.subscriptions.lock().unwrap().clear();
+        self.last_keyframe_request.clear();
+        self.client_paused.clear();
+        self.subscriber_ready = false;
+        self.last_movement_sent.clear();
+        self.far_positions_sent.clear();
+        self.resumed = false;
+
+        ctx.address().do_send(SendingMessage::RoomSwitched { room_id: self.room.id.clone() });
+        self.enter_room(ctx);
+    }
+
+    /// Close a connection that didn't get past the room password
+    fn close_unauthenticated(&mut self, reason: &str, ctx: &mut ws::WebsocketContext<Self>) {
+        tracing::info!("[{}] Turned away from room {}: {}", self.player_data.name, self.room.id, reason);
+        self.awaiting_password = false;
+        self.leaving = true;
+        ctx.close(Some(ws::CloseReason {
+            code: ws::CloseCode::Other(WRONG_PASSWORD_CLOSE_CODE),
+            description: Some(reason.to_string()),
+        }));
+        ctx.stop();
+    }
+
+    /// Hand a parsed client message to the actor, unless it's over budget. Chaos mode may hold
+    /// signaling back to shake out ordering races
+    fn dispatch(&mut self, message: ReceivedMessage, ctx: &mut ws::WebsocketContext<Self>) {
+        if !self.within_rate_limit(&message, ctx) {
+            return;
+        }
+        // Nothing but the password gets through before the player has joined
+        if self.awaiting_password {
+            if let ReceivedMessage::Authenticate { password } = message {
+                if self.room.check_password(&password) {
+                    self.awaiting_password = false;
+                    self.join_room(ctx);
+                } else {
+                    self.close_unauthenticated("wrong room password", ctx);
+                }
+            }
+            return;
+        }
+        if message.rate_class() == MessageClass::Signaling {
+            if let Some(delay) = chaos::signaling_delay() {
+                ctx.notify_later(message, delay);
+                return;
+            }
+        } else {
+            self.mark_active();
+        }
+        ctx.address().do_send(message);
+    }
+
+    /// Charge an incoming message to its budget; false if it should be dropped
+    fn within_rate_limit(&mut self, message: &ReceivedMessage, ctx: &mut ws::WebsocketContext<Self>) -> bool {
+        let class = message.rate_class();
+        match self.rate_limiter.check(class) {
+            RateDecision::Allow => true,
+            RateDecision::Drop => false,
+            RateDecision::Warn => {
+                ctx.address().do_send(SendingMessage::RateLimited { class });
+                false
+            }
+            RateDecision::Close => {
+                tracing::warn!("[{}] Kept flooding {:?} messages, disconnecting", self.player_data.name, class);
+                self.leaving = true;
+                ctx.close(Some(ws::CloseReason {
+                    code: ws::CloseCode::Policy,
+                    description: Some("message rate limit exceeded".to_string()),
+                }));
+                ctx.stop();
+                false
+            }
+        }
+    }
+
+    fn check_revoked(&mut self, ctx: &mut ws::WebsocketContext<Self>) {
+        let revoked = self
+            .revocations
+            .as_ref()
+            .is_some_and(|(profile_id, token_id, list)| list.read().unwrap().is_revoked(profile_id, token_id.as_deref()));
+        if revoked {
+            self.close_revoked(ctx);
+        }
+    }
+
+    fn close_revoked(&mut self, ctx: &mut ws::WebsocketContext<Self>) {
+        tracing::info!("[{}] Profile revoked, disconnecting", self.player_data.name);
+        self.leaving = true;
+        self.send(ctx, &SendingMessage::SessionRevoked);
+        ctx.close(Some(ws::CloseReason {
+            code: ws::CloseCode::Policy,
+            description: Some("session revoked".to_string()),
+        }));
+        ctx.stop();
+    }
+
+    /// Add time since the last check to the profile's usage; warns near the limit and disconnects past it
+    fn check_time_limit(&mut self, ctx: &mut ws::WebsocketContext<Self>) {
+        let Some((profile_id, _, store)) = &self.time_limits else {
+            return;
+        };
+        let elapsed = std::mem::replace(&mut self.usage_since, std::time::Instant::now()).elapsed();
+        let status = store.lock().unwrap().record(profile_id, elapsed);
+        match status {
+            TimeLimitStatus::Exceeded { reason } => {
+                tracing::info!("[{}] Time limit reached, disconnecting", self.player_data.name);
+                self.leaving = true;
+                let message = SendingMessage::TimeLimitReached { reason: reason.clone() };
+                self.send(ctx, &message);
+                ctx.close(Some(ws::CloseReason {
+                    code: ws::CloseCode::Policy,
+                    description: Some(reason),
+                }));
+                ctx.stop();
+            }
+            TimeLimitStatus::Allowed { remaining: Some(remaining) } if remaining <= TIME_LIMIT_WARNING => {
+                if !self.time_limit_warned {
+                    self.time_limit_warned = true;
+                    ctx.address().do_send(SendingMessage::TimeLimitWarning {
+                        minutes_remaining: remaining.as_secs().div_ceil(60),
+                    });
+                }
+            }
+            TimeLimitStatus::Allowed { .. } => self.time_limit_warned = false,
+        }
+    }
+
+    /// Periodic correction for players outside the interest radius, who don't get live movement
+    fn sync_far_players(&mut self, ctx: &mut ws::WebsocketContext<Self>) {
+        let Some(own) = self.room.get_player_data(&self.player_id) else {
+            return;
+        };
+        let mut sent = HashMap::new();
+        let players: Vec<PlayerPosition> = self
+            .room
+            .get_peers_data(&self.player_id)
+            .into_iter()
+            .filter(|player| !within_interest(&own.position, &player.position))
+            .filter_map(|player| {
+                sent.insert(player.id.clone(), player.position.clone());
+                (self.far_positions_sent.get(&player.id) != Some(&player.position)).then(|| PlayerPosition::from(player))
+            })
+            .collect();
+        self.far_positions_sent = sent;
+        if !players.is_empty() {
+            ctx.address().do_send(SendingMessage::PlayerPositions { players });
+        }
+    }
+
+    /// Screen-reader summaries derived from a message about to be sent to this client
+    fn accessibility_events(&mut self, msg: &SendingMessage) -> Vec<SendingMessage> {
+        let Some(tracker) = self.accessibility.as_mut() else {
+            return Vec::new();
+        };
+        let event = |kind, player_id: &str, text: String| SendingMessage::AccessibilityEvent {
+            kind,
+            player_id: Some(player_id.to_string()),
+            text,
+        };
+
+        match msg {
+            SendingMessage::PlayerJoined { player } => {
+                tracker.remember(&player.id, &player.name);
+                vec![event(AccessibilityEventKind::PlayerJoined, &player.id, format!("{} joined the room", player.name))]
+            }
+            SendingMessage::PlayerLeft { player_id } => {
+                let name = tracker.forget(player_id).unwrap_or_else(|| "Someone".to_string());
+                vec![event(AccessibilityEventKind::PlayerLeft, player_id, format!("{} left the room", name))]
+            }
+            SendingMessage::PlayersMoved { players, .. } => {
+                let Some(own) = self.room.get_player_data(&self.player_id) else {
+                    return Vec::new();
+                };
+                players
+                    .iter()
+                    .filter_map(|update| {
+                        let player_id = &update.player_id;
+                        let name = tracker.name(player_id).unwrap_or("Someone").to_string();
+                        match tracker.update_distance(player_id, &own.position, &update.position) {
+                            Some(true) => Some(event(AccessibilityEventKind::PlayerApproached, player_id, format!("{} is near you", name))),
+                            Some(false) => Some(event(AccessibilityEventKind::PlayerMovedAway, player_id, format!("{} moved away", name))),
+                            None => None,
+                        }
+                    })
+                    .collect()
+            }
+            SendingMessage::Published { player_id, .. } if *player_id != self.player_id && tracker.is_nearby(player_id) => {
+                let name = tracker.name(player_id).unwrap_or("Someone");
+                vec![event(AccessibilityEventKind::StartedSpeaking, player_id, format!("{} started speaking nearby", name))]
+            }
+            SendingMessage::HostChanged { host_id } => {
+                let text = if *host_id == self.player_id {
+                    "You are now the host".to_string()
+                } else {
+                    format!("{} is now the host", tracker.name(host_id).unwrap_or("Someone"))
+                };
+                vec![event(AccessibilityEventKind::HostChanged, host_id, text)]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn ice_batch(&mut self, target: IceTarget) -> &mut IceBatch {
+        match target {
+            IceTarget::Publisher => &mut self.publisher_ice,
+            IceTarget::Subscriber => &mut self.subscriber_ice,
+        }
+    }
+
+    /// Send everything buffered for a transport, then arm the end-of-candidates marker
+    fn flush_ice_batch(&mut self, target: IceTarget, ctx: &mut ws::WebsocketContext<Self>) {
+        let batch = self.ice_batch(target);
+        batch.flush_scheduled = false;
+        let candidates = std::mem::take(&mut batch.pending);
+        if !candidates.is_empty() {
+            tracing::debug!("[ICE] Flushing {} {:?} candidates", candidates.len(), target);
+            ctx.address().do_send(ice_batch_message(target, candidates, false));
+        }
+
+        let end_timer = ctx.run_later(ICE_GATHERING_QUIET_PERIOD, move |act, ctx| {
+            act.ice_batch(target).end_timer = None;
+            ctx.address().do_send(ice_batch_message(target, Vec::new(), true));
+        });
+        if let Some(previous) = self.ice_batch(target).end_timer.replace(end_timer) {
+            ctx.cancel_future(previous);
+        }
+    }
+}
+
+/// Transport config shared by fresh and warmed transports
+fn transport_config(ice_servers: &[RTCIceServer], room_id: &str) -> rheomesh::config::WebRTCTransportConfig {
+    // Relay by default: webrtc-rs has bugs in both active and passive DTLS modes that cause
+    // intermittent handshake failures, and TURN gives a more reliable path. LAN deployments
+    // can allow direct candidates to save TURN bandwidth.
+    let media = &crate::config::get().media;
+    let policy = media.ice_policy_for(base_room_id(room_id));
+    let mut config = rheomesh::config::WebRTCTransportConfig::default();
+    config.configuration = RTCConfiguration {
+        ice_servers: ice_servers.to_vec(),
+        ice_transport_policy: policy.into(),
+        ..Default::default()
+    };
+    config.network_types = media.network_types.iter().map(|&network_type| NetworkType::from(network_type)).collect();
+    // ICE timeouts
+    config.ice_disconnected_timeout = Some(media.ice_disconnected_timeout());
+    config.ice_failed_timeout = Some(media.ice_failed_timeout());
+    config.ice_keep_alive_interval = Some(media.ice_keep_alive_interval());
+    // Behind Docker/NAT: bind a small port range and advertise the public address, so direct
+    // paths work without falling back to relay mode
+    if let Some(range) = media.udp_port_range {
+        config.port_range = Some(rheomesh::config::PortRange { min: range.min, max: range.max });
+    }
+    if !media.public_ips.is_empty() {
+        config.announced_ips = media.public_ips.clone();
+    }
+
+    tracing::info!("[SESSION] Using ice_transport_policy={:?} for room {}", policy, room_id);
+    config
+}
+
+/// Subscribe to one publisher, backing off while it may still be setting up at the SFU
+async fn subscribe_with_retry(
+    subscribe_transport: &rheomesh::subscribe_transport::SubscribeTransport,
+    publisher_id: &str,
+) -> Result<(Arc<Mutex<Subscriber>>, RTCSessionDescription), String> {
+    let max_retries = 5;
+    let mut last_error = String::new();
+    for attempt in 0..max_retries {
+        if attempt > 0 {
+            tokio::time::sleep(tokio::time::Duration::from_millis(100 * (1 << (attempt - 1)))).await;
+        }
+        let subscribed = if chaos::fail_subscribe() {
+            Err("chaos mode: injected subscribe failure".to_string())
+        } else {
+            subscribe_transport.subscribe(publisher_id.to_string()).await.map_err(|e| e.to_string())
+        };
+        match subscribed {
+            Ok(subscribed) => return Ok(subscribed),
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+/// Fresh transports on the room's router (warmed ones when the pool has them), no publishers yet
+async fn open_session_media(room: &Arc<Room<StreamingSession>>, config: &rheomesh::config::WebRTCTransportConfig, player_name: &str) -> SessionMedia {
+    let (publish_transport, subscribe_transport) = match room.transport_pool.take().await {
+        Some(warm) => {
+            tracing::info!("[SESSION] Using warmed transports for player={}", player_name);
+            warm
+        }
+        None => {
+            let router = room.router.lock().await;
+            (
+                router.create_publish_transport(config.clone()).await,
+                router.create_subscribe_transport(config.clone()).await,
+            )
+        }
+    };
+
+    // DIAGNOSTIC: Log transport IDs for correlation
+    tracing::info!("[SESSION] player={} pub={} sub={}",
+        player_name, &publish_transport.id[..8], &subscribe_transport.id[..8]);
+
+    // Warm the next pair while this player is busy negotiating
+    let pool_room = room.clone();
+    let pool_config = config.clone();
+    actix::spawn(async move {
+        pool_room.transport_pool.refill(&pool_room.router, pool_config).await;
+    });
+
+    SessionMedia {
+        publish_transport: Arc::new(publish_transport),
+        subscribe_transport: Arc::new(subscribe_transport),
+        publishers: Arc::new(Mutex::new(HashMap::new())),
+        subscribers: Arc::new(Mutex::new(HashMap::new())),
+    }
+}
+
+/// Route a session's transport ICE candidates and renegotiation offers to its actor
+async fn register_transport_callbacks(
+    publish_transport: Arc<rheomesh::publish_transport::PublishTransport>,
+    subscribe_transport: Arc<rheomesh::subscribe_transport::SubscribeTransport>,
+    addr: actix::Addr<StreamingSession>,
+) {
+    // Publish transport: ICE candidate callback
+    let addr_clone = addr.clone();
+    publish_transport.on_ice_candidate(Box::new(move |candidate| {
+        if let Ok(json) = candidate.to_json() {
+            tracing::debug!("[ICE] Publisher candidate generated");
+            addr_clone.do_send(QueueIceCandidate { target: IceTarget::Publisher, candidate: json });
+        }
+    })).await;
+    
+    // Subscribe transport: ICE candidate callback
+    let addr_clone = addr.clone();
+    subscribe_transport.on_ice_candidate(Box::new(move |candidate| {
+        if let Ok(json) = candidate.to_json() {
+            tracing::debug!("[ICE] Subscriber candidate generated");
+            addr_clone.do_send(QueueIceCandidate { target: IceTarget::Subscriber, candidate: json });
+        }
+    })).await;
+    
+    // Subscribe transport: Negotiation needed callback (triggers Offer when tracks are added)
+    let addr_clone = addr.clone();
+    subscribe_transport.on_negotiation_needed(Box::new(move |offer| {
+        tracing::debug!("[SUBSCRIBE] Negotiation needed, sending Offer");
+        addr_clone.do_send(SendingMessage::Offer { sdp: offer });
+    })).await;
+    
+    tracing::info!("[SESSION] All callbacks registered");
+}
+
+/// Close a session's publishers and transports, telling peers the streams are gone
+fn close_session_media(owner: Data<Mutex<RoomOwner<StreamingSession>>>, room: Arc<Room<StreamingSession>>, player_id: String, media: SessionMedia) {
+    actix::spawn(async move {
+        let publisher_ids: Vec<String> = media.publishers.lock().await.keys().cloned().collect();
+        unpublish(&owner, &room, &player_id, publisher_ids).await;
+        media.close().await;
+    });
+}
+
+/// Take publishers out of the room and tell everyone else they're gone, skipping ones already taken
+/// down (e.g. by the device that took this player over)
+async fn unpublish(owner: &Data<Mutex<RoomOwner<StreamingSession>>>, room: &Room<StreamingSession>, player_id: &str, publisher_ids: Vec<String>) {
+    for publisher_id in publisher_ids {
+        if room.get_publisher_owner(&publisher_id).is_none() {
+            continue;
+        }
+        if room.unregister_publisher(&publisher_id) {
+            stop_relaying(owner, &room.id, &publisher_id).await;
+        }
+        room.get_peers(player_id).iter().for_each(|peer| {
+            peer.do_send(SendingMessage::Unpublished { publisher_id: publisher_id.clone() });
+        });
+    }
+}
+
+/// Take a player out of their room, handing off hosting and closing the room once it's empty
+fn remove_from_room(owner: &Data<Mutex<RoomOwner<StreamingSession>>>, room: &Arc<Room<StreamingSession>>, player_id: &str) {
+    for peer in room.get_peers(player_id) {
+        peer.do_send(SendingMessage::PlayerLeft { player_id: player_id.to_string() });
+    }
+
+    let was_host = room.is_host(player_id);
+    let Some(remaining) = room.remove_player(player_id) else {
+        return;
+    };
+    if was_host {
+        if let Some(host_id) = room.get_host_id() {
+            for peer in room.get_all_addrs() {
+                peer.do_send(SendingMessage::HostChanged { host_id: host_id.clone() });
+            }
+        }
+    }
+    if remaining == 0 {
+        // The lock and password were the host's; an empty room starts over open
+        room.set_locked(false);
+        room.set_password(None);
+
+        // Players bouncing back within the TTL find the router and warm transports still there;
+        // echo tests are one-off rooms nobody comes back to
+        let ttl = if is_echo_room(&room.id) {
+            std::time::Duration::ZERO
+        } else {
+            crate::config::get().server.empty_room_ttl()
+        };
+        let owner = owner.clone();
+        let room_id = room.id.clone();
+        actix::spawn(async move {
+            tokio::time::sleep(ttl).await;
+            let Some(room) = owner.lock().await.remove_room(&room_id, ttl) else {
+                return;
+            };
+            for observer in room.get_observers() {
+                observer.do_send(SendingMessage::RoomClosed);
+            }
+            room.transport_pool.drain().await;
+            if let Some(tts) = room.set_tts(None) {
+                tts.close().await;
+            }
+        });
+    }
+}
+
+fn ice_batch_message(target: IceTarget, candidates: Vec<RTCIceCandidateInit>, end_of_candidates: bool) -> SendingMessage {
+    match target {
+        IceTarget::Publisher => SendingMessage::PublisherIceBatch { candidates, end_of_candidates },
+        IceTarget::Subscriber => SendingMessage::SubscriberIceBatch { candidates, end_of_candidates },
+    }
+}
+
+impl Actor for StreamingSession {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        let address = ctx.address();
+        
+        // CRITICAL: Set up ALL callbacks BEFORE processing any messages
+        // Using ctx.wait() ensures the actor won't process ANY messages until this completes
+        let setup_fut = register_transport_callbacks(self.publish_transport.clone(), self.subscribe_transport.clone(), address);
+
+        // Block message processing until callbacks are set up
+        ctx.wait(setup_fut.into_actor(self));
+
+        // Password rooms hold the join until the client proves it knows the passphrase; switching
+        // devices and resuming are the same player coming back, and observers are admins
+        if self.room.has_password() && self.transfer_from.is_none() && !self.ghost {
+            match self.password_attempt.take() {
+                Some(attempt) if self.room.check_password(&attempt) => {}
+                Some(_) => {
+                    self.close_unauthenticated("wrong room password", ctx);
+                    return;
+                }
+                None => {
+                    self.awaiting_password = true;
+                    ctx.address().do_send(SendingMessage::PasswordRequired);
+                    ctx.run_later(PASSWORD_PROMPT_TIMEOUT, |act, ctx| {
+                        if act.awaiting_password {
+                            act.close_unauthenticated("room password required", ctx);
+                        }
+                    });
+                    return;
+                }
+            }
+        }
+        self.join_room(ctx);
+    }
+
+    fn stopping(&mut self, _ctx: &mut Self::Context) -> Running {
+        // A dropped connection may come back with its resume token, so keep the player and media around
+        let can_resume = !self.leaving && !self.transferred_away && !self.ghost && !resume_grace().is_zero();
+        if can_resume && !self.player_id.is_empty() && self.room.get_player_data(&self.player_id).is_some() {
+            self.park();
+        }
+        Running::Stop
+    }
+
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        // Never got past the room password, so there's no player to remove
+        if self.player_id.is_empty() {
+            tracing::info!("[LEFT] player={} before joining room {}", self.player_data.name, self.room.id);
+            close_session_media(self.owner.clone(), self.room.clone(), self.player_id.clone(), self.media());
+            return;
+        }
+
+        tracing::info!("[LEFT] player={} id={}", self.player_data.name, &self.player_id[..8.min(self.player_id.len())]);
+
+        if let Some((profile_id, _, store)) = &self.time_limits {
+            store.lock().unwrap().record(profile_id, self.usage_since.elapsed());
+        }
+
+        // Parked in `stopping`; the player and media wait for the connection to resume
+        if self.parked {
+            return;
+        }
+
+        let publisher_registry = self.publisher_registry.take();
+        if let Some((session_key, registry)) = publisher_registry {
+            actix::spawn(async move { registry.clear(&session_key).await });
+        }
+        close_session_media(self.owner.clone(), self.room.clone(), self.player_id.clone(), self.media());
+
+        // The player lives on in the device that took over
+        if self.transferred_away {
+            return;
+        }
+
+        // Observers were never visible, so there's nobody to tell
+        if self.ghost {
+            self.room.remove_observer(&self.player_id);
+            return;
+        }
+
+        remove_from_room(&self.owner, &self.room, &self.player_id);
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for StreamingSession {
+    fn handle(&mut self, item: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        // Any frame shows the client is still there, not just pongs
+        if item.is_ok() {
+            self.missed_heartbeats = 0;
+        }
+        match item {
+            Ok(ws::Message::Ping(msg)) => ctx.pong(&msg),
+            Ok(ws::Message::Pong(_)) => {},
+            Ok(ws::Message::Text(text)) => {
+                if let Ok(message) = serde_json::from_str::<ReceivedMessage>(&text) {
+                    self.dispatch(message, ctx);
+                }
+            },
+            // Binary frames carry MessagePack from clients using the binary protocol
+            Ok(ws::Message::Binary(bin)) => {
+                if let Ok(message) = rmp_serde::from_slice::<ReceivedMessage>(&bin) {
+                    self.dispatch(message, ctx);
+                }
+            },
+            Ok(ws::Message::Close(reason)) => {
+                self.leaving = true;
+                ctx.close(reason);
+            }
+            _ => (),
+        }
+    }
+}
+
+impl Handler<ReceivedMessage> for StreamingSession {
+    type Result = ();
+
+    fn handle(&mut self, msg: ReceivedMessage, ctx: &mut Self::Context) -> Self::Result {
+        let address = ctx.address();
+        let player_name = self.player_data.name.clone();
+
+        if self.ghost && !msg.allowed_for_observers() {
+            return;
+        }
+
+        match msg {
+            ReceivedMessage::Ping => {
+                address.do_send(SendingMessage::Pong);
+            }
+            ReceivedMessage::TimeSync { client_time } => {
+                address.do_send(SendingMessage::TimeSync {
+                    client_time,
+                    server_time: chrono::Utc::now().timestamp_millis(),
+                });
+            }
+            ReceivedMessage::Hello { capabilities } => {
+                // Switching features under media that's already flowing would leave it half-negotiated
+                if !self.said_hello {
+                    self.said_hello = true;
+                    self.features = SessionFeatures::negotiate(&capabilities);
+                    tracing::info!("[{}] Hello: {:?}", player_name, self.features);
+                }
+                address.do_send(SendingMessage::HelloAck { features: self.features });
+            }
+            ReceivedMessage::E2eeKey { to_player_id, key } => {
+                if !self.features.e2ee || key.len() > MAX_E2EE_KEY_CHARS {
+                    return;
+                }
+                if let Some(recipient) = self.room.get_addr(&to_player_id) {
+                    recipient.do_send(SendingMessage::E2eeKey {
+                        from_player_id: self.player_id.clone(),
+                        key,
+                    });
+                }
+            }
+            ReceivedMessage::PublisherInit => {
+                // Callbacks are set up in started(), this just logs
+                tracing::info!("[{}] PublisherInit (callbacks already registered)", player_name);
+            }
+            ReceivedMessage::SubscriberInit => {
+                // Callbacks are set up in started()
+                // Just send existing publishers to this client
+                tracing::info!("[{}] SubscriberInit (callbacks already registered)", player_name);
+                self.subscriber_ready = true;
+                let room = self.room.clone();
+                
+                // Send existing publishers grouped by player
+                let mut publishers_by_player: HashMap<String, Vec<PublisherInfo>> = HashMap::new();
+                for info in room.get_publisher_infos() {
+                    publishers_by_player.entry(info.player_id.clone()).or_default().push(info);
+                }
+                for (player_id, infos) in publishers_by_player {
+                    address.do_send(SendingMessage::Published {
+                        publisher_ids: infos.iter().map(|info| info.publisher_id.clone()).collect(),
+                        player_id: player_id.clone(),
+                        metadata: infos.iter().map(|info| (info.publisher_id.clone(), info.metadata.clone())).collect(),
+                    });
+                    for info in infos.into_iter().filter(|info| room.is_publisher_muted(&info.publisher_id)) {
+                        address.do_send(SendingMessage::PublisherMuted {
+                            publisher_id: info.publisher_id,
+                            player_id: player_id.clone(),
+                            muted: true,
+                        });
+                    }
+                }
+            }
+            ReceivedMessage::GetPublishers => {
+                // Return all active publishers for polling-based discovery
+                tracing::info!("[{}] GetPublishers", player_name);
+                let publishers = self.room.get_publisher_infos();
+                address.do_send(SendingMessage::PublisherList { publishers });
+            }
+            ReceivedMessage::PublisherIce { candidate } => {
+                tracing::info!("[{}] PublisherIce received: {}", player_name, candidate.candidate.chars().take(60).collect::<String>());
+                let publish_transport = self.publish_transport.clone();
+                actix::spawn(async move {
+                    let _ = publish_transport.add_ice_candidate(candidate).await;
+                });
+            }
+            ReceivedMessage::SubscriberIce { candidate } => {
+                tracing::info!("[{}] SubscriberIce received", player_name);
+                let subscribe_transport = self.subscribe_transport.clone();
+                actix::spawn(async move {
+                    let _ = subscribe_transport.add_ice_candidate(candidate).await;
+                });
+            }
+            ReceivedMessage::Offer { sdp } => {
+                tracing::info!("[{}] Offer len={}", player_name, sdp.sdp.len());
+                self.offered_track_kinds = track_kinds(&sdp.sdp);
+                let publish_transport = self.publish_transport.clone();
+                let player = player_name.clone();
+                let max_video_kbps = max_video_kbps(&self.room.id);
+                actix::spawn(async move {
+                    match publish_transport.get_answer(sdp).await {
+                        Ok(mut answer) => {
+                            if let Some(kbps) = max_video_kbps {
+                                answer.sdp = cap_video_bandwidth(&answer.sdp, kbps);
+                            }
+                            tracing::info!("[{}] Answer sent", player);
+                            address.do_send(SendingMessage::Answer { sdp: answer });
+                        }
+                        Err(e) => {
+                            tracing::error!("[{}] Answer error: {}", player, e);
+                        }
+                    }
+                });
+            }
+            ReceivedMessage::Subscribe { publisher_id } => self.subscribe(vec![publisher_id], &address),
+            ReceivedMessage::SubscribeMany { mut publisher_ids } => {
+                // Each live publisher once, and never more than the bandwidth profile could carry
+                let publishing: HashSet<String> = self.room.get_all_publishers().into_iter().map(|(id, _)| id).collect();
+                let mut seen = HashSet::new();
+                publisher_ids.retain(|id| publishing.contains(id) && seen.insert(id.clone()));
+                publisher_ids.truncate(self.bandwidth_profile.limits().max_subscriptions);
+                self.subscribe(publisher_ids, &address)
+            }
+            ReceivedMessage::RestartIce { target } => {
+                tracing::info!("[{}] ICE restart requested for {:?}", player_name, target);
+                let transport = match target {
+                    // The client is the offerer here: it restarts with `iceRestart` and the next Offer is answered as usual
+                    IceTarget::Publisher => {
+                        address.do_send(SendingMessage::IceRestartStarted { target });
+                        return;
+                    }
+                    IceTarget::Subscriber => self.subscribe_transport.clone(),
+                };
+                let player = player_name.clone();
+                actix::spawn(async move {
+                    match transport.restart_ice().await {
+                        Ok(offer) => {
+                            address.do_send(SendingMessage::IceRestartStarted { target });
+                            address.do_send(SendingMessage::Offer { sdp: offer });
+                        }
+                        Err(e) => {
+                            tracing::error!("[{}] ICE restart failed: {}", player, e);
+                            address.do_send(SendingMessage::IceRestartFailed { target, reason: e.to_string() });
+                        }
+                    }
+                });
+            }
+            ReceivedMessage::Answer { sdp } => {
+                let subscribe_transport = self.subscribe_transport.clone();
+                actix::spawn(async move {
+                    let _ = subscribe_transport.set_answer(sdp).await;
+                });
+            }
+            ReceivedMessage::Publish { publisher_id, source, label } => {
+                if let Some(remaining) = self.room.media_muted_for(&self.player_id) {
+                    address.do_send(SendingMessage::SystemMessage {
+                        message: format!("A moderator muted you, you can share again in {}s", remaining.as_secs_f32().ceil() as u64),
+                    });
+                    return;
+                }
+                let kind = self.offered_track_kinds.get(&publisher_id).copied();
+                let metadata = PublisherMetadata::new(source, label, kind, self.features.e2ee);
+                if metadata.may_carry_voice() && self.room.is_force_muted(&self.player_id) {
+                    address.do_send(SendingMessage::SystemMessage {
+                        message: "A moderator muted your mic, you can speak again once they unmute you".to_string(),
+                    });
+                    return;
+                }
+                let start = std::time::Instant::now();
+                let pub_id_short = &publisher_id[..8.min(publisher_id.len())];
+                tracing::info!("[{}] Publish track={}", player_name, pub_id_short);
+
+                let room = self.room.clone();
+                let player_id = self.player_id.clone();
+                let publish_transport = self.publish_transport.clone();
+                let subscribe_transport = self.subscribe_transport.clone();
+                let publishers = self.publishers.clone();
+                let subscribers = self.subscribers.clone();
                 let player = player_name.clone();
+                let publisher_registry = self.publisher_registry.clone();
 
                 actix::spawn(async move {
                     // DIAGNOSTIC: 30s timeout to detect DTLS failures
@@ -412,15 +2205,39 @@ impl Handler<ReceivedMessage> for StreamingSession {
                             tracing::info!("[{}] PUBLISH_OK track={} elapsed={:?}", player, &track_id[..8.min(track_id.len())], elapsed);
 
                             publishers.lock().await.insert(track_id.clone(), publisher);
-                            room.register_publisher(track_id.clone(), player_id.clone());
+                            room.register_publisher(track_id.clone(), player_id.clone(), metadata.clone());
+                            // Start under the room's cap; later hints follow how viewers are receiving it
+                            if let Some(kbps) = max_video_kbps(&room.id).filter(|_| metadata.kind == Some(RTPCodecType::Video)) {
+                                address.do_send(SendingMessage::BitrateHint { publisher_id: track_id.clone(), kbps });
+                            }
+                            if let Some((session_key, registry)) = &publisher_registry {
+                                registry.register(session_key, &track_id, &room.id, &player_id).await;
+                            }
 
                             let peers = room.get_peers(&player_id);
                             peers.iter().for_each(|peer| {
                                 peer.do_send(SendingMessage::Published {
                                     publisher_ids: vec![track_id.clone()],
                                     player_id: player_id.clone(),
+                                    metadata: HashMap::from([(track_id.clone(), metadata.clone())]),
                                 });
                             });
+
+                            // Echo test: loop the player's own media straight back to them
+                            if is_echo_room(&room.id) {
+                                match subscribe_transport.subscribe(track_id.clone()).await {
+                                    Ok((subscriber, offer)) => {
+                                        let id = subscriber.lock().await.id.clone();
+                                        subscribers.lock().await.insert(id.clone(), subscriber);
+                                        address.do_send(SendingMessage::Offer { sdp: offer });
+                                        address.do_send(SendingMessage::Subscribed { subscriber_id: id });
+                                    }
+                                    Err(e) => {
+                                        tracing::error!("[{}] Echo loopback failed: {}", player, e);
+                                        address.do_send(SendingMessage::SubscribeFailed { publisher_id: track_id, error: e.to_string() });
+                                    }
+                                }
+                            }
                         }
                         Ok(Err(err)) => {
                             // DIAGNOSTIC: Publish error
@@ -433,49 +2250,664 @@ impl Handler<ReceivedMessage> for StreamingSession {
                     }
                 });
             }
+            ReceivedMessage::SetMuted { publisher_id, muted } => {
+                if self.room.get_publisher_owner(&publisher_id).as_deref() != Some(self.player_id.as_str()) {
+                    return;
+                }
+                if !self.room.set_publisher_muted(&publisher_id, muted) {
+                    return;
+                }
+                let message = SendingMessage::PublisherMuted {
+                    publisher_id,
+                    player_id: self.player_id.clone(),
+                    muted,
+                };
+                for peer in self.room.get_all_addrs() {
+                    peer.do_send(message.clone());
+                }
+            }
             ReceivedMessage::StopPublish { publisher_id } => {
+                let owner = self.owner.clone();
+                let room = self.room.clone();
+                let player_id = self.player_id.clone();
+                let publishers = self.publishers.clone();
+                let publisher_registry = self.publisher_registry.clone();
+                actix::spawn(async move {
+                    if let Some(publisher) = publishers.lock().await.remove(&publisher_id) {
+                        publisher.lock().await.close().await;
+                        if room.unregister_publisher(&publisher_id) {
+                            stop_relaying(&owner, &room.id, &publisher_id).await;
+                        }
+                        if let Some((session_key, registry)) = &publisher_registry {
+                            registry.unregister(session_key, &publisher_id).await;
+                        }
+                        room.get_peers(&player_id).iter().for_each(|peer| {
+                            peer.do_send(SendingMessage::Unpublished { publisher_id: publisher_id.clone() });
+                        });
+                    }
+                });
+            }
+            ReceivedMessage::StopSubscribe { subscriber_id } => {
+                self.subscriptions.lock().unwrap().retain(|_, id| id.as_deref() != Some(subscriber_id.as_str()));
+                self.last_keyframe_request.remove(&subscriber_id);
+                self.client_paused.remove(&subscriber_id);
+                let subscribers = self.subscribers.clone();
+                actix::spawn(async move {
+                    if let Some(subscriber) = subscribers.lock().await.remove(&subscriber_id) {
+                        subscriber.lock().await.close().await;
+                    }
+                });
+            }
+            ReceivedMessage::PauseSubscriber { subscriber_id } => self.set_subscriber_paused(subscriber_id, true, &address),
+            ReceivedMessage::ResumeSubscriber { subscriber_id } => self.set_subscriber_paused(subscriber_id, false, &address),
+            ReceivedMessage::RequestKeyFrame { subscriber_id } => {
+                let now = std::time::Instant::now();
+                if self
+                    .last_keyframe_request
+                    .get(&subscriber_id)
+                    .is_some_and(|last| now.duration_since(*last) < KEYFRAME_REQUEST_INTERVAL)
+                {
+                    return;
+                }
+                let subscribers = self.subscribers.clone();
+                let id = subscriber_id.clone();
+                let lookup = async move { subscribers.lock().await.get(&id).cloned() };
+                // Only real subscriptions take a slot in the throttle, so made-up ids can't grow it
+                ctx.spawn(lookup.into_actor(self).map(move |subscriber, act, _| {
+                    let Some(subscriber) = subscriber else {
+                        address.do_send(SendingMessage::KeyFrameRequestFailed {
+                            subscriber_id,
+                            error: "no such subscription".to_string(),
+                        });
+                        return;
+                    };
+                    act.last_keyframe_request.insert(subscriber_id.clone(), now);
+                    actix::spawn(async move {
+                        // Sends a PLI upstream to the publishing client over its publish transport
+                        if let Err(e) = subscriber.lock().await.request_keyframe().await {
+                            address.do_send(SendingMessage::KeyFrameRequestFailed { subscriber_id, error: e.to_string() });
+                        }
+                    });
+                }));
+            }
+            ReceivedMessage::SelectLayer { subscriber_id, rid } => {
+                if !self.features.simulcast {
+                    address.do_send(SendingMessage::SelectLayerFailed {
+                        subscriber_id,
+                        error: "simulcast is not enabled for this session".to_string(),
+                    });
+                    return;
+                }
+                let Some(layer) = simulcast_layer(&rid) else {
+                    address.do_send(SendingMessage::SelectLayerFailed {
+                        subscriber_id,
+                        error: format!("unknown simulcast layer {}", rid),
+                    });
+                    return;
+                };
+                if let Some(kbps) = max_video_kbps(&self.room.id).filter(|kbps| layer > max_layer_for(*kbps)) {
+                    address.do_send(SendingMessage::SelectLayerFailed {
+                        subscriber_id,
+                        error: format!("this room caps video at {} kbps", kbps),
+                    });
+                    return;
+                }
+                let subscribers = self.subscribers.clone();
+                let player = player_name.clone();
+                actix::spawn(async move {
+                    let Some(subscriber) = subscribers.lock().await.get(&subscriber_id).cloned() else {
+                        address.do_send(SendingMessage::SelectLayerFailed {
+                            subscriber_id,
+                            error: "no such subscription".to_string(),
+                        });
+                        return;
+                    };
+                    // Non-simulcast publishers only have one layer; rheomesh reports that as an error
+                    match subscriber.lock().await.set_preferred_layer(layer, None).await {
+                        Ok(()) => {
+                            tracing::info!("[{}] Subscriber {} switched to layer {}", player, &subscriber_id[..8.min(subscriber_id.len())], rid);
+                            address.do_send(SendingMessage::LayerSelected { subscriber_id, rid });
+                        }
+                        Err(e) => address.do_send(SendingMessage::SelectLayerFailed { subscriber_id, error: e.to_string() }),
+                    }
+                });
+            }
+            ReceivedMessage::ChatMessage { message, upload_id } => {
+                self.set_typing(false, ctx);
+                if upload_id.as_deref().is_some_and(|upload_id| !is_valid_upload_id(upload_id)) {
+                    tracing::warn!("[{}] Chat message with invalid upload id dropped", player_name);
+                    return;
+                }
+                if message.trim() == "/who" {
+                    let names: Vec<String> = self
+                        .room
+                        .get_all_players()
+                        .iter()
+                        .map(|player| match &player.handle {
+                            Some(handle) => format!("{} (@{})", player.name, handle),
+                            None => player.name.clone(),
+                        })
+                        .collect();
+                    address.do_send(SendingMessage::SystemMessage {
+                        message: format!("In this room: {}", names.join(", ")),
+                    });
+                    return;
+                }
+                if !self.chat_allowed(&message, ctx) || !self.slow_mode_allows(ctx) {
+                    return;
+                }
+                self.moderate_and_deliver(message, ChatDelivery::Room { upload_id }, ctx);
+            }
+            ReceivedMessage::StartTyping => self.set_typing(true, ctx),
+            ReceivedMessage::StopTyping => self.set_typing(false, ctx),
+            ReceivedMessage::Reaction { emoji } => {
+                if !is_allowed_reaction(&emoji) {
+                    return;
+                }
+                let reaction = SendingMessage::PlayerReaction {
+                    player_id: self.player_id.clone(),
+                    emoji,
+                };
+                for peer in self.room.get_all_addrs() {
+                    peer.do_send(reaction.clone());
+                }
+            }
+            ReceivedMessage::EditMessage { message_id, message } => {
+                let author = self.author_identity();
+                let owned = self.room.get_chat(&message_id).is_some_and(|record| record.author_identity == author);
+                if !owned {
+                    address.do_send(SendingMessage::MessageActionFailed {
+                        message_id,
+                        reason: "You can only edit your own messages".to_string(),
+                    });
+                    return;
+                }
+                // An edit puts new text in front of the room, so it's held to the same limits as a new message
+                if message.trim().is_empty() || !self.chat_allowed(&message, ctx) || !self.slow_mode_allows(ctx) {
+                    return;
+                }
+                self.moderate_and_deliver(message, ChatDelivery::Edit { message_id }, ctx);
+            }
+            ReceivedMessage::DeleteMessage { message_id } => {
+                // Authors can take back their own messages; the host and moderators can scrub anyone's
+                let can_scrub = self.room.is_host(&self.player_id) || self.player_data.role.is_moderator();
+                let author = self.author_identity();
+                let allowed = self
+                    .room
+                    .get_chat(&message_id)
+                    .is_some_and(|record| record.author_identity == author || can_scrub);
+                if !allowed {
+                    address.do_send(SendingMessage::MessageActionFailed {
+                        message_id,
+                        reason: "Only the author, the host or a moderator can delete that message".to_string(),
+                    });
+                    return;
+                }
+                if self.room.delete_chat(&message_id).is_some() {
+                    tracing::info!("[{}] Deleted chat message {}", player_name, &message_id[..8.min(message_id.len())]);
+                    let deleted = SendingMessage::MessageDeleted {
+                        message_id,
+                        deleted_by: self.player_id.clone(),
+                    };
+                    for peer in self.room.get_all_addrs() {
+                        peer.do_send(deleted.clone());
+                    }
+                }
+            }
+            ReceivedMessage::Kick { player_id } => match self.moderation_target(&player_id) {
+                Ok(target) => {
+                    tracing::info!("[{}] Kicked player {}", player_name, &player_id[..8.min(player_id.len())]);
+                    target.do_send(SendingMessage::Kicked {
+                        reason: format!("Removed by moderator {}", self.player_data.name),
+                    });
+                }
+                Err(reason) => address.do_send(SendingMessage::ModerationFailed {
+                    player_id,
+                    reason: reason.to_string(),
+                }),
+            },
+            ReceivedMessage::MutePlayer { player_id } => match self.moderation_target(&player_id) {
+                Ok(_) => {
+                    let duration = std::time::Duration::from_secs(crate::config::get().moderation.mute_secs);
+                    tracing::info!("[{}] Muted player {} for {:?}", player_name, &player_id[..8.min(player_id.len())], duration);
+                    self.room.mute_chat(&player_id, duration);
+                    self.room.mute_media(&player_id, duration);
+                    let muted = SendingMessage::PlayerMuted {
+                        player_id,
+                        muted_by: self.player_data.name.clone(),
+                        duration_secs: duration.as_secs(),
+                    };
+                    for peer in self.room.get_all_addrs() {
+                        peer.do_send(muted.clone());
+                    }
+                }
+                Err(reason) => address.do_send(SendingMessage::ModerationFailed {
+                    player_id,
+                    reason: reason.to_string(),
+                }),
+            },
+            ReceivedMessage::ForceMute { player_id } => self.set_force_muted(player_id, true, &address),
+            ReceivedMessage::LiftForceMute { player_id } => self.set_force_muted(player_id, false, &address),
+            ReceivedMessage::DirectMessage { to_player_id, message } => {
+                if message.trim().is_empty() || to_player_id == self.player_id {
+                    return;
+                }
+                if !self.chat_allowed(&message, ctx) {
+                    return;
+                }
+                self.moderate_and_deliver(message, ChatDelivery::Direct { to_player_id }, ctx);
+            }
+            ReceivedMessage::PlayerMove { position, rotation, is_moving } => {
+                let room = self.room.clone();
+                let player_id = self.player_id.clone();
+                let Some(current) = room.get_player_data(&player_id).map(|data| data.position) else {
+                    return;
+                };
+                let requested = position;
+                let position = match self.movement.check(&current, requested.clone()) {
+                    MoveCheck::Accept(position) | MoveCheck::Clamped(position) => position,
+                    MoveCheck::Rejected => {
+                        tracing::debug!("Rejected invalid position from player {}", player_id);
+                        address.do_send(SendingMessage::PositionCorrected { position: current });
+                        return;
+                    }
+                };
+                let position = room.update_player_position(&player_id, position, rotation, is_moving);
+                // Moved further than they could have, out of bounds or through a wall: put the client back in sync
+                if position != requested {
+                    address.do_send(SendingMessage::PositionCorrected { position: position.clone() });
+                }
+                // Broadcast with everyone else's moves on the room's next tick
+                room.queue_move(PlayerPosition {
+                    player_id: player_id.clone(),
+                    position: position.clone(),
+                    rotation,
+                    is_moving,
+                });
+
+                // Footsteps and trails go straight out, to players within the interest radius
+                let interested: Vec<_> = room
+                    .get_players_with_addrs()
+                    .into_iter()
+                    .filter(|(_, player)| player.id != player_id && within_interest(&position, &player.position))
+                    .map(|(addr, _)| addr)
+                    .collect();
+
+                // Footsteps and trails are derived once here so clients don't each infer them
+                for event in self.motion.update(&position, room.get_movement_effects()) {
+                    let message = match event {
+                        MotionEvent::Footstep { speed } => SendingMessage::Footstep {
+                            player_id: player_id.clone(),
+                            position: position.clone(),
+                            speed,
+                        },
+                        MotionEvent::TrailPoint => SendingMessage::TrailPoint {
+                            player_id: player_id.clone(),
+                            position: position.clone(),
+                        },
+                    };
+                    address.do_send(message.clone());
+                    interested.iter().for_each(|peer| peer.do_send(message.clone()));
+                }
+
+                // Our own movement changes who is nearby too
+                if let Some(tracker) = self.accessibility.as_mut() {
+                    for other in room.get_peers_data(&player_id) {
+                        let (kind, text) = match tracker.update_distance(&other.id, &position, &other.position) {
+                            Some(true) => (AccessibilityEventKind::PlayerApproached, format!("You are near {}", other.name)),
+                            Some(false) => (AccessibilityEventKind::PlayerMovedAway, format!("You moved away from {}", other.name)),
+                            None => continue,
+                        };
+                        address.do_send(SendingMessage::AccessibilityEvent {
+                            kind,
+                            player_id: Some(other.id),
+                            text,
+                        });
+                    }
+                }
+            }
+            ReceivedMessage::SetTextToSpeech { enabled, voice } => {
+                if !self.room.is_host(&self.player_id) {
+                    address.do_send(SendingMessage::TextToSpeechFailed {
+                        reason: "only the host can change text-to-speech".to_string(),
+                    });
+                    return;
+                }
+                let owner = self.owner.clone();
+                let room = self.room.clone();
+                actix::spawn(async move {
+                    // Replacing the narrator (e.g. new voice) tears the old one down first
+                    if let Some(previous) = room.set_tts(None) {
+                        previous.close().await;
+                        if room.unregister_publisher(&previous.publisher_id) {
+                            stop_relaying(&owner, &room.id, &previous.publisher_id).await;
+                        }
+                        for peer in room.get_all_addrs() {
+                            peer.do_send(SendingMessage::Unpublished { publisher_id: previous.publisher_id.clone() });
+                        }
+                    }
+                    if !enabled {
+                        for peer in room.get_all_addrs() {
+                            peer.do_send(SendingMessage::TextToSpeechChanged { enabled: false, voice: None });
+                        }
+                        return;
+                    }
+
+                    match TtsNarrator::start(room.router.clone(), voice).await {
+                        Ok(narrator) => {
+                            let narrator = Arc::new(narrator);
+                            let metadata = PublisherMetadata::new(Some(PublisherSource::Mic), Some("Narrator".to_string()), Some(RTPCodecType::Audio), false);
+                            room.register_publisher(narrator.publisher_id.clone(), TTS_PLAYER_ID.to_string(), metadata.clone());
+                            room.set_tts(Some(narrator.clone()));
+                            for peer in room.get_all_addrs() {
+                                peer.do_send(SendingMessage::Published {
+                                    publisher_ids: vec![narrator.publisher_id.clone()],
+                                    player_id: TTS_PLAYER_ID.to_string(),
+                                    metadata: HashMap::from([(narrator.publisher_id.clone(), metadata.clone())]),
+                                });
+                                peer.do_send(SendingMessage::TextToSpeechChanged {
+                                    enabled: true,
+                                    voice: Some(narrator.voice.clone()),
+                                });
+                            }
+                        }
+                        Err(reason) => {
+                            tracing::error!("Failed to start TTS narrator in room {}: {}", room.id, reason);
+                            address.do_send(SendingMessage::TextToSpeechFailed { reason });
+                        }
+                    }
+                });
+            }
+            ReceivedMessage::SetTimeLimits { settings, pin } => {
+                let Some((profile_id, verified, store)) = &self.time_limits else {
+                    address.do_send(SendingMessage::TimeLimitsRejected {
+                        reason: "join with a profile to use time limits".to_string(),
+                    });
+                    return;
+                };
+                let result = store.lock().unwrap().update(profile_id, settings.clone(), pin.as_deref(), *verified);
+                match result {
+                    Ok(()) => {
+                        tracing::info!("[{}] Time limits updated: {:?}", player_name, settings);
+                        address.do_send(SendingMessage::TimeLimitsChanged { settings });
+                        self.time_limit_warned = false;
+                        self.check_time_limit(ctx);
+                    }
+                    Err(reason) => address.do_send(SendingMessage::TimeLimitsRejected { reason: reason.to_string() }),
+                }
+            }
+            ReceivedMessage::RequestTransferCode => {
+                let owner = self.owner.clone();
+                let room_id = self.room.id.clone();
+                let player_id = self.player_id.clone();
+                actix::spawn(async move {
+                    let transfer_code = owner.lock().await.issue_transfer_code(&room_id, &player_id);
+                    address.do_send(SendingMessage::TransferSession {
+                        transfer_code,
+                        expires_in_secs: TRANSFER_CODE_TTL.as_secs(),
+                    });
+                });
+            }
+            ReceivedMessage::SetAccessibility { enabled } => {
+                if !enabled {
+                    self.accessibility = None;
+                    return;
+                }
+                let own = self.room.get_player_data(&self.player_id).map(|data| data.position).unwrap_or_default();
+                let others = self.room.get_peers_data(&self.player_id);
+                let tracker = AccessibilityTracker::new(&own, &others);
+                let nearby = tracker.nearby_names();
+                let mut text = format!("You are in {} with {} other player(s)", self.room.theme, others.len());
+                if !nearby.is_empty() {
+                    text.push_str(&format!(". Near you: {}", nearby.join(", ")));
+                }
+                address.do_send(SendingMessage::AccessibilityEvent {
+                    kind: AccessibilityEventKind::RoomEntered,
+                    player_id: None,
+                    text,
+                });
+                self.accessibility = Some(tracker);
+            }
+            ReceivedMessage::LinkRoom { source_room_id } => {
+                if !self.room.is_host(&self.player_id) {
+                    address.do_send(SendingMessage::RelayFailed { reason: "only the host can link rooms".to_string() });
+                    return;
+                }
+                if source_room_id.as_deref() == Some(self.room.id.as_str()) {
+                    address.do_send(SendingMessage::RelayFailed { reason: "a room can't be linked to itself".to_string() });
+                    return;
+                }
+
+                let owner = self.owner.clone();
+                let room = self.room.clone();
+                actix::spawn(async move {
+                    let source = match &source_room_id {
+                        Some(id) => match owner.lock().await.find_by_id(id.clone()) {
+                            Some(source) => Some(source),
+                            None => {
+                                address.do_send(SendingMessage::RelayFailed { reason: "source room not found".to_string() });
+                                return;
+                            }
+                        },
+                        None => None,
+                    };
+
+                    tracing::info!("Room {} linked to {:?}", room.id, source_room_id);
+                    let publishers = link_audience(&room, source.as_deref()).await;
+                    for peer in room.get_all_addrs() {
+                        peer.do_send(SendingMessage::RoomLinked {
+                            source_room_id: source_room_id.clone(),
+                            publishers: publishers.clone(),
+                        });
+                    }
+                });
+            }
+            ReceivedMessage::SetPublisherRelayed { publisher_id, relayed } => {
+                if !self.room.is_host(&self.player_id) {
+                    address.do_send(SendingMessage::RelayFailed { reason: "only the host can relay publishers".to_string() });
+                    return;
+                }
+                if !self.room.set_publisher_relayed(&publisher_id, relayed) {
+                    address.do_send(SendingMessage::RelayFailed { reason: "publisher not found in this room".to_string() });
+                    return;
+                }
+
+                let owner = self.owner.clone();
                 let room = self.room.clone();
-                let player_id = self.player_id.clone();
-                let publishers = self.publishers.clone();
                 actix::spawn(async move {
-                    if let Some(publisher) = publishers.lock().await.remove(&publisher_id) {
-                        publisher.lock().await.close().await;
-                        room.unregister_publisher(&publisher_id);
-                        room.get_peers(&player_id).iter().for_each(|peer| {
-                            peer.do_send(SendingMessage::Unpublished { publisher_id: publisher_id.clone() });
-                        });
+                    if relayed {
+                        relay_publisher(&owner, &room, &publisher_id).await;
+                    } else {
+                        stop_relaying(&owner, &room.id, &publisher_id).await;
                     }
                 });
             }
-            ReceivedMessage::StopSubscribe { subscriber_id } => {
-                let subscribers = self.subscribers.clone();
+            ReceivedMessage::RelaySubscribe { publisher_id } => {
+                // Relayed publishers are piped onto this room's router, so they're subscribed to like any other
+                if !self.room.is_piped_publisher(&publisher_id) {
+                    address.do_send(SendingMessage::RelayFailed { reason: "publisher is not relayed".to_string() });
+                    return;
+                }
+                self.subscribe(vec![publisher_id], &address);
+            }
+            ReceivedMessage::FetchChatHistory { before, limit } => {
+                let limit = limit.unwrap_or(JOIN_HISTORY_MESSAGES).clamp(1, MAX_HISTORY_PAGE);
+                match self.room.chat_page(before.as_deref(), limit) {
+                    Ok((messages, has_more)) => address.do_send(SendingMessage::ChatHistory { messages, has_more }),
+                    Err(reason) => address.do_send(SendingMessage::ChatHistoryRejected { reason }),
+                }
+            }
+            ReceivedMessage::SetBandwidthProfile { profile } => {
+                tracing::info!("[{}] Bandwidth profile {:?} -> {:?}", player_name, self.bandwidth_profile, profile);
+                // Existing subscriptions are kept; a lower cap only blocks new ones
+                self.bandwidth_profile = profile;
+                address.do_send(SendingMessage::BandwidthProfileChanged {
+                    profile,
+                    limits: profile.limits(),
+                });
+            }
+            ReceivedMessage::SetSlowMode { interval_secs } => {
+                if !self.room.is_host(&self.player_id) {
+                    tracing::warn!("[{}] SetSlowMode rejected: not host", player_name);
+                    return;
+                }
+                let interval_secs = interval_secs.min(MAX_SLOW_MODE_SECS);
+                self.room.set_slow_mode(std::time::Duration::from_secs(interval_secs));
+                tracing::info!("[{}] Slow mode in room {} set to {}s", player_name, self.room.id, interval_secs);
+                self.room.get_all_addrs().iter().for_each(|peer| {
+                    peer.do_send(SendingMessage::SlowModeChanged { interval_secs });
+                });
+            }
+            ReceivedMessage::LockRoom | ReceivedMessage::UnlockRoom => {
+                if !self.room.is_host(&self.player_id) {
+                    address.do_send(SendingMessage::LockRejected {
+                        reason: "only the host can lock the room".to_string(),
+                    });
+                    return;
+                }
+                let locked = matches!(msg, ReceivedMessage::LockRoom);
+                self.room.set_locked(locked);
+                tracing::info!("[{}] Room {} {}", player_name, self.room.id, if locked { "locked" } else { "unlocked" });
+                self.room.get_all_addrs().iter().for_each(|peer| {
+                    peer.do_send(SendingMessage::RoomLockChanged { locked });
+                });
+                if locked {
+                    return;
+                }
+
+                // Let anyone in the waiting room know they can come in now
+                let owner = self.owner.clone();
+                let room_id = self.room.id.clone();
                 actix::spawn(async move {
-                    if let Some(subscriber) = subscribers.lock().await.remove(&subscriber_id) {
-                        subscriber.lock().await.close().await;
+                    let Some(waiting_room) = owner.lock().await.find_by_id(waiting_room_id(&room_id)) else {
+                        return;
+                    };
+                    for peer in waiting_room.get_all_addrs() {
+                        peer.do_send(SendingMessage::RoomUnlocked { room_id: room_id.clone() });
                     }
                 });
             }
-            ReceivedMessage::ChatMessage { message } => {
-                let room = self.room.clone();
-                let sender = self.player_data.name.clone();
-                room.get_all_addrs().iter().for_each(|peer| {
-                    peer.do_send(SendingMessage::ChatMessage {
-                        sender: sender.clone(),
-                        message: message.clone(),
+            ReceivedMessage::StartCountdown { seconds, label } => {
+                if !self.room.is_host(&self.player_id) {
+                    address.do_send(SendingMessage::CountdownRejected {
+                        reason: "only the host can start a countdown".to_string(),
                     });
+                    return;
+                }
+                if seconds == 0 || seconds > MAX_COUNTDOWN_SECS {
+                    address.do_send(SendingMessage::CountdownRejected {
+                        reason: format!("countdowns must be between 1 and {} seconds", MAX_COUNTDOWN_SECS),
+                    });
+                    return;
+                }
+                let countdown = Countdown {
+                    countdown_id: uuid::Uuid::new_v4().to_string(),
+                    label: label.chars().take(MAX_COUNTDOWN_LABEL_CHARS).collect(),
+                    started_by: self.player_id.clone(),
+                    ends_at: chrono::Utc::now().timestamp_millis() + (seconds * 1000) as i64,
+                };
+                tracing::info!("[{}] Countdown '{}' for {}s in room {}", player_name, countdown.label, seconds, self.room.id);
+                if !start_countdown(&self.room, countdown, std::time::Duration::from_secs(seconds)) {
+                    address.do_send(SendingMessage::CountdownRejected {
+                        reason: "too many countdowns are already running".to_string(),
+                    });
+                }
+            }
+            ReceivedMessage::CancelCountdown { countdown_id } => {
+                if !self.room.is_host(&self.player_id) {
+                    address.do_send(SendingMessage::CountdownRejected {
+                        reason: "only the host can cancel a countdown".to_string(),
+                    });
+                    return;
+                }
+                if self.room.remove_countdown(&countdown_id).is_some() {
+                    self.room.get_all_addrs().iter().for_each(|peer| {
+                        peer.do_send(SendingMessage::CountdownCancelled { countdown_id: countdown_id.clone() });
+                    });
+                }
+            }
+            ReceivedMessage::SetRoomPassword { password } => {
+                if !self.room.is_host(&self.player_id) {
+                    address.do_send(SendingMessage::RoomPasswordRejected {
+                        reason: "only the host can set the room password".to_string(),
+                    });
+                    return;
+                }
+                let password = password.filter(|password| !password.trim().is_empty());
+                if password.as_ref().is_some_and(|password| password.len() > MAX_ROOM_PASSWORD_LEN) {
+                    address.do_send(SendingMessage::RoomPasswordRejected {
+                        reason: format!("the password can be at most {} characters", MAX_ROOM_PASSWORD_LEN),
+                    });
+                    return;
+                }
+                self.room.set_password(password.as_deref());
+                let password_protected = password.is_some();
+                tracing::info!(
+                    "[{}] Room {} password {}",
+                    player_name,
+                    self.room.id,
+                    if password_protected { "set" } else { "cleared" }
+                );
+                self.room.get_all_addrs().iter().for_each(|peer| {
+                    peer.do_send(SendingMessage::RoomPasswordChanged { password_protected });
                 });
             }
-            ReceivedMessage::PlayerMove { position, rotation, is_moving } => {
-                let room = self.room.clone();
-                let player_id = self.player_id.clone();
-                room.update_player_position(&player_id, position.clone(), rotation, is_moving);
-                room.get_peers(&player_id).iter().for_each(|peer| {
-                    peer.do_send(SendingMessage::PlayerMoved {
-                        player_id: player_id.clone(),
-                        position: position.clone(),
-                        rotation,
-                        is_moving,
+            // Only meaningful before joining, see `dispatch`
+            ReceivedMessage::Authenticate { .. } => {}
+            ReceivedMessage::SwitchRoom { room_id, password } => self.switch_room(room_id, password, ctx),
+            ReceivedMessage::PinMessage { message_id } => {
+                if !self.room.is_host(&self.player_id) {
+                    address.do_send(SendingMessage::PinRejected {
+                        reason: "only the host can pin messages".to_string(),
+                    });
+                    return;
+                }
+                // Pins quote what was actually said, so they come from the room's history
+                let Some(record) = self.room.get_chat(&message_id) else {
+                    address.do_send(SendingMessage::PinRejected {
+                        reason: "that message isn't in the chat history".to_string(),
+                    });
+                    return;
+                };
+                let pin = PinnedMessage {
+                    pin_id: uuid::Uuid::new_v4().to_string(),
+                    record,
+                    pinned_by: player_name.clone(),
+                    pinned_at: chrono::Utc::now().timestamp_millis(),
+                };
+                if let Err(reason) = self.room.pin_message(pin) {
+                    address.do_send(SendingMessage::PinRejected { reason });
+                    return;
+                }
+                let pinned_messages = self.room.get_pinned_messages();
+                self.room.get_all_addrs().iter().for_each(|peer| {
+                    peer.do_send(SendingMessage::PinnedMessagesChanged { pinned_messages: pinned_messages.clone() });
+                });
+            }
+            ReceivedMessage::UnpinMessage { pin_id } => {
+                if !self.room.is_host(&self.player_id) {
+                    address.do_send(SendingMessage::PinRejected {
+                        reason: "only the host can unpin messages".to_string(),
                     });
+                    return;
+                }
+                if !self.room.unpin_message(&pin_id) {
+                    return;
+                }
+                let pinned_messages = self.room.get_pinned_messages();
+                self.room.get_all_addrs().iter().for_each(|peer| {
+                    peer.do_send(SendingMessage::PinnedMessagesChanged { pinned_messages: pinned_messages.clone() });
+                });
+            }
+            ReceivedMessage::SetMovementEffects { footsteps, trails } => {
+                if !self.room.is_host(&self.player_id) {
+                    tracing::warn!("[{}] SetMovementEffects rejected: not host", player_name);
+                    return;
+                }
+                let movement_effects = MovementEffects { footsteps, trails };
+                self.room.set_movement_effects(movement_effects);
+                self.room.get_all_addrs().iter().for_each(|peer| {
+                    peer.do_send(SendingMessage::MovementEffectsChanged { movement_effects });
                 });
             }
             ReceivedMessage::PlayAnimation { animation } => {
@@ -488,6 +2920,127 @@ impl Handler<ReceivedMessage> for StreamingSession {
                     });
                 });
             }
+            ReceivedMessage::PlayCutscene { cutscene_id } => {
+                if !self.room.is_host(&self.player_id) {
+                    address.do_send(SendingMessage::CutsceneRejected {
+                        cutscene_id,
+                        reason: "only the host can start cutscenes".to_string(),
+                    });
+                    return;
+                }
+                if !theme_for_room(&self.room.id).cutscenes.contains(&cutscene_id) {
+                    address.do_send(SendingMessage::CutsceneRejected {
+                        cutscene_id,
+                        reason: "unknown cutscene for this room".to_string(),
+                    });
+                    return;
+                }
+
+                // Everyone starts at the same server timestamp regardless of when the broadcast lands
+                let start_at = chrono::Utc::now().timestamp_millis() + CUTSCENE_LEAD_TIME_MS;
+                tracing::info!("[{}] PlayCutscene {} at {}", player_name, cutscene_id, start_at);
+                self.room.get_all_addrs().iter().for_each(|peer| {
+                    peer.do_send(SendingMessage::CutsceneStarted {
+                        cutscene_id: cutscene_id.clone(),
+                        started_by: self.player_id.clone(),
+                        start_at,
+                    });
+                });
+            }
+        }
+    }
+}
+
+impl Handler<QueueIceCandidate> for StreamingSession {
+    type Result = ();
+
+    fn handle(&mut self, msg: QueueIceCandidate, ctx: &mut Self::Context) -> Self::Result {
+        let target = msg.target;
+        let batch = self.ice_batch(target);
+        batch.pending.push(msg.candidate);
+        // Still gathering, so the end-of-candidates marker has to wait
+        if let Some(end_timer) = batch.end_timer.take() {
+            ctx.cancel_future(end_timer);
+        }
+        if !batch.flush_scheduled {
+            batch.flush_scheduled = true;
+            ctx.run_later(ICE_BATCH_WINDOW, move |act, ctx| act.flush_ice_batch(target, ctx));
+        }
+    }
+}
+
+/// A session's WebRTC side as the admin API reports it
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TransportState {
+    pub publisher_ids: Vec<String>,
+    pub subscriber_ids: Vec<String>,
+    pub bandwidth_profile: BandwidthProfile,
+    pub features: SessionFeatures,
+    pub resumed: bool,
+    pub connected_secs: u64,
+    pub missed_heartbeats: u32,
+    /// ICE candidates waiting in each batch: publisher, subscriber
+    pub pending_ice_candidates: [usize; 2],
+}
+
+/// Ask a session for its transport state; sent by the admin API
+#[derive(Message)]
+#[rtype(result = "TransportState")]
+pub struct InspectTransports;
+
+impl Handler<InspectTransports> for StreamingSession {
+    type Result = actix::ResponseFuture<TransportState>;
+
+    fn handle(&mut self, _msg: InspectTransports, _ctx: &mut Self::Context) -> Self::Result {
+        let publishers = self.publishers.clone();
+        let subscribers = self.subscribers.clone();
+        let bandwidth_profile = self.bandwidth_profile;
+        let features = self.features;
+        let resumed = self.resumed;
+        let connected_secs = self.session_started.elapsed().as_secs();
+        let missed_heartbeats = self.missed_heartbeats;
+        let pending_ice_candidates = [
+            self.publisher_ice.pending.len(),
+            self.subscriber_ice.pending.len(),
+        ];
+        Box::pin(async move {
+            TransportState {
+                publisher_ids: publishers.lock().await.keys().cloned().collect(),
+                subscriber_ids: subscribers.lock().await.keys().cloned().collect(),
+                bandwidth_profile,
+                features,
+                resumed,
+                connected_secs,
+                missed_heartbeats,
+                pending_ice_candidates,
+            }
+        })
+    }
+}
+
+/// Close the session if it belongs to a revoked account or joined with a revoked token; sent to
+/// every session by the admin API
+#[derive(Message, Clone)]
+#[rtype(result = "()")]
+pub enum RevokeSession {
+    Account(String),
+    Token(String),
+}
+
+impl Handler<RevokeSession> for StreamingSession {
+    type Result = ();
+
+    fn handle(&mut self, msg: RevokeSession, ctx: &mut Self::Context) -> Self::Result {
+        let Some((profile_id, token_id, _)) = &self.revocations else {
+            return;
+        };
+        let revoked = match &msg {
+            RevokeSession::Account(revoked) => revoked == profile_id,
+            RevokeSession::Token(revoked) => token_id.as_ref() == Some(revoked),
+        };
+        if revoked {
+            self.close_revoked(ctx);
         }
     }
 }
@@ -495,11 +3048,102 @@ impl Handler<ReceivedMessage> for StreamingSession {
 impl Handler<SendingMessage> for StreamingSession {
     type Result = ();
 
-    fn handle(&mut self, msg: SendingMessage, ctx: &mut Self::Context) -> Self::Result {
-        ctx.text(serde_json::to_string(&msg).expect("failed to serialize SendingMessage"));
+    fn handle(&mut self, mut msg: SendingMessage, ctx: &mut Self::Context) -> Self::Result {
+        if msg.is_broadcast() && chaos::drop_broadcast() {
+            return;
+        }
+        self.auto_subscribe(&msg, ctx);
+        if let SendingMessage::SessionTransferred = msg {
+            self.transferred_away = true;
+            self.send(ctx, &msg);
+            ctx.close(Some(ws::CloseReason {
+                code: ws::CloseCode::Normal,
+                description: Some("session moved to another device".to_string()),
+            }));
+            ctx.stop();
+            return;
+        }
+        if let SendingMessage::Kicked { .. } = msg {
+            self.leaving = true;
+            self.send(ctx, &msg);
+            ctx.close(Some(ws::CloseReason {
+                code: ws::CloseCode::Policy,
+                description: Some("kicked".to_string()),
+            }));
+            ctx.stop();
+            return;
+        }
+        if let SendingMessage::PlayerMuted { player_id, .. } = &msg {
+            if *player_id == self.player_id {
+                self.close_publishers(false);
+            }
+        }
+        if let SendingMessage::PublisherMuted { publisher_id, muted, .. } = &msg {
+            self.set_publisher_forwarding(publisher_id, !muted);
+        }
+        // The piped copy is already closed; this drops the session's end of it
+        if let SendingMessage::RelayUnpublished { publisher_id } = &msg {
+            self.unsubscribe(publisher_id);
+        }
+        if let SendingMessage::PlayerForceMuted { player_id, muted: true, .. } = &msg {
+            if *player_id == self.player_id {
+                self.close_publishers(true);
+            }
+        }
+        if let SendingMessage::RoleChanged { player_id, role } = &msg {
+            if *player_id == self.player_id {
+                self.player_data.role = *role;
+            }
+        }
+        if let SendingMessage::RoomClosed = msg {
+            self.send(ctx, &msg);
+            ctx.close(Some(ws::CloseReason {
+                code: ws::CloseCode::Normal,
+                description: Some("room closed".to_string()),
+            }));
+            ctx.stop();
+            return;
+        }
+
+        // Throttle movement to the profile's update rate; stop events always go through so avatars settle
+        if let SendingMessage::PlayersMoved { players, .. } = &mut msg {
+            let interval = self.bandwidth_profile.limits().movement_update_interval;
+            let now = std::time::Instant::now();
+            let last_movement_sent = &mut self.last_movement_sent;
+            players.retain(|update| {
+                if !update.is_moving {
+                    return true;
+                }
+                if last_movement_sent.get(&update.player_id).is_some_and(|last| now.duration_since(*last) < interval) {
+                    return false;
+                }
+                last_movement_sent.insert(update.player_id.clone(), now);
+                true
+            });
+            if players.is_empty() {
+                return;
+            }
+        }
+        if let SendingMessage::PlayerLeft { player_id } = &msg {
+            self.last_movement_sent.remove(player_id);
+        }
+
+        let accessibility_events = self.accessibility_events(&msg);
+        self.send(ctx, &msg);
+        for event in accessibility_events {
+            self.send(ctx, &event);
+        }
     }
 }
 
+/// Where an accepted chat message goes
+enum ChatDelivery {
+    Room { upload_id: Option<String> },
+    Direct { to_player_id: String },
+    /// New text for one of the sender's earlier room messages
+    Edit { message_id: String },
+}
+
 /// Messages received from the client
 #[derive(Deserialize, Message, Debug)]
 #[serde(tag = "action")]
@@ -507,6 +3151,15 @@ impl Handler<SendingMessage> for StreamingSession {
 enum ReceivedMessage {
     #[serde(rename_all = "camelCase")]
     Ping,
+    /// Clock sync probe; `client_time` is echoed back alongside the server's clock
+    #[serde(rename_all = "camelCase")]
+    TimeSync { client_time: f64 },
+    /// First message after connecting: the optional features the client handles, see `SessionFeatures::negotiate`
+    #[serde(rename_all = "camelCase")]
+    Hello { capabilities: Vec<String> },
+    /// A wrapped media key for another E2EE player; the server only passes it along
+    #[serde(rename_all = "camelCase")]
+    E2eeKey { to_player_id: String, key: String },
     #[serde(rename_all = "camelCase")]
     PublisherInit,
     #[serde(rename_all = "camelCase")]
@@ -521,14 +3174,68 @@ enum ReceivedMessage {
     Subscribe { publisher_id: String },
     #[serde(rename_all = "camelCase")]
     Answer { sdp: RTCSessionDescription },
+    /// Recover a transport after a network change without rejoining
+    #[serde(rename_all = "camelCase")]
+    RestartIce { target: IceTarget },
     #[serde(rename_all = "camelCase")]
-    Publish { publisher_id: String },
+    Publish {
+        publisher_id: String,
+        source: Option<PublisherSource>,
+        label: Option<String>,
+    },
     #[serde(rename_all = "camelCase")]
     StopPublish { publisher_id: String },
+    /// Mute or unmute one of our own publishers
+    #[serde(rename_all = "camelCase")]
+    SetMuted { publisher_id: String, muted: bool },
     #[serde(rename_all = "camelCase")]
     StopSubscribe { subscriber_id: String },
+    /// Ask the publisher behind a subscription for a fresh keyframe, e.g. after a decode error
+    #[serde(rename_all = "camelCase")]
+    RequestKeyFrame { subscriber_id: String },
+    /// Subscribe to several publishers with a single offer, e.g. everything already in a busy room
     #[serde(rename_all = "camelCase")]
-    ChatMessage { message: String },
+    SubscribeMany { publisher_ids: Vec<String> },
+    /// Stop RTP for a subscription (e.g. an off-screen avatar's video) without renegotiating
+    #[serde(rename_all = "camelCase")]
+    PauseSubscriber { subscriber_id: String },
+    #[serde(rename_all = "camelCase")]
+    ResumeSubscriber { subscriber_id: String },
+    /// Switch a simulcast subscription to another quality layer (`q`/`h`/`f`)
+    #[serde(rename_all = "camelCase")]
+    SelectLayer { subscriber_id: String, rid: String },
+    /// Chat text, optionally sharing an image from `POST /api/uploads`
+    #[serde(rename_all = "camelCase")]
+    ChatMessage {
+        message: String,
+        #[serde(default)]
+        upload_id: Option<String>,
+    },
+    /// Float an emoji above this player's avatar (see `REACTION_EMOJI`)
+    #[serde(rename_all = "camelCase")]
+    Reaction { emoji: String },
+    /// Show "is typing" to others; expires after `TYPING_TIMEOUT` unless sent again
+    StartTyping,
+    StopTyping,
+    /// Replace the text of one of this player's room messages
+    #[serde(rename_all = "camelCase")]
+    EditMessage { message_id: String, message: String },
+    #[serde(rename_all = "camelCase")]
+    DeleteMessage { message_id: String },
+    /// Moderators only: disconnect a player
+    #[serde(rename_all = "camelCase")]
+    Kick { player_id: String },
+    /// Moderators only: stop a player's chat and publishers for the configured mute time
+    #[serde(rename_all = "camelCase")]
+    MutePlayer { player_id: String },
+    /// Moderators only: cut off a player's mic at the SFU until `LiftForceMute`
+    #[serde(rename_all = "camelCase")]
+    ForceMute { player_id: String },
+    #[serde(rename_all = "camelCase")]
+    LiftForceMute { player_id: String },
+    /// Private message to one player in the same room
+    #[serde(rename_all = "camelCase")]
+    DirectMessage { to_player_id: String, message: String },
     #[serde(rename_all = "camelCase")]
     PlayerMove { position: Position, rotation: f32, is_moving: bool },
     #[serde(rename_all = "camelCase")]
@@ -536,44 +3243,494 @@ enum ReceivedMessage {
     /// Client requests list of all active publishers (polling mechanism)
     #[serde(rename_all = "camelCase")]
     GetPublishers,
+    /// Host starts a synchronized cutscene for the whole room
+    #[serde(rename_all = "camelCase")]
+    PlayCutscene { cutscene_id: String },
+    /// Host toggles derived movement events for the room
+    #[serde(rename_all = "camelCase")]
+    SetMovementEffects { footsteps: bool, trails: bool },
+    /// Host links this (audience) room to a stage room, or unlinks with `None`
+    #[serde(rename_all = "camelCase")]
+    LinkRoom { source_room_id: Option<String> },
+    /// Stage host chooses which publishers linked rooms may watch
+    #[serde(rename_all = "camelCase")]
+    SetPublisherRelayed { publisher_id: String, relayed: bool },
+    /// Subscribe to a relayed publisher; the offer comes over the subscriber connection like any other
+    #[serde(rename_all = "camelCase")]
+    RelaySubscribe { publisher_id: String },
+    #[serde(rename_all = "camelCase")]
+    SetBandwidthProfile { profile: BandwidthProfile },
+    /// Host limits chat to one message per player every `interval_secs` (0 disables)
+    #[serde(rename_all = "camelCase")]
+    SetSlowMode { interval_secs: u64 },
+    /// Host stops new players joining (e.g. during a game or recording); members stay
+    #[serde(rename_all = "camelCase")]
+    LockRoom,
+    #[serde(rename_all = "camelCase")]
+    UnlockRoom,
+    /// Host starts a room-wide countdown (pomodoro, game round, event start)
+    #[serde(rename_all = "camelCase")]
+    StartCountdown { seconds: u64, label: String },
+    #[serde(rename_all = "camelCase")]
+    CancelCountdown { countdown_id: String },
+    /// Host sets the passphrase new joins must supply; `None` or empty opens the room again
+    #[serde(rename_all = "camelCase")]
+    SetRoomPassword {
+        #[serde(default)]
+        password: Option<String>,
+    },
+    /// Answer to `PasswordRequired`
+    #[serde(rename_all = "camelCase")]
+    Authenticate { password: String },
+    /// Move to another active room (e.g. through a hub portal) without reconnecting
+    #[serde(rename_all = "camelCase")]
+    SwitchRoom {
+        room_id: String,
+        #[serde(default)]
+        password: Option<String>,
+    },
+    /// Set daily limits/schedule for this profile; `pin` is required once one has been set, and only a
+    /// verified (signed-in) profile can set the first one
+    #[serde(rename_all = "camelCase")]
+    SetTimeLimits {
+        settings: TimeLimitSettings,
+        #[serde(default)]
+        pin: Option<String>,
+    },
+    /// Ask for a code to continue this session on another device
+    #[serde(rename_all = "camelCase")]
+    RequestTransferCode,
+    /// Opt in/out of AccessibilityEvent text summaries
+    #[serde(rename_all = "camelCase")]
+    SetAccessibility { enabled: bool },
+    /// Host toggles reading chat aloud as an audio publisher
+    #[serde(rename_all = "camelCase")]
+    SetTextToSpeech {
+        enabled: bool,
+        #[serde(default)]
+        voice: Option<String>,
+    },
+    /// Host pins a chat message to the room bulletin, by the `messageId` the server gave it
+    #[serde(rename_all = "camelCase")]
+    PinMessage { message_id: String },
+    #[serde(rename_all = "camelCase")]
+    UnpinMessage { pin_id: String },
+    /// Page of chat older than message `before` (the newest when omitted)
+    #[serde(rename_all = "camelCase")]
+    FetchChatHistory {
+        #[serde(default)]
+        before: Option<String>,
+        #[serde(default)]
+        limit: Option<usize>,
+    },
+}
+
+impl ReceivedMessage {
+    fn rate_class(&self) -> MessageClass {
+        match self {
+            // Typing refreshes are superseded by the next one just like moves
+            ReceivedMessage::PlayerMove { .. }
+            | ReceivedMessage::PlayAnimation { .. }
+            | ReceivedMessage::StartTyping
+            | ReceivedMessage::StopTyping => MessageClass::Movement,
+            ReceivedMessage::ChatMessage { .. }
+            | ReceivedMessage::DirectMessage { .. }
+            | ReceivedMessage::EditMessage { .. }
+            | ReceivedMessage::DeleteMessage { .. }
+            | ReceivedMessage::PinMessage { .. }
+            | ReceivedMessage::UnpinMessage { .. }
+            | ReceivedMessage::Authenticate { .. } => MessageClass::Chat,
+            ReceivedMessage::Reaction { .. } => MessageClass::Reaction,
+            _ => MessageClass::Signaling,
+        }
+    }
+
+    /// Observers may watch and subscribe to media, but never act in the room
+    fn allowed_for_observers(&self) -> bool {
+        matches!(
+            self,
+            ReceivedMessage::Ping
+                | ReceivedMessage::TimeSync { .. }
+                | ReceivedMessage::Hello { .. }
+                | ReceivedMessage::SubscriberInit
+                | ReceivedMessage::SubscriberIce { .. }
+                | ReceivedMessage::Subscribe { .. }
+                | ReceivedMessage::SubscribeMany { .. }
+                | ReceivedMessage::RequestKeyFrame { .. }
+                | ReceivedMessage::Answer { .. }
+                | ReceivedMessage::RestartIce { target: IceTarget::Subscriber }
+                | ReceivedMessage::StopSubscribe { .. }
+                | ReceivedMessage::SelectLayer { .. }
+                | ReceivedMessage::PauseSubscriber { .. }
+                | ReceivedMessage::ResumeSubscriber { .. }
+                | ReceivedMessage::GetPublishers
+                | ReceivedMessage::RelaySubscribe { .. }
+                | ReceivedMessage::SetBandwidthProfile { .. }
+                | ReceivedMessage::FetchChatHistory { .. }
+        )
+    }
 }
 
 /// Messages sent to the client
-#[derive(Serialize, Message, Debug)]
+#[derive(Serialize, Message, Debug, Clone)]
 #[serde(tag = "action")]
 #[rtype(result = "()")]
-enum SendingMessage {
+pub enum SendingMessage {
     #[serde(rename_all = "camelCase")]
     Pong,
+    /// Reply to `TimeSync`; the client estimates its offset as `server_time` minus the midpoint of the round trip
+    #[serde(rename_all = "camelCase")]
+    TimeSync { client_time: f64, server_time: i64 },
+    /// Reply to `Hello`: the features in effect from now on
+    #[serde(rename_all = "camelCase")]
+    HelloAck { features: SessionFeatures },
+    #[serde(rename_all = "camelCase")]
+    E2eeKey { from_player_id: String, key: String },
+    /// Subscriber restarts are followed by a fresh Offer; for the publisher the client re-offers
+    #[serde(rename_all = "camelCase")]
+    IceRestartStarted { target: IceTarget },
+    #[serde(rename_all = "camelCase")]
+    IceRestartFailed { target: IceTarget, reason: String },
     #[serde(rename_all = "camelCase")]
     Answer { sdp: RTCSessionDescription },
     #[serde(rename_all = "camelCase")]
     Offer { sdp: RTCSessionDescription },
+    /// Batched local candidates; `end_of_candidates` marks gathering as complete
     #[serde(rename_all = "camelCase")]
-    PublisherIce { candidate: RTCIceCandidateInit },
+    PublisherIceBatch { candidates: Vec<RTCIceCandidateInit>, end_of_candidates: bool },
     #[serde(rename_all = "camelCase")]
-    SubscriberIce { candidate: RTCIceCandidateInit },
+    SubscriberIceBatch { candidates: Vec<RTCIceCandidateInit>, end_of_candidates: bool },
     #[serde(rename_all = "camelCase")]
-    Published { publisher_ids: Vec<String>, player_id: String },
+    Published {
+        publisher_ids: Vec<String>,
+        player_id: String,
+        /// Source and label per publisher ID, for those the client described
+        metadata: HashMap<String, PublisherMetadata>,
+    },
     #[serde(rename_all = "camelCase")]
     Subscribed { subscriber_id: String },
     #[serde(rename_all = "camelCase")]
     SubscribeFailed { publisher_id: String, error: String },
     #[serde(rename_all = "camelCase")]
+    LayerSelected { subscriber_id: String, rid: String },
+    /// How the audience is receiving one of this player's publishers
+    #[serde(rename_all = "camelCase")]
+    PublishQuality {
+        #[serde(flatten)]
+        quality: PublishQuality,
+    },
+    #[serde(rename_all = "camelCase")]
+    SelectLayerFailed { subscriber_id: String, error: String },
+    /// Encoder bitrate this player's publisher should stay under, from how its viewers are receiving it
+    #[serde(rename_all = "camelCase")]
+    BitrateHint { publisher_id: String, kbps: u32 },
+    /// Proximity subscribe dropped a subscription because its publisher moved out of range
+    #[serde(rename_all = "camelCase")]
+    Unsubscribed { subscriber_id: String, publisher_id: String },
+    #[serde(rename_all = "camelCase")]
+    SubscriberPaused { subscriber_id: String, paused: bool },
+    #[serde(rename_all = "camelCase")]
+    PauseSubscriberFailed { subscriber_id: String, error: String },
+    #[serde(rename_all = "camelCase")]
+    KeyFrameRequestFailed { subscriber_id: String, error: String },
+    #[serde(rename_all = "camelCase")]
     Unpublished { publisher_id: String },
     #[serde(rename_all = "camelCase")]
-    ChatMessage { sender: String, message: String },
+    PublisherMuted {
+        publisher_id: String,
+        player_id: String,
+        muted: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    ChatMessage {
+        message_id: String,
+        sender: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        sender_handle: Option<String>,
+        message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        upload_id: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    PlayerReaction { player_id: String, emoji: String },
+    #[serde(rename_all = "camelCase")]
+    PlayerTyping { player_id: String, typing: bool },
+    #[serde(rename_all = "camelCase")]
+    MessageEdited { message_id: String, message: String, edited_at: i64 },
+    #[serde(rename_all = "camelCase")]
+    MessageDeleted { message_id: String, deleted_by: String },
+    /// An edit or delete was refused
+    #[serde(rename_all = "camelCase")]
+    MessageActionFailed { message_id: String, reason: String },
+    /// Private message from another player in the room
+    #[serde(rename_all = "camelCase")]
+    DirectMessage {
+        message_id: String,
+        from_player_id: String,
+        sender: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        sender_handle: Option<String>,
+        message: String,
+    },
+    /// The recipient's connection received a direct message
+    #[serde(rename_all = "camelCase")]
+    DirectMessageDelivered { message_id: String, to_player_id: String },
+    #[serde(rename_all = "camelCase")]
+    DirectMessageFailed { message_id: String, to_player_id: String, reason: String },
+    /// A chat message mentioned this player's `@handle` (or `@everyone`), possibly from a linked room
+    #[serde(rename_all = "camelCase")]
+    Mentioned {
+        message_id: String,
+        by: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        by_handle: Option<String>,
+        room_id: String,
+        message: String,
+    },
+    /// Server-originated notice shown in chat (e.g. operator announcements)
     #[serde(rename_all = "camelCase")]
-    RoomState { your_player_id: String, players: Vec<PlayerData>, room_theme: String, ice_servers: Vec<IceServerConfig> },
+    SystemMessage { message: String },
+    #[serde(rename_all = "camelCase")]
+    RoomState {
+        your_player_id: String,
+        players: Vec<PlayerData>,
+        room_theme: String,
+        ice_servers: Vec<IceServerConfig>,
+        host_id: Option<String>,
+        movement_effects: MovementEffects,
+        ambient_sounds: Vec<AmbientEmitter>,
+        /// Voice attenuation the server applies in `AudioGain`, for client-side rendering to match
+        speaking_distance: SpeakingDistance,
+        /// Walkable area; the server clamps every move to it
+        world_bounds: WorldBounds,
+        bandwidth_profile: BandwidthProfile,
+        bandwidth_limits: BandwidthLimits,
+        /// Current chat slow-mode interval, 0 when off
+        slow_mode_secs: u64,
+        pinned_messages: Vec<PinnedMessage>,
+        /// Latest chat messages, oldest first; page further back with `FetchChatHistory`
+        chat_history: Vec<ChatRecord>,
+        /// New joins are turned away while the host has the room locked
+        locked: bool,
+        /// New joins have to supply the room password
+        password_protected: bool,
+        countdowns: Vec<Countdown>,
+        /// Unix milliseconds when this was sent, so clients can correct for clock skew
+        server_time: i64,
+        reconnect: ReconnectPolicy,
+        /// Pass as `reconnectToken` when rejoining after a drop to return to this room
+        reconnect_token: String,
+        /// Pass as `resumeToken` within `resumeGraceSecs` of a drop to keep this player, its
+        /// publishers and subscriptions; the client keeps its peer connections
+        resume_token: String,
+        resume_grace_secs: u64,
+        /// This connection picked up a dropped session instead of joining fresh
+        resumed: bool,
+        /// Send as `Authorization: Bearer` to `POST /api/uploads` while in this room
+        upload_token: String,
+        /// Optional features in effect until the client's `Hello` is answered
+        features: SessionFeatures,
+    },
     #[serde(rename_all = "camelCase")]
     PlayerJoined { player: PlayerData },
     #[serde(rename_all = "camelCase")]
     PlayerLeft { player_id: String },
+    /// Every move since the last room tick, see `spawn_movement_tick_loop`. `seq` increases with each
+    /// tick and `server_time` (unix millis) is when it was sent, for interpolating between ticks
     #[serde(rename_all = "camelCase")]
-    PlayerMoved { player_id: String, position: Position, rotation: f32, is_moving: bool },
+    PlayersMoved {
+        players: Vec<PlayerPosition>,
+        seq: u64,
+        server_time: i64,
+    },
     #[serde(rename_all = "camelCase")]
     PlayerAnimation { player_id: String, animation: String },
     /// Response with all active publishers (for polling)
     #[serde(rename_all = "camelCase")]
     PublisherList { publishers: Vec<PublisherInfo> },
+    #[serde(rename_all = "camelCase")]
+    HostChanged { host_id: String },
+    /// Cutscene broadcast; `start_at` is a server timestamp in unix millis
+    #[serde(rename_all = "camelCase")]
+    CutsceneStarted { cutscene_id: String, started_by: String, start_at: i64 },
+    #[serde(rename_all = "camelCase")]
+    CutsceneRejected { cutscene_id: String, reason: String },
+    /// Portal layout and live occupancy for the teleporter hub
+    #[serde(rename_all = "camelCase")]
+    HubPortals { portals: Vec<Portal> },
+    #[serde(rename_all = "camelCase")]
+    Footstep { player_id: String, position: Position, speed: f32 },
+    #[serde(rename_all = "camelCase")]
+    TrailPoint { player_id: String, position: Position },
+    #[serde(rename_all = "camelCase")]
+    MovementEffectsChanged { movement_effects: MovementEffects },
+    #[serde(rename_all = "camelCase")]
+    RoomLinked { source_room_id: Option<String>, publishers: Vec<PublisherInfo> },
+    #[serde(rename_all = "camelCase")]
+    RelayPublished { publisher_ids: Vec<String>, player_id: String },
+    #[serde(rename_all = "camelCase")]
+    RelayUnpublished { publisher_id: String },
+    #[serde(rename_all = "camelCase")]
+    RelayFailed { reason: String },
+    #[serde(rename_all = "camelCase")]
+    BandwidthProfileChanged { profile: BandwidthProfile, limits: BandwidthLimits },
+    #[serde(rename_all = "camelCase")]
+    EchoReport { report: EchoReport },
+    #[serde(rename_all = "camelCase")]
+    SlowModeChanged { interval_secs: u64 },
+    /// Chat message dropped by slow mode; seconds until the next one is allowed
+    #[serde(rename_all = "camelCase")]
+    SlowModeActive { retry_after: u64 },
+    #[serde(rename_all = "camelCase")]
+    ChatRejected { reason: String, retry_after_ms: u64 },
+    /// Reply to `FetchChatHistory`, oldest first
+    #[serde(rename_all = "camelCase")]
+    ChatHistory { messages: Vec<ChatRecord>, has_more: bool },
+    #[serde(rename_all = "camelCase")]
+    ChatHistoryRejected { reason: String },
+    /// Messages of this class are being dropped; keep flooding and the connection is closed
+    #[serde(rename_all = "camelCase")]
+    RateLimited { class: MessageClass },
+    #[serde(rename_all = "camelCase")]
+    PinnedMessagesChanged { pinned_messages: Vec<PinnedMessage> },
+    #[serde(rename_all = "camelCase")]
+    PinRejected { reason: String },
+    #[serde(rename_all = "camelCase")]
+    RoomLockChanged { locked: bool },
+    #[serde(rename_all = "camelCase")]
+    CountdownStarted {
+        countdown: Countdown,
+        /// Unix milliseconds when this was sent, so clients can correct for clock skew
+        server_time: i64,
+    },
+    #[serde(rename_all = "camelCase")]
+    CountdownFinished { countdown_id: String, label: String },
+    #[serde(rename_all = "camelCase")]
+    CountdownCancelled { countdown_id: String },
+    #[serde(rename_all = "camelCase")]
+    CountdownRejected { reason: String },
+    #[serde(rename_all = "camelCase")]
+    LockRejected { reason: String },
+    /// The room has a password; reply with `Authenticate` before anything else or be disconnected
+    #[serde(rename_all = "camelCase")]
+    PasswordRequired,
+    #[serde(rename_all = "camelCase")]
+    RoomPasswordChanged { password_protected: bool },
+    #[serde(rename_all = "camelCase")]
+    RoomPasswordRejected { reason: String },
+    /// This connection left its room for `room_id`; drop all WebRTC state, a new `RoomState` follows
+    #[serde(rename_all = "camelCase")]
+    RoomSwitched { room_id: String },
+    #[serde(rename_all = "camelCase")]
+    SwitchRoomFailed { room_id: String, reason: String },
+    /// Sent to the waiting room when the room it waits on opens again; join with `room=<room_id>`
+    #[serde(rename_all = "camelCase")]
+    RoomUnlocked { room_id: String },
+    #[serde(rename_all = "camelCase")]
+    TimeLimitsChanged { settings: TimeLimitSettings },
+    #[serde(rename_all = "camelCase")]
+    TimeLimitsRejected { reason: String },
+    #[serde(rename_all = "camelCase")]
+    TimeLimitWarning { minutes_remaining: u64 },
+    /// Sent right before the server disconnects a profile that's out of time
+    #[serde(rename_all = "camelCase")]
+    TimeLimitReached { reason: String },
+    /// The server is going down; reconnect with the given policy and token
+    #[serde(rename_all = "camelCase")]
+    ServerShutdown { reconnect: ReconnectPolicy, reconnect_token: String },
+    /// The session length limit was reached; the token rejoins the same room
+    #[serde(rename_all = "camelCase")]
+    SessionExpired { reconnect: ReconnectPolicy, reconnect_token: String },
+    /// Handoff code to enter on the other device (join with `transferCode`)
+    #[serde(rename_all = "camelCase")]
+    TransferSession { transfer_code: String, expires_in_secs: u64 },
+    /// Another device took over this session; the connection closes next
+    #[serde(rename_all = "camelCase")]
+    SessionTransferred,
+    /// The profile was revoked by an admin; the connection closes next
+    #[serde(rename_all = "camelCase")]
+    SessionRevoked,
+    /// The observed room emptied and was removed; the observer's connection closes next
+    #[serde(rename_all = "camelCase")]
+    RoomClosed,
+    /// A moderator or admin removed this player (or closed the room); the connection closes next
+    #[serde(rename_all = "camelCase")]
+    Kicked { reason: String },
+    /// A moderator muted a player's chat and media; their publishers have been closed
+    #[serde(rename_all = "camelCase")]
+    PlayerMuted { player_id: String, muted_by: String, duration_secs: u64 },
+    /// A moderator cut off (or restored) a player's mic; the target's voice publishers have been closed
+    #[serde(rename_all = "camelCase")]
+    PlayerForceMuted { player_id: String, muted_by: String, muted: bool },
+    /// A Kick, MutePlayer or ForceMute was refused
+    #[serde(rename_all = "camelCase")]
+    ModerationFailed { player_id: String, reason: String },
+    /// A player's role was changed through the admin API
+    #[serde(rename_all = "camelCase")]
+    RoleChanged { player_id: String, role: Role },
+    #[serde(rename_all = "camelCase")]
+    PlayerStatus { player_id: String, status: PlayerStatus },
+    /// From the server reading each mic's audio level, see `spawn_speaking_monitor`
+    #[serde(rename_all = "camelCase")]
+    SpeakingChanged { player_id: String, speaking: bool },
+    /// Publishers this client had before the server restarted unexpectedly; publish them again
+    #[serde(rename_all = "camelCase")]
+    RepublishRequired { publisher_ids: Vec<String> },
+    /// Position correction for players outside the interest radius
+    #[serde(rename_all = "camelCase")]
+    PlayerPositions { players: Vec<PlayerPosition> },
+    /// Our own last move was rejected or cut short; snap to this position
+    #[serde(rename_all = "camelCase")]
+    PositionCorrected { position: Position },
+    /// Volume (0.0 - 1.0) to play a remote publisher at, based on distance
+    #[serde(rename_all = "camelCase")]
+    AudioGain { publisher_id: String, gain: f32 },
+    #[serde(rename_all = "camelCase")]
+    TextToSpeechChanged { enabled: bool, voice: Option<String> },
+    #[serde(rename_all = "camelCase")]
+    TextToSpeechFailed { reason: String },
+    /// Screen-reader friendly description of something that happened around the player
+    #[serde(rename_all = "camelCase")]
+    AccessibilityEvent {
+        kind: AccessibilityEventKind,
+        player_id: Option<String>,
+        text: String,
+    },
+    /// Follow-up to a ChatMessage containing a URL
+    #[serde(rename_all = "camelCase")]
+    LinkPreview {
+        message_id: String,
+        #[serde(flatten)]
+        preview: LinkPreview,
+    },
+}
+
+impl SendingMessage {
+    /// Room-wide fan-out, as opposed to replies to this client's own requests
+    fn is_broadcast(&self) -> bool {
+        self.is_game_state()
+            || matches!(
+                self,
+                SendingMessage::ChatMessage { .. }
+                    | SendingMessage::PlayerTyping { .. }
+                    | SendingMessage::PlayerJoined { .. }
+                    | SendingMessage::PlayerLeft { .. }
+                    | SendingMessage::Published { .. }
+                    | SendingMessage::Unpublished { .. }
+                    | SendingMessage::HostChanged { .. }
+            )
+    }
+
+    /// High-frequency world updates, eligible for the binary protocol
+    fn is_game_state(&self) -> bool {
+        matches!(
+            self,
+            SendingMessage::PlayersMoved { .. }
+                | SendingMessage::PlayerPositions { .. }
+                | SendingMessage::PlayerAnimation { .. }
+                | SendingMessage::Footstep { .. }
+                | SendingMessage::TrailPoint { .. }
+                | SendingMessage::AudioGain { .. }
+        )
+    }
 }
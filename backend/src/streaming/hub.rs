@@ -0,0 +1,86 @@
+use actix::Actor;
+use actix_web::web::Data;
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use super::echo::is_echo_room;
+use super::handler::{Position, SendingMessage, StreamingSession};
+use super::room::RoomOwner;
+
+pub const HUB_ROOM_ID: &str = "hub";
+pub const HUB_ROOM_THEME: &str = "Teleporter Hub";
+
+/// Distance from the hub spawn to the ring of portals
+const PORTAL_RING_RADIUS: f32 = 10.0;
+/// How often hub occupancy labels are refreshed
+const HUB_REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// A walkable portal in the hub leading to another active room
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Portal {
+    pub room_id: String,
+    pub room_theme: String,
+    pub language: String,
+    pub player_count: usize,
+    pub position: Position,
+}
+
+/// Lay out one portal per active room in a ring around the hub spawn
+pub fn build_portals<T: Actor>(owner: &RoomOwner<T>) -> Vec<Portal> {
+    let mut rooms: Vec<_> = owner
+        .list_rooms()
+        .into_iter()
+        .filter(|room| room.id != HUB_ROOM_ID && !is_echo_room(&room.id))
+        .collect();
+    // Stable ordering so portals don't jump around as occupancy changes
+    rooms.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let count = rooms.len().max(1) as f32;
+    rooms
+        .iter()
+        .enumerate()
+        .map(|(i, room)| {
+            let angle = i as f32 / count * std::f32::consts::TAU;
+            Portal {
+                room_id: room.id.clone(),
+                room_theme: room.theme.clone(),
+                language: room.language.clone(),
+                player_count: room.player_count(),
+                position: Position {
+                    x: angle.cos() * PORTAL_RING_RADIUS,
+                    y: 0.0,
+                    z: angle.sin() * PORTAL_RING_RADIUS,
+                },
+            }
+        })
+        .collect()
+}
+
+/// Periodically push portal occupancy to everyone standing in the hub
+pub fn spawn_hub_updater(owner: Data<Mutex<RoomOwner<StreamingSession>>>) {
+    actix::spawn(async move {
+        let mut interval = tokio::time::interval(HUB_REFRESH_INTERVAL);
+        let mut last_portals: Vec<Portal> = Vec::new();
+        loop {
+            interval.tick().await;
+
+            let (hub, portals) = {
+                let owner = owner.lock().await;
+                (owner.find_by_id(HUB_ROOM_ID.to_string()), build_portals(&owner))
+            };
+            let Some(hub) = hub else {
+                last_portals.clear();
+                continue;
+            };
+            if portals == last_portals {
+                continue;
+            }
+
+            for addr in hub.get_all_addrs() {
+                addr.do_send(SendingMessage::HubPortals { portals: portals.clone() });
+            }
+            last_portals = portals;
+        }
+    });
+}
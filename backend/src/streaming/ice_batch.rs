@@ -0,0 +1,35 @@
+use std::time::Duration;
+use actix::{Message, SpawnHandle};
+use serde::{Deserialize, Serialize};
+use webrtc::ice_transport::ice_candidate::RTCIceCandidateInit;
+
+/// How long candidates are buffered before a batch is flushed to the client
+pub const ICE_BATCH_WINDOW: Duration = Duration::from_millis(50);
+/// With no new candidates for this long, gathering is considered complete.
+/// Relay allocations can take a couple of seconds, so this is deliberately generous.
+pub const ICE_GATHERING_QUIET_PERIOD: Duration = Duration::from_secs(3);
+
+/// Which transport a candidate belongs to
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IceTarget {
+    Publisher,
+    Subscriber,
+}
+
+/// Internal message: a transport generated a local candidate that should be batched
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct QueueIceCandidate {
+    pub target: IceTarget,
+    pub candidate: RTCIceCandidateInit,
+}
+
+/// Candidates waiting to be sent for one transport
+#[derive(Default)]
+pub struct IceBatch {
+    pub pending: Vec<RTCIceCandidateInit>,
+    pub flush_scheduled: bool,
+    /// Pending end-of-candidates marker, cancelled whenever a new candidate arrives
+    pub end_timer: Option<SpawnHandle>,
+}
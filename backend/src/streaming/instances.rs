@@ -0,0 +1,26 @@
+use super::language::split_language;
+
+/// `music-lounge` + 3 -> `music-lounge-3`; the first instance keeps the plain ID
+pub fn instance_room_id(base_room_id: &str, instance: u32) -> String {
+    if instance <= 1 {
+        base_room_id.to_string()
+    } else {
+        format!("{}-{}", base_room_id, instance)
+    }
+}
+
+/// Split an overflow instance ID (`music-lounge-3`) into its base ID and instance number
+pub fn split_instance(room_id: &str) -> (&str, u32) {
+    match room_id.rsplit_once('-') {
+        Some((base, number)) if !number.starts_with('0') => match number.parse::<u32>() {
+            Ok(instance) if instance > 1 => (base, instance),
+            _ => (room_id, 1),
+        },
+        _ => (room_id, 1),
+    }
+}
+
+/// Theme ID a room's settings are keyed by, without its language suffix or instance number
+pub fn base_room_id(room_id: &str) -> &str {
+    split_instance(split_language(room_id).0).0
+}
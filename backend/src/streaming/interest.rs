@@ -0,0 +1,29 @@
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use super::handler::Position;
+
+/// How often players outside the interest radius get a position correction
+pub const FAR_PLAYER_SYNC_INTERVAL: Duration = Duration::from_secs(2);
+const DEFAULT_INTEREST_RADIUS: f32 = 40.0;
+
+/// `INTEREST_RADIUS` env var; `0` turns interest management off (everyone hears every move)
+static INTEREST_RADIUS: LazyLock<Option<f32>> = LazyLock::new(|| {
+    let radius = std::env::var("INTEREST_RADIUS")
+        .ok()
+        .and_then(|radius| radius.parse::<f32>().ok())
+        .unwrap_or(DEFAULT_INTEREST_RADIUS);
+    (radius > 0.0).then_some(radius)
+});
+
+pub fn interest_radius() -> Option<f32> {
+    *INTEREST_RADIUS
+}
+
+/// Whether movement at `a` should be forwarded to a player at `b`
+pub fn within_interest(a: &Position, b: &Position) -> bool {
+    match interest_radius() {
+        Some(radius) => (a.x - b.x).powi(2) + (a.z - b.z).powi(2) <= radius * radius,
+        None => true,
+    }
+}
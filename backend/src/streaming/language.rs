@@ -0,0 +1,29 @@
+/// Rooms without a language suffix are English
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Accept ISO 639-1 codes (`es`, `PT-br` -> `pt`), anything else falls back to the default
+pub fn normalize_language(language: &str) -> String {
+    let code = language.split(['-', '_']).next().unwrap_or_default().to_ascii_lowercase();
+    if code.len() == 2 && code.chars().all(|c| c.is_ascii_lowercase()) {
+        code
+    } else {
+        DEFAULT_LANGUAGE.to_string()
+    }
+}
+
+/// `music-lounge` + `es` -> `music-lounge-es`; default-language rooms keep their plain ID
+pub fn localized_room_id(base_room_id: &str, language: &str) -> String {
+    if language == DEFAULT_LANGUAGE {
+        base_room_id.to_string()
+    } else {
+        format!("{}-{}", base_room_id, language)
+    }
+}
+
+/// Split a room ID into its base (theme) ID and language
+pub fn split_language(room_id: &str) -> (&str, &str) {
+    match room_id.rsplit_once('-') {
+        Some((base, code)) if code.len() == 2 && code.chars().all(|c| c.is_ascii_lowercase()) => (base, code),
+        _ => (room_id, DEFAULT_LANGUAGE),
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use reqwest::Url;
+use serde::Serialize;
+
+/// How long fetched previews (and failures) are reused
+const PREVIEW_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+const PREVIEW_CACHE_CAPACITY: usize = 1000;
+const PREVIEW_FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+/// Only the head of the page is needed for OpenGraph tags
+const PREVIEW_MAX_BODY_BYTES: usize = 512 * 1024;
+const PREVIEW_MAX_FIELD_CHARS: usize = 300;
+
+/// OpenGraph summary of a URL posted in chat
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkPreview {
+    pub url: String,
+    pub title: Option<String>,
+    pub image: Option<String>,
+    pub description: Option<String>,
+}
+
+/// URL -> (fetched at, preview); `None` caches a failed or empty fetch
+static PREVIEW_CACHE: LazyLock<Mutex<HashMap<String, (Instant, Option<LinkPreview>)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// First http(s) URL in a chat message
+pub fn extract_url(message: &str) -> Option<String> {
+    message
+        .split_whitespace()
+        .find(|word| word.starts_with("http://") || word.starts_with("https://"))
+        .map(|word| word.trim_end_matches(['.', ',', ')', '!', '?']).to_string())
+}
+
+/// Fetch (or reuse) the preview for a URL
+pub async fn fetch_link_preview(url: &str) -> Option<LinkPreview> {
+    if let Some((fetched_at, preview)) = PREVIEW_CACHE.lock().unwrap().get(url) {
+        if fetched_at.elapsed() < PREVIEW_CACHE_TTL {
+            return preview.clone();
+        }
+    }
+
+    let preview = fetch_uncached(url).await;
+    let mut cache = PREVIEW_CACHE.lock().unwrap();
+    if cache.len() >= PREVIEW_CACHE_CAPACITY {
+        cache.retain(|_, (fetched_at, _)| fetched_at.elapsed() < PREVIEW_CACHE_TTL);
+        if cache.len() >= PREVIEW_CACHE_CAPACITY {
+            cache.clear();
+        }
+    }
+    cache.insert(url.to_string(), (Instant::now(), preview.clone()));
+    preview
+}
+
+async fn fetch_uncached(raw_url: &str) -> Option<LinkPreview> {
+    let url = Url::parse(raw_url).ok()?;
+    if !matches!(url.scheme(), "http" | "https") || !url.username().is_empty() || url.password().is_some() {
+        return None;
+    }
+    // Non-default ports are a common way to reach internal services
+    if url.port().is_some() {
+        return None;
+    }
+    let host = url.host_str()?.to_string();
+    let port = url.port_or_known_default()?;
+
+    // Resolve once, vet every address, then pin the connection to a vetted one so a
+    // second DNS answer can't point the request somewhere internal
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), port)).await.ok()?.collect();
+    if addrs.is_empty() || addrs.iter().any(|addr| !is_public_ip(addr.ip())) {
+        tracing::warn!("Link preview blocked for non-public host {}", host);
+        return None;
+    }
+
+    let client = reqwest::Client::builder()
+        .resolve(&host, addrs[0])
+        .redirect(reqwest::redirect::Policy::none())
+        .timeout(PREVIEW_FETCH_TIMEOUT)
+        .user_agent("WebHanginLinkPreview/1.0")
+        .build()
+        .ok()?;
+    let mut response = client.get(url.clone()).send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    let is_html = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/html"));
+    if !is_html {
+        return None;
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.ok()? {
+        body.extend_from_slice(&chunk);
+        if body.len() >= PREVIEW_MAX_BODY_BYTES {
+            break;
+        }
+    }
+    let html = String::from_utf8_lossy(&body);
+
+    let preview = LinkPreview {
+        url: raw_url.to_string(),
+        title: meta_content(&html, "og:title").or_else(|| title_tag(&html)),
+        image: meta_content(&html, "og:image").and_then(|image| url.join(&image).ok()).map(|image| image.to_string()),
+        description: meta_content(&html, "og:description").or_else(|| meta_content(&html, "description")),
+    };
+    if preview.title.is_none() && preview.image.is_none() && preview.description.is_none() {
+        return None;
+    }
+    Some(preview)
+}
+
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                // Carrier-grade NAT and benchmarking ranges
+                || (a == 100 && (64..128).contains(&b))
+                || (a == 198 && (18..20).contains(&b))
+                || a == 0
+                || a >= 240)
+        }
+        IpAddr::V6(ip) => {
+            if let Some(mapped) = ip.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(mapped));
+            }
+            let first = ip.segments()[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // Unique local (fc00::/7) and link-local (fe80::/10)
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+/// `content` of the first `<meta property|name="key">` tag
+fn meta_content(html: &str, key: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let mut offset = 0;
+    while let Some(start) = lower[offset..].find("<meta") {
+        let start = offset + start;
+        let end = start + lower[start..].find('>')?;
+        let tag = &html[start..end];
+        let matches_key = [attribute(tag, "property"), attribute(tag, "name")]
+            .into_iter()
+            .flatten()
+            .any(|value| value.eq_ignore_ascii_case(key));
+        if matches_key {
+            return attribute(tag, "content").map(|content| clean_text(&content)).filter(|content| !content.is_empty());
+        }
+        offset = end;
+    }
+    None
+}
+
+fn title_tag(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let start = lower.find("<title")?;
+    let start = start + lower[start..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    Some(clean_text(&html[start..end])).filter(|title| !title.is_empty())
+}
+
+/// Value of a quoted attribute inside a single tag
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let mut offset = 0;
+    while let Some(found) = lower[offset..].find(name) {
+        let at = offset + found;
+        offset = at + name.len();
+        // Must be a whole attribute name, not a suffix like `og:name`
+        let preceded_ok = at == 0 || lower.as_bytes()[at - 1].is_ascii_whitespace();
+        let rest = lower[offset..].trim_start();
+        if !preceded_ok || !rest.starts_with('=') {
+            continue;
+        }
+        let value_start = tag.len() - rest.len() + 1;
+        let value = tag[value_start..].trim_start();
+        let quote = value.chars().next()?;
+        if quote != '"' && quote != '\'' {
+            return value.split_whitespace().next().map(str::to_string);
+        }
+        let value = &value[1..];
+        return value.find(quote).map(|end| value[..end].to_string());
+    }
+    None
+}
+
+fn clean_text(text: &str) -> String {
+    let decoded = text
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&");
+    decoded.split_whitespace().collect::<Vec<_>>().join(" ").chars().take(PREVIEW_MAX_FIELD_CHARS).collect()
+}
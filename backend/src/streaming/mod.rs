@@ -1,7 +1,56 @@
+pub mod accessibility;
+pub mod bandwidth;
+pub mod chat;
+pub mod chaos;
+pub mod codecs;
+pub mod collision;
+pub mod countdown;
+pub mod echo;
 pub mod handler;
+pub mod hub;
+pub mod ice_batch;
+pub mod instances;
+pub mod interest;
+pub mod language;
+pub mod link_preview;
+pub mod moderation;
+pub mod motion;
+pub mod movement;
+pub mod mutes;
+pub mod protocol;
+#[cfg(test)]
+mod protocol_fuzz;
+pub mod proximity;
+pub mod publish_quality;
+pub mod publisher_registry;
+pub mod rate_limit;
+pub mod reactions;
+pub mod reconnect;
+pub mod relay;
+pub mod resume;
+pub mod roles;
 pub mod room;
+pub mod room_password;
+pub mod simulcast;
+pub mod spatial_audio;
+pub mod speaking;
+pub mod theme;
+pub mod tick;
+pub mod transfer;
+pub mod transport_pool;
+pub mod tts;
+pub mod turn_health;
 pub mod turn_server;
 
+pub use bandwidth::BandwidthProfile;
 pub use handler::{StreamingSession, PlayerData, FacialFeatures};
+pub use protocol::{SessionFeatures, WireProtocol};
+pub use echo::{ECHO_TEST_ROOM_ID, ECHO_TEST_ROOM_THEME};
+pub use hub::{spawn_hub_updater, HUB_ROOM_ID, HUB_ROOM_THEME};
 pub use room::RoomOwner;
-pub use turn_server::fetch_xirsys_ice_servers;
+pub use publish_quality::spawn_publish_quality_loop;
+pub use publisher_registry::PublisherRegistry;
+pub use spatial_audio::spawn_audio_gain_loop;
+pub use speaking::spawn_speaking_monitor;
+pub use tick::spawn_movement_tick_loop;
+pub use turn_server::fetch_ice_servers;
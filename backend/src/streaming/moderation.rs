@@ -0,0 +1,239 @@
+use std::sync::LazyLock;
+use std::time::Duration;
+use futures_util::future::BoxFuture;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{FilterAction, ModerationSettings};
+
+/// What happens to a chat message after review
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Allow,
+    /// Deliver this text instead
+    Mask(String),
+    Drop { reason: String },
+    /// Drop it and mute the sender's chat
+    Mute { reason: String },
+}
+
+/// A message under review, as later stages see it after earlier masking
+pub struct ChatReview<'a> {
+    pub message: &'a str,
+    pub sender: &'a str,
+    pub room_id: &'a str,
+}
+
+/// One stage of the chat moderation pipeline
+pub trait ChatModerator: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn review<'a>(&'a self, chat: &'a ChatReview<'a>) -> BoxFuture<'a, Verdict>;
+}
+
+/// Replace every character of each match with `*`
+fn mask_matches(pattern: &Regex, message: &str) -> String {
+    pattern.replace_all(message, |caps: &regex::Captures| "*".repeat(caps[0].chars().count())).into_owned()
+}
+
+fn apply(pattern: &Regex, action: FilterAction, message: &str, reason: &str) -> Option<Verdict> {
+    if !pattern.is_match(message) {
+        return None;
+    }
+    Some(match action {
+        FilterAction::Mask => Verdict::Mask(mask_matches(pattern, message)),
+        FilterAction::Drop => Verdict::Drop { reason: reason.to_string() },
+        FilterAction::Mute => Verdict::Mute { reason: reason.to_string() },
+    })
+}
+
+/// Blocked words, matched case-insensitively as whole words so "class" doesn't trip on "ass"
+pub struct WordListModerator {
+    pattern: Regex,
+    action: FilterAction,
+}
+
+impl WordListModerator {
+    pub fn new(words: &[String], action: FilterAction) -> Option<Self> {
+        let words: Vec<String> = words.iter().filter(|word| !word.trim().is_empty()).map(|word| regex::escape(word.trim())).collect();
+        if words.is_empty() {
+            return None;
+        }
+        let pattern = Regex::new(&format!(r"(?i)\b(?:{})\b", words.join("|"))).ok()?;
+        Some(Self { pattern, action })
+    }
+}
+
+impl ChatModerator for WordListModerator {
+    fn name(&self) -> &'static str {
+        "word list"
+    }
+
+    fn review<'a>(&'a self, chat: &'a ChatReview<'a>) -> BoxFuture<'a, Verdict> {
+        let verdict = apply(&self.pattern, self.action, chat.message, "That message contains a blocked word");
+        Box::pin(async move { verdict.unwrap_or(Verdict::Allow) })
+    }
+}
+
+/// Operator regex rules, each with its own action; the first matching drop/mute wins, masks stack
+pub struct RegexModerator {
+    rules: Vec<(Regex, FilterAction)>,
+}
+
+impl ChatModerator for RegexModerator {
+    fn name(&self) -> &'static str {
+        "regex rules"
+    }
+
+    fn review<'a>(&'a self, chat: &'a ChatReview<'a>) -> BoxFuture<'a, Verdict> {
+        let mut masked: Option<String> = None;
+        for (pattern, action) in &self.rules {
+            let current = masked.as_deref().unwrap_or(chat.message);
+            match apply(pattern, *action, current, "That message isn't allowed here") {
+                Some(Verdict::Mask(text)) => masked = Some(text),
+                Some(verdict) => return Box::pin(async move { verdict }),
+                None => {}
+            }
+        }
+        Box::pin(async move { masked.map(Verdict::Mask).unwrap_or(Verdict::Allow) })
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EndpointRequest<'a> {
+    message: &'a str,
+    sender: &'a str,
+    room_id: &'a str,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum EndpointAction {
+    Allow,
+    Mask,
+    Drop,
+    Mute,
+}
+
+#[derive(Deserialize)]
+struct EndpointResponse {
+    action: EndpointAction,
+    /// Replacement text for `mask`
+    message: Option<String>,
+    reason: Option<String>,
+}
+
+/// External moderation service; when it's down or slow, messages go through rather than chat stalling
+pub struct HttpModerator {
+    endpoint: String,
+    client: reqwest::Client,
+}
+
+impl HttpModerator {
+    pub fn new(endpoint: String, timeout: Duration) -> Option<Self> {
+        let client = reqwest::Client::builder().timeout(timeout).build().ok()?;
+        Some(Self { endpoint, client })
+    }
+}
+
+impl ChatModerator for HttpModerator {
+    fn name(&self) -> &'static str {
+        "endpoint"
+    }
+
+    fn review<'a>(&'a self, chat: &'a ChatReview<'a>) -> BoxFuture<'a, Verdict> {
+        Box::pin(async move {
+            let request = EndpointRequest {
+                message: chat.message,
+                sender: chat.sender,
+                room_id: chat.room_id,
+            };
+            let response = match self.client.post(&self.endpoint).json(&request).send().await {
+                Ok(response) => response.json::<EndpointResponse>().await,
+                Err(e) => Err(e),
+            };
+            let response = match response {
+                Ok(response) => response,
+                Err(e) => {
+                    tracing::warn!("Moderation endpoint failed, allowing message: {}", e);
+                    return Verdict::Allow;
+                }
+            };
+            let reason = response.reason.unwrap_or_else(|| "That message isn't allowed here".to_string());
+            match response.action {
+                EndpointAction::Allow => Verdict::Allow,
+                EndpointAction::Mask => response.message.map(Verdict::Mask).unwrap_or(Verdict::Allow),
+                EndpointAction::Drop => Verdict::Drop { reason },
+                EndpointAction::Mute => Verdict::Mute { reason },
+            }
+        })
+    }
+}
+
+/// Chat moderators applied in order; a mask carries on to the next stage, drop and mute stop
+pub struct ModerationPipeline {
+    stages: Vec<Box<dyn ChatModerator>>,
+    mute_duration: Duration,
+}
+
+impl ModerationPipeline {
+    pub fn from_settings(settings: &ModerationSettings) -> Self {
+        let mut stages: Vec<Box<dyn ChatModerator>> = Vec::new();
+        if let Some(words) = WordListModerator::new(&settings.blocked_words, settings.blocked_words_action) {
+            stages.push(Box::new(words));
+        }
+        // Patterns were validated when the config was loaded
+        let rules: Vec<(Regex, FilterAction)> = settings
+            .rules
+            .iter()
+            .filter_map(|rule| Regex::new(&rule.pattern).ok().map(|pattern| (pattern, rule.action)))
+            .collect();
+        if !rules.is_empty() {
+            stages.push(Box::new(RegexModerator { rules }));
+        }
+        if let Some(endpoint) = settings.endpoint.clone() {
+            if let Some(http) = HttpModerator::new(endpoint, Duration::from_millis(settings.endpoint_timeout_ms)) {
+                stages.push(Box::new(http));
+            }
+        }
+        Self {
+            stages,
+            mute_duration: Duration::from_secs(settings.mute_secs),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    pub fn mute_duration(&self) -> Duration {
+        self.mute_duration
+    }
+
+    pub async fn review(&self, message: String, sender: &str, room_id: &str) -> Verdict {
+        let mut current = message;
+        let mut masked = false;
+        for stage in &self.stages {
+            let chat = ChatReview { message: &current, sender, room_id };
+            match stage.review(&chat).await {
+                Verdict::Allow => {}
+                Verdict::Mask(text) => {
+                    current = text;
+                    masked = true;
+                }
+                verdict => {
+                    tracing::info!("Chat from {} stopped by {} filter", sender, stage.name());
+                    return verdict;
+                }
+            }
+        }
+        if masked { Verdict::Mask(current) } else { Verdict::Allow }
+    }
+}
+
+static PIPELINE: LazyLock<ModerationPipeline> = LazyLock::new(|| ModerationPipeline::from_settings(&crate::config::get().moderation));
+
+/// The pipeline built from the `[moderation]` config
+pub fn pipeline() -> &'static ModerationPipeline {
+    &PIPELINE
+}
@@ -0,0 +1,90 @@
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+
+use super::handler::Position;
+
+/// Distance walked between footstep triggers
+const STRIDE_LENGTH: f32 = 1.2;
+/// Minimum gap between trail points for a single player
+const TRAIL_INTERVAL: Duration = Duration::from_millis(250);
+/// Speeds below this (units/sec) are treated as standing still
+const MIN_MOVING_SPEED: f32 = 0.5;
+/// Anything faster is a teleport/respawn, not walking
+const MAX_WALKING_SPEED: f32 = 50.0;
+
+/// Per-room toggles for derived movement events
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct MovementEffects {
+    pub footsteps: bool,
+    pub trails: bool,
+}
+
+/// Events derived from a player's movement
+pub enum MotionEvent {
+    Footstep { speed: f32 },
+    TrailPoint,
+}
+
+/// Tracks a single player's velocity so events are computed once on the server
+pub struct MotionTracker {
+    last_position: Option<Position>,
+    last_update: Instant,
+    stride_distance: f32,
+    last_trail_at: Instant,
+}
+
+impl Default for MotionTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MotionTracker {
+    pub fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            last_position: None,
+            last_update: now,
+            stride_distance: 0.0,
+            last_trail_at: now,
+        }
+    }
+
+    /// Feed a new position, returns any footstep/trail events it produced
+    pub fn update(&mut self, position: &Position, effects: MovementEffects) -> Vec<MotionEvent> {
+        let now = Instant::now();
+        let dt = now.duration_since(self.last_update).as_secs_f32();
+        self.last_update = now;
+
+        let Some(last) = self.last_position.replace(position.clone()) else {
+            return Vec::new();
+        };
+        if dt <= 0.0 {
+            return Vec::new();
+        }
+
+        let dx = position.x - last.x;
+        let dz = position.z - last.z;
+        let distance = (dx * dx + dz * dz).sqrt();
+        let speed = distance / dt;
+        if !(MIN_MOVING_SPEED..=MAX_WALKING_SPEED).contains(&speed) {
+            self.stride_distance = 0.0;
+            return Vec::new();
+        }
+
+        let mut events = Vec::new();
+        if effects.footsteps {
+            self.stride_distance += distance;
+            if self.stride_distance >= STRIDE_LENGTH {
+                self.stride_distance %= STRIDE_LENGTH;
+                events.push(MotionEvent::Footstep { speed });
+            }
+        }
+        if effects.trails && now.duration_since(self.last_trail_at) >= TRAIL_INTERVAL {
+            self.last_trail_at = now;
+            events.push(MotionEvent::TrailPoint);
+        }
+        events
+    }
+}
@@ -0,0 +1,135 @@
+use std::time::Instant;
+
+use super::handler::Position;
+
+/// Fastest a player may travel; the client walks at 5 units/sec
+const MAX_SPEED: f32 = 8.0;
+/// Slack on top of the banked distance for updates that arrive bunched up after network jitter
+const JITTER_ALLOWANCE: f32 = 1.5;
+/// Most distance a player can bank: half a second at full speed plus the jitter slack, so standing
+/// still doesn't save up a teleport
+const BUDGET_CAPACITY: f32 = MAX_SPEED * 0.5 + JITTER_ALLOWANCE;
+
+/// Outcome of checking a `PlayerMove` against the player's last accepted position
+#[derive(Debug, Clone, PartialEq)]
+pub enum MoveCheck {
+    Accept(Position),
+    /// Too far for the time elapsed: cut short along the same direction
+    Clamped(Position),
+    /// Not a usable position (NaN or infinite): the player stays where they were
+    Rejected,
+}
+
+fn is_finite(position: &Position) -> bool {
+    position.x.is_finite() && position.y.is_finite() && position.z.is_finite()
+}
+
+/// Caps how far a single player can move over time: a distance budget that refills at `MAX_SPEED`
+/// and that every move spends from, however many messages it's split into
+pub struct MovementValidator {
+    /// Distance the player may still cover right now
+    budget: f32,
+    last_refill: Option<Instant>,
+}
+
+impl Default for MovementValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MovementValidator {
+    pub fn new() -> Self {
+        Self {
+            budget: BUDGET_CAPACITY,
+            last_refill: None,
+        }
+    }
+
+    pub fn check(&mut self, from: &Position, to: Position) -> MoveCheck {
+        self.check_at(from, to, Instant::now())
+    }
+
+    fn check_at(&mut self, from: &Position, to: Position, now: Instant) -> MoveCheck {
+        if !is_finite(&to) {
+            return MoveCheck::Rejected;
+        }
+        if let Some(last) = self.last_refill {
+            let refill = MAX_SPEED * now.saturating_duration_since(last).as_secs_f32();
+            self.budget = (self.budget + refill).min(BUDGET_CAPACITY);
+        }
+        self.last_refill = Some(now);
+
+        let (dx, dy, dz) = (to.x - from.x, to.y - from.y, to.z - from.z);
+        let distance = (dx * dx + dy * dy + dz * dz).sqrt();
+        if distance <= self.budget {
+            self.budget -= distance;
+            return MoveCheck::Accept(to);
+        }
+        let scale = self.budget / distance;
+        self.budget = 0.0;
+        MoveCheck::Clamped(Position {
+            x: from.x + dx * scale,
+            y: from.y + dy * scale,
+            z: from.z + dz * scale,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    /// Send `count` moves of `step` units along x, `interval` apart, returning the final position
+    fn walk(validator: &mut MovementValidator, count: u32, step: f32, interval: Duration) -> Position {
+        let start = Instant::now();
+        let mut position = Position::default();
+        for i in 0..count {
+            let to = Position { x: position.x + step, ..position.clone() };
+            position = match validator.check_at(&position, to, start + interval * i) {
+                MoveCheck::Accept(to) | MoveCheck::Clamped(to) => to,
+                MoveCheck::Rejected => position,
+            };
+        }
+        position
+    }
+
+    #[test]
+    fn rapid_moves_cannot_outrun_max_speed() {
+        // 60 messages a second, each asking for the old per-move allowance
+        let mut validator = MovementValidator::new();
+        let position = walk(&mut validator, 60, JITTER_ALLOWANCE, Duration::from_millis(1000 / 60));
+        let one_second = MAX_SPEED + BUDGET_CAPACITY;
+        assert!(position.x <= one_second, "travelled {} units in a second", position.x);
+    }
+
+    #[test]
+    fn walking_speed_is_never_clamped() {
+        let mut validator = MovementValidator::new();
+        let start = Instant::now();
+        let mut from = Position::default();
+        for i in 0..600 {
+            let to = Position { x: from.x + 5.0 / 60.0, ..from.clone() };
+            let now = start + Duration::from_millis(1000 / 60) * i;
+            assert_eq!(validator.check_at(&from, to.clone(), now), MoveCheck::Accept(to.clone()));
+            from = to;
+        }
+    }
+
+    #[test]
+    fn bunched_updates_within_the_budget_are_accepted() {
+        let mut validator = MovementValidator::new();
+        // Half a second of walking delivered all at once after a stall
+        let position = walk(&mut validator, 30, 5.0 / 60.0, Duration::ZERO);
+        assert!((position.x - 2.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn non_finite_positions_are_rejected() {
+        let mut validator = MovementValidator::new();
+        let to = Position { x: f32::NAN, y: 0.0, z: 0.0 };
+        assert_eq!(validator.check(&Position::default(), to), MoveCheck::Rejected);
+    }
+}
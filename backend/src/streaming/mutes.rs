@@ -0,0 +1,64 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A player's stable identity for moderation: their verified profile, else the address they joined
+/// from, so reconnecting under a new player ID doesn't shake a mute off
+pub fn moderation_identity(verified_profile_id: Option<&str>, client_ip: Option<std::net::IpAddr>) -> Option<String> {
+    match (verified_profile_id, client_ip) {
+        (Some(profile_id), _) => Some(format!("profile:{}", profile_id)),
+        (None, Some(ip)) => Some(format!("ip:{}", ip)),
+        (None, None) => None,
+    }
+}
+
+/// Moderator mutes by moderation identity, owned by `RoomOwner` and shared with every room so they
+/// follow a player through reconnects and `SwitchRoom`
+#[derive(Default)]
+pub struct MuteRegistry {
+    /// identity -> when their chat mute ends
+    chat: Mutex<HashMap<String, Instant>>,
+    /// identity -> when a moderator's mute on their publishers ends
+    media: Mutex<HashMap<String, Instant>>,
+    /// Identities whose mics are cut off until a moderator lifts it, see `ForceMute`
+    force_muted: Mutex<HashSet<String>>,
+}
+
+fn remaining(mutes: &Mutex<HashMap<String, Instant>>, identity: &str) -> Option<Duration> {
+    let mut mutes = mutes.lock().unwrap();
+    let now = Instant::now();
+    mutes.retain(|_, until| *until > now);
+    mutes.get(identity).map(|until| *until - now)
+}
+
+impl MuteRegistry {
+    pub fn mute_chat(&self, identity: &str, duration: Duration) {
+        self.chat.lock().unwrap().insert(identity.to_string(), Instant::now() + duration);
+    }
+
+    pub fn mute_media(&self, identity: &str, duration: Duration) {
+        self.media.lock().unwrap().insert(identity.to_string(), Instant::now() + duration);
+    }
+
+    pub fn chat_muted_for(&self, identity: &str) -> Option<Duration> {
+        remaining(&self.chat, identity)
+    }
+
+    pub fn media_muted_for(&self, identity: &str) -> Option<Duration> {
+        remaining(&self.media, identity)
+    }
+
+    /// Returns false if the identity was already in that state
+    pub fn set_force_muted(&self, identity: &str, muted: bool) -> bool {
+        let mut force_muted = self.force_muted.lock().unwrap();
+        if muted {
+            force_muted.insert(identity.to_string())
+        } else {
+            force_muted.remove(identity)
+        }
+    }
+
+    pub fn is_force_muted(&self, identity: &str) -> bool {
+        self.force_muted.lock().unwrap().contains(identity)
+    }
+}
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+/// Encoding for high-frequency game-state messages, chosen with the `binary` capability in `Hello`
+/// (or `?protocol=msgpack` from clients without the handshake); signaling and everything else stays
+/// JSON text frames either way
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WireProtocol {
+    #[default]
+    Json,
+    /// MessagePack (named fields, same shape as the JSON) in binary frames
+    Msgpack,
+}
+
+/// What a session actually uses, reported in `RoomState` and `HelloAck` so the client can fall back on the rest
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionFeatures {
+    pub binary_protocol: bool,
+    pub simulcast: bool,
+    /// The client encrypts its media end to end: its publishers are marked encrypted, only other E2EE
+    /// sessions may subscribe to them, and it may exchange keys with `E2eeKey`
+    pub e2ee: bool,
+    /// The server subscribes the client to every publisher and sends the offers itself, so the
+    /// client only answers them
+    pub auto_subscribe: bool,
+}
+
+impl SessionFeatures {
+    /// Until a client says `Hello` it keeps exactly what frontends from before the handshake had
+    pub fn legacy(protocol: WireProtocol) -> Self {
+        Self {
+            binary_protocol: protocol == WireProtocol::Msgpack,
+            simulcast: true,
+            e2ee: false,
+            auto_subscribe: false,
+        }
+    }
+
+    /// Features for the capabilities a client declared in `Hello` (`binary`, `simulcast`, `e2ee`,
+    /// `autosubscribe`); anything it leaves out is off
+    pub fn negotiate(capabilities: &[String]) -> Self {
+        let declared = |name: &str| capabilities.iter().any(|capability| capability.trim().eq_ignore_ascii_case(name));
+        Self {
+            binary_protocol: declared("binary"),
+            simulcast: declared("simulcast"),
+            e2ee: declared("e2ee"),
+            auto_subscribe: declared("autosubscribe"),
+        }
+    }
+
+    pub fn protocol(&self) -> WireProtocol {
+        if self.binary_protocol { WireProtocol::Msgpack } else { WireProtocol::Json }
+    }
+}
+
+impl Default for SessionFeatures {
+    fn default() -> Self {
+        Self::legacy(WireProtocol::default())
+    }
+}
@@ -0,0 +1,253 @@
+// Property tests feeding hostile client traffic through a real session: random `ReceivedMessage`
+// shapes, malformed JSON and junk binary frames. Every run must finish without a panic or hang,
+// leave no publishers or players behind, and keep room state and outbound traffic bounded.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Once;
+use std::time::Duration;
+use actix_web::error::PayloadError;
+use actix_web::web::{Bytes, Data};
+use actix_web_actors::ws;
+use futures_util::StreamExt;
+use proptest::prelude::*;
+use serde_json::{json, Map, Value};
+use tokio::sync::Mutex;
+
+use super::bandwidth::BandwidthProfile;
+use super::chat::MAX_PINNED_MESSAGES;
+use super::codecs::media_config;
+use super::countdown::MAX_ACTIVE_COUNTDOWNS;
+use super::handler::{FacialFeatures, PlayerData, StreamingSession};
+use super::room::RoomOwner;
+use super::turn_server::IceServerCache;
+
+const FUZZ_ROOM_ID: &str = "fuzz-room";
+/// A hung session counts as a failure
+const SESSION_TIMEOUT: Duration = Duration::from_secs(20);
+/// Time for the spawned cleanup in `stopped` to close publishers
+const CLEANUP_GRACE: Duration = Duration::from_millis(300);
+/// Outbound bytes allowed per inbound frame, on top of the join burst
+const MAX_OUTPUT_PER_FRAME: usize = 16 * 1024;
+const MAX_JOIN_OUTPUT: usize = 64 * 1024;
+
+/// Every action the server understands, plus names it must ignore
+const ACTIONS: &[&str] = &[
+    "Ping", "TimeSync", "Hello", "E2eeKey", "PublisherInit", "SubscriberInit", "PublisherIce", "SubscriberIce", "Offer",
+    "Subscribe", "SubscribeMany", "Answer", "Publish", "StopPublish", "SetMuted", "StopSubscribe",
+    "PauseSubscriber", "ResumeSubscriber", "SelectLayer", "RequestKeyFrame", "ChatMessage",
+    "Reaction", "StartTyping", "StopTyping", "EditMessage", "DeleteMessage", "Kick", "MutePlayer", "ForceMute",
+    "LiftForceMute", "DirectMessage", "PlayerMove", "PlayAnimation", "GetPublishers", "PlayCutscene",
+    "SetMovementEffects", "LinkRoom", "SetPublisherRelayed", "RelaySubscribe",
+    "SetBandwidthProfile", "SetSlowMode", "LockRoom", "UnlockRoom", "StartCountdown", "CancelCountdown",
+    "SetRoomPassword", "Authenticate", "SwitchRoom", "SetTimeLimits", "RequestTransferCode", "SetAccessibility",
+    "SetTextToSpeech", "PinMessage", "UnpinMessage", "FetchChatHistory", "Pong", "playerMove",
+    "", "DropTables",
+];
+
+/// Field names used across `ReceivedMessage`, so random payloads often deserialize
+const FIELDS: &[&str] = &[
+    "publisherId", "publisherIds", "subscriberId", "rid", "fractionLost", "jitterMs", "message", "position",
+    "rotation", "isMoving", "animation", "cutsceneId", "footsteps", "trails", "sourceRoomId", "relayed",
+    "profile", "intervalSecs", "seconds", "label", "countdownId", "enabled", "pinId", "sdp",
+    "candidate", "voice", "dailyMinutes", "allowedHours", "utcOffsetMinutes", "pin", "profileId", "replyTo",
+    "before", "limit", "toPlayerId", "messageId", "emoji", "playerId", "password", "roomId", "clientTime",
+    "source", "muted", "capabilities", "key",
+];
+
+static PANICS: AtomicUsize = AtomicUsize::new(0);
+static PANIC_HOOK: Once = Once::new();
+
+/// Count panics anywhere in the process, including inside spawned actor futures that never surface
+fn install_panic_counter() {
+    PANIC_HOOK.call_once(|| {
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            PANICS.fetch_add(1, Ordering::SeqCst);
+            default_hook(info);
+        }));
+    });
+}
+
+#[derive(Debug, Clone)]
+enum ClientFrame {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+fn leaf_value() -> impl Strategy<Value = Value> {
+    prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(|n| json!(n)),
+        any::<f64>().prop_map(|n| json!(n)),
+        Just(json!(u64::MAX)),
+        Just(json!(-1)),
+        ".{0,40}".prop_map(Value::String),
+        "(q|h|f|low|high|[a-z]{1,8})".prop_map(Value::String),
+        (any::<f32>(), any::<f32>(), any::<f32>()).prop_map(|(x, y, z)| json!({ "x": x, "y": y, "z": z })),
+        Just(json!({ "type": "offer", "sdp": "v=0\r\n" })),
+        Just(json!({ "candidate": "candidate:0 1 UDP 1 0.0.0.0 9 typ host", "sdpMid": "0" })),
+        Just(Value::String("x".repeat(20_000))),
+    ]
+}
+
+fn json_value() -> impl Strategy<Value = Value> {
+    leaf_value().prop_recursive(3, 24, 6, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..6).prop_map(Value::Array),
+            prop::collection::hash_map("[a-zA-Z]{1,10}", inner, 0..6).prop_map(|map| Value::Object(map.into_iter().collect())),
+        ]
+    })
+}
+
+/// Plausible messages: a known action with a random subset of known fields
+fn action_message() -> impl Strategy<Value = String> {
+    (
+        prop::sample::select(ACTIONS),
+        prop::collection::vec((prop::sample::select(FIELDS), json_value()), 0..6),
+    )
+        .prop_map(|(action, fields)| {
+            let mut message: Map<String, Value> = fields.into_iter().map(|(field, value)| (field.to_string(), value)).collect();
+            message.insert("action".to_string(), Value::String(action.to_string()));
+            Value::Object(message).to_string()
+        })
+}
+
+fn client_frame() -> impl Strategy<Value = ClientFrame> {
+    prop_oneof![
+        6 => action_message().prop_map(ClientFrame::Text),
+        // Truncated, wrongly tagged and non-JSON text
+        1 => action_message().prop_flat_map(|text| {
+            let len = text.len();
+            (Just(text), 0..=len).prop_map(|(text, cut)| ClientFrame::Text(text.chars().take(cut).collect()))
+        }),
+        1 => json_value().prop_map(|value| ClientFrame::Text(value.to_string())),
+        1 => ".{0,200}".prop_map(ClientFrame::Text),
+        // MessagePack path
+        1 => prop::collection::vec(any::<u8>(), 0..512).prop_map(ClientFrame::Binary),
+        1 => action_message().prop_map(|text| {
+            let value: Value = serde_json::from_str(&text).unwrap();
+            ClientFrame::Binary(rmp_serde::to_vec_named(&value).unwrap())
+        }),
+    ]
+}
+
+/// Encode a frame the way a browser would send it (masked; a zero key leaves the payload as is)
+fn encode_client_frame(frame: &ClientFrame) -> Bytes {
+    let (opcode, payload) = match frame {
+        ClientFrame::Text(text) => (0x1, text.as_bytes()),
+        ClientFrame::Binary(bytes) => (0x2, bytes.as_slice()),
+    };
+    let mut encoded = vec![0x80 | opcode];
+    match payload.len() {
+        len if len < 126 => encoded.push(0x80 | len as u8),
+        len if len <= u16::MAX as usize => {
+            encoded.push(0x80 | 126);
+            encoded.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            encoded.push(0x80 | 127);
+            encoded.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    encoded.extend_from_slice(&[0; 4]);
+    encoded.extend_from_slice(payload);
+    Bytes::from(encoded)
+}
+
+fn fuzz_player() -> PlayerData {
+    PlayerData {
+        id: String::new(),
+        name: "fuzzer".to_string(),
+        color: "#ff00ff".to_string(),
+        activity: "fuzzing".to_string(),
+        facial_features: FacialFeatures::default(),
+        position: Default::default(),
+        rotation: 0.0,
+        is_moving: false,
+        language: "en".to_string(),
+        role: Default::default(),
+        status: Default::default(),
+        handle: None,
+        moderation_identity: None,
+    }
+}
+
+/// What was left behind after a session handled `frames` and disconnected
+struct SessionOutcome {
+    finished: bool,
+    output_bytes: usize,
+    players_left: usize,
+    publishers_left: usize,
+    pinned_messages: usize,
+    countdowns: usize,
+}
+
+/// Masked Close frame without a payload; a client leaving this way isn't held for resuming
+const CLOSE_FRAME: &[u8] = &[0x88, 0x80, 0, 0, 0, 0];
+
+/// Run one session over an in-process websocket against a real rheomesh worker
+fn run_session(frames: &[ClientFrame]) -> SessionOutcome {
+    crate::config::init().expect("default config");
+    actix::System::new().block_on(async {
+        let worker = rheomesh::worker::Worker::new(rheomesh::config::WorkerConfig::default())
+            .await
+            .expect("rheomesh worker");
+        let owner = Data::new(Mutex::new(RoomOwner::new(vec![worker], rheomesh::config::WorkerConfig::default(), IceServerCache::default())));
+        let room = owner
+            .lock()
+            .await
+            .create_new_room(FUZZ_ROOM_ID.to_string(), "Fuzz Room".to_string(), media_config(FUZZ_ROOM_ID))
+            .await
+            .expect("fuzz room");
+
+        let session = StreamingSession::new(room.clone(), owner.clone(), fuzz_player(), Vec::new(), BandwidthProfile::default()).await;
+        let mut input: Vec<Result<Bytes, PayloadError>> = frames.iter().map(|frame| Ok(encode_client_frame(frame))).collect();
+        input.push(Ok(Bytes::from_static(CLOSE_FRAME)));
+        let mut output = Box::pin(ws::WebsocketContext::create(session, futures_util::stream::iter(input)));
+
+        // The session stops once the client closes and its stream ends
+        let mut output_bytes = 0;
+        let drained = tokio::time::timeout(SESSION_TIMEOUT, async {
+            while let Some(chunk) = output.next().await {
+                output_bytes += chunk.map(|bytes| bytes.len()).unwrap_or(0);
+            }
+        })
+        .await;
+        tokio::time::sleep(CLEANUP_GRACE).await;
+
+        SessionOutcome {
+            finished: drained.is_ok(),
+            output_bytes,
+            players_left: room.player_count(),
+            publishers_left: room.get_all_publishers().len(),
+            pinned_messages: room.get_pinned_messages().len(),
+            countdowns: room.get_countdowns().len(),
+        }
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig { cases: 32, ..ProptestConfig::default() })]
+
+    #[test]
+    fn hostile_clients_cannot_break_sessions(frames in prop::collection::vec(client_frame(), 0..60)) {
+        install_panic_counter();
+        let panics_before = PANICS.load(Ordering::SeqCst);
+
+        let outcome = run_session(&frames);
+
+        prop_assert_eq!(PANICS.load(Ordering::SeqCst), panics_before, "session panicked");
+        prop_assert!(outcome.finished, "session did not stop after the client disconnected");
+        prop_assert_eq!(outcome.players_left, 0);
+        prop_assert_eq!(outcome.publishers_left, 0, "publishers leaked after disconnect");
+        prop_assert!(outcome.pinned_messages <= MAX_PINNED_MESSAGES);
+        prop_assert!(outcome.countdowns <= MAX_ACTIVE_COUNTDOWNS);
+        prop_assert!(
+            outcome.output_bytes <= MAX_JOIN_OUTPUT + frames.len() * MAX_OUTPUT_PER_FRAME,
+            "{} bytes sent for {} frames",
+            outcome.output_bytes,
+            frames.len()
+        );
+    }
+}
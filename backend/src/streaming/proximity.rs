@@ -0,0 +1,17 @@
+use std::time::Duration;
+
+use super::handler::Position;
+use super::spatial_audio::SpeakingDistance;
+
+/// How often auto-subscribed sessions re-check who is close enough to receive
+pub const PROXIMITY_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+/// Subscriptions are kept until the publisher is this much further than the cutoff, so someone
+/// pacing around the edge doesn't cause a renegotiation every step
+const HYSTERESIS: f32 = 1.25;
+
+/// Whether a player at `own` should receive a publisher owned by a player at `publisher`.
+/// Voices are silent past the room's speaking cutoff, so there's nothing to gain forwarding them
+pub fn wants_subscription(own: &Position, publisher: &Position, distance: &SpeakingDistance, subscribed: bool) -> bool {
+    let range = if subscribed { distance.cutoff * HYSTERESIS } else { distance.cutoff };
+    (own.x - publisher.x).powi(2) + (own.z - publisher.z).powi(2) <= range * range
+}
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use webrtc::rtp_transceiver::rtp_codec::RTPCodecType;
+
+use super::codecs::max_video_kbps;
+use super::handler::{SendingMessage, StreamingSession};
+use super::room::Room;
+
+/// How often publishers get a `PublishQuality` summary
+const PUBLISH_QUALITY_INTERVAL: Duration = Duration::from_secs(5);
+/// Reports older than this are from viewers who stopped watching or reporting
+const REPORT_MAX_AGE: Duration = Duration::from_secs(15);
+/// Loss every viewer sees beyond this points at the publisher's uplink
+const UPLINK_LOSS_THRESHOLD: f32 = 0.05;
+/// Bitrate hints start (and top out) at what the highest bandwidth profile aims for, or the room's cap
+const MAX_HINT_KBPS: u32 = 2500;
+/// Below this video is unwatchable anyway, so hints stop going lower
+const MIN_HINT_KBPS: u32 = 150;
+/// A viewer losing more than this means the stream is more than their link can carry
+const CONGESTED_LOSS: f32 = 0.10;
+/// Every viewer losing less than this means there's room to step back up
+const CLEAR_LOSS: f32 = 0.02;
+/// Step up slowly, since overshooting costs a round of loss before the next report
+const RECOVERY_FACTOR: f32 = 1.08;
+
+/// One viewer's reception of a publisher, from the RTCP their subscription sends back to the SFU
+#[derive(Debug, Clone, Copy)]
+pub struct ReceiverReport {
+    /// Fraction of packets lost since the last receiver report, 0.0 - 1.0
+    pub fraction_lost: f32,
+    pub jitter_ms: f32,
+    /// The viewer's REMB bandwidth estimate, if their browser sends one
+    pub remb_kbps: Option<u32>,
+    pub received_at: Instant,
+}
+
+/// Aggregated audience reception for one publisher
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishQuality {
+    pub publisher_id: String,
+    pub audience: usize,
+    pub worst_loss: f32,
+    pub best_loss: f32,
+    pub average_jitter_ms: f32,
+    /// Lowest REMB estimate among the viewers: more than this and someone can't keep up
+    pub min_remb_kbps: Option<u32>,
+    /// Every viewer is losing packets, so the problem is most likely on the publisher's side
+    pub uplink_suspect: bool,
+}
+
+fn aggregate(publisher_id: &str, reports: &HashMap<String, ReceiverReport>) -> Option<PublishQuality> {
+    if reports.is_empty() {
+        return None;
+    }
+    let losses = reports.values().map(|report| report.fraction_lost);
+    let worst_loss = losses.clone().fold(0.0, f32::max);
+    let best_loss = losses.fold(1.0, f32::min);
+    let average_jitter_ms = reports.values().map(|report| report.jitter_ms).sum::<f32>() / reports.len() as f32;
+    let min_remb_kbps = reports.values().filter_map(|report| report.remb_kbps).min();
+    Some(PublishQuality {
+        publisher_id: publisher_id.to_string(),
+        audience: reports.len(),
+        worst_loss,
+        best_loss,
+        average_jitter_ms,
+        min_remb_kbps,
+        uplink_suspect: best_loss > UPLINK_LOSS_THRESHOLD,
+    })
+}
+
+/// Loss-based rate control: back off in proportion to the worst viewer's loss, creep back up
+/// once everyone is receiving cleanly (up to `ceiling`), hold steady in between. The lowest REMB
+/// estimate caps the result, since that viewer has already said what their link can carry
+fn next_hint_kbps(current: u32, quality: &PublishQuality, ceiling: u32) -> u32 {
+    let ceiling = quality.min_remb_kbps.map_or(ceiling, |remb| remb.min(ceiling));
+    let next = if quality.worst_loss > CONGESTED_LOSS {
+        current as f32 * (1.0 - 0.5 * quality.worst_loss)
+    } else if quality.worst_loss < CLEAR_LOSS {
+        current as f32 * RECOVERY_FACTOR
+    } else {
+        current as f32
+    };
+    // Whole steps of 10 kbps, so tiny wobbles don't each become a hint
+    ((next / 10.0).round() as u32 * 10).clamp(MIN_HINT_KBPS.min(ceiling), ceiling)
+}
+
+/// Per-room loop summarizing viewers' RTCP feedback back to each publisher; ends once the room is dropped
+pub fn spawn_publish_quality_loop(room: &Arc<Room<StreamingSession>>) {
+    let room: Weak<Room<StreamingSession>> = Arc::downgrade(room);
+    actix::spawn(async move {
+        let mut interval = tokio::time::interval(PUBLISH_QUALITY_INTERVAL);
+        // publisher_id -> last `BitrateHint` sent
+        let mut hints: HashMap<String, u32> = HashMap::new();
+        loop {
+            interval.tick().await;
+            let Some(room) = room.upgrade() else {
+                break;
+            };
+
+            let reports = room.take_receiver_reports(REPORT_MAX_AGE);
+            let publishers = room.get_all_publishers();
+            // The room's cap also bounds recovery
+            let ceiling = max_video_kbps(&room.id).map_or(MAX_HINT_KBPS, |kbps| kbps.min(MAX_HINT_KBPS));
+            hints.retain(|publisher_id, _| publishers.iter().any(|(id, _)| id == publisher_id));
+            if reports.is_empty() {
+                continue;
+            }
+            let addrs: HashMap<String, _> = room
+                .get_players_with_addrs()
+                .into_iter()
+                .map(|(addr, player)| (player.id, addr))
+                .collect();
+            for (publisher_id, owner_id) in publishers {
+                let (Some(addr), Some(reports)) = (addrs.get(&owner_id), reports.get(&publisher_id)) else {
+                    continue;
+                };
+                let Some(quality) = aggregate(&publisher_id, reports) else {
+                    continue;
+                };
+                // Voice is a few dozen kbps and Opus adapts on its own; hints are for video
+                if room.get_publisher_metadata(&publisher_id).kind == Some(RTPCodecType::Video) {
+                    let current = hints.get(&publisher_id).copied().unwrap_or(ceiling);
+                    let kbps = next_hint_kbps(current, &quality, ceiling);
+                    if kbps != current {
+                        addr.do_send(SendingMessage::BitrateHint { publisher_id: publisher_id.clone(), kbps });
+                    }
+                    hints.insert(publisher_id, kbps);
+                }
+                addr.do_send(SendingMessage::PublishQuality { quality });
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quality(worst_loss: f32, min_remb_kbps: Option<u32>) -> PublishQuality {
+        PublishQuality {
+            publisher_id: "publisher".to_string(),
+            audience: 2,
+            worst_loss,
+            best_loss: 0.0,
+            average_jitter_ms: 0.0,
+            min_remb_kbps,
+            uplink_suspect: false,
+        }
+    }
+
+    #[test]
+    fn congestion_backs_off_with_loss() {
+        assert_eq!(next_hint_kbps(1000, &quality(0.2, None), MAX_HINT_KBPS), 900);
+        assert_eq!(next_hint_kbps(1000, &quality(0.6, None), MAX_HINT_KBPS), 700);
+    }
+
+    #[test]
+    fn clean_reception_recovers_up_to_the_ceiling() {
+        assert_eq!(next_hint_kbps(1000, &quality(0.0, None), MAX_HINT_KBPS), 1080);
+        assert_eq!(next_hint_kbps(2450, &quality(0.0, None), MAX_HINT_KBPS), MAX_HINT_KBPS);
+        assert_eq!(next_hint_kbps(1000, &quality(0.0, None), 300), 300);
+    }
+
+    #[test]
+    fn moderate_loss_holds_steady() {
+        assert_eq!(next_hint_kbps(1000, &quality(0.05, None), MAX_HINT_KBPS), 1000);
+    }
+
+    #[test]
+    fn remb_caps_the_hint() {
+        assert_eq!(next_hint_kbps(1000, &quality(0.0, Some(600)), MAX_HINT_KBPS), 600);
+        assert_eq!(next_hint_kbps(1000, &quality(0.0, Some(5000)), MAX_HINT_KBPS), 1080);
+    }
+
+    #[test]
+    fn hints_never_drop_below_the_floor() {
+        assert_eq!(next_hint_kbps(160, &quality(0.9, None), MAX_HINT_KBPS), MIN_HINT_KBPS);
+        // Unless the room's own cap is lower still
+        assert_eq!(next_hint_kbps(100, &quality(0.9, None), 100), 100);
+    }
+}
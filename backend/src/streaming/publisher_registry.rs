@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::time::Duration;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+
+/// Registrations expire this long after their session's last renewal, so a crashed process's
+/// entries clean themselves up
+pub const PUBLISHER_LEASE: Duration = Duration::from_secs(90);
+/// How often live sessions renew their lease
+pub const LEASE_RENEW_INTERVAL: Duration = Duration::from_secs(30);
+/// Longest client-chosen session key accepted
+const MAX_SESSION_KEY_CHARS: usize = 64;
+
+/// Identifies this process; registrations from any other node ID belong to a dead or different process
+static NODE_ID: LazyLock<String> = LazyLock::new(|| uuid::Uuid::new_v4().to_string());
+
+/// Where a publisher lived when it was registered
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisteredPublisher {
+    pub publisher_id: String,
+    pub room_id: String,
+    pub player_id: String,
+    node_id: String,
+}
+
+/// Publisher <-> player <-> room mappings in Redis (`REDIS_URL`), keyed by the client's session key,
+/// so a client reconnecting after a crash learns which publishers it has to set up again.
+/// Without `REDIS_URL` every operation is a no-op
+#[derive(Clone, Default)]
+pub struct PublisherRegistry {
+    redis: Option<ConnectionManager>,
+}
+
+fn registry_key(session_key: &str) -> String {
+    format!("webhangin:publishers:{}", session_key)
+}
+
+/// Session keys are client-generated, so only accept short IDs that are safe inside a Redis key
+pub fn is_valid_session_key(session_key: &str) -> bool {
+    !session_key.is_empty()
+        && session_key.len() <= MAX_SESSION_KEY_CHARS
+        && session_key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+impl PublisherRegistry {
+    pub async fn connect() -> Self {
+        let Ok(url) = std::env::var("REDIS_URL") else {
+            return Self::default();
+        };
+        let manager = match redis::Client::open(url) {
+            Ok(client) => ConnectionManager::new(client).await,
+            Err(e) => Err(e),
+        };
+        match manager {
+            Ok(manager) => {
+                println!("🗂️  Publisher registry in Redis (node {})", &NODE_ID[..8]);
+                Self { redis: Some(manager) }
+            }
+            Err(e) => {
+                tracing::error!("Failed to connect to Redis, publisher registry disabled: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.redis.is_some()
+    }
+
+    pub async fn register(&self, session_key: &str, publisher_id: &str, room_id: &str, player_id: &str) {
+        let Some(mut redis) = self.redis.clone() else {
+            return;
+        };
+        let entry = RegisteredPublisher {
+            publisher_id: publisher_id.to_string(),
+            room_id: room_id.to_string(),
+            player_id: player_id.to_string(),
+            node_id: NODE_ID.clone(),
+        };
+        let Ok(json) = serde_json::to_string(&entry) else {
+            return;
+        };
+        let key = registry_key(session_key);
+        let result: redis::RedisResult<()> = redis::pipe()
+            .hset(&key, publisher_id, json)
+            .expire(&key, PUBLISHER_LEASE.as_secs() as i64)
+            .query_async(&mut redis)
+            .await;
+        if let Err(e) = result {
+            tracing::warn!("Failed to register publisher {}: {}", publisher_id, e);
+        }
+    }
+
+    pub async fn unregister(&self, session_key: &str, publisher_id: &str) {
+        let Some(mut redis) = self.redis.clone() else {
+            return;
+        };
+        let result: redis::RedisResult<()> = redis.hdel(registry_key(session_key), publisher_id).await;
+        if let Err(e) = result {
+            tracing::warn!("Failed to unregister publisher {}: {}", publisher_id, e);
+        }
+    }
+
+    /// Extend the lease on a live session's registrations
+    pub async fn renew(&self, session_key: &str) {
+        let Some(mut redis) = self.redis.clone() else {
+            return;
+        };
+        let result: redis::RedisResult<()> = redis.expire(registry_key(session_key), PUBLISHER_LEASE.as_secs() as i64).await;
+        if let Err(e) = result {
+            tracing::warn!("Failed to renew publisher lease: {}", e);
+        }
+    }
+
+    /// Drop every registration for a session that left cleanly
+    pub async fn clear(&self, session_key: &str) {
+        let Some(mut redis) = self.redis.clone() else {
+            return;
+        };
+        let result: redis::RedisResult<()> = redis.del(registry_key(session_key)).await;
+        if let Err(e) = result {
+            tracing::warn!("Failed to clear publisher registrations: {}", e);
+        }
+    }
+
+    /// Remove and return registrations a previous process left behind for this session key
+    pub async fn take_stale(&self, session_key: &str) -> Vec<RegisteredPublisher> {
+        let Some(mut redis) = self.redis.clone() else {
+            return Vec::new();
+        };
+        let key = registry_key(session_key);
+        let entries: HashMap<String, String> = match redis.hgetall(&key).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::warn!("Failed to read publisher registrations: {}", e);
+                return Vec::new();
+            }
+        };
+        let stale: Vec<RegisteredPublisher> = entries
+            .values()
+            .filter_map(|json| serde_json::from_str::<RegisteredPublisher>(json).ok())
+            .filter(|entry| entry.node_id != *NODE_ID)
+            .collect();
+        if !stale.is_empty() {
+            let publisher_ids: Vec<&str> = stale.iter().map(|entry| entry.publisher_id.as_str()).collect();
+            let result: redis::RedisResult<()> = redis.hdel(&key, publisher_ids).await;
+            if let Err(e) = result {
+                tracing::warn!("Failed to remove stale publisher registrations: {}", e);
+            }
+        }
+        stale
+    }
+}
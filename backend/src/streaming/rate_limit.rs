@@ -0,0 +1,134 @@
+use std::time::{Duration, Instant};
+use serde::Serialize;
+
+/// Window dropped messages are counted over before a connection is closed
+const ABUSE_WINDOW: Duration = Duration::from_secs(10);
+/// Dropped chat/reaction/signaling messages within the window that get a connection closed
+const MAX_DROPPED_PER_WINDOW: u32 = 50;
+/// At most one warning per this interval, so the warnings themselves don't flood the client
+const WARNING_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Which budget an incoming message is charged to
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageClass {
+    Movement,
+    Chat,
+    /// Emoji reactions, budgeted apart from chat so they can't crowd it out
+    Reaction,
+    Signaling,
+}
+
+impl MessageClass {
+    /// (burst, refill per second)
+    fn budget(self) -> (f64, f64) {
+        match self {
+            // Clients send a move every frame; anything past this is superseded by the next move anyway
+            MessageClass::Movement => (120.0, 60.0),
+            MessageClass::Chat => (5.0, 2.0),
+            MessageClass::Reaction => (5.0, 1.0),
+            // ICE candidates arrive in bursts during negotiation
+            MessageClass::Signaling => (200.0, 50.0),
+        }
+    }
+}
+
+struct TokenBucket {
+    tokens: f64,
+    capacity: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new((capacity, refill_per_sec): (f64, f64)) -> Self {
+        Self {
+            tokens: capacity,
+            capacity,
+            refill_per_sec,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn try_take(&mut self) -> bool {
+        let now = Instant::now();
+        let refill = now.duration_since(self.last_refill).as_secs_f64() * self.refill_per_sec;
+        self.tokens = (self.tokens + refill).min(self.capacity);
+        self.last_refill = now;
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+}
+
+/// What to do with an incoming message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateDecision {
+    Allow,
+    /// Over budget: drop the message
+    Drop,
+    /// Over budget: drop it and tell the client to slow down
+    Warn,
+    /// Kept flooding after warnings: close the connection
+    Close,
+}
+
+/// Per-connection budgets, so one client can't flood the room broadcast path
+pub struct MessageRateLimiter {
+    movement: TokenBucket,
+    chat: TokenBucket,
+    reaction: TokenBucket,
+    signaling: TokenBucket,
+    window_start: Instant,
+    dropped_in_window: u32,
+    last_warning: Option<Instant>,
+}
+
+impl Default for MessageRateLimiter {
+    fn default() -> Self {
+        Self {
+            movement: TokenBucket::new(MessageClass::Movement.budget()),
+            chat: TokenBucket::new(MessageClass::Chat.budget()),
+            reaction: TokenBucket::new(MessageClass::Reaction.budget()),
+            signaling: TokenBucket::new(MessageClass::Signaling.budget()),
+            window_start: Instant::now(),
+            dropped_in_window: 0,
+            last_warning: None,
+        }
+    }
+}
+
+impl MessageRateLimiter {
+    pub fn check(&mut self, class: MessageClass) -> RateDecision {
+        let bucket = match class {
+            MessageClass::Movement => &mut self.movement,
+            MessageClass::Chat => &mut self.chat,
+            MessageClass::Reaction => &mut self.reaction,
+            MessageClass::Signaling => &mut self.signaling,
+        };
+        if bucket.try_take() {
+            return RateDecision::Allow;
+        }
+        // Dropping excess moves costs the client nothing, so that alone isn't abuse
+        if class == MessageClass::Movement {
+            return RateDecision::Drop;
+        }
+
+        let now = Instant::now();
+        if now.duration_since(self.window_start) >= ABUSE_WINDOW {
+            self.window_start = now;
+            self.dropped_in_window = 0;
+        }
+        self.dropped_in_window += 1;
+        if self.dropped_in_window > MAX_DROPPED_PER_WINDOW {
+            return RateDecision::Close;
+        }
+        if self.last_warning.is_some_and(|last| now.duration_since(last) < WARNING_INTERVAL) {
+            return RateDecision::Drop;
+        }
+        self.last_warning = Some(now);
+        RateDecision::Warn
+    }
+}
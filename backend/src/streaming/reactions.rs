@@ -0,0 +1,6 @@
+/// Emoji players can float above their avatar; anything else is ignored
+pub const REACTION_EMOJI: &[&str] = &["👍", "❤️", "😂", "😮", "😢", "🎉", "👏", "🔥"];
+
+pub fn is_allowed_reaction(emoji: &str) -> bool {
+    REACTION_EMOJI.contains(&emoji)
+}
@@ -0,0 +1,72 @@
+use std::sync::LazyLock;
+use std::time::Duration;
+use actix_web::web::Data;
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use crate::config;
+use crate::signed_token;
+
+use super::handler::{SendingMessage, StreamingSession};
+use super::room::RoomOwner;
+
+/// How long a reconnect token brings a player back to their room
+const RECONNECT_TOKEN_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Signing key for reconnect tokens; set `auth.reconnect_secret` so tokens survive restarts
+static RECONNECT_SECRET: LazyLock<String> =
+    LazyLock::new(|| signed_token::secret_or_random(config::get().auth.reconnect_secret.as_deref(), "auth.reconnect_secret"));
+
+/// Backoff clients should use when their connection drops, so a blip doesn't turn into a stampede
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconnectPolicy {
+    pub initial_delay_ms: u64,
+    pub max_delay_ms: u64,
+    /// Fraction of each delay to randomize, 0.0 - 1.0
+    pub jitter: f32,
+}
+
+impl ReconnectPolicy {
+    /// Unexpected drops: retry quickly
+    pub const DEFAULT: Self = Self {
+        initial_delay_ms: 1000,
+        max_delay_ms: 30_000,
+        jitter: 0.5,
+    };
+    /// Server restarts: every client reconnects at once, so spread them out more
+    pub const AFTER_SHUTDOWN: Self = Self {
+        initial_delay_ms: 3000,
+        max_delay_ms: 60_000,
+        jitter: 1.0,
+    };
+}
+
+/// Token that routes the holder back to `room_id` on their next join
+pub fn issue_reconnect_token(room_id: &str) -> String {
+    let expires_at = chrono::Utc::now().timestamp() + RECONNECT_TOKEN_TTL.as_secs() as i64;
+    signed_token::issue(&RECONNECT_SECRET, &format!("{}.{}", room_id, expires_at))
+}
+
+/// Room ID from a valid, unexpired reconnect token
+pub fn verify_reconnect_token(token: &str) -> Option<String> {
+    let payload = signed_token::verify(&RECONNECT_SECRET, token)?;
+    let (room_id, expires_at) = payload.rsplit_once('.')?;
+    if expires_at.parse::<i64>().ok()? < chrono::Utc::now().timestamp() {
+        return None;
+    }
+    Some(room_id.to_string())
+}
+
+/// Tell every connected client the server is going away and how to come back
+pub async fn broadcast_shutdown(owner: &Data<Mutex<RoomOwner<StreamingSession>>>) {
+    let rooms = owner.lock().await.list_rooms();
+    for room in rooms {
+        for addr in room.get_all_addrs() {
+            addr.do_send(SendingMessage::ServerShutdown {
+                reconnect: ReconnectPolicy::AFTER_SHUTDOWN,
+                reconnect_token: issue_reconnect_token(&room.id),
+            });
+        }
+    }
+}
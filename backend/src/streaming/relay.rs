@@ -0,0 +1,92 @@
+use actix_web::web::Data;
+use tokio::sync::Mutex;
+
+use super::handler::{PublisherInfo, SendingMessage, StreamingSession};
+use super::room::{Room, RoomOwner};
+
+/// Relayed publishers of a stage room with their owners and metadata
+fn relayed_publisher_infos(stage: &Room<StreamingSession>) -> Vec<PublisherInfo> {
+    stage
+        .get_relayed_publishers()
+        .into_iter()
+        .map(|(publisher_id, player_id)| PublisherInfo {
+            metadata: stage.get_publisher_metadata(&publisher_id),
+            publisher_id,
+            player_id,
+        })
+        .collect()
+}
+
+/// What an audience room can watch: the stage's relayed publishers that made it onto its router
+pub fn watchable_publishers(stage: &Room<StreamingSession>, audience: &Room<StreamingSession>) -> Vec<PublisherInfo> {
+    let mut publishers = relayed_publisher_infos(stage);
+    publishers.retain(|info| audience.is_piped_publisher(&info.publisher_id));
+    publishers
+}
+
+/// Mirror a stage publisher onto an audience room's router. Viewers subscribe to it there like any
+/// other publisher, so the stage router carries one copy per audience room rather than one per viewer
+async fn pipe_publisher(stage: &Room<StreamingSession>, audience: &Room<StreamingSession>, publisher_id: &str) -> Result<(), String> {
+    if audience.is_piped_publisher(publisher_id) {
+        return Ok(());
+    }
+    let piped = stage
+        .router
+        .lock()
+        .await
+        .pipe_to_router(publisher_id.to_string(), audience.router.clone())
+        .await
+        .map_err(|e| e.to_string())?;
+    audience.add_piped_publisher(publisher_id.to_string(), piped);
+    Ok(())
+}
+
+/// Start relaying a stage publisher into every linked audience room and announce it there
+pub async fn relay_publisher(owner: &Data<Mutex<RoomOwner<StreamingSession>>>, stage: &Room<StreamingSession>, publisher_id: &str) {
+    let audiences = owner.lock().await.linked_audiences(&stage.id);
+    let player_id = stage.get_publisher_owner(publisher_id).unwrap_or_default();
+    for audience in audiences {
+        if let Err(e) = pipe_publisher(stage, &audience, publisher_id).await {
+            tracing::error!("Couldn't relay publisher {} into room {}: {}", publisher_id, audience.id, e);
+            continue;
+        }
+        for peer in audience.get_all_addrs() {
+            peer.do_send(SendingMessage::RelayPublished {
+                publisher_ids: vec![publisher_id.to_string()],
+                player_id: player_id.clone(),
+            });
+        }
+    }
+}
+
+/// Stop relaying a stage publisher, whether the host took it off the relay or it stopped publishing,
+/// and tell every linked audience it's gone
+pub async fn stop_relaying(owner: &Data<Mutex<RoomOwner<StreamingSession>>>, stage_id: &str, publisher_id: &str) {
+    let audiences = owner.lock().await.linked_audiences(stage_id);
+    for audience in audiences {
+        if let Some(piped) = audience.take_piped_publisher(publisher_id) {
+            piped.lock().await.close().await;
+        }
+        for peer in audience.get_all_addrs() {
+            peer.do_send(SendingMessage::RelayUnpublished { publisher_id: publisher_id.to_string() });
+        }
+    }
+}
+
+/// Point an audience room at a stage, or at nothing: the old stage's publishers come off its router
+/// and the new stage's relayed ones are piped in. Returns what the audience can watch now
+pub async fn link_audience(audience: &Room<StreamingSession>, stage: Option<&Room<StreamingSession>>) -> Vec<PublisherInfo> {
+    for (_, piped) in audience.take_piped_publishers() {
+        piped.lock().await.close().await;
+    }
+    audience.set_relay_source(stage.map(|stage| stage.id.clone()));
+    let Some(stage) = stage else {
+        return Vec::new();
+    };
+    for info in relayed_publisher_infos(stage) {
+        if let Err(e) = pipe_publisher(stage, audience, &info.publisher_id).await {
+            tracing::error!("Couldn't relay publisher {} into room {}: {}", info.publisher_id, audience.id, e);
+        }
+    }
+    watchable_publishers(stage, audience)
+}
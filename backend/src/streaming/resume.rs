@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use rheomesh::publish_transport::PublishTransport;
+use rheomesh::publisher::Publisher;
+use rheomesh::subscribe_transport::SubscribeTransport;
+use rheomesh::subscriber::Subscriber;
+use rheomesh::transport::Transport;
+use tokio::sync::Mutex;
+
+/// A session's WebRTC side: handed to a resumed connection, or closed when the player leaves
+pub struct SessionMedia {
+    pub publish_transport: Arc<PublishTransport>,
+    pub subscribe_transport: Arc<SubscribeTransport>,
+    pub publishers: Arc<Mutex<HashMap<String, Arc<Mutex<Publisher>>>>>,
+    pub subscribers: Arc<Mutex<HashMap<String, Arc<Mutex<Subscriber>>>>>,
+}
+
+impl SessionMedia {
+    /// Close every publisher and both transports
+    pub async fn close(self) {
+        let publishers: Vec<_> = self.publishers.lock().await.drain().map(|(_, publisher)| publisher).collect();
+        for publisher in publishers {
+            publisher.lock().await.close().await;
+        }
+        let _ = self.subscribe_transport.close().await;
+        let _ = self.publish_transport.close().await;
+    }
+}
+
+/// A player whose connection dropped without a Close frame, kept in the room for the grace window
+pub struct ParkedSession {
+    pub room_id: String,
+    pub player_id: String,
+    pub media: SessionMedia,
+    /// When the original connection joined, so resuming doesn't restart the session length limit
+    pub session_started: Instant,
+    parked_at: Instant,
+}
+
+/// How long a dropped connection can come back with its resume token (`RESUME_GRACE_SECS`, 0 = off)
+pub fn resume_grace() -> Duration {
+    Duration::from_secs(crate::config::get().server.resume_grace_secs)
+}
+
+/// New resume token; tokens are random and single-use, so they need no signature
+pub fn issue_resume_token() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+/// Dropped sessions waiting to be resumed, keyed by resume token. Owned by `RoomOwner` and shared
+/// with every room, so a closing connection can park itself without waiting on the owner's lock
+#[derive(Default)]
+pub struct ResumeRegistry {
+    parked: std::sync::Mutex<HashMap<String, ParkedSession>>,
+}
+
+impl ResumeRegistry {
+    pub fn park(&self, resume_token: String, room_id: String, player_id: String, media: SessionMedia, session_started: Instant) {
+        self.parked.lock().unwrap().insert(resume_token, ParkedSession {
+            room_id,
+            player_id,
+            media,
+            session_started,
+            parked_at: Instant::now(),
+        });
+    }
+
+    /// Claim a parked session; each token works once. Sessions past the grace window are left for
+    /// their expiry task to release
+    pub fn take(&self, resume_token: &str) -> Option<ParkedSession> {
+        let mut parked = self.parked.lock().unwrap();
+        if parked.get(resume_token)?.parked_at.elapsed() >= resume_grace() {
+            return None;
+        }
+        parked.remove(resume_token)
+    }
+
+    /// Remove a parked session once its grace window ended, unless it was resumed first
+    pub fn expire(&self, resume_token: &str) -> Option<ParkedSession> {
+        self.parked.lock().unwrap().remove(resume_token)
+    }
+}
@@ -0,0 +1,60 @@
+use std::sync::LazyLock;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::signed_token;
+
+/// How long a role token is accepted when the admin API isn't told otherwise
+pub const DEFAULT_ROLE_TOKEN_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Signing key for role tokens; set `auth.role_secret` so tokens survive restarts and work across nodes
+static ROLE_SECRET: LazyLock<String> =
+    LazyLock::new(|| signed_token::secret_or_random(config::get().auth.role_secret.as_deref(), "auth.role_secret"));
+
+/// What a player may do beyond playing
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    #[default]
+    Player,
+    /// Can kick and mute other players
+    Moderator,
+}
+
+impl Role {
+    fn as_str(self) -> &'static str {
+        match self {
+            Role::Player => "player",
+            Role::Moderator => "moderator",
+        }
+    }
+
+    fn parse(role: &str) -> Option<Self> {
+        match role {
+            "player" => Some(Role::Player),
+            "moderator" => Some(Role::Moderator),
+            _ => None,
+        }
+    }
+
+    pub fn is_moderator(self) -> bool {
+        self == Role::Moderator
+    }
+}
+
+/// Token granting `role` to whoever joins with it (`roleToken`), until it expires
+pub fn issue_role_token(role: Role, ttl: Duration) -> (String, i64) {
+    let expires_at = chrono::Utc::now().timestamp() + ttl.as_secs() as i64;
+    (signed_token::issue(&ROLE_SECRET, &format!("{}.{}", role.as_str(), expires_at)), expires_at)
+}
+
+/// Role from a valid, unexpired role token
+pub fn verify_role_token(token: &str) -> Option<Role> {
+    let payload = signed_token::verify(&ROLE_SECRET, token)?;
+    let (role, expires_at) = payload.split_once('.')?;
+    if expires_at.parse::<i64>().ok()? < chrono::Utc::now().timestamp() {
+        return None;
+    }
+    Role::parse(role)
+}
@@ -1,13 +1,46 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use actix::{Actor, Addr};
+use actix_web::web::Data;
 use tokio::sync::Mutex;
 use rheomesh::config::MediaConfig;
+use rheomesh::publisher::Publisher;
 use rheomesh::router::Router;
+use rheomesh::config::{WebRTCTransportConfig, WorkerConfig};
 use rheomesh::worker::Worker;
 use webrtc::ice_transport::ice_server::RTCIceServer;
+use super::turn_health::{probe_turn_url, turn_urls, TurnHealth, TURN_PROBE_INTERVAL};
+use super::turn_server::{fetch_ice_servers, IceServerCache};
 
-use super::handler::{PlayerData, Position};
+use super::chat::{ChatHistory, ChatRecord, PinnedMessage, MAX_PINNED_MESSAGES};
+use super::collision::resolve_move;
+use super::countdown::{Countdown, MAX_ACTIVE_COUNTDOWNS};
+use super::handler::{PlayerData, PlayerPosition, PlayerStatus, Position, PublisherInfo, PublisherMetadata};
+use super::instances::instance_room_id;
+use super::language::{localized_room_id, split_language};
+use super::motion::MovementEffects;
+use super::mutes::MuteRegistry;
+use super::publish_quality::ReceiverReport;
+use super::theme::theme_for_room;
+use super::resume::{ParkedSession, ResumeRegistry, SessionMedia};
+use super::roles::Role;
+use super::room_password::RoomPassword;
+use super::transfer::{PendingTransfer, TransferRegistry};
+use super::transport_pool::TransportPool;
+use super::tts::TtsNarrator;
+
+/// Suffix of the room players wait in while `<room>` is locked
+const WAITING_ROOM_SUFFIX: &str = "-waiting";
+
+pub fn waiting_room_id(room_id: &str) -> String {
+    format!("{}{}", room_id, WAITING_ROOM_SUFFIX)
+}
+
+pub fn is_waiting_room(room_id: &str) -> bool {
+    room_id.ends_with(WAITING_ROOM_SUFFIX)
+}
 
 /// A room represents a virtual meeting space where users can publish and subscribe to media
 pub struct Room<T>
@@ -16,24 +49,100 @@ where
 {
     pub id: String,
     pub theme: String,
+    /// Language spoken in this room, from its ID suffix
+    pub language: String,
     pub router: Arc<Mutex<Router>>,
+    /// Warmed transports handed out to joining sessions
+    pub transport_pool: TransportPool,
     /// Maps player_id -> (actor address, player data)
     players: std::sync::Mutex<HashMap<String, (Addr<T>, PlayerData)>>,
+    /// Admins watching in ghost mode: observer_id -> actor address. They receive room broadcasts
+    /// but never show up in player lists, counts or hosting
+    observers: std::sync::Mutex<HashMap<String, Addr<T>>>,
     /// Maps publisher_id -> player_id (tracks which player owns which publisher)
     publishers: std::sync::Mutex<HashMap<String, String>>,
+    /// publisher_id -> source and label the client gave when publishing
+    publisher_metadata: std::sync::Mutex<HashMap<String, PublisherMetadata>>,
+    /// Publishers their owner has muted
+    muted_publishers: std::sync::Mutex<HashSet<String>>,
+    /// The player allowed to run room-wide events (first to join, handed off on leave)
+    host_id: std::sync::Mutex<Option<String>>,
+    /// Derived movement events currently enabled (theme default, host can toggle)
+    movement_effects: std::sync::Mutex<MovementEffects>,
+    /// Stage room whose relayed publishers this (audience) room can watch
+    relay_source: std::sync::Mutex<Option<String>>,
+    /// Publishers in this (stage) room that linked audience rooms may subscribe to
+    relayed_publishers: std::sync::Mutex<HashSet<String>>,
+    /// The linked stage's relayed publishers, piped onto this (audience) room's router
+    piped_publishers: std::sync::Mutex<HashMap<String, Arc<Mutex<Publisher>>>>,
+    /// Minimum gap between chat messages per player (zero when off, host is exempt)
+    slow_mode: std::sync::Mutex<Duration>,
+    /// When the host last used `@everyone`
+    last_everyone_mention: std::sync::Mutex<Option<Instant>>,
+    /// Chat messages pinned by the host, oldest first
+    pinned_messages: std::sync::Mutex<Vec<PinnedMessage>>,
+    /// Latest chat messages, backfilled to players joining mid-conversation
+    chat_history: std::sync::Mutex<ChatHistory>,
+    /// Moderator mutes, shared by every room
+    mutes: Arc<MuteRegistry>,
+    /// Dropped sessions waiting to resume, shared by every room
+    parked_sessions: Arc<ResumeRegistry>,
+    /// Reads chat aloud as an audio publisher while enabled by the host
+    tts: std::sync::Mutex<Option<Arc<TtsNarrator>>>,
+    /// Latest movement per player since the last tick, see `spawn_movement_tick_loop`
+    pending_moves: std::sync::Mutex<HashMap<String, PlayerPosition>>,
+    /// Number of the last movement tick broadcast, so clients can discard batches that arrive out of order
+    move_seq: AtomicU64,
+    /// Set by the host to turn away new joins while keeping current members
+    locked: AtomicBool,
+    /// Passphrase set by the host that new joins must supply
+    password: std::sync::Mutex<Option<RoomPassword>>,
+    /// When the last player left (or the room was created); `None` while occupied
+    emptied_at: std::sync::Mutex<Option<Instant>>,
+    /// publisher_id -> viewer player_id -> latest reception stats
+    receiver_reports: std::sync::Mutex<HashMap<String, HashMap<String, ReceiverReport>>>,
+    /// Running countdowns, see `start_countdown`
+    countdowns: std::sync::Mutex<Vec<Countdown>>,
 }
 
 impl<T> Room<T>
 where
     T: Actor,
 {
-    pub fn new(id: String, theme: String, router: Arc<Mutex<Router>>) -> Self {
+    pub fn new(id: String, theme: String, router: Arc<Mutex<Router>>, mutes: Arc<MuteRegistry>, parked_sessions: Arc<ResumeRegistry>) -> Self {
+        let movement_effects = theme_for_room(&id).movement_effects;
+        let language = split_language(&id).1.to_string();
+        let chat_history = ChatHistory::load(&id);
         Self {
             id,
             theme,
+            language,
             router,
+            transport_pool: TransportPool::default(),
             players: std::sync::Mutex::new(HashMap::new()),
+            observers: std::sync::Mutex::new(HashMap::new()),
             publishers: std::sync::Mutex::new(HashMap::new()),
+            publisher_metadata: std::sync::Mutex::new(HashMap::new()),
+            muted_publishers: std::sync::Mutex::new(HashSet::new()),
+            host_id: std::sync::Mutex::new(None),
+            movement_effects: std::sync::Mutex::new(movement_effects),
+            relay_source: std::sync::Mutex::new(None),
+            relayed_publishers: std::sync::Mutex::new(HashSet::new()),
+            piped_publishers: std::sync::Mutex::new(HashMap::new()),
+            slow_mode: std::sync::Mutex::new(Duration::ZERO),
+            last_everyone_mention: std::sync::Mutex::new(None),
+            pinned_messages: std::sync::Mutex::new(Vec::new()),
+            chat_history: std::sync::Mutex::new(chat_history),
+            mutes,
+            parked_sessions,
+            tts: std::sync::Mutex::new(None),
+            pending_moves: std::sync::Mutex::new(HashMap::new()),
+            move_seq: AtomicU64::new(0),
+            locked: AtomicBool::new(false),
+            password: std::sync::Mutex::new(None),
+            emptied_at: std::sync::Mutex::new(Some(Instant::now())),
+            receiver_reports: std::sync::Mutex::new(HashMap::new()),
+            countdowns: std::sync::Mutex::new(Vec::new()),
         }
     }
 
@@ -41,49 +150,305 @@ where
     pub fn add_player(&self, addr: Addr<T>, mut player_data: PlayerData) -> String {
         let player_id = uuid::Uuid::new_v4().to_string();
         player_data.id = player_id.clone();
-        player_data.position = Position::default();
         player_data.rotation = 0.0;
         player_data.is_moving = false;
+        player_data.status = PlayerStatus::Active;
         
         let mut players = self.players.lock().unwrap();
+        let occupied: Vec<Position> = players.values().map(|(_, data)| data.position.clone()).collect();
+        player_data.position = theme_for_room(&self.id).free_spawn(&occupied);
         players.insert(player_id.clone(), (addr, player_data));
+        *self.emptied_at.lock().unwrap() = None;
         tracing::info!("Player {} joined room {}. Total players: {}", player_id, self.id, players.len());
+
+        let mut host_id = self.host_id.lock().unwrap();
+        if host_id.is_none() {
+            *host_id = Some(player_id.clone());
+            tracing::info!("Player {} is now host of room {}", player_id, self.id);
+        }
         player_id
     }
 
-    /// Remove a player from the room, returns remaining player count
-    #[allow(dead_code)]
-    pub fn remove_player(&self, player_id: &str) -> usize {
+    /// Attach an invisible observer, returns its ID
+    pub fn add_observer(&self, addr: Addr<T>) -> String {
+        let observer_id = uuid::Uuid::new_v4().to_string();
+        self.observers.lock().unwrap().insert(observer_id.clone(), addr);
+        tracing::info!("Observer {} attached to room {}", observer_id, self.id);
+        observer_id
+    }
+
+    pub fn remove_observer(&self, observer_id: &str) {
+        if self.observers.lock().unwrap().remove(observer_id).is_some() {
+            tracing::info!("Observer {} left room {}", observer_id, self.id);
+        }
+    }
+
+    pub fn get_observers(&self) -> Vec<Addr<T>> {
+        self.observers.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Hand an existing player over to a new connection (device handoff), returns the old address and player data
+    pub fn adopt_player(&self, player_id: &str, addr: Addr<T>) -> Option<(Addr<T>, PlayerData)> {
         let mut players = self.players.lock().unwrap();
-        players.remove(player_id);
+        let (previous, player_data) = players.get_mut(player_id)?;
+        let previous = std::mem::replace(previous, addr);
+        tracing::info!("Player {} moved to a new connection in room {}", player_id, self.id);
+        Some((previous, player_data.clone()))
+    }
+
+    /// Remove a player from the room, returns the remaining player count if they were here
+    pub fn remove_player(&self, player_id: &str) -> Option<usize> {
+        let mut players = self.players.lock().unwrap();
+        players.remove(player_id)?;
         let remaining = players.len();
+        if remaining == 0 {
+            *self.emptied_at.lock().unwrap() = Some(Instant::now());
+        }
         tracing::info!("Player {} left room {}. Remaining players: {}", player_id, self.id, remaining);
-        remaining
+
+        // Hand hosting off to someone still in the room
+        let mut host_id = self.host_id.lock().unwrap();
+        if host_id.as_deref() == Some(player_id) {
+            *host_id = players.keys().next().cloned();
+        }
+        Some(remaining)
     }
 
-    /// Remove a player by their actor address, returns (player_id, remaining count) if found
-    pub fn remove_player_by_addr(&self, addr: &Addr<T>) -> Option<(String, usize)> {
-        let mut players = self.players.lock().unwrap();
-        let player_id = players.iter()
-            .find(|(_, (a, _))| a == addr)
-            .map(|(id, _)| id.clone());
-        
-        if let Some(ref id) = player_id {
-            players.remove(id);
-            let remaining = players.len();
-            tracing::info!("Player {} left room {}. Remaining players: {}", id, self.id, remaining);
-            return Some((id.clone(), remaining));
+    /// Get the current host's player ID
+    pub fn get_host_id(&self) -> Option<String> {
+        self.host_id.lock().unwrap().clone()
+    }
+
+    pub fn is_host(&self, player_id: &str) -> bool {
+        self.host_id.lock().unwrap().as_deref() == Some(player_id)
+    }
+
+    pub fn get_movement_effects(&self) -> MovementEffects {
+        *self.movement_effects.lock().unwrap()
+    }
+
+    pub fn set_movement_effects(&self, effects: MovementEffects) {
+        *self.movement_effects.lock().unwrap() = effects;
+    }
+
+    pub fn get_slow_mode(&self) -> Duration {
+        *self.slow_mode.lock().unwrap()
+    }
+
+    pub fn set_slow_mode(&self, interval: Duration) {
+        *self.slow_mode.lock().unwrap() = interval;
+    }
+
+    /// Record an `@everyone` mention, or return how long until one is allowed again
+    pub fn try_mention_everyone(&self, cooldown: Duration) -> Result<(), Duration> {
+        let mut last = self.last_everyone_mention.lock().unwrap();
+        if let Some(elapsed) = last.map(|last| last.elapsed()).filter(|elapsed| *elapsed < cooldown) {
+            return Err(cooldown - elapsed);
         }
-        None
+        *last = Some(Instant::now());
+        Ok(())
+    }
+
+    /// How long the room has had no players, `None` while it has some
+    pub fn empty_for(&self) -> Option<Duration> {
+        self.emptied_at.lock().unwrap().map(|emptied_at| emptied_at.elapsed())
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::Relaxed)
+    }
+
+    pub fn set_locked(&self, locked: bool) {
+        self.locked.store(locked, Ordering::Relaxed);
+    }
+
+    pub fn has_password(&self) -> bool {
+        self.password.lock().unwrap().is_some()
+    }
+
+    /// Set or clear (`None`) the passphrase; only its hash is kept
+    pub fn set_password(&self, passphrase: Option<&str>) {
+        *self.password.lock().unwrap() = passphrase.map(RoomPassword::new);
+    }
+
+    /// Whether `attempt` opens the room; always true once the password is cleared
+    pub fn check_password(&self, attempt: &str) -> bool {
+        self.password.lock().unwrap().as_ref().is_none_or(|password| password.matches(attempt))
     }
 
-    pub fn update_player_position(&self, player_id: &str, position: Position, rotation: f32, is_moving: bool) {
+    pub fn record_chat(&self, record: ChatRecord) {
+        self.chat_history.lock().unwrap().push(record);
+    }
+
+    pub fn get_chat(&self, message_id: &str) -> Option<ChatRecord> {
+        self.chat_history.lock().unwrap().get(message_id).cloned()
+    }
+
+    pub fn edit_chat(&self, message_id: &str, message: String) -> Option<ChatRecord> {
+        self.chat_history.lock().unwrap().edit(message_id, message)
+    }
+
+    pub fn delete_chat(&self, message_id: &str) -> Option<ChatRecord> {
+        self.chat_history.lock().unwrap().delete(message_id)
+    }
+
+    pub fn recent_chat(&self, limit: usize) -> Vec<ChatRecord> {
+        self.chat_history.lock().unwrap().recent(limit)
+    }
+
+    /// A page of history older than `before`, and whether there's more before it
+    pub fn chat_page(&self, before: Option<&str>, limit: usize) -> Result<(Vec<ChatRecord>, bool), String> {
+        self.chat_history.lock().unwrap().page(before, limit)
+    }
+
+    /// Chat history matching a lowercased search term, newest first
+    pub fn search_chat(&self, needle: &str) -> Vec<ChatRecord> {
+        self.chat_history.lock().unwrap().search(needle)
+    }
+
+    /// Hold a dropped connection's player and media in this room for the resume grace window
+    pub fn park_session(&self, resume_token: String, player_id: String, media: SessionMedia, session_started: std::time::Instant) {
+        self.parked_sessions.park(resume_token, self.id.clone(), player_id, media, session_started);
+    }
+
+    /// Who mutes apply to: the player's moderation identity, or just this connection if they have none
+    fn moderation_identity(&self, player_id: &str) -> String {
+        self.players
+            .lock()
+            .unwrap()
+            .get(player_id)
+            .and_then(|(_, player_data)| player_data.moderation_identity.clone())
+            .unwrap_or_else(|| format!("player:{}", player_id))
+    }
+
+    pub fn mute_chat(&self, player_id: &str, duration: Duration) {
+        self.mutes.mute_chat(&self.moderation_identity(player_id), duration);
+    }
+
+    /// Stop a player publishing media until `duration` has passed
+    pub fn mute_media(&self, player_id: &str, duration: Duration) {
+        self.mutes.mute_media(&self.moderation_identity(player_id), duration);
+    }
+
+    /// Time left on a moderator's media mute, if the player has one
+    pub fn media_muted_for(&self, player_id: &str) -> Option<Duration> {
+        self.mutes.media_muted_for(&self.moderation_identity(player_id))
+    }
+
+    /// Returns false if the player was already in that state
+    pub fn set_force_muted(&self, player_id: &str, muted: bool) -> bool {
+        self.mutes.set_force_muted(&self.moderation_identity(player_id), muted)
+    }
+
+    pub fn is_force_muted(&self, player_id: &str) -> bool {
+        self.mutes.is_force_muted(&self.moderation_identity(player_id))
+    }
+
+    pub fn set_player_role(&self, player_id: &str, role: Role) -> bool {
         let mut players = self.players.lock().unwrap();
-        if let Some((_, player_data)) = players.get_mut(player_id) {
-            player_data.position = position;
-            player_data.rotation = rotation;
-            player_data.is_moving = is_moving;
+        match players.get_mut(player_id) {
+            Some((_, player_data)) => {
+                player_data.role = role;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn set_player_status(&self, player_id: &str, status: PlayerStatus) {
+        if let Some((_, player_data)) = self.players.lock().unwrap().get_mut(player_id) {
+            player_data.status = status;
+        }
+    }
+
+    /// Time left on a player's chat mute, if they're muted
+    pub fn chat_muted_for(&self, player_id: &str) -> Option<Duration> {
+        self.mutes.chat_muted_for(&self.moderation_identity(player_id))
+    }
+
+    pub fn get_pinned_messages(&self) -> Vec<PinnedMessage> {
+        self.pinned_messages.lock().unwrap().clone()
+    }
+
+    /// Pin a message, or say why it can't be
+    pub fn pin_message(&self, pin: PinnedMessage) -> Result<(), String> {
+        let mut pinned_messages = self.pinned_messages.lock().unwrap();
+        if pinned_messages.iter().any(|pinned| pinned.record.message_id == pin.record.message_id) {
+            return Err("that message is already pinned".to_string());
+        }
+        if pinned_messages.len() >= MAX_PINNED_MESSAGES {
+            return Err(format!("at most {} messages can be pinned", MAX_PINNED_MESSAGES));
+        }
+        pinned_messages.push(pin);
+        Ok(())
+    }
+
+    /// Remove a pin, returns false if it didn't exist
+    pub fn unpin_message(&self, pin_id: &str) -> bool {
+        let mut pinned_messages = self.pinned_messages.lock().unwrap();
+        let before = pinned_messages.len();
+        pinned_messages.retain(|pin| pin.pin_id != pin_id);
+        pinned_messages.len() != before
+    }
+
+    pub fn get_countdowns(&self) -> Vec<Countdown> {
+        self.countdowns.lock().unwrap().clone()
+    }
+
+    /// Track a new countdown, returns false if too many are running
+    pub fn add_countdown(&self, countdown: Countdown) -> bool {
+        let mut countdowns = self.countdowns.lock().unwrap();
+        if countdowns.len() >= MAX_ACTIVE_COUNTDOWNS {
+            return false;
         }
+        countdowns.push(countdown);
+        true
+    }
+
+    /// Stop tracking a countdown (finished or cancelled), returns it if it was running
+    pub fn remove_countdown(&self, countdown_id: &str) -> Option<Countdown> {
+        let mut countdowns = self.countdowns.lock().unwrap();
+        let index = countdowns.iter().position(|countdown| countdown.countdown_id == countdown_id)?;
+        Some(countdowns.remove(index))
+    }
+
+    pub fn get_tts(&self) -> Option<Arc<TtsNarrator>> {
+        self.tts.lock().unwrap().clone()
+    }
+
+    /// Swap the narrator, returns the previous one so the caller can close it
+    pub fn set_tts(&self, tts: Option<Arc<TtsNarrator>>) -> Option<Arc<TtsNarrator>> {
+        std::mem::replace(&mut *self.tts.lock().unwrap(), tts)
+    }
+
+    /// Store a player's movement, returns the position after clamping it to the room bounds and
+    /// stopping it at any solid geometry in the way
+    pub fn update_player_position(&self, player_id: &str, position: Position, rotation: f32, is_moving: bool) -> Position {
+        let theme = theme_for_room(&self.id);
+        let position = theme.bounds.clamp(position);
+        let mut players = self.players.lock().unwrap();
+        let Some((_, player_data)) = players.get_mut(player_id) else {
+            return position;
+        };
+        let position = resolve_move(&theme.colliders, &player_data.position, position);
+        player_data.position = position.clone();
+        player_data.rotation = rotation;
+        player_data.is_moving = is_moving;
+        position
+    }
+
+    /// Buffer a move for the next tick; only the latest state per player is kept
+    pub fn queue_move(&self, update: PlayerPosition) {
+        self.pending_moves.lock().unwrap().insert(update.player_id.clone(), update);
+    }
+
+    pub fn take_pending_moves(&self) -> Vec<PlayerPosition> {
+        self.pending_moves.lock().unwrap().drain().map(|(_, update)| update).collect()
+    }
+
+    /// Number the next movement tick
+    pub fn next_move_seq(&self) -> u64 {
+        self.move_seq.fetch_add(1, Ordering::Relaxed) + 1
     }
 
     pub fn get_player_data(&self, player_id: &str) -> Option<PlayerData> {
@@ -91,36 +456,185 @@ where
         players.get(player_id).map(|(_, data)| data.clone())
     }
 
+    pub fn get_players_with_addrs(&self) -> Vec<(Addr<T>, PlayerData)> {
+        let players = self.players.lock().unwrap();
+        players.values().cloned().collect()
+    }
+
+    /// Player data for everyone except the given player
+    pub fn get_peers_data(&self, player_id: &str) -> Vec<PlayerData> {
+        let players = self.players.lock().unwrap();
+        players.iter()
+            .filter(|(id, _)| id.as_str() != player_id)
+            .map(|(_, (_, data))| data.clone())
+            .collect()
+    }
+
     pub fn get_all_players(&self) -> Vec<PlayerData> {
         let players = self.players.lock().unwrap();
         players.values().map(|(_, data)| data.clone()).collect()
     }
 
+    pub fn get_addr(&self, player_id: &str) -> Option<Addr<T>> {
+        self.players.lock().unwrap().get(player_id).map(|(addr, _)| addr.clone())
+    }
+
+    /// The connection of the player using a `@handle`
+    pub fn get_addr_by_handle(&self, handle: &str) -> Option<Addr<T>> {
+        let players = self.players.lock().unwrap();
+        players
+            .values()
+            .find(|(_, data)| data.handle.as_deref() == Some(handle))
+            .map(|(addr, _)| addr.clone())
+    }
+
+    /// Everyone who should see the player's events: the other players and any observers
     pub fn get_peers(&self, player_id: &str) -> Vec<Addr<T>> {
         let players = self.players.lock().unwrap();
         players.iter()
             .filter(|(id, _)| *id != player_id)
             .map(|(_, (addr, _))| addr.clone())
+            .chain(self.get_observers())
             .collect()
     }
 
+    /// Every connection receiving room broadcasts, observers included
     pub fn get_all_addrs(&self) -> Vec<Addr<T>> {
         let players = self.players.lock().unwrap();
-        players.values().map(|(addr, _)| addr.clone()).collect()
+        players.values().map(|(addr, _)| addr.clone()).chain(self.get_observers()).collect()
+    }
+
+    pub fn player_count(&self) -> usize {
+        self.players.lock().unwrap().len()
     }
 
     /// Register a publisher for a player
-    pub fn register_publisher(&self, publisher_id: String, player_id: String) {
+    pub fn register_publisher(&self, publisher_id: String, player_id: String, metadata: PublisherMetadata) {
+        self.publisher_metadata.lock().unwrap().insert(publisher_id.clone(), metadata);
         let mut publishers = self.publishers.lock().unwrap();
         publishers.insert(publisher_id.clone(), player_id.clone());
         tracing::debug!("Registered publisher {} for player {}", publisher_id, player_id);
     }
 
     /// Unregister a publisher
-    pub fn unregister_publisher(&self, publisher_id: &str) {
+    /// Returns whether the publisher was relayed, so linked rooms need telling it's gone
+    pub fn unregister_publisher(&self, publisher_id: &str) -> bool {
         let mut publishers = self.publishers.lock().unwrap();
         publishers.remove(publisher_id);
+        self.publisher_metadata.lock().unwrap().remove(publisher_id);
+        self.muted_publishers.lock().unwrap().remove(publisher_id);
+        self.receiver_reports.lock().unwrap().remove(publisher_id);
+        let relayed = self.relayed_publishers.lock().unwrap().remove(publisher_id);
         tracing::debug!("Unregistered publisher {}", publisher_id);
+        relayed
+    }
+
+    /// Get the player ID owning a publisher
+    pub fn get_publisher_owner(&self, publisher_id: &str) -> Option<String> {
+        self.publishers.lock().unwrap().get(publisher_id).cloned()
+    }
+
+    pub fn get_publisher_metadata(&self, publisher_id: &str) -> PublisherMetadata {
+        self.publisher_metadata.lock().unwrap().get(publisher_id).cloned().unwrap_or_default()
+    }
+
+    /// Returns false if the publisher isn't in this room or was already in that state
+    pub fn set_publisher_muted(&self, publisher_id: &str, muted: bool) -> bool {
+        if !self.publishers.lock().unwrap().contains_key(publisher_id) {
+            return false;
+        }
+        let mut muted_publishers = self.muted_publishers.lock().unwrap();
+        if muted {
+            muted_publishers.insert(publisher_id.to_string())
+        } else {
+            muted_publishers.remove(publisher_id)
+        }
+    }
+
+    pub fn is_publisher_muted(&self, publisher_id: &str) -> bool {
+        self.muted_publishers.lock().unwrap().contains(publisher_id)
+    }
+
+    pub fn get_muted_publishers(&self) -> HashSet<String> {
+        self.muted_publishers.lock().unwrap().clone()
+    }
+
+    /// Every publisher with its owner, source and label
+    pub fn get_publisher_infos(&self) -> Vec<PublisherInfo> {
+        self.get_all_publishers()
+            .into_iter()
+            .map(|(publisher_id, player_id)| PublisherInfo {
+                metadata: self.get_publisher_metadata(&publisher_id),
+                publisher_id,
+                player_id,
+            })
+            .collect()
+    }
+
+    pub fn get_relay_source(&self) -> Option<String> {
+        self.relay_source.lock().unwrap().clone()
+    }
+
+    pub fn set_relay_source(&self, source_room_id: Option<String>) {
+        *self.relay_source.lock().unwrap() = source_room_id;
+    }
+
+    /// Mark a publisher as relayed to linked rooms, returns false if it isn't in this room
+    pub fn set_publisher_relayed(&self, publisher_id: &str, relayed: bool) -> bool {
+        if !self.publishers.lock().unwrap().contains_key(publisher_id) {
+            return false;
+        }
+        let mut relayed_publishers = self.relayed_publishers.lock().unwrap();
+        if relayed {
+            relayed_publishers.insert(publisher_id.to_string());
+        } else {
+            relayed_publishers.remove(publisher_id);
+        }
+        true
+    }
+
+    /// Get relayed publishers with their player IDs
+    pub fn get_relayed_publishers(&self) -> Vec<(String, String)> {
+        let all_publishers = self.get_all_publishers();
+        let relayed_publishers = self.relayed_publishers.lock().unwrap();
+        all_publishers
+            .into_iter()
+            .filter(|(publisher_id, _)| relayed_publishers.contains(publisher_id))
+            .collect()
+    }
+
+    pub fn add_piped_publisher(&self, publisher_id: String, publisher: Arc<Mutex<Publisher>>) {
+        self.piped_publishers.lock().unwrap().insert(publisher_id, publisher);
+    }
+
+    pub fn take_piped_publisher(&self, publisher_id: &str) -> Option<Arc<Mutex<Publisher>>> {
+        self.piped_publishers.lock().unwrap().remove(publisher_id)
+    }
+
+    pub fn take_piped_publishers(&self) -> Vec<(String, Arc<Mutex<Publisher>>)> {
+        self.piped_publishers.lock().unwrap().drain().collect()
+    }
+
+    pub fn is_piped_publisher(&self, publisher_id: &str) -> bool {
+        self.piped_publishers.lock().unwrap().contains_key(publisher_id)
+    }
+
+    pub fn record_receiver_report(&self, publisher_id: &str, viewer_id: &str, report: ReceiverReport) {
+        if !self.publishers.lock().unwrap().contains_key(publisher_id) {
+            return;
+        }
+        let mut reports = self.receiver_reports.lock().unwrap();
+        reports.entry(publisher_id.to_string()).or_default().insert(viewer_id.to_string(), report);
+    }
+
+    /// Drain the reports collected since the last call, dropping any older than `max_age`
+    pub fn take_receiver_reports(&self, max_age: Duration) -> HashMap<String, HashMap<String, ReceiverReport>> {
+        let mut reports = std::mem::take(&mut *self.receiver_reports.lock().unwrap());
+        reports.retain(|_, viewers| {
+            viewers.retain(|_, report| report.received_at.elapsed() <= max_age);
+            !viewers.is_empty()
+        });
+        reports
     }
 
     /// Get all publishers with their player IDs
@@ -130,49 +644,439 @@ where
     }
 }
 
+/// How often each worker is probed for responsiveness
+const WORKER_HEALTH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+/// A worker that can't open a probe transport within this window is considered stuck
+const WORKER_HEALTH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+const DEFAULT_ICE_REFRESH_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// Refresh this long before TURN credentials expire
+const ICE_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+/// Don't hammer the provider when credentials are short-lived or a fetch keeps failing
+const MIN_ICE_REFRESH_WAIT: Duration = Duration::from_secs(15);
+/// Joining sessions get credentials valid for at least this long, fetching new ones if needed
+const SESSION_CREDENTIAL_MIN_LIFETIME: Duration = Duration::from_secs(10 * 60);
+/// After a failed join-time fetch, joins use what's cached for this long instead of each waiting on the provider
+const SESSION_FETCH_FAILURE_BACKOFF: Duration = Duration::from_secs(30);
+/// How often the idle monitor checks whether workers can be released
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A worker counts as healthy while it can still open (and close) a transport on a router in time
+async fn probe_worker(worker: &Arc<Mutex<Worker>>, probe_router: &ProbeRouter) -> bool {
+    let probe = async {
+        // One router per worker for its whole life; a probe cut off while opening it leaves the cell empty
+        let router = probe_router
+            .get_or_init(|| async { worker.lock().await.new_router(MediaConfig::default()) })
+            .await;
+        let transport = router.lock().await.create_publish_transport(WebRTCTransportConfig::default()).await;
+        let _ = transport.close().await;
+    };
+    tokio::time::timeout(WORKER_HEALTH_TIMEOUT, probe).await.is_ok()
+}
+
+/// Router the health monitor opens its probe transports on, created by the first probe
+type ProbeRouter = Arc<tokio::sync::OnceCell<Arc<Mutex<Router>>>>;
+
+/// A rheomesh worker plus the bookkeeping used to shard rooms across workers
+struct WorkerSlot {
+    worker: Arc<Mutex<Worker>>,
+    healthy: Arc<AtomicBool>,
+    room_count: usize,
+    probe_router: ProbeRouter,
+}
+
+impl WorkerSlot {
+    fn new(worker: Arc<Mutex<Worker>>) -> Self {
+        Self {
+            worker,
+            healthy: Arc::new(AtomicBool::new(true)),
+            room_count: 0,
+            probe_router: ProbeRouter::default(),
+        }
+    }
+}
+
 /// RoomOwner manages all active rooms and creates new rooms on demand
 pub struct RoomOwner<T>
 where
     T: Actor,
 {
     rooms: HashMap<String, Arc<Room<T>>>,
-    worker: Arc<Mutex<Worker>>,
-    ice_servers: Vec<RTCIceServer>,
+    /// Empty while released for idling; recreated on the next room creation
+    workers: Vec<WorkerSlot>,
+    /// How many workers to run when active
+    worker_count: usize,
+    /// What the workers were started with, so ones recreated after idling match
+    worker_config: WorkerConfig,
+    /// Maps room_id -> index into `workers`
+    room_workers: HashMap<String, usize>,
+    ice_servers: IceServerCache,
+    /// Held while fetching ICE servers so concurrent joins don't each hit the provider
+    ice_refresh_gate: Arc<Mutex<()>>,
+    /// Relay scores from background probes, applied to every ICE list handed out
+    turn_health: Arc<std::sync::Mutex<TurnHealth>>,
+    /// When a join-time ICE fetch last failed to bring back TURN credentials
+    ice_fetch_failed_at: Option<Instant>,
+    /// When the last room closed, for idle shutdown
+    idle_since: Option<Instant>,
+    /// Outstanding device handoff codes
+    transfers: TransferRegistry,
+    /// Dropped sessions inside their resume grace window
+    parked_sessions: Arc<ResumeRegistry>,
+    /// Rooms created at startup that stay open while empty
+    standing_rooms: HashSet<String>,
+    /// Moderator mutes, kept here so they outlive rooms and the connections they were placed on
+    mutes: Arc<MuteRegistry>,
 }
 
 impl<T> RoomOwner<T>
 where
     T: Actor,
 {
-    pub fn new(worker: Arc<Mutex<Worker>>, ice_servers: Vec<RTCIceServer>) -> Self {
+    pub fn new(workers: Vec<Arc<Mutex<Worker>>>, worker_config: WorkerConfig, ice_servers: IceServerCache) -> Self {
+        assert!(!workers.is_empty(), "RoomOwner needs at least one worker");
+        let worker_count = workers.len();
         Self {
             rooms: HashMap::new(),
-            worker,
+            workers: workers.into_iter().map(WorkerSlot::new).collect(),
+            worker_count,
+            worker_config,
+            room_workers: HashMap::new(),
             ice_servers,
+            ice_refresh_gate: Arc::new(Mutex::new(())),
+            turn_health: Arc::new(std::sync::Mutex::new(TurnHealth::default())),
+            ice_fetch_failed_at: None,
+            idle_since: None,
+            transfers: TransferRegistry::default(),
+            parked_sessions: Arc::new(ResumeRegistry::default()),
+            standing_rooms: HashSet::new(),
+            mutes: Arc::new(MuteRegistry::default()),
+        }
+    }
+
+    /// Periodically probe every worker and flag the ones that stop responding
+    pub fn spawn_worker_health_monitor(owner: Data<Mutex<Self>>) {
+        actix::spawn(async move {
+            let mut interval = tokio::time::interval(WORKER_HEALTH_INTERVAL);
+            loop {
+                interval.tick().await;
+                // Re-read each round: workers come and go with idle shutdown
+                let probes: Vec<(usize, Arc<Mutex<Worker>>, ProbeRouter, Arc<AtomicBool>)> = owner
+                    .lock()
+                    .await
+                    .workers
+                    .iter()
+                    .enumerate()
+                    .map(|(index, slot)| (index, slot.worker.clone(), slot.probe_router.clone(), slot.healthy.clone()))
+                    .collect();
+                for (index, worker, probe_router, healthy) in &probes {
+                    let responsive = probe_worker(worker, probe_router).await;
+                    let was_healthy = healthy.swap(responsive, Ordering::SeqCst);
+                    if was_healthy && !responsive {
+                        tracing::error!("Worker {} stopped responding, rebalancing new rooms away from it", index);
+                    } else if !was_healthy && responsive {
+                        tracing::info!("Worker {} recovered", index);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Release all workers once no rooms have existed for `idle_timeout`
+    pub fn spawn_idle_monitor(owner: Data<Mutex<Self>>, idle_timeout: Duration) {
+        actix::spawn(async move {
+            let mut interval = tokio::time::interval(IDLE_CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+                owner.lock().await.release_if_idle(idle_timeout);
+            }
+        });
+    }
+
+    fn release_if_idle(&mut self, idle_timeout: Duration) {
+        if !self.rooms.is_empty() {
+            self.idle_since = None;
+            return;
+        }
+        let idle_since = *self.idle_since.get_or_insert_with(Instant::now);
+        if idle_since.elapsed() >= idle_timeout && !self.workers.is_empty() {
+            // Dropping the last handles shuts the workers (and their sockets/threads) down
+            self.workers.clear();
+            self.room_workers.clear();
+            tracing::info!("💤 No sessions for {:?}, released rheomesh workers", idle_timeout);
+        }
+    }
+
+    /// Recreate workers after an idle shutdown; fails if none of them could be started, leaving the
+    /// next room creation to try again
+    async fn ensure_workers(&mut self) -> Result<(), String> {
+        self.idle_since = None;
+        if !self.workers.is_empty() {
+            return Ok(());
+        }
+        for _ in 0..self.worker_count {
+            match Worker::new(self.worker_config.clone()).await {
+                Ok(worker) => self.workers.push(WorkerSlot::new(worker)),
+                Err(e) => tracing::error!("Failed to restart worker: {}", e),
+            }
+        }
+        if self.workers.is_empty() {
+            return Err("no media workers could be started".to_string());
         }
+        tracing::info!("⏰ Woke from idle with {} worker(s)", self.workers.len());
+        Ok(())
+    }
+
+    /// Pick the healthy worker hosting the fewest rooms, or worker 0 if none are; `None` while
+    /// there are no workers at all
+    fn pick_worker(&self) -> Option<usize> {
+        let healthy = self
+            .workers
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| slot.healthy.load(Ordering::SeqCst))
+            .min_by_key(|(_, slot)| slot.room_count)
+            .map(|(index, _)| index);
+        if healthy.is_none() && !self.workers.is_empty() {
+            tracing::warn!("No healthy workers, falling back to worker 0");
+            return Some(0);
+        }
+        healthy
+    }
+
+    /// Whether new sessions can be hosted: some worker is responding, or they were released for idling and
+    /// will be restarted on demand
+    pub fn is_healthy(&self) -> bool {
+        self.workers.is_empty() || self.workers.iter().any(|slot| slot.healthy.load(Ordering::SeqCst))
+    }
+
+    /// Rooms on a failed worker stay up for the players in them but stop taking new joins
+    pub fn is_room_worker_healthy(&self, room_id: &str) -> bool {
+        self.room_workers
+            .get(room_id)
+            .is_none_or(|index| self.workers[*index].healthy.load(Ordering::SeqCst))
     }
 
+    /// Worker a room lives on and whether that worker is responding
+    pub fn room_worker(&self, room_id: &str) -> Option<(usize, bool)> {
+        let index = *self.room_workers.get(room_id)?;
+        Some((index, self.workers[index].healthy.load(Ordering::SeqCst)))
+    }
+
+    pub fn issue_transfer_code(&mut self, room_id: &str, player_id: &str) -> String {
+        self.transfers.issue(room_id, player_id)
+    }
+
+    pub fn redeem_transfer_code(&mut self, code: &str) -> Option<PendingTransfer> {
+        self.transfers.redeem(code)
+    }
+
+    pub fn resume_session(&mut self, resume_token: &str) -> Option<ParkedSession> {
+        self.parked_sessions.take(resume_token)
+    }
+
+    pub fn expire_parked_session(&mut self, resume_token: &str) -> Option<ParkedSession> {
+        self.parked_sessions.expire(resume_token)
+    }
+
+    /// ICE servers for a new session; never hands out expired TURN credentials
     pub fn get_ice_servers(&self) -> Vec<RTCIceServer> {
-        self.ice_servers.clone()
+        self.turn_health.lock().unwrap().rank(self.ice_servers.current())
+    }
+
+    /// Refetch ICE servers ahead of credential expiry, and at least every `ICE_REFRESH_SECS`
+    pub fn spawn_ice_refresh(owner: Data<Mutex<Self>>) {
+        let interval = std::env::var("ICE_REFRESH_SECS")
+            .ok()
+            .and_then(|secs| secs.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_ICE_REFRESH_INTERVAL);
+        actix::spawn(async move {
+            loop {
+                let expires_at = owner.lock().await.ice_servers.expires_at();
+                let mut wait = interval;
+                if let Some(expires_at) = expires_at {
+                    let until_expiry = expires_at.saturating_duration_since(Instant::now());
+                    wait = wait.min(until_expiry.saturating_sub(ICE_REFRESH_MARGIN)).max(MIN_ICE_REFRESH_WAIT);
+                }
+                tokio::time::sleep(wait).await;
+
+                // Fetch without holding the owner lock; joins keep using the old servers meanwhile
+                let gate = owner.lock().await.ice_refresh_gate.clone();
+                let _refreshing = gate.lock().await;
+                let fresh = fetch_ice_servers().await;
+                owner.lock().await.store_ice_servers(fresh);
+            }
+        });
+    }
+
+    /// ICE servers for a joining session, refetched first when the cached credentials wouldn't
+    /// outlive a typical session (e.g. after the provider rotated them). While the provider is
+    /// failing, joins get the last good list rather than waiting on it again
+    pub async fn session_ice_servers(owner: &Data<Mutex<Self>>) -> Vec<RTCIceServer> {
+        let gate = {
+            let owner = owner.lock().await;
+            if !owner.ice_servers.expires_within(SESSION_CREDENTIAL_MIN_LIFETIME) || owner.ice_fetch_recently_failed() {
+                return owner.get_ice_servers();
+            }
+            owner.ice_refresh_gate.clone()
+        };
+
+        // One fetch at a time; joins that waited on it reuse its result
+        let _refreshing = gate.lock().await;
+        {
+            let owner = owner.lock().await;
+            if !owner.ice_servers.expires_within(SESSION_CREDENTIAL_MIN_LIFETIME) || owner.ice_fetch_recently_failed() {
+                return owner.get_ice_servers();
+            }
+        }
+        let fresh = fetch_ice_servers().await;
+        let mut owner = owner.lock().await;
+        if !fresh.has_turn() && owner.ice_servers.has_turn() {
+            tracing::warn!("Join-time ICE fetch failed, serving cached servers for {:?}", SESSION_FETCH_FAILURE_BACKOFF);
+            owner.ice_fetch_failed_at = Some(Instant::now());
+        } else {
+            owner.ice_fetch_failed_at = None;
+        }
+        owner.store_ice_servers(fresh);
+        owner.get_ice_servers()
+    }
+
+    fn ice_fetch_recently_failed(&self) -> bool {
+        self.ice_fetch_failed_at.is_some_and(|failed_at| failed_at.elapsed() < SESSION_FETCH_FAILURE_BACKOFF)
+    }
+
+    /// Probe every configured TURN relay in the background so sessions get the healthy ones first
+    pub fn spawn_turn_health_monitor(owner: Data<Mutex<Self>>) {
+        actix::spawn(async move {
+            let mut interval = tokio::time::interval(TURN_PROBE_INTERVAL);
+            loop {
+                interval.tick().await;
+                let (relays, health) = {
+                    let owner = owner.lock().await;
+                    (turn_urls(&owner.ice_servers.current()), owner.turn_health.clone())
+                };
+                let probes = relays.iter().map(|(url, username, credential)| probe_turn_url(url, username, credential));
+                let results = futures_util::future::join_all(probes).await;
+
+                let mut health = health.lock().unwrap();
+                let urls: Vec<String> = relays.into_iter().map(|(url, _, _)| url).collect();
+                health.retain(&urls);
+                for (url, result) in urls.iter().zip(&results) {
+                    health.record(url, result);
+                }
+            }
+        });
     }
 
+    fn store_ice_servers(&mut self, fresh: IceServerCache) {
+        if fresh.has_turn() || !self.ice_servers.has_turn() {
+            self.ice_servers = fresh;
+        } else {
+            tracing::warn!("ICE refresh fell back to STUN, keeping current TURN credentials until they expire");
+        }
+    }
+
+    /// An open room, wherever it's hosted; `None` once it's been closed or removed
     pub fn find_by_id(&self, room_id: String) -> Option<Arc<Room<T>>> {
         self.rooms.get(&room_id).cloned()
     }
 
-    pub async fn create_new_room(&mut self, room_id: String, theme: String, config: MediaConfig) -> Arc<Room<T>> {
-        let mut worker = self.worker.lock().await;
-        let router = worker.new_router(config);
-        let room = Arc::new(Room::new(room_id.clone(), theme.clone(), router));
+    /// ID of the first instance of a routed room with space for another player, which may not exist
+    /// yet. Filling the lowest instance first lets overflow instances drain as players leave, and
+    /// they're removed like any other room once empty
+    pub fn instance_with_space(&self, base_room_id: &str, language: &str, max_players: usize) -> String {
+        let mut instance = 1;
+        loop {
+            let room_id = localized_room_id(&instance_room_id(base_room_id, instance), language);
+            // Password rooms count as full so routing never sends anyone into one they can't open, and
+            // rooms on a failed worker so new joins land on a fresh instance on a healthy one
+            let full = self.rooms.get(&room_id).is_some_and(|room| {
+                !self.is_room_worker_healthy(&room_id) || room.player_count() >= max_players || room.has_password()
+            });
+            if !full {
+                return room_id;
+            }
+            instance += 1;
+        }
+    }
+
+    pub fn list_rooms(&self) -> Vec<Arc<Room<T>>> {
+        self.rooms.values().cloned().collect()
+    }
+
+    /// Audience rooms currently linked to a stage room
+    pub fn linked_audiences(&self, source_room_id: &str) -> Vec<Arc<Room<T>>> {
+        self.rooms
+            .values()
+            .filter(|room| room.get_relay_source().as_deref() == Some(source_room_id))
+            .cloned()
+            .collect()
+    }
+
+    /// Rooms sharing a stage link with this one (the stage and all its audiences), excluding itself
+    pub fn linked_rooms(&self, room_id: &str) -> Vec<Arc<Room<T>>> {
+        let stage_id = self
+            .rooms
+            .get(room_id)
+            .and_then(|room| room.get_relay_source())
+            .unwrap_or_else(|| room_id.to_string());
+        let mut rooms = self.linked_audiences(&stage_id);
+        rooms.extend(self.rooms.get(&stage_id).cloned());
+        rooms.retain(|room| room.id != room_id);
+        rooms
+    }
+
+    pub async fn create_new_room(&mut self, room_id: String, theme: String, config: MediaConfig) -> Result<Arc<Room<T>>, String> {
+        // Replacing an open room would orphan the sessions still in it
+        if self.rooms.contains_key(&room_id) {
+            return Err(format!("room {} is already open", room_id));
+        }
+        self.ensure_workers().await?;
+        let index = self.pick_worker().ok_or("no media workers available")?;
+        let router = {
+            let mut worker = self.workers[index].worker.lock().await;
+            worker.new_router(config)
+        };
+        let room = Arc::new(Room::new(room_id.clone(), theme.clone(), router, self.mutes.clone(), self.parked_sessions.clone()));
 
+        self.room_workers.insert(room_id.clone(), index);
+        self.workers[index].room_count += 1;
         self.rooms.insert(room_id.clone(), room.clone());
-        tracing::info!("Created new room: {} (theme: {})", room_id, theme);
+        tracing::info!("Created new room: {} (theme: {}) on worker {}", room_id, theme, index);
 
-        room
+        Ok(room)
     }
 
-    pub fn remove_room(&mut self, room_id: String) {
-        self.rooms.remove(&room_id);
+    /// Take a room out of service even while it's occupied; its sessions still have to be told to leave
+    pub fn close_room(&mut self, room_id: &str) -> Option<Arc<Room<T>>> {
+        let room = self.rooms.remove(room_id)?;
+        if let Some(index) = self.room_workers.remove(room_id) {
+            self.workers[index].room_count -= 1;
+        }
+        tracing::info!("Closed room: {}", room_id);
+        Some(room)
+    }
+
+    /// Keep a room open while it's empty, unlike rooms created on demand
+    pub fn mark_standing(&mut self, room_id: &str) {
+        self.standing_rooms.insert(room_id.to_string());
+    }
+
+    /// Remove a room that has been empty for at least `ttl`, returning it so it can be cleaned up
+    pub fn remove_room(&mut self, room_id: &str, ttl: Duration) -> Option<Arc<Room<T>>> {
+        if self.standing_rooms.contains(room_id) {
+            return None;
+        }
+        // Someone may have joined (or a replacement instance was created) since the last player left
+        if !self.rooms.get(room_id)?.empty_for().is_some_and(|empty_for| empty_for >= ttl) {
+            tracing::info!("Room {} was occupied again, keeping it", room_id);
+            return None;
+        }
+        let room = self.rooms.remove(room_id)?;
+        if let Some(index) = self.room_workers.remove(room_id) {
+            self.workers[index].room_count -= 1;
+        }
         tracing::info!("Removed room: {}", room_id);
+        Some(room)
     }
 }
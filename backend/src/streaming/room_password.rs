@@ -0,0 +1,32 @@
+use std::time::Duration;
+use sha2::{Digest, Sha256};
+
+/// How long a client has to answer `PasswordRequired` before it's disconnected
+pub const PASSWORD_PROMPT_TIMEOUT: Duration = Duration::from_secs(60);
+/// Longest passphrase a host may set
+pub const MAX_ROOM_PASSWORD_LEN: usize = 128;
+/// Close code for a join that never supplied the right room password
+pub const WRONG_PASSWORD_CLOSE_CODE: u16 = 4003;
+
+/// A room passphrase, kept only as a salted hash
+pub struct RoomPassword {
+    salt: String,
+    hash: [u8; 32],
+}
+
+fn hash(salt: &str, passphrase: &str) -> [u8; 32] {
+    Sha256::digest(format!("{}:{}", salt, passphrase).as_bytes()).into()
+}
+
+impl RoomPassword {
+    pub fn new(passphrase: &str) -> Self {
+        let salt = uuid::Uuid::new_v4().to_string();
+        let hash = hash(&salt, passphrase);
+        Self { salt, hash }
+    }
+
+    /// Compares every byte so response timing doesn't leak how close a guess was
+    pub fn matches(&self, attempt: &str) -> bool {
+        hash(&self.salt, attempt).iter().zip(self.hash.iter()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+    }
+}
@@ -0,0 +1,18 @@
+/// Map a simulcast RID to rheomesh's spatial layer index (0 = lowest); accepts the common
+/// `q`/`h`/`f` naming as well as `low`/`medium`/`high`
+pub fn simulcast_layer(rid: &str) -> Option<u8> {
+    match rid {
+        "q" | "low" => Some(0),
+        "h" | "medium" => Some(1),
+        "f" | "high" => Some(2),
+        _ => None,
+    }
+}
+
+/// What browsers usually send on each simulcast layer in kbps, lowest first
+const LAYER_KBPS: [u32; 3] = [150, 500, 1500];
+
+/// Highest layer that stays under a room's video cap; the lowest one when nothing does
+pub fn max_layer_for(kbps: u32) -> u8 {
+    LAYER_KBPS.iter().rposition(|layer_kbps| *layer_kbps <= kbps).unwrap_or(0) as u8
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+
+use super::handler::{Position, SendingMessage, StreamingSession};
+use super::room::Room;
+use super::theme::theme_for_room;
+
+/// How often each room recomputes voice attenuation
+const AUDIO_GAIN_TICK: Duration = Duration::from_millis(200);
+/// Gains are rounded to this step so small movements don't flood clients
+const GAIN_STEP: f32 = 0.05;
+
+/// Proximity voice attenuation curve, set per theme and sent to clients in `RoomState`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all(serialize = "camelCase"), default)]
+pub struct SpeakingDistance {
+    /// Voices are at full volume within this distance
+    pub full_volume: f32,
+    /// ...and silent beyond this one
+    pub cutoff: f32,
+    /// Shape of the falloff in between: 1.0 is linear, higher drops off faster near the speaker
+    pub rolloff: f32,
+    /// Loudest a voice gets, below 1.0 for whisper-quiet rooms
+    pub max_gain: f32,
+}
+
+impl SpeakingDistance {
+    pub const DEFAULT: Self = Self {
+        full_volume: 2.0,
+        cutoff: 20.0,
+        rolloff: 1.0,
+        max_gain: 1.0,
+    };
+}
+
+impl Default for SpeakingDistance {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Gain for a voice heard from `distance` away, rounded to `GAIN_STEP`
+pub fn gain_for_distance(distance: f32, curve: &SpeakingDistance) -> f32 {
+    let remaining = 1.0 - (distance - curve.full_volume) / (curve.cutoff - curve.full_volume);
+    let gain = remaining.clamp(0.0, 1.0).powf(curve.rolloff) * curve.max_gain;
+    (gain / GAIN_STEP).round() * GAIN_STEP
+}
+
+fn distance(a: &Position, b: &Position) -> f32 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2) + (a.z - b.z).powi(2)).sqrt()
+}
+
+/// Per-room loop pushing `AudioGain` updates to each listener; ends once the room is dropped
+pub fn spawn_audio_gain_loop(room: &Arc<Room<StreamingSession>>) {
+    let room: Weak<Room<StreamingSession>> = Arc::downgrade(room);
+    actix::spawn(async move {
+        let Some(curve) = room.upgrade().map(|room| theme_for_room(&room.id).speaking_distance) else {
+            return;
+        };
+        let mut interval = tokio::time::interval(AUDIO_GAIN_TICK);
+        // (listener player_id, publisher_id) -> last gain sent
+        let mut last_sent: HashMap<(String, String), f32> = HashMap::new();
+        loop {
+            interval.tick().await;
+            let Some(room) = room.upgrade() else {
+                break;
+            };
+
+            let players = room.get_players_with_addrs();
+            let positions: HashMap<&str, &Position> = players
+                .iter()
+                .map(|(_, player)| (player.id.as_str(), &player.position))
+                .collect();
+            let publishers = room.get_all_publishers();
+
+            let mut current = HashMap::with_capacity(last_sent.len());
+            for (addr, listener) in &players {
+                for (publisher_id, owner_id) in &publishers {
+                    // Own media and publishers without an avatar (narrator, relays) aren't attenuated
+                    if *owner_id == listener.id {
+                        continue;
+                    }
+                    let Some(source) = positions.get(owner_id.as_str()) else {
+                        continue;
+                    };
+                    let gain = gain_for_distance(distance(&listener.position, source), &curve);
+                    let key = (listener.id.clone(), publisher_id.clone());
+                    if last_sent.get(&key) != Some(&gain) {
+                        addr.do_send(SendingMessage::AudioGain { publisher_id: publisher_id.clone(), gain });
+                    }
+                    current.insert(key, gain);
+                }
+            }
+            // Drops entries for players and publishers that are gone
+            last_sent = current;
+        }
+    });
+}
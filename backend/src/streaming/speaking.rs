@@ -0,0 +1,314 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+use rheomesh::router::Router;
+use rheomesh::subscribe_transport::SubscribeTransport;
+use rheomesh::subscriber::Subscriber;
+use rheomesh::transport::Transport;
+use tokio::sync::{mpsc, Mutex};
+use webrtc::api::interceptor_registry::register_default_interceptors;
+use webrtc::api::media_engine::MediaEngine;
+use webrtc::api::APIBuilder;
+use webrtc::ice_transport::ice_candidate::RTCIceCandidate;
+use webrtc::interceptor::registry::Registry;
+use webrtc::peer_connection::configuration::RTCConfiguration;
+use webrtc::peer_connection::sdp::session_description::RTCSessionDescription;
+use webrtc::peer_connection::RTCPeerConnection;
+use webrtc::rtp::packet::Packet;
+use webrtc::rtp_transceiver::rtp_codec::{RTCRtpHeaderExtensionCapability, RTPCodecType};
+use webrtc::rtp_transceiver::rtp_receiver::RTCRtpReceiver;
+use webrtc::rtp_transceiver::RTCRtpTransceiver;
+use webrtc::track::track_remote::TrackRemote;
+use webrtc_ice::network_type::NetworkType;
+
+use super::handler::{SendingMessage, StreamingSession};
+use super::room::Room;
+use super::tts::TTS_PLAYER_ID;
+
+/// RFC 6464 client-to-mixer audio level, which browsers add to every Opus packet
+const AUDIO_LEVEL_URI: &str = "urn:ietf:params:rtp-hdrext:ssrc-audio-level";
+/// How often the room's publishers are checked for ones to start or stop listening to
+const RECONCILE_INTERVAL: Duration = Duration::from_secs(1);
+/// Packets at or above this level (in -dBov: 0 is loudest, 127 silence) count as voice
+const VOICE_LEVEL: u8 = 50;
+/// Without the audio-level extension, Opus packets this big carry speech rather than comfort noise
+const VOICE_PAYLOAD_BYTES: usize = 40;
+/// A player keeps showing as speaking through pauses shorter than this
+const SILENCE_HOLD: Duration = Duration::from_millis(600);
+
+enum MonitorEvent {
+    Speaking { publisher_id: String, speaking: bool },
+    /// Video tracks are subscribed to before their kind is known, then dropped
+    NotAudio { publisher_id: String },
+}
+
+fn is_voice(packet: &Packet, level_id: Option<u8>) -> bool {
+    match level_id.and_then(|id| packet.header.get_extension(id)) {
+        // Low 7 bits are the level; the top bit is the sender's own VAD flag, which browsers don't set reliably
+        Some(level) if !level.is_empty() => level[0] & 0x7f <= VOICE_LEVEL,
+        _ => packet.payload.len() >= VOICE_PAYLOAD_BYTES,
+    }
+}
+
+/// Read one audio track until it ends, reporting when its speaker starts and stops
+async fn watch_track(track: Arc<TrackRemote>, level_id: Option<u8>, events: mpsc::UnboundedSender<MonitorEvent>) {
+    let publisher_id = track.id();
+    let mut speaking = false;
+    let mut last_voice = Instant::now();
+    loop {
+        // DTX stops packets entirely during silence, so time out instead of waiting for the next one
+        let voiced = match tokio::time::timeout(SILENCE_HOLD, track.read_rtp()).await {
+            Ok(Ok((packet, _))) => is_voice(&packet, level_id),
+            Ok(Err(_)) => break,
+            Err(_) => false,
+        };
+        if voiced {
+            last_voice = Instant::now();
+        }
+        let now_speaking = voiced || (speaking && last_voice.elapsed() < SILENCE_HOLD);
+        if now_speaking != speaking {
+            speaking = now_speaking;
+            let event = MonitorEvent::Speaking {
+                publisher_id: publisher_id.clone(),
+                speaking,
+            };
+            if events.send(event).is_err() {
+                return;
+            }
+        }
+    }
+    if speaking {
+        let _ = events.send(MonitorEvent::Speaking { publisher_id, speaking: false });
+    }
+}
+
+/// Take a subscribe transport's offer from the in-process peer and hand back its answer
+async fn answer(peer: &RTCPeerConnection, transport: &SubscribeTransport, offer: RTCSessionDescription) -> Result<(), String> {
+    peer.set_remote_description(offer).await.map_err(|e| e.to_string())?;
+    let answer = peer.create_answer(None).await.map_err(|e| e.to_string())?;
+    peer.set_local_description(answer.clone()).await.map_err(|e| e.to_string())?;
+    transport.set_answer(answer).await.map_err(|e| e.to_string())
+}
+
+/// Listens to every audio publisher in a room from an in-process peer, the same way a player
+/// subscribes, so clients get speaking indicators without analyzing every track themselves
+struct SpeakingMonitor {
+    peer: Arc<RTCPeerConnection>,
+    subscribe_transport: Arc<SubscribeTransport>,
+    /// publisher_id -> our subscription to it
+    subscribers: HashMap<String, Arc<Mutex<Subscriber>>>,
+    /// Video publishers, left alone once seen
+    ignored: HashSet<String>,
+    /// Offers from `subscribe` and renegotiation must not be answered concurrently
+    negotiation: Arc<Mutex<()>>,
+}
+
+impl SpeakingMonitor {
+    async fn start(router: Arc<Mutex<Router>>, events: mpsc::UnboundedSender<MonitorEvent>) -> Result<Self, String> {
+        // Loopback connection, no TURN needed
+        let mut config = rheomesh::config::WebRTCTransportConfig::default();
+        config.network_types = vec![NetworkType::Udp4];
+        let subscribe_transport = router.lock().await.create_subscribe_transport(config).await;
+
+        let mut media_engine = MediaEngine::default();
+        media_engine.register_default_codecs().map_err(|e| e.to_string())?;
+        media_engine
+            .register_header_extension(
+                RTCRtpHeaderExtensionCapability { uri: AUDIO_LEVEL_URI.to_owned() },
+                RTPCodecType::Audio,
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+        let registry = register_default_interceptors(Registry::new(), &mut media_engine).map_err(|e| e.to_string())?;
+        let api = APIBuilder::new()
+            .with_media_engine(media_engine)
+            .with_interceptor_registry(registry)
+            .build();
+        let peer = Arc::new(api.new_peer_connection(RTCConfiguration::default()).await.map_err(|e| e.to_string())?);
+
+        peer.on_track(Box::new(move |track: Arc<TrackRemote>, receiver: Arc<RTCRtpReceiver>, _: Arc<RTCRtpTransceiver>| {
+            let events = events.clone();
+            Box::pin(async move {
+                if track.kind() != RTPCodecType::Audio {
+                    let _ = events.send(MonitorEvent::NotAudio { publisher_id: track.id() });
+                    return;
+                }
+                let level_id = receiver
+                    .get_parameters()
+                    .await
+                    .header_extensions
+                    .iter()
+                    .find(|extension| extension.uri == AUDIO_LEVEL_URI)
+                    .map(|extension| extension.id as u8);
+                tokio::spawn(watch_track(track, level_id, events));
+            })
+        }));
+
+        // Trickle ICE both ways in-process
+        let transport = subscribe_transport.clone();
+        peer.on_ice_candidate(Box::new(move |candidate: Option<RTCIceCandidate>| {
+            let transport = transport.clone();
+            Box::pin(async move {
+                if let Some(Ok(candidate)) = candidate.map(|candidate| candidate.to_json()) {
+                    let _ = transport.add_ice_candidate(candidate).await;
+                }
+            })
+        }));
+        let peer_clone = peer.clone();
+        subscribe_transport.on_ice_candidate(Box::new(move |candidate| {
+            if let Ok(candidate) = candidate.to_json() {
+                let peer = peer_clone.clone();
+                tokio::spawn(async move {
+                    let _ = peer.add_ice_candidate(candidate).await;
+                });
+            }
+        })).await;
+
+        let negotiation = Arc::new(Mutex::new(()));
+        let peer_clone = peer.clone();
+        let transport = Arc::downgrade(&subscribe_transport);
+        let negotiation_clone = negotiation.clone();
+        subscribe_transport.on_negotiation_needed(Box::new(move |offer| {
+            let peer = peer_clone.clone();
+            let transport = transport.clone();
+            let negotiation = negotiation_clone.clone();
+            tokio::spawn(async move {
+                let Some(transport) = transport.upgrade() else {
+                    return;
+                };
+                let _guard = negotiation.lock().await;
+                if let Err(e) = answer(&peer, &transport, offer).await {
+                    tracing::warn!("Speaking monitor renegotiation failed: {}", e);
+                }
+            });
+        })).await;
+
+        Ok(Self {
+            peer,
+            subscribe_transport,
+            subscribers: HashMap::new(),
+            ignored: HashSet::new(),
+            negotiation,
+        })
+    }
+
+    /// Subscribe to new publishers and drop the ones that are gone or muted
+    async fn reconcile(&mut self, publishers: &[(String, String)], muted: &HashSet<String>) {
+        // The narrator has no avatar to highlight
+        let current: HashSet<&str> = publishers
+            .iter()
+            .filter(|(publisher_id, owner_id)| owner_id != TTS_PLAYER_ID && !muted.contains(publisher_id))
+            .map(|(publisher_id, _)| publisher_id.as_str())
+            .collect();
+        self.ignored.retain(|publisher_id| current.contains(publisher_id.as_str()));
+        let gone: Vec<String> = self.subscribers.keys().filter(|id| !current.contains(id.as_str())).cloned().collect();
+        for publisher_id in gone {
+            self.unsubscribe(&publisher_id).await;
+        }
+
+        for publisher_id in current {
+            if self.subscribers.contains_key(publisher_id) || self.ignored.contains(publisher_id) {
+                continue;
+            }
+            let _guard = self.negotiation.lock().await;
+            match self.subscribe_transport.subscribe(publisher_id.to_string()).await {
+                Ok((subscriber, offer)) => {
+                    self.subscribers.insert(publisher_id.to_string(), subscriber);
+                    if let Err(e) = answer(&self.peer, &self.subscribe_transport, offer).await {
+                        tracing::warn!("Speaking monitor couldn't listen to {}: {}", publisher_id, e);
+                    }
+                }
+                Err(e) => tracing::debug!("Speaking monitor couldn't subscribe to {}: {}", publisher_id, e),
+            }
+        }
+    }
+
+    async fn unsubscribe(&mut self, publisher_id: &str) {
+        if let Some(subscriber) = self.subscribers.remove(publisher_id) {
+            subscriber.lock().await.close().await;
+        }
+    }
+
+    async fn close(&mut self) {
+        for (_, subscriber) in self.subscribers.drain() {
+            subscriber.lock().await.close().await;
+        }
+        let _ = self.subscribe_transport.close().await;
+        let _ = self.peer.close().await;
+    }
+}
+
+/// Per-room loop broadcasting `SpeakingChanged` as players start and stop talking; ends once the room is dropped
+pub fn spawn_speaking_monitor(room: &Arc<Room<StreamingSession>>) {
+    if !crate::config::get().media.speaking_detection {
+        return;
+    }
+    let router = room.router.clone();
+    let room: Weak<Room<StreamingSession>> = Arc::downgrade(room);
+    actix::spawn(async move {
+        let (events_tx, mut events) = mpsc::unbounded_channel();
+        let mut monitor = match SpeakingMonitor::start(router, events_tx).await {
+            Ok(monitor) => monitor,
+            Err(e) => {
+                tracing::warn!("Speaking detection unavailable: {}", e);
+                return;
+            }
+        };
+        // player_id -> their publishers currently carrying voice (mic and screen-share audio can overlap)
+        let mut speaking: HashMap<String, HashSet<String>> = HashMap::new();
+        // publisher_id -> owner, remembered while speaking so the stop still goes out after it unpublishes
+        let mut voice_owners: HashMap<String, String> = HashMap::new();
+        let mut interval = tokio::time::interval(RECONCILE_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    let Some(room) = room.upgrade() else {
+                        break;
+                    };
+                    monitor.reconcile(&room.get_all_publishers(), &room.get_muted_publishers()).await;
+                }
+                Some(event) = events.recv() => {
+                    let (publisher_id, is_speaking) = match event {
+                        MonitorEvent::Speaking { publisher_id, speaking } => (publisher_id, speaking),
+                        MonitorEvent::NotAudio { publisher_id } => {
+                            monitor.unsubscribe(&publisher_id).await;
+                            monitor.ignored.insert(publisher_id);
+                            continue;
+                        }
+                    };
+                    let Some(room) = room.upgrade() else {
+                        break;
+                    };
+                    let owner = if is_speaking {
+                        room.get_publisher_owner(&publisher_id)
+                    } else {
+                        voice_owners.remove(&publisher_id)
+                    };
+                    let Some(player_id) = owner else {
+                        continue;
+                    };
+                    let voices = speaking.entry(player_id.clone()).or_default();
+                    let was_speaking = !voices.is_empty();
+                    if is_speaking {
+                        voice_owners.insert(publisher_id.clone(), player_id.clone());
+                        voices.insert(publisher_id);
+                    } else {
+                        voices.remove(&publisher_id);
+                    }
+                    let now_speaking = !voices.is_empty();
+                    if now_speaking == was_speaking {
+                        continue;
+                    }
+                    if !now_speaking {
+                        speaking.remove(&player_id);
+                    }
+                    let message = SendingMessage::SpeakingChanged { player_id, speaking: now_speaking };
+                    for addr in room.get_all_addrs() {
+                        addr.do_send(message.clone());
+                    }
+                }
+            }
+        }
+        monitor.close().await;
+    });
+}
@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+
+use super::codecs::{CodecSettings, VideoCodec};
+use super::collision::Collider;
+use super::handler::Position;
+use super::instances::base_room_id;
+use super::motion::MovementEffects;
+use super::spatial_audio::SpeakingDistance;
+
+/// A looping positional sound placed in the room (fountain, arcade machine, ...)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AmbientEmitter {
+    pub sound: String,
+    pub position: Position,
+    /// Gain at the emitter, 0.0 - 1.0
+    pub volume: f32,
+    /// Distance at which the sound fades out completely
+    pub radius: f32,
+}
+
+/// Walkable floor area on the x/z plane; positions outside it are pulled back to the edge
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all(serialize = "camelCase"))]
+pub struct WorldBounds {
+    pub min_x: f32,
+    pub max_x: f32,
+    pub min_z: f32,
+    pub max_z: f32,
+}
+
+impl WorldBounds {
+    const fn square(half_extent: f32) -> Self {
+        Self {
+            min_x: -half_extent,
+            max_x: half_extent,
+            min_z: -half_extent,
+            max_z: half_extent,
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        self.min_x <= self.max_x && self.min_z <= self.max_z
+    }
+
+    /// Pull a position back inside the bounds; height is left alone
+    pub fn clamp(&self, position: Position) -> Position {
+        Position {
+            x: position.x.clamp(self.min_x, self.max_x),
+            y: position.y,
+            z: position.z.clamp(self.min_z, self.max_z),
+        }
+    }
+}
+
+/// Players closer than this to a spawn point are considered to be standing on it
+const SPAWN_CLEARANCE: f32 = 1.5;
+
+fn ground_distance(a: &Position, b: &Position) -> f32 {
+    (a.x - b.x).hypot(a.z - b.z)
+}
+
+fn spawn(x: f32, z: f32) -> Position {
+    Position { x, y: 0.0, z }
+}
+
+fn emitter(sound: &str, x: f32, z: f32, volume: f32, radius: f32) -> AmbientEmitter {
+    AmbientEmitter {
+        sound: sound.to_string(),
+        position: Position { x, y: 0.0, z },
+        volume,
+        radius,
+    }
+}
+
+/// Per-theme content shared by every player in a themed room. Deployments add or override themes
+/// under `[themes.<base room id>]` in `config.toml`; fields left out keep the default theme's values
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ThemeInfo {
+    /// Cutscenes a host is allowed to trigger in this theme
+    pub cutscenes: Vec<String>,
+    /// Which derived movement events are emitted by default
+    pub movement_effects: MovementEffects,
+    /// Ambient sound layout every client renders identically
+    pub ambient_sounds: Vec<AmbientEmitter>,
+    /// How far voices carry and how they fade
+    pub speaking_distance: SpeakingDistance,
+    /// Codec parameters for the room's media
+    pub codecs: CodecSettings,
+    /// Where joining players are placed, tried in order
+    pub spawn_points: Vec<Position>,
+    /// Walkable area every position is clamped to
+    pub bounds: WorldBounds,
+    /// Solid geometry players can't walk through
+    pub colliders: Vec<Collider>,
+}
+
+/// Theme for rooms without one of their own
+pub const DEFAULT_THEME_ID: &str = "default";
+
+fn cutscenes(ids: &[&str]) -> Vec<String> {
+    ids.iter().map(|id| id.to_string()).collect()
+}
+
+impl Default for ThemeInfo {
+    fn default() -> Self {
+        Self {
+            cutscenes: cutscenes(&["countdown", "fireworks"]),
+            movement_effects: MovementEffects { footsteps: true, trails: false },
+            ambient_sounds: vec![emitter("fountain", 0.0, 0.0, 0.6, 12.0)],
+            speaking_distance: SpeakingDistance::DEFAULT,
+            codecs: CodecSettings::default(),
+            spawn_points: vec![
+                spawn(0.0, 4.0),
+                spawn(3.0, 4.0),
+                spawn(-3.0, 4.0),
+                spawn(0.0, 7.0),
+                spawn(3.0, 7.0),
+                spawn(-3.0, 7.0),
+            ],
+            bounds: WorldBounds::square(25.0),
+            colliders: Vec::new(),
+        }
+    }
+}
+
+impl ThemeInfo {
+    /// The first spawn point nobody is standing on; when all are taken, the one furthest from anyone
+    pub fn free_spawn(&self, occupied: &[Position]) -> Position {
+        let nearest = |point: &Position| {
+            occupied.iter().map(|other| ground_distance(point, other)).fold(f32::INFINITY, f32::min)
+        };
+        self.spawn_points
+            .iter()
+            .find(|point| nearest(point) >= SPAWN_CLEARANCE)
+            .or_else(|| self.spawn_points.iter().max_by(|a, b| nearest(a).total_cmp(&nearest(b))))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Catch configured themes that would break clamping or the voice falloff
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.bounds.is_valid() {
+            return Err("bounds need min_x <= max_x and min_z <= max_z".to_string());
+        }
+        if self.speaking_distance.cutoff <= self.speaking_distance.full_volume {
+            return Err("speaking_distance.cutoff must be beyond full_volume".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Themes that ship with the server, keyed by base room ID; `config.toml` can override any of them
+pub fn builtin_themes() -> HashMap<String, ThemeInfo> {
+    let themes = [
+        (DEFAULT_THEME_ID, ThemeInfo::default()),
+        (
+            "music-lounge",
+            ThemeInfo {
+                cutscenes: cutscenes(&["countdown", "stage-lights", "encore"]),
+                ambient_sounds: vec![emitter("crowd-murmur", 0.0, -8.0, 0.3, 15.0)],
+                // Hi-fi stereo for live music; DTX would chop off quiet notes
+                codecs: CodecSettings {
+                    opus_stereo: true,
+                    opus_max_average_bitrate: Some(256_000),
+                    opus_dtx: Some(false),
+                    ..CodecSettings::default()
+                },
+                ..ThemeInfo::default()
+            },
+        ),
+        (
+            "art-studio",
+            ThemeInfo {
+                cutscenes: cutscenes(&["countdown", "gallery-reveal"]),
+                ambient_sounds: vec![emitter("rain-window", -6.0, 4.0, 0.4, 10.0)],
+                ..ThemeInfo::default()
+            },
+        ),
+        (
+            "focus-den",
+            ThemeInfo {
+                cutscenes: cutscenes(&["countdown", "break-time"]),
+                bounds: WorldBounds::square(12.0),
+                colliders: vec![Collider::new(4.0, -6.0, 6.0, -4.5)],
+                // Keep the study room quiet
+                movement_effects: MovementEffects { footsteps: false, trails: false },
+                ambient_sounds: vec![
+                    emitter("fireplace", 5.0, -5.0, 0.5, 10.0),
+                    emitter("clock-tick", -5.0, -5.0, 0.2, 4.0),
+                ],
+                // Voice only, keep it light; webcams get just enough for a study-buddy view
+                codecs: CodecSettings {
+                    opus_max_average_bitrate: Some(24_000),
+                    max_video_kbps: Some(300),
+                    ..CodecSettings::default()
+                },
+                ..ThemeInfo::default()
+            },
+        ),
+        (
+            "gaming-corner",
+            ThemeInfo {
+                cutscenes: cutscenes(&["countdown", "round-start", "victory"]),
+                movement_effects: MovementEffects { footsteps: true, trails: true },
+                ambient_sounds: vec![
+                    emitter("arcade-machine", -6.0, -6.0, 0.6, 8.0),
+                    emitter("arcade-machine", 6.0, -6.0, 0.6, 8.0),
+                ],
+                // The arcade cabinets the machine sounds come from
+                colliders: vec![
+                    Collider::new(-7.0, -7.0, -5.0, -5.5),
+                    Collider::new(5.0, -7.0, 7.0, -5.5),
+                ],
+                ..ThemeInfo::default()
+            },
+        ),
+        (
+            "cinema",
+            ThemeInfo {
+                cutscenes: cutscenes(&["countdown", "lights-down", "intermission"]),
+                movement_effects: MovementEffects { footsteps: false, trails: false },
+                ambient_sounds: vec![emitter("projector-hum", 0.0, 10.0, 0.25, 6.0)],
+                // Screens are the point here: prefer VP9's better quality per bit
+                codecs: CodecSettings {
+                    video: vec![VideoCodec::Vp9, VideoCodec::H264, VideoCodec::Vp8],
+                    max_video_kbps: Some(2500),
+                    ..CodecSettings::default()
+                },
+                // Whispers carry across the theater without drowning out the film
+                speaking_distance: SpeakingDistance {
+                    full_volume: 3.0,
+                    cutoff: 40.0,
+                    rolloff: 2.0,
+                    max_gain: 0.5,
+                },
+                // Seats face the screen at the far end
+                spawn_points: vec![
+                    spawn(-4.0, -6.0),
+                    spawn(-2.0, -6.0),
+                    spawn(0.0, -6.0),
+                    spawn(2.0, -6.0),
+                    spawn(4.0, -6.0),
+                    spawn(-4.0, -9.0),
+                    spawn(-2.0, -9.0),
+                    spawn(0.0, -9.0),
+                    spawn(2.0, -9.0),
+                    spawn(4.0, -9.0),
+                ],
+                bounds: WorldBounds {
+                    min_x: -10.0,
+                    max_x: 10.0,
+                    min_z: -14.0,
+                    max_z: 12.0,
+                },
+                // The screen behind the stage, and the projection booth at the back
+                colliders: vec![
+                    Collider::new(-8.0, 5.5, 10.0, 6.5),
+                    Collider::new(-2.0, 9.0, 2.0, 12.0),
+                ],
+            },
+        ),
+        (
+            "city",
+            ThemeInfo {
+                cutscenes: cutscenes(&["countdown", "fireworks", "parade"]),
+                movement_effects: MovementEffects { footsteps: true, trails: true },
+                ambient_sounds: vec![
+                    emitter("traffic", 0.0, 20.0, 0.5, 25.0),
+                    emitter("fountain", 0.0, 0.0, 0.6, 12.0),
+                ],
+                // Busy streets: only people right next to you are heard
+                speaking_distance: SpeakingDistance {
+                    full_volume: 1.5,
+                    cutoff: 10.0,
+                    rolloff: 1.0,
+                    max_gain: 1.0,
+                },
+                // Plazas around the central fountain
+                spawn_points: vec![
+                    spawn(0.0, 8.0),
+                    spawn(8.0, 0.0),
+                    spawn(0.0, -8.0),
+                    spawn(-8.0, 0.0),
+                    spawn(6.0, 6.0),
+                    spawn(-6.0, -6.0),
+                ],
+                bounds: WorldBounds::square(60.0),
+                ..ThemeInfo::default()
+            },
+        ),
+    ];
+    themes.into_iter().map(|(id, theme)| (id.to_string(), theme)).collect()
+}
+
+/// The theme for a room id (e.g. "music-lounge"), from `config.toml` or the built-in ones
+pub fn theme_for_room(room_id: &str) -> &'static ThemeInfo {
+    // Language variants (`music-lounge-es`) and overflow instances (`music-lounge-2`) share their base room's theme
+    let themes = &crate::config::get().themes;
+    themes
+        .get(base_room_id(room_id))
+        .or_else(|| themes.get(DEFAULT_THEME_ID))
+        .expect("the default theme is always loaded")
+}
@@ -0,0 +1,68 @@
+use std::sync::{Arc, LazyLock, Weak};
+use std::time::Duration;
+use actix::dev::ToEnvelope;
+use actix::{Actor, Handler};
+
+use super::handler::{SendingMessage, StreamingSession};
+use super::interest::within_interest;
+use super::room::Room;
+
+const DEFAULT_MOVEMENT_TICK_HZ: u32 = 20;
+
+/// `MOVEMENT_TICK_HZ` env var: how often buffered movement is broadcast
+static MOVEMENT_TICK: LazyLock<Duration> = LazyLock::new(|| {
+    let hz = std::env::var("MOVEMENT_TICK_HZ")
+        .ok()
+        .and_then(|hz| hz.parse::<u32>().ok())
+        .filter(|hz| *hz > 0)
+        .unwrap_or(DEFAULT_MOVEMENT_TICK_HZ);
+    Duration::from_secs(1) / hz
+});
+
+/// Per-room loop flushing buffered moves as one `PlayersMoved` per player per tick; ends once the room is dropped
+pub fn spawn_movement_tick_loop(room: &Arc<Room<StreamingSession>>) {
+    let room: Weak<Room<StreamingSession>> = Arc::downgrade(room);
+    actix::spawn(async move {
+        let mut interval = tokio::time::interval(*MOVEMENT_TICK);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            let Some(room) = room.upgrade() else {
+                break;
+            };
+            broadcast_pending_moves(&room);
+        }
+    });
+}
+
+/// Send each player the moves buffered since the last tick that are within their interest
+pub fn broadcast_pending_moves<T>(room: &Room<T>)
+where
+    T: Actor + Handler<SendingMessage>,
+    T::Context: ToEnvelope<T, SendingMessage>,
+{
+    let moves = room.take_pending_moves();
+    if moves.is_empty() {
+        return;
+    }
+    let seq = room.next_move_seq();
+    let server_time = chrono::Utc::now().timestamp_millis();
+    for (addr, player) in room.get_players_with_addrs() {
+        let players: Vec<_> = moves
+            .iter()
+            .filter(|update| update.player_id != player.id && within_interest(&update.position, &player.position))
+            .cloned()
+            .collect();
+        if !players.is_empty() {
+            addr.do_send(SendingMessage::PlayersMoved { players, seq, server_time });
+        }
+    }
+    // Observers have no position, so they see every move
+    for addr in room.get_observers() {
+        addr.do_send(SendingMessage::PlayersMoved {
+            players: moves.clone(),
+            seq,
+            server_time,
+        });
+    }
+}
@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// How long a handoff code stays valid
+pub const TRANSFER_CODE_TTL: Duration = Duration::from_secs(120);
+const TRANSFER_CODE_LENGTH: usize = 6;
+/// No 0/O or 1/I/L so codes survive being read off one screen and typed into another
+const TRANSFER_CODE_ALPHABET: &[u8] = b"23456789ABCDEFGHJKMNPQRSTUVWXYZ";
+
+/// A player waiting for another device to take over their session
+pub struct PendingTransfer {
+    pub room_id: String,
+    pub player_id: String,
+    issued_at: Instant,
+}
+
+/// Short-lived codes for moving a session between devices
+#[derive(Default)]
+pub struct TransferRegistry {
+    pending: HashMap<String, PendingTransfer>,
+}
+
+impl TransferRegistry {
+    /// Issue a code for a player, replacing any earlier code they requested
+    pub fn issue(&mut self, room_id: &str, player_id: &str) -> String {
+        self.pending.retain(|_, transfer| transfer.issued_at.elapsed() < TRANSFER_CODE_TTL && transfer.player_id != player_id);
+
+        let code = loop {
+            let random = uuid::Uuid::new_v4();
+            let code: String = random
+                .as_bytes()
+                .iter()
+                .take(TRANSFER_CODE_LENGTH)
+                .map(|byte| TRANSFER_CODE_ALPHABET[*byte as usize % TRANSFER_CODE_ALPHABET.len()] as char)
+                .collect();
+            if !self.pending.contains_key(&code) {
+                break code;
+            }
+        };
+        self.pending.insert(code.clone(), PendingTransfer {
+            room_id: room_id.to_string(),
+            player_id: player_id.to_string(),
+            issued_at: Instant::now(),
+        });
+        code
+    }
+
+    /// Consume a code; each code works once
+    pub fn redeem(&mut self, code: &str) -> Option<PendingTransfer> {
+        let transfer = self.pending.remove(&code.trim().to_ascii_uppercase())?;
+        (transfer.issued_at.elapsed() < TRANSFER_CODE_TTL).then_some(transfer)
+    }
+}
@@ -0,0 +1,79 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use rheomesh::config::WebRTCTransportConfig;
+use rheomesh::publish_transport::PublishTransport;
+use rheomesh::router::Router;
+use rheomesh::subscribe_transport::SubscribeTransport;
+use rheomesh::transport::Transport;
+use tokio::sync::Mutex;
+
+/// Number of warmed transport pairs kept ready per room
+const POOL_SIZE: usize = 2;
+/// Warmed transports carry ICE credentials, so don't hand out ones that may have expired
+const MAX_WARM_AGE: Duration = Duration::from_secs(300);
+
+struct WarmTransports {
+    publish: PublishTransport,
+    subscribe: SubscribeTransport,
+    created_at: Instant,
+}
+
+/// Pre-created publish/subscribe transports so joins skip transport construction
+#[derive(Default)]
+pub struct TransportPool {
+    warm: Mutex<Vec<WarmTransports>>,
+    refilling: AtomicBool,
+}
+
+impl TransportPool {
+    /// Take a warmed transport pair if one is available and still fresh
+    pub async fn take(&self) -> Option<(PublishTransport, SubscribeTransport)> {
+        let mut warm = self.warm.lock().await;
+        while let Some(pair) = warm.pop() {
+            if pair.created_at.elapsed() < MAX_WARM_AGE {
+                return Some((pair.publish, pair.subscribe));
+            }
+            let _ = pair.publish.close().await;
+            let _ = pair.subscribe.close().await;
+        }
+        None
+    }
+
+    /// Top the pool back up in the background after a join
+    pub async fn refill(&self, router: &Arc<Mutex<Router>>, config: WebRTCTransportConfig) {
+        if self.refilling.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        loop {
+            if self.warm.lock().await.len() >= POOL_SIZE {
+                break;
+            }
+            let (publish, subscribe) = {
+                let router = router.lock().await;
+                (
+                    router.create_publish_transport(config.clone()).await,
+                    router.create_subscribe_transport(config.clone()).await,
+                )
+            };
+            self.warm.lock().await.push(WarmTransports {
+                publish,
+                subscribe,
+                created_at: Instant::now(),
+            });
+        }
+        tracing::debug!("Transport pool refilled to {}", POOL_SIZE);
+
+        self.refilling.store(false, Ordering::SeqCst);
+    }
+
+    /// Close all warmed transports (room teardown)
+    pub async fn drain(&self) {
+        let warm = std::mem::take(&mut *self.warm.lock().await);
+        for pair in warm {
+            let _ = pair.publish.close().await;
+            let _ = pair.subscribe.close().await;
+        }
+    }
+}
@@ -0,0 +1,292 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use rheomesh::publish_transport::PublishTransport;
+use rheomesh::publisher::Publisher;
+use rheomesh::router::Router;
+use rheomesh::transport::Transport;
+use serde::Serialize;
+use tokio::sync::{Mutex, Notify};
+use webrtc::api::interceptor_registry::register_default_interceptors;
+use webrtc::api::media_engine::{MediaEngine, MIME_TYPE_OPUS};
+use webrtc::api::APIBuilder;
+use webrtc::ice_transport::ice_candidate::RTCIceCandidate;
+use webrtc::interceptor::registry::Registry;
+use webrtc::media::Sample;
+use webrtc::peer_connection::configuration::RTCConfiguration;
+use webrtc::peer_connection::RTCPeerConnection;
+use webrtc::rtp_transceiver::rtp_codec::RTCRtpCodecCapability;
+use webrtc::track::track_local::track_local_static_sample::TrackLocalStaticSample;
+use webrtc::track::track_local::TrackLocal;
+use webrtc_ice::network_type::NetworkType;
+
+/// Player ID the narrator's publisher is registered under
+pub const TTS_PLAYER_ID: &str = "tts";
+/// Longest chat message read aloud; the rest is cut off
+const MAX_TTS_CHARS: usize = 300;
+/// Lines waiting to be read out; a busy chat drops the oldest so the narrator stays close to live
+const MAX_QUEUED_LINES: usize = 5;
+const TTS_REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+const TTS_PUBLISH_TIMEOUT: Duration = Duration::from_secs(30);
+/// Opus always runs at 48kHz regardless of input rate
+const OPUS_CLOCK_RATE: u64 = 48000;
+
+/// Lines waiting for the speaker task, oldest first
+#[derive(Default)]
+struct SpeechQueue {
+    lines: std::sync::Mutex<VecDeque<String>>,
+    ready: Notify,
+    closed: AtomicBool,
+}
+
+impl SpeechQueue {
+    fn push(&self, line: String) {
+        let mut lines = self.lines.lock().unwrap();
+        if lines.len() >= MAX_QUEUED_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+        drop(lines);
+        self.ready.notify_one();
+    }
+
+    fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        self.ready.notify_one();
+    }
+
+    /// Next line to speak, or None once the narrator is closed
+    async fn next(&self) -> Option<String> {
+        loop {
+            if self.closed.load(Ordering::SeqCst) {
+                return None;
+            }
+            if let Some(line) = self.lines.lock().unwrap().pop_front() {
+                return Some(line);
+            }
+            self.ready.notified().await;
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct TtsRequest<'a> {
+    text: &'a str,
+    voice: &'a str,
+}
+
+/// Reads room chat aloud: text goes to the `TTS_URL` service (which returns Ogg/Opus) and the audio
+/// is published into the room from an in-process peer, like any other player's microphone
+pub struct TtsNarrator {
+    pub publisher_id: String,
+    pub voice: String,
+    queue: Arc<SpeechQueue>,
+    peer: Arc<RTCPeerConnection>,
+    publish_transport: Arc<PublishTransport>,
+    publisher: Arc<Mutex<Publisher>>,
+}
+
+impl TtsNarrator {
+    /// Connect a narrator to the room's router and start publishing
+    pub async fn start(router: Arc<Mutex<Router>>, voice: Option<String>) -> Result<Self, String> {
+        let url = std::env::var("TTS_URL").map_err(|_| "text-to-speech is not configured on this server".to_string())?;
+        let voice = voice
+            .or_else(|| std::env::var("TTS_DEFAULT_VOICE").ok())
+            .unwrap_or_else(|| "default".to_string());
+
+        // Loopback connection, no TURN needed
+        let mut config = rheomesh::config::WebRTCTransportConfig::default();
+        config.network_types = vec![NetworkType::Udp4];
+        let publish_transport = router.lock().await.create_publish_transport(config).await;
+
+        let mut media_engine = MediaEngine::default();
+        media_engine.register_default_codecs().map_err(|e| e.to_string())?;
+        let registry = register_default_interceptors(Registry::new(), &mut media_engine).map_err(|e| e.to_string())?;
+        let api = APIBuilder::new()
+            .with_media_engine(media_engine)
+            .with_interceptor_registry(registry)
+            .build();
+        let peer = Arc::new(api.new_peer_connection(RTCConfiguration::default()).await.map_err(|e| e.to_string())?);
+
+        let track = Arc::new(TrackLocalStaticSample::new(
+            RTCRtpCodecCapability {
+                mime_type: MIME_TYPE_OPUS.to_owned(),
+                clock_rate: OPUS_CLOCK_RATE as u32,
+                channels: 2,
+                sdp_fmtp_line: "minptime=10;useinbandfec=1".to_owned(),
+                rtcp_feedback: vec![],
+            },
+            uuid::Uuid::new_v4().to_string(),
+            "webhangin-tts".to_owned(),
+        ));
+        let rtp_sender = peer
+            .add_track(track.clone() as Arc<dyn TrackLocal + Send + Sync>)
+            .await
+            .map_err(|e| e.to_string())?;
+        // RTCP has to be read for interceptors (NACK etc.) to work
+        tokio::spawn(async move {
+            let mut buf = vec![0u8; 1500];
+            while rtp_sender.read(&mut buf).await.is_ok() {}
+        });
+
+        // Trickle ICE both ways in-process
+        let transport = publish_transport.clone();
+        peer.on_ice_candidate(Box::new(move |candidate: Option<RTCIceCandidate>| {
+            let transport = transport.clone();
+            Box::pin(async move {
+                if let Some(Ok(candidate)) = candidate.map(|candidate| candidate.to_json()) {
+                    let _ = transport.add_ice_candidate(candidate).await;
+                }
+            })
+        }));
+        let peer_clone = peer.clone();
+        publish_transport.on_ice_candidate(Box::new(move |candidate| {
+            if let Ok(candidate) = candidate.to_json() {
+                let peer = peer_clone.clone();
+                tokio::spawn(async move {
+                    let _ = peer.add_ice_candidate(candidate).await;
+                });
+            }
+        })).await;
+
+        let offer = peer.create_offer(None).await.map_err(|e| e.to_string())?;
+        peer.set_local_description(offer.clone()).await.map_err(|e| e.to_string())?;
+        let answer = publish_transport.get_answer(offer).await.map_err(|e| e.to_string())?;
+        peer.set_remote_description(answer).await.map_err(|e| e.to_string())?;
+
+        let publisher = tokio::time::timeout(TTS_PUBLISH_TIMEOUT, publish_transport.publish(track.id().to_string()))
+            .await
+            .map_err(|_| "timed out publishing narrator track".to_string())?
+            .map_err(|e| e.to_string())?;
+        let publisher_id = publisher.lock().await.track_id.clone();
+
+        let queue = Arc::new(SpeechQueue::default());
+        tokio::spawn(run_speaker(track, url, voice.clone(), queue.clone()));
+
+        tracing::info!("🔊 TTS narrator publishing as {} (voice: {})", publisher_id, voice);
+        Ok(Self {
+            publisher_id,
+            voice,
+            queue,
+            peer,
+            publish_transport,
+            publisher,
+        })
+    }
+
+    /// Queue a chat message to be read out
+    pub fn speak(&self, sender: &str, message: &str) {
+        let text: String = format!("{} says: {}", sender, message).chars().take(MAX_TTS_CHARS).collect();
+        self.queue.push(text);
+    }
+
+    pub async fn close(&self) {
+        self.queue.close();
+        self.publisher.lock().await.close().await;
+        let _ = self.publish_transport.close().await;
+        let _ = self.peer.close().await;
+    }
+}
+
+/// Synthesize queued lines one at a time so speech never overlaps
+async fn run_speaker(track: Arc<TrackLocalStaticSample>, url: String, voice: String, pending: Arc<SpeechQueue>) {
+    let client = reqwest::Client::new();
+    while let Some(text) = pending.next().await {
+        let response = client
+            .post(&url)
+            .timeout(TTS_REQUEST_TIMEOUT)
+            .json(&TtsRequest { text: &text, voice: &voice })
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status());
+        let audio = match response {
+            Ok(resp) => match resp.bytes().await {
+                Ok(audio) => audio,
+                Err(e) => {
+                    tracing::warn!("TTS response read failed: {}", e);
+                    continue;
+                }
+            },
+            Err(e) => {
+                tracing::warn!("TTS request failed: {}", e);
+                continue;
+            }
+        };
+        if let Err(e) = play_ogg(&track, &audio).await {
+            tracing::warn!("TTS playback failed: {}", e);
+        }
+    }
+}
+
+/// Split an Ogg stream into the packets its pages carry. A packet spans segments until one shorter
+/// than 255 bytes, and may continue onto the next page
+fn ogg_packets(audio: &[u8]) -> Result<Vec<Vec<u8>>, String> {
+    let mut packets = Vec::new();
+    let mut packet = Vec::new();
+    let mut offset = 0;
+    while offset < audio.len() {
+        let header = audio.get(offset..offset + 27).ok_or("truncated Ogg page header")?;
+        if &header[..4] != b"OggS" {
+            return Err("missing Ogg capture pattern".to_string());
+        }
+        let segment_count = header[26] as usize;
+        let segments = audio
+            .get(offset + 27..offset + 27 + segment_count)
+            .ok_or("truncated Ogg segment table")?;
+        let mut body = offset + 27 + segment_count;
+        for &segment in segments {
+            let data = audio.get(body..body + segment as usize).ok_or("truncated Ogg page")?;
+            packet.extend_from_slice(data);
+            body += segment as usize;
+            if segment < 255 {
+                packets.push(std::mem::take(&mut packet));
+            }
+        }
+        offset = body;
+    }
+    Ok(packets)
+}
+
+/// Playback length of an Opus packet from its TOC byte (RFC 6716 section 3.1)
+fn opus_packet_duration(packet: &[u8]) -> Duration {
+    let Some(&toc) = packet.first() else {
+        return Duration::ZERO;
+    };
+    let config = toc >> 3;
+    let frame_micros: u64 = match config {
+        0..=11 => [10_000, 20_000, 40_000, 60_000][(config % 4) as usize],
+        12..=15 => [10_000, 20_000][(config % 2) as usize],
+        _ => [2_500, 5_000, 10_000, 20_000][(config % 4) as usize],
+    };
+    let frames = match toc & 0x3 {
+        0 => 1,
+        1 | 2 => 2,
+        _ => packet.get(1).map_or(0, |count| (count & 0x3f) as u64),
+    };
+    Duration::from_micros(frame_micros * frames)
+}
+
+/// Write the Opus packets of an Ogg stream to the track in real time, one sample per packet
+async fn play_ogg(track: &TrackLocalStaticSample, audio: &[u8]) -> Result<(), String> {
+    for packet in ogg_packets(audio)? {
+        // Identification and comment headers carry no audio
+        if packet.starts_with(b"OpusHead") || packet.starts_with(b"OpusTags") {
+            continue;
+        }
+        let duration = opus_packet_duration(&packet);
+        if duration.is_zero() {
+            continue;
+        }
+        track
+            .write_sample(&Sample {
+                data: packet.into(),
+                duration,
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+        tokio::time::sleep(duration).await;
+    }
+    Ok(())
+}
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use webrtc::ice_transport::ice_server::RTCIceServer;
+use webrtc::turn::client::{Client, ClientConfig};
+use webrtc::util::Conn;
+
+/// How often every configured relay is probed
+pub const TURN_PROBE_INTERVAL: Duration = Duration::from_secs(30);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Relays failing this many probes in a row are left out of new sessions' ICE lists
+const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+/// Weight of the newest probe in the moving averages
+const SMOOTHING: f64 = 0.3;
+/// Relays not probed yet rank as if they had this RTT
+const UNPROBED_RTT_MS: f64 = 250.0;
+
+#[derive(Debug, Clone)]
+struct RelayHealth {
+    /// Moving average of probe success, 0.0 - 1.0
+    success_rate: f64,
+    rtt_ms: Option<f64>,
+    consecutive_failures: u32,
+}
+
+impl Default for RelayHealth {
+    fn default() -> Self {
+        Self {
+            success_rate: 1.0,
+            rtt_ms: None,
+            consecutive_failures: 0,
+        }
+    }
+}
+
+impl RelayHealth {
+    fn is_dead(&self) -> bool {
+        self.consecutive_failures >= MAX_CONSECUTIVE_FAILURES
+    }
+
+    /// Higher is better: reliability first, then latency
+    fn score(&self) -> f64 {
+        self.success_rate * 1000.0 - self.rtt_ms.unwrap_or(UNPROBED_RTT_MS)
+    }
+}
+
+/// Live scores for each TURN URL, from background allocation probes
+#[derive(Debug, Default)]
+pub struct TurnHealth {
+    relays: HashMap<String, RelayHealth>,
+}
+
+fn is_turn_url(url: &str) -> bool {
+    url.starts_with("turn:") || url.starts_with("turns:")
+}
+
+impl TurnHealth {
+    pub fn record(&mut self, url: &str, result: &Result<Duration, String>) {
+        let relay = self.relays.entry(url.to_string()).or_default();
+        match result {
+            Ok(rtt) => {
+                let rtt_ms = rtt.as_secs_f64() * 1000.0;
+                relay.success_rate = relay.success_rate * (1.0 - SMOOTHING) + SMOOTHING;
+                relay.rtt_ms = Some(relay.rtt_ms.map_or(rtt_ms, |avg| avg * (1.0 - SMOOTHING) + rtt_ms * SMOOTHING));
+                relay.consecutive_failures = 0;
+            }
+            Err(e) => {
+                relay.success_rate *= 1.0 - SMOOTHING;
+                relay.consecutive_failures += 1;
+                if relay.consecutive_failures == MAX_CONSECUTIVE_FAILURES {
+                    tracing::warn!("TURN relay {} is down ({}), leaving it out of new sessions", url, e);
+                }
+            }
+        }
+    }
+
+    /// Forget relays that are no longer configured
+    pub fn retain(&mut self, urls: &[String]) {
+        self.relays.retain(|url, _| urls.contains(url));
+    }
+
+    fn health(&self, url: &str) -> RelayHealth {
+        self.relays.get(url).cloned().unwrap_or_default()
+    }
+
+    /// Best relays first and dead ones dropped. STUN entries are kept as they are; if every relay
+    /// looks dead the full list is handed out, since a stale probe beats having no relay at all
+    pub fn rank(&self, servers: Vec<RTCIceServer>) -> Vec<RTCIceServer> {
+        let any_alive = servers
+            .iter()
+            .flat_map(|server| &server.urls)
+            .any(|url| is_turn_url(url) && !self.health(url).is_dead());
+        if !any_alive {
+            return servers;
+        }
+
+        let mut ranked: Vec<(f64, RTCIceServer)> = servers
+            .into_iter()
+            .filter_map(|mut server| {
+                if !server.urls.iter().any(|url| is_turn_url(url)) {
+                    return Some((f64::INFINITY, server));
+                }
+                server.urls.retain(|url| !is_turn_url(url) || !self.health(url).is_dead());
+                server.urls.sort_by(|a, b| self.health(b).score().total_cmp(&self.health(a).score()));
+                let best = server.urls.first().map(|url| self.health(url).score())?;
+                Some((best, server))
+            })
+            .collect();
+        ranked.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+        ranked.into_iter().map(|(_, server)| server).collect()
+    }
+}
+
+/// TURN URLs with their credentials, as probed by the health monitor
+pub fn turn_urls(servers: &[RTCIceServer]) -> Vec<(String, String, String)> {
+    servers
+        .iter()
+        .flat_map(|server| {
+            server
+                .urls
+                .iter()
+                .filter(|url| is_turn_url(url))
+                .map(|url| (url.clone(), server.username.clone(), server.credential.clone()))
+        })
+        .collect()
+}
+
+/// `host:port` and whether to use UDP, from `turn:host[:port][?transport=udp|tcp]`
+fn parse_turn_url(url: &str) -> Option<(String, bool)> {
+    let (secure, rest) = match url.split_once(':')? {
+        ("turn", rest) => (false, rest),
+        ("turns", rest) => (true, rest),
+        _ => return None,
+    };
+    let (host_port, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let udp = !secure && !query.contains("transport=tcp");
+    let default_port = if secure { 5349 } else { 3478 };
+    // IPv6 hosts are bracketed, so their port follows the `]`
+    let has_port = if host_port.starts_with('[') { host_port.contains("]:") } else { host_port.contains(':') };
+    let host_port = if has_port { host_port.to_string() } else { format!("{}:{}", host_port, default_port) };
+    Some((host_port, udp))
+}
+
+/// Time to a relay allocation over UDP, or to a TCP connection for `turns:`/TCP relays
+pub async fn probe_turn_url(url: &str, username: &str, credential: &str) -> Result<Duration, String> {
+    let (host_port, udp) = parse_turn_url(url).ok_or_else(|| format!("unsupported TURN URL {}", url))?;
+    let probe = async {
+        let addr = tokio::net::lookup_host(&host_port)
+            .await
+            .map_err(|e| e.to_string())?
+            .next()
+            .ok_or_else(|| format!("{} did not resolve", host_port))?;
+        let start = Instant::now();
+        if !udp {
+            tokio::net::TcpStream::connect(addr).await.map_err(|e| e.to_string())?;
+            return Ok(start.elapsed());
+        }
+
+        let conn = tokio::net::UdpSocket::bind("0.0.0.0:0").await.map_err(|e| e.to_string())?;
+        let client = Client::new(ClientConfig {
+            stun_serv_addr: String::new(),
+            turn_serv_addr: addr.to_string(),
+            username: username.to_string(),
+            password: credential.to_string(),
+            realm: String::new(),
+            software: String::new(),
+            rto_in_ms: 0,
+            conn: Arc::new(conn),
+            vnet: None,
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+        client.listen().await.map_err(|e| e.to_string())?;
+        let allocation = client.allocate().await;
+        let rtt = start.elapsed();
+        if let Ok(relay) = &allocation {
+            let _ = relay.close().await;
+        }
+        let _ = client.close().await;
+        allocation.map(|_| rtt).map_err(|e| e.to_string())
+    };
+    tokio::time::timeout(PROBE_TIMEOUT, probe)
+        .await
+        .map_err(|_| "timed out".to_string())?
+}
@@ -1,7 +1,359 @@
+use std::time::{Duration, Instant};
 use base64::{engine::general_purpose::STANDARD, Engine};
+use futures_util::future::BoxFuture;
+use hmac::{Hmac, Mac};
 use serde::Deserialize;
+use sha1::Sha1;
 use webrtc::ice_transport::ice_server::RTCIceServer;
 
+/// How long coturn credentials stay valid unless `TURN_CREDENTIAL_TTL_SECS` says otherwise
+const DEFAULT_TURN_CREDENTIAL_TTL_SECS: u64 = 24 * 60 * 60;
+/// Xirsys doesn't report a lifetime, so its credentials are treated as good for this long
+const XIRSYS_CREDENTIAL_TTL: Duration = Duration::from_secs(30 * 60);
+/// Joins can wait on a fetch, so a provider that hangs mustn't hold them for long
+const ICE_FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A source of STUN/TURN servers; errors fall back to public STUN servers
+pub trait IceProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn fetch(&self) -> BoxFuture<'_, Result<Vec<RTCIceServer>, String>>;
+    /// How long fetched TURN credentials stay valid
+    fn credential_ttl(&self) -> Duration;
+}
+
+/// Last fetched ICE servers and when their TURN credentials stop working
+#[derive(Debug, Clone, Default)]
+pub struct IceServerCache {
+    servers: Vec<RTCIceServer>,
+    expires_at: Option<Instant>,
+}
+
+impl IceServerCache {
+    fn stun_only() -> Self {
+        Self {
+            servers: default_ice_servers(),
+            expires_at: None,
+        }
+    }
+
+    /// Usable servers: TURN entries are dropped once their credentials have expired
+    pub fn current(&self) -> Vec<RTCIceServer> {
+        if self.expires_at.is_some_and(|expires_at| Instant::now() >= expires_at) {
+            self.servers.iter().filter(|server| server.credential.is_empty()).cloned().collect()
+        } else {
+            self.servers.clone()
+        }
+    }
+
+    /// Whether any relay is configured (false after falling back to STUN)
+    pub fn has_turn(&self) -> bool {
+        self.servers.iter().any(|server| !server.credential.is_empty())
+    }
+
+    pub fn expires_at(&self) -> Option<Instant> {
+        self.expires_at
+    }
+
+    /// Whether TURN credentials run out within `lifetime` (never, for STUN-only sets)
+    pub fn expires_within(&self, lifetime: Duration) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= Instant::now() + lifetime)
+    }
+}
+
+/// Pick the provider from `ICE_PROVIDER` (`coturn`, `cloudflare`, `twilio` or `xirsys`), or from whichever
+/// credentials are set when it's absent
+pub fn ice_provider_from_env() -> Option<Box<dyn IceProvider>> {
+    let provider = std::env::var("ICE_PROVIDER").unwrap_or_else(|_| {
+        if std::env::var("TURN_SECRET").is_ok() {
+            "coturn".to_string()
+        } else if std::env::var("CLOUDFLARE_TURN_KEY_ID").is_ok() {
+            "cloudflare".to_string()
+        } else if std::env::var("TWILIO_ACCOUNT_SID").is_ok() {
+            "twilio".to_string()
+        } else {
+            "xirsys".to_string()
+        }
+    });
+    match provider.as_str() {
+        "coturn" => CoturnProvider::from_env().map(|provider| Box::new(provider) as Box<dyn IceProvider>),
+        "cloudflare" => CloudflareProvider::from_env().map(|provider| Box::new(provider) as Box<dyn IceProvider>),
+        "twilio" => TwilioProvider::from_env().map(|provider| Box::new(provider) as Box<dyn IceProvider>),
+        "xirsys" => XirsysProvider::from_env().map(|provider| Box::new(provider) as Box<dyn IceProvider>),
+        other => {
+            tracing::error!("Unknown ICE_PROVIDER '{}', expected coturn, cloudflare, twilio or xirsys", other);
+            None
+        }
+    }
+}
+
+/// ICE servers for the deployment from the configured provider, or public STUN only
+pub async fn fetch_ice_servers() -> IceServerCache {
+    let Some(provider) = ice_provider_from_env() else {
+        tracing::warn!("No TURN provider configured, using default STUN servers only");
+        return IceServerCache::stun_only();
+    };
+    tracing::info!("Fetching TURN servers from {}", provider.name());
+    match provider.fetch().await {
+        Ok(servers) if !servers.is_empty() => {
+            tracing::info!("✅ Successfully configured {} ICE server groups from {}", servers.len(), provider.name());
+            IceServerCache {
+                servers,
+                expires_at: Some(Instant::now() + provider.credential_ttl()),
+            }
+        }
+        Ok(_) => {
+            tracing::warn!("{} returned no ICE servers, using defaults", provider.name());
+            IceServerCache::stun_only()
+        }
+        Err(e) => {
+            tracing::error!("Failed to fetch from {}: {}", provider.name(), e);
+            IceServerCache::stun_only()
+        }
+    }
+}
+
+/// Split a flat URL list into a STUN group and a credentialed TURN group
+fn group_ice_urls(urls: &[String], username: &str, credential: &str) -> Vec<RTCIceServer> {
+    let mut servers = Vec::new();
+    let stun_urls: Vec<String> = urls.iter().filter(|url| url.starts_with("stun:")).cloned().collect();
+    let turn_urls: Vec<String> = urls
+        .iter()
+        .filter(|url| url.starts_with("turn:") || url.starts_with("turns:"))
+        .cloned()
+        .collect();
+    if !stun_urls.is_empty() {
+        servers.push(RTCIceServer {
+            urls: stun_urls,
+            ..Default::default()
+        });
+    }
+    if !turn_urls.is_empty() {
+        servers.push(RTCIceServer {
+            urls: turn_urls,
+            username: username.to_string(),
+            credential: credential.to_string(),
+            ..Default::default()
+        });
+    }
+    servers
+}
+
+/// Ephemeral credentials for coturn's REST API scheme (`use-auth-secret` / `static-auth-secret`):
+/// the username is `<expiry>:<name>` and the credential is base64(HMAC-SHA1(secret, username))
+pub fn coturn_credentials(secret: &str, name: &str, ttl_secs: u64) -> (String, String) {
+    let expiry = chrono::Utc::now().timestamp() as u64 + ttl_secs;
+    let username = format!("{}:{}", expiry, name);
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(username.as_bytes());
+    let credential = STANDARD.encode(mac.finalize().into_bytes());
+    (username, credential)
+}
+
+/// Self-hosted coturn from `TURN_SECRET` and `TURN_URLS` (comma-separated `turn:`/`turns:`/`stun:` URLs)
+pub struct CoturnProvider {
+    secret: String,
+    urls: Vec<String>,
+    name: String,
+    ttl_secs: u64,
+}
+
+impl CoturnProvider {
+    pub fn from_env() -> Option<Self> {
+        let secret = std::env::var("TURN_SECRET").ok().filter(|secret| !secret.is_empty())?;
+        let urls: Vec<String> = std::env::var("TURN_URLS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty())
+            .collect();
+        if urls.is_empty() {
+            tracing::warn!("TURN_SECRET is set but TURN_URLS is empty");
+            return None;
+        }
+        let ttl_secs = std::env::var("TURN_CREDENTIAL_TTL_SECS")
+            .ok()
+            .and_then(|ttl| ttl.parse().ok())
+            .unwrap_or(DEFAULT_TURN_CREDENTIAL_TTL_SECS);
+        let name = std::env::var("TURN_USERNAME").unwrap_or_else(|_| "webhangin".to_string());
+        Some(Self { secret, urls, name, ttl_secs })
+    }
+}
+
+impl IceProvider for CoturnProvider {
+    fn name(&self) -> &'static str {
+        "coturn"
+    }
+
+    fn credential_ttl(&self) -> Duration {
+        Duration::from_secs(self.ttl_secs)
+    }
+
+    fn fetch(&self) -> BoxFuture<'_, Result<Vec<RTCIceServer>, String>> {
+        Box::pin(async move {
+            let (username, credential) = coturn_credentials(&self.secret, &self.name, self.ttl_secs);
+            let mut servers = default_ice_servers();
+            servers.extend(group_ice_urls(&self.urls, &username, &credential));
+            Ok(servers)
+        })
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct CloudflareCredentialsResponse {
+    ice_servers: CloudflareIceServers,
+}
+
+/// Older responses return a single object, newer ones a list
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum CloudflareIceServers {
+    One(CloudflareIceServer),
+    Many(Vec<CloudflareIceServer>),
+}
+
+#[derive(Deserialize, Debug)]
+struct CloudflareIceServer {
+    urls: Vec<String>,
+    username: Option<String>,
+    credential: Option<String>,
+}
+
+/// Cloudflare Calls TURN: credentials generated per fetch from a TURN key (`CLOUDFLARE_TURN_KEY_ID`)
+/// and its API token (`CLOUDFLARE_TURN_API_TOKEN`)
+pub struct CloudflareProvider {
+    key_id: String,
+    api_token: String,
+    ttl_secs: u64,
+}
+
+impl CloudflareProvider {
+    pub fn from_env() -> Option<Self> {
+        let key_id = std::env::var("CLOUDFLARE_TURN_KEY_ID").ok().filter(|id| !id.is_empty())?;
+        let Ok(api_token) = std::env::var("CLOUDFLARE_TURN_API_TOKEN") else {
+            tracing::warn!("CLOUDFLARE_TURN_KEY_ID is set but CLOUDFLARE_TURN_API_TOKEN is missing");
+            return None;
+        };
+        let ttl_secs = std::env::var("TURN_CREDENTIAL_TTL_SECS")
+            .ok()
+            .and_then(|ttl| ttl.parse().ok())
+            .unwrap_or(DEFAULT_TURN_CREDENTIAL_TTL_SECS);
+        Some(Self { key_id, api_token, ttl_secs })
+    }
+}
+
+impl IceProvider for CloudflareProvider {
+    fn name(&self) -> &'static str {
+        "Cloudflare"
+    }
+
+    fn credential_ttl(&self) -> Duration {
+        Duration::from_secs(self.ttl_secs)
+    }
+
+    fn fetch(&self) -> BoxFuture<'_, Result<Vec<RTCIceServer>, String>> {
+        Box::pin(async move {
+            let url = format!("https://rtc.live.cloudflare.com/v1/turn/keys/{}/credentials/generate", self.key_id);
+            let resp = reqwest::Client::new()
+                .post(&url)
+                .timeout(ICE_FETCH_TIMEOUT)
+                .bearer_auth(&self.api_token)
+                .json(&serde_json::json!({ "ttl": self.ttl_secs }))
+                .send()
+                .await
+                .map_err(|e| e.to_string())?;
+            if !resp.status().is_success() {
+                return Err(format!("Cloudflare API error: {}", resp.status()));
+            }
+            let data: CloudflareCredentialsResponse = resp.json().await.map_err(|e| e.to_string())?;
+            let ice_servers = match data.ice_servers {
+                CloudflareIceServers::One(server) => vec![server],
+                CloudflareIceServers::Many(servers) => servers,
+            };
+            Ok(ice_servers
+                .iter()
+                .flat_map(|server| {
+                    group_ice_urls(
+                        &server.urls,
+                        server.username.as_deref().unwrap_or_default(),
+                        server.credential.as_deref().unwrap_or_default(),
+                    )
+                })
+                .collect())
+        })
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct TwilioTokenResponse {
+    ice_servers: Vec<TwilioIceServer>,
+}
+
+#[derive(Deserialize, Debug)]
+struct TwilioIceServer {
+    urls: String,
+    username: Option<String>,
+    credential: Option<String>,
+}
+
+/// Twilio Network Traversal Service: ephemeral TURN credentials from `TWILIO_ACCOUNT_SID` and
+/// `TWILIO_AUTH_TOKEN` (an API key SID/secret can be given as `TWILIO_API_KEY`/`TWILIO_API_SECRET`)
+pub struct TwilioProvider {
+    account_sid: String,
+    auth_user: String,
+    auth_secret: String,
+    ttl_secs: u64,
+}
+
+impl TwilioProvider {
+    pub fn from_env() -> Option<Self> {
+        let account_sid = std::env::var("TWILIO_ACCOUNT_SID").ok().filter(|sid| !sid.is_empty())?;
+        let (auth_user, auth_secret) = match (std::env::var("TWILIO_API_KEY"), std::env::var("TWILIO_API_SECRET")) {
+            (Ok(key), Ok(secret)) => (key, secret),
+            _ => (account_sid.clone(), std::env::var("TWILIO_AUTH_TOKEN").ok()?),
+        };
+        let ttl_secs = std::env::var("TURN_CREDENTIAL_TTL_SECS")
+            .ok()
+            .and_then(|ttl| ttl.parse().ok())
+            .unwrap_or(DEFAULT_TURN_CREDENTIAL_TTL_SECS);
+        Some(Self { account_sid, auth_user, auth_secret, ttl_secs })
+    }
+}
+
+impl IceProvider for TwilioProvider {
+    fn name(&self) -> &'static str {
+        "Twilio"
+    }
+
+    fn credential_ttl(&self) -> Duration {
+        Duration::from_secs(self.ttl_secs)
+    }
+
+    fn fetch(&self) -> BoxFuture<'_, Result<Vec<RTCIceServer>, String>> {
+        Box::pin(async move {
+            let url = format!("https://api.twilio.com/2010-04-01/Accounts/{}/Tokens.json", self.account_sid);
+            let resp = reqwest::Client::new()
+                .post(&url)
+                .timeout(ICE_FETCH_TIMEOUT)
+                .basic_auth(&self.auth_user, Some(&self.auth_secret))
+                .form(&[("Ttl", self.ttl_secs.to_string())])
+                .send()
+                .await
+                .map_err(|e| e.to_string())?;
+            if !resp.status().is_success() {
+                return Err(format!("Twilio API error: {}", resp.status()));
+            }
+            let token: TwilioTokenResponse = resp.json().await.map_err(|e| e.to_string())?;
+
+            // Twilio lists one URL per entry; TURN entries share the same credentials
+            let username = token.ice_servers.iter().find_map(|server| server.username.clone()).unwrap_or_default();
+            let credential = token.ice_servers.iter().find_map(|server| server.credential.clone()).unwrap_or_default();
+            let urls: Vec<String> = token.ice_servers.into_iter().map(|server| server.urls).collect();
+            Ok(group_ice_urls(&urls, &username, &credential))
+        })
+    }
+}
+
 #[derive(Deserialize, Debug)]
 struct XirsysResponse {
     v: Option<XirsysValue>,
@@ -21,106 +373,72 @@ struct XirsysIceServers {
     credential: Option<String>,
 }
 
-/// Fetches TURN/STUN servers from Xirsys API
-pub async fn fetch_xirsys_ice_servers() -> Vec<RTCIceServer> {
-    // Check for both XIRSYS_* and NEXT_PUBLIC_XIRSYS_* (frontend's .env format)
-    let username = std::env::var("XIRSYS_USERNAME")
-        .or_else(|_| std::env::var("NEXT_PUBLIC_XIRSYS_USERNAME"))
-        .unwrap_or_default();
-    let secret = std::env::var("XIRSYS_SECRET")
-        .or_else(|_| std::env::var("NEXT_PUBLIC_XIRSYS_SECRET"))
-        .unwrap_or_default();
-    let channel = std::env::var("XIRSYS_CHANNEL")
-        .or_else(|_| std::env::var("NEXT_PUBLIC_XIRSYS_CHANNEL"))
-        .unwrap_or_else(|_| "webhangin".to_string());
-
-    if username.is_empty() || secret.is_empty() {
-        tracing::warn!("Xirsys credentials not found, using default STUN servers only");
-        tracing::warn!("Set XIRSYS_USERNAME and XIRSYS_SECRET environment variables for TURN support");
-        return default_ice_servers();
-    }
-
-    let credentials = STANDARD.encode(format!("{}:{}", username, secret));
-    let url = format!("https://global.xirsys.net/_turn/{}", channel);
-
-    tracing::info!("Fetching TURN servers from Xirsys for channel: {}", channel);
-
-    let client = reqwest::Client::new();
-    let response = client
-        .put(&url)
-        .header("Authorization", format!("Basic {}", credentials))
-        .header("Content-Type", "application/json")
-        .body(r#"{"format":"urls"}"#)
-        .send()
-        .await;
-
-    match response {
-        Ok(resp) => {
-            if !resp.status().is_success() {
-                tracing::error!("Xirsys API error: {}", resp.status());
-                return default_ice_servers();
-            }
+/// Xirsys from `XIRSYS_USERNAME`, `XIRSYS_SECRET` and `XIRSYS_CHANNEL`
+pub struct XirsysProvider {
+    username: String,
+    secret: String,
+    channel: String,
+}
 
-            match resp.json::<XirsysResponse>().await {
-                Ok(data) => {
-                    if let Some(v) = data.v {
-                        if let Some(ice_servers) = v.ice_servers {
-                            let mut servers = Vec::new();
-
-                            // Separate STUN and TURN servers
-                            let stun_urls: Vec<String> = ice_servers
-                                .urls
-                                .iter()
-                                .filter(|url| url.starts_with("stun:"))
-                                .cloned()
-                                .collect();
-
-                            let turn_urls: Vec<String> = ice_servers
-                                .urls
-                                .iter()
-                                .filter(|url| url.starts_with("turn:") || url.starts_with("turns:"))
-                                .cloned()
-                                .collect();
-
-                            // Add STUN servers (no credentials needed)
-                            if !stun_urls.is_empty() {
-                                servers.push(RTCIceServer {
-                                    urls: stun_urls.clone(),
-                                    ..Default::default()
-                                });
-                                tracing::info!("Added {} STUN servers from Xirsys", stun_urls.len());
-                            }
-
-                            // Add TURN servers with credentials
-                            if !turn_urls.is_empty() {
-                                servers.push(RTCIceServer {
-                                    urls: turn_urls.clone(),
-                                    username: ice_servers.username.clone().unwrap_or_default(),
-                                    credential: ice_servers.credential.clone().unwrap_or_default(),
-                                    ..Default::default()
-                                });
-                                tracing::info!("Added {} TURN servers from Xirsys", turn_urls.len());
-                            }
-
-                            if !servers.is_empty() {
-                                tracing::info!("✅ Successfully configured {} ICE server groups from Xirsys", servers.len());
-                                return servers;
-                            }
-                        }
-                    }
-                    tracing::warn!("Xirsys response missing ice_servers, using defaults");
-                    default_ice_servers()
-                }
-                Err(e) => {
-                    tracing::error!("Failed to parse Xirsys response: {}", e);
-                    default_ice_servers()
-                }
-            }
-        }
-        Err(e) => {
-            tracing::error!("Failed to fetch from Xirsys: {}", e);
-            default_ice_servers()
+impl XirsysProvider {
+    pub fn from_env() -> Option<Self> {
+        // Check for both XIRSYS_* and NEXT_PUBLIC_XIRSYS_* (frontend's .env format)
+        let username = std::env::var("XIRSYS_USERNAME")
+            .or_else(|_| std::env::var("NEXT_PUBLIC_XIRSYS_USERNAME"))
+            .unwrap_or_default();
+        let secret = std::env::var("XIRSYS_SECRET")
+            .or_else(|_| std::env::var("NEXT_PUBLIC_XIRSYS_SECRET"))
+            .unwrap_or_default();
+        let channel = std::env::var("XIRSYS_CHANNEL")
+            .or_else(|_| std::env::var("NEXT_PUBLIC_XIRSYS_CHANNEL"))
+            .unwrap_or_else(|_| "webhangin".to_string());
+
+        if username.is_empty() || secret.is_empty() {
+            tracing::warn!("Xirsys credentials not found");
+            tracing::warn!("Set XIRSYS_USERNAME and XIRSYS_SECRET environment variables for TURN support");
+            return None;
         }
+        Some(Self { username, secret, channel })
+    }
+}
+
+impl IceProvider for XirsysProvider {
+    fn name(&self) -> &'static str {
+        "Xirsys"
+    }
+
+    fn credential_ttl(&self) -> Duration {
+        XIRSYS_CREDENTIAL_TTL
+    }
+
+    fn fetch(&self) -> BoxFuture<'_, Result<Vec<RTCIceServer>, String>> {
+        Box::pin(async move {
+            let credentials = STANDARD.encode(format!("{}:{}", self.username, self.secret));
+            let url = format!("https://global.xirsys.net/_turn/{}", self.channel);
+            tracing::info!("Requesting Xirsys channel: {}", self.channel);
+
+            let resp = reqwest::Client::new()
+                .put(&url)
+                .timeout(ICE_FETCH_TIMEOUT)
+                .header("Authorization", format!("Basic {}", credentials))
+                .header("Content-Type", "application/json")
+                .body(r#"{"format":"urls"}"#)
+                .send()
+                .await
+                .map_err(|e| e.to_string())?;
+            if !resp.status().is_success() {
+                return Err(format!("Xirsys API error: {}", resp.status()));
+            }
+            let data: XirsysResponse = resp.json().await.map_err(|e| e.to_string())?;
+            let Some(ice_servers) = data.v.and_then(|v| v.ice_servers) else {
+                return Err(format!("response missing ice_servers (status: {:?})", data.s));
+            };
+            Ok(group_ice_urls(
+                &ice_servers.urls,
+                ice_servers.username.as_deref().unwrap_or_default(),
+                ice_servers.credential.as_deref().unwrap_or_default(),
+            ))
+        })
     }
 }
 
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use chrono::{Duration as ChronoDuration, NaiveDateTime, Timelike};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::file_writer;
+
+/// Limits a profile put on its own play time
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeLimitSettings {
+    /// Minutes allowed per local day
+    pub daily_minutes: Option<u32>,
+    /// Local hours `[start, end)` when the profile may be connected, e.g. `[16, 20]`
+    pub allowed_hours: Option<(u8, u8)>,
+    /// Client's offset from UTC, used to find "today" and the local hour
+    #[serde(default)]
+    pub utc_offset_minutes: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+struct ProfileUsage {
+    settings: TimeLimitSettings,
+    /// Salted SHA-256 of the PIN guarding changes, if one was set
+    pin_hash: Option<String>,
+    /// Per-profile salt for `pin_hash`; PINs saved before salting have none until they're next used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pin_salt: Option<String>,
+    /// Local date `seconds_used` belongs to
+    day: String,
+    seconds_used: u64,
+}
+
+pub enum TimeLimitStatus {
+    Allowed { remaining: Option<Duration> },
+    Exceeded { reason: String },
+}
+
+/// Per-profile limits and daily usage, persisted as JSON so reconnecting doesn't reset the clock
+pub struct TimeLimitStore {
+    profiles: HashMap<String, ProfileUsage>,
+    path: PathBuf,
+}
+
+fn hash_pin(salt: Option<&str>, pin: &str) -> String {
+    let salted = match salt {
+        Some(salt) => format!("{}:{}", salt, pin),
+        None => pin.to_string(),
+    };
+    format!("{:x}", Sha256::digest(salted.as_bytes()))
+}
+
+impl ProfileUsage {
+    /// Compares every byte so response timing doesn't leak how close a guess was
+    fn pin_matches(&self, pin: &str) -> bool {
+        let Some(pin_hash) = &self.pin_hash else {
+            return false;
+        };
+        let attempt = hash_pin(self.pin_salt.as_deref(), pin);
+        attempt.len() == pin_hash.len() && attempt.bytes().zip(pin_hash.bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+    }
+
+    fn set_pin(&mut self, pin: &str) {
+        let salt = uuid::Uuid::new_v4().to_string();
+        self.pin_hash = Some(hash_pin(Some(&salt), pin));
+        self.pin_salt = Some(salt);
+    }
+}
+
+fn local_now(settings: &TimeLimitSettings) -> NaiveDateTime {
+    chrono::Utc::now().naive_utc() + ChronoDuration::minutes(settings.utc_offset_minutes as i64)
+}
+
+impl TimeLimitStore {
+    /// Load from `TIME_LIMITS_FILE` (default `time_limits.json`)
+    pub fn load() -> Self {
+        let path = PathBuf::from(std::env::var("TIME_LIMITS_FILE").unwrap_or_else(|_| "time_limits.json".to_string()));
+        let profiles = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                tracing::error!("Failed to parse {}: {}", path.display(), e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        Self { profiles, path }
+    }
+
+    /// Whether `pin` is the PIN the profile set; profiles without one can't be proven this way
+    pub fn verify_pin(&self, profile_id: &str, pin: &str) -> bool {
+        self.profiles.get(profile_id).is_some_and(|usage| usage.pin_matches(pin))
+    }
+
+    pub fn settings(&self, profile_id: &str) -> TimeLimitSettings {
+        self.profiles.get(profile_id).map(|usage| usage.settings.clone()).unwrap_or_default()
+    }
+
+    /// Change a profile's limits; once a PIN is set, the same PIN is needed for later changes. Only a
+    /// verified identity may set the first PIN, or anyone who learned the profile ID could lock its owner out
+    pub fn update(&mut self, profile_id: &str, settings: TimeLimitSettings, pin: Option<&str>, verified: bool) -> Result<(), &'static str> {
+        if settings.allowed_hours.is_some_and(|(start, end)| start > 23 || end > 24 || start >= end) {
+            return Err("allowed hours must be a range within 0-24");
+        }
+        let usage = self.profiles.get(profile_id).filter(|usage| usage.pin_hash.is_some());
+        let new_pin = match (usage, pin) {
+            // Unsalted PINs from before salting are re-hashed once they're proven
+            (Some(usage), Some(pin)) if usage.pin_matches(pin) => usage.pin_salt.is_none().then_some(pin),
+            (Some(_), _) => return Err("incorrect PIN"),
+            (None, Some(_)) if !verified => return Err("sign in to set a PIN"),
+            (None, pin) => pin,
+        };
+        let usage = self.profiles.entry(profile_id.to_string()).or_default();
+        if let Some(pin) = new_pin {
+            usage.set_pin(pin);
+        }
+        usage.settings = settings;
+        self.save();
+        Ok(())
+    }
+
+    /// Add connected time to today's usage and report whether the profile may stay
+    pub fn record(&mut self, profile_id: &str, elapsed: Duration) -> TimeLimitStatus {
+        let Some(usage) = self.profiles.get_mut(profile_id) else {
+            return TimeLimitStatus::Allowed { remaining: None };
+        };
+        if usage.settings.daily_minutes.is_none() && usage.settings.allowed_hours.is_none() {
+            return TimeLimitStatus::Allowed { remaining: None };
+        }
+
+        let now = local_now(&usage.settings);
+        let today = now.format("%Y-%m-%d").to_string();
+        if usage.day != today {
+            usage.day = today;
+            usage.seconds_used = 0;
+        }
+        usage.seconds_used += elapsed.as_secs();
+
+        let mut remaining = None;
+        if let Some((start, end)) = usage.settings.allowed_hours {
+            let hour = now.hour() as u8;
+            if hour < start || hour >= end {
+                self.save();
+                return TimeLimitStatus::Exceeded {
+                    reason: format!("You can hang out between {}:00 and {}:00", start, end),
+                };
+            }
+            let seconds_into_hour = now.minute() as u64 * 60 + now.second() as u64;
+            remaining = Some(Duration::from_secs((end - hour) as u64 * 3600 - seconds_into_hour));
+        }
+        if let Some(daily_minutes) = usage.settings.daily_minutes {
+            let allowed = daily_minutes as u64 * 60;
+            if usage.seconds_used >= allowed {
+                self.save();
+                return TimeLimitStatus::Exceeded {
+                    reason: "You've reached today's time limit, see you tomorrow!".to_string(),
+                };
+            }
+            let left = Duration::from_secs(allowed - usage.seconds_used);
+            remaining = Some(remaining.map_or(left, |remaining: Duration| remaining.min(left)));
+        }
+        self.save();
+        TimeLimitStatus::Allowed { remaining }
+    }
+
+    /// Queue a snapshot for the writer thread; usage is recorded from session actors, which mustn't block
+    fn save(&self) {
+        match serde_json::to_string_pretty(&self.profiles) {
+            Ok(json) => file_writer::replace(self.path.clone(), json),
+            Err(e) => tracing::error!("Failed to serialize time limits: {}", e),
+        }
+    }
+}
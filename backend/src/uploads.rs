@@ -0,0 +1,327 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::web::{self, Bytes, Data};
+use actix_web::{HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::auth;
+use crate::client_ip::TrustedProxies;
+use crate::signed_token;
+use crate::storage::BlobStorage;
+use crate::streaming::{RoomOwner, StreamingSession};
+
+/// Largest image accepted by `POST /api/uploads`
+const MAX_UPLOAD_BYTES: usize = 5 * 1024 * 1024;
+const NSFW_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Window uploads are counted over, per client address
+const UPLOAD_WINDOW: Duration = Duration::from_secs(10 * 60);
+const MAX_UPLOADS_PER_WINDOW: usize = 10;
+
+/// Signing key for upload tokens; they're only good while their session is, so a restart can drop them
+static UPLOAD_SECRET: LazyLock<String> = LazyLock::new(|| uuid::Uuid::new_v4().to_string());
+
+/// Image formats we accept, detected from magic bytes rather than the client's Content-Type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ImageKind {
+    Jpeg,
+    Png,
+    Gif,
+}
+
+impl ImageKind {
+    fn detect(data: &[u8]) -> Option<Self> {
+        if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(ImageKind::Jpeg)
+        } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(ImageKind::Png)
+        } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+            Some(ImageKind::Gif)
+        } else {
+            None
+        }
+    }
+
+    fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "jpg" => Some(ImageKind::Jpeg),
+            "png" => Some(ImageKind::Png),
+            "gif" => Some(ImageKind::Gif),
+            _ => None,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ImageKind::Jpeg => "jpg",
+            ImageKind::Png => "png",
+            ImageKind::Gif => "gif",
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            ImageKind::Jpeg => "image/jpeg",
+            ImageKind::Png => "image/png",
+            ImageKind::Gif => "image/gif",
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UploadResponse {
+    upload_id: String,
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct NsfwCheckResponse {
+    allowed: bool,
+}
+
+/// Recent uploads per client address, so one client can't fill storage
+#[derive(Default)]
+pub struct UploadLimiter {
+    recent: HashMap<IpAddr, VecDeque<Instant>>,
+}
+
+impl UploadLimiter {
+    /// Record an upload, or return how long until the address may upload again
+    fn check(&mut self, ip: IpAddr) -> Result<(), Duration> {
+        let now = Instant::now();
+        self.recent.retain(|_, uploads| uploads.back().is_some_and(|last| now.duration_since(*last) < UPLOAD_WINDOW));
+        let uploads = self.recent.entry(ip).or_default();
+        while uploads.front().is_some_and(|t| now.duration_since(*t) >= UPLOAD_WINDOW) {
+            uploads.pop_front();
+        }
+        if uploads.len() >= MAX_UPLOADS_PER_WINDOW {
+            let oldest = *uploads.front().unwrap();
+            return Err(UPLOAD_WINDOW - now.duration_since(oldest));
+        }
+        uploads.push_back(now);
+        Ok(())
+    }
+}
+
+/// Token a player uploads with, handed out in `RoomState`
+pub fn issue_upload_token(room_id: &str, player_id: &str) -> String {
+    signed_token::issue(&UPLOAD_SECRET, &format!("{}.{}", room_id, player_id))
+}
+
+/// Room and player ID from a validly signed upload token
+fn verify_upload_token(token: &str) -> Option<(String, String)> {
+    let payload = signed_token::verify(&UPLOAD_SECRET, token)?;
+    let (room_id, player_id) = payload.rsplit_once('.')?;
+    Some((room_id.to_string(), player_id.to_string()))
+}
+
+/// Uploads take `Authorization: Bearer` with a verified join token, or the upload token of a player
+/// who is still in their room
+async fn is_authorized(req: &HttpRequest, room_owner: &Data<Mutex<RoomOwner<StreamingSession>>>) -> bool {
+    let Some(token) = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+    if auth::verifier().is_some_and(|verifier| verifier.verify(token).is_ok()) {
+        return true;
+    }
+    let Some((room_id, player_id)) = verify_upload_token(token) else {
+        return false;
+    };
+    let rooms = room_owner.lock().await.list_rooms();
+    rooms.iter().any(|room| room.id == room_id && room.get_player_data(&player_id).is_some())
+}
+
+/// Upload IDs are `<uuid>.<ext>`, as handed out by `upload_image`
+pub fn is_valid_upload_id(upload_id: &str) -> bool {
+    upload_id
+        .split_once('.')
+        .is_some_and(|(id, extension)| uuid::Uuid::parse_str(id).is_ok() && ImageKind::from_extension(extension).is_some())
+}
+
+/// Register image upload endpoints
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/api/uploads")
+            .app_data(web::PayloadConfig::new(MAX_UPLOAD_BYTES))
+            .route(web::post().to(upload_image)),
+    )
+    .route("/api/uploads/{upload_id}", web::get().to(get_upload));
+}
+
+/// Accept a raw image body from a signed-in player or a live session, strip metadata and store it
+async fn upload_image(
+    req: HttpRequest,
+    body: Bytes,
+    storage: Data<dyn BlobStorage>,
+    room_owner: Data<Mutex<RoomOwner<StreamingSession>>>,
+    limiter: Data<std::sync::Mutex<UploadLimiter>>,
+    trusted_proxies: Data<TrustedProxies>,
+) -> actix_web::Result<HttpResponse> {
+    if !is_authorized(&req, &room_owner).await {
+        return Ok(HttpResponse::Unauthorized().body("uploads need a sign-in or session token"));
+    }
+    if let Some(client_ip) = trusted_proxies.client_ip(&req) {
+        if let Err(retry_after) = limiter.lock().unwrap().check(client_ip) {
+            return Ok(HttpResponse::TooManyRequests()
+                .insert_header(("Retry-After", retry_after.as_secs().max(1).to_string()))
+                .body("Too many uploads, slow down"));
+        }
+    }
+    let Some(kind) = ImageKind::detect(&body) else {
+        return Ok(HttpResponse::UnsupportedMediaType().body("only JPEG, PNG and GIF images are accepted"));
+    };
+    let Some(cleaned) = strip_metadata(kind, &body) else {
+        return Ok(HttpResponse::BadRequest().body("malformed image"));
+    };
+    if !nsfw_check(&cleaned, kind).await {
+        return Ok(HttpResponse::UnprocessableEntity().body("image rejected by content check"));
+    }
+
+    let upload_id = format!("{}.{}", uuid::Uuid::new_v4(), kind.extension());
+    let key = upload_id.clone();
+    web::block(move || storage.put(&key, &cleaned))
+        .await?
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    tracing::info!("📷 Stored upload {} ({} bytes)", upload_id, body.len());
+    Ok(HttpResponse::Created().json(UploadResponse {
+        url: format!("/api/uploads/{}", upload_id),
+        upload_id,
+    }))
+}
+
+async fn get_upload(upload_id: web::Path<String>, storage: Data<dyn BlobStorage>) -> actix_web::Result<HttpResponse> {
+    let upload_id = upload_id.into_inner();
+    if !is_valid_upload_id(&upload_id) {
+        return Ok(HttpResponse::NotFound().finish());
+    }
+    let kind = upload_id.rsplit('.').next().and_then(ImageKind::from_extension);
+
+    let key = upload_id.clone();
+    let data = web::block(move || storage.get(&key))
+        .await?
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    match (data, kind) {
+        (Some(data), Some(kind)) => Ok(HttpResponse::Ok()
+            .content_type(kind.content_type())
+            .insert_header(("Cache-Control", "public, max-age=31536000, immutable"))
+            .insert_header(("X-Content-Type-Options", "nosniff"))
+            .body(data)),
+        _ => Ok(HttpResponse::NotFound().finish()),
+    }
+}
+
+/// Optional external classifier at `NSFW_CHECK_URL`; rejects when it errors so failures aren't a bypass
+async fn nsfw_check(data: &[u8], kind: ImageKind) -> bool {
+    let Ok(url) = std::env::var("NSFW_CHECK_URL") else {
+        return true;
+    };
+
+    let response = reqwest::Client::new()
+        .post(&url)
+        .header("Content-Type", kind.content_type())
+        .timeout(NSFW_CHECK_TIMEOUT)
+        .body(data.to_vec())
+        .send()
+        .await;
+    match response {
+        Ok(resp) if resp.status().is_success() => match resp.json::<NsfwCheckResponse>().await {
+            Ok(check) => check.allowed,
+            Err(e) => {
+                tracing::error!("NSFW check returned invalid JSON: {}", e);
+                false
+            }
+        },
+        Ok(resp) => {
+            tracing::error!("NSFW check error: {}", resp.status());
+            false
+        }
+        Err(e) => {
+            tracing::error!("NSFW check request failed: {}", e);
+            false
+        }
+    }
+}
+
+/// Drop EXIF/XMP/text metadata (GPS coordinates, device info); returns None if the image is malformed
+fn strip_metadata(kind: ImageKind, data: &[u8]) -> Option<Vec<u8>> {
+    match kind {
+        ImageKind::Jpeg => strip_jpeg(data),
+        ImageKind::Png => strip_png(data),
+        // GIF has no EXIF block; comment extensions are harmless
+        ImageKind::Gif => Some(data.to_vec()),
+    }
+}
+
+fn strip_jpeg(data: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(&data[..2]);
+    let mut pos = 2;
+    loop {
+        if *data.get(pos)? != 0xFF {
+            return None;
+        }
+        // Markers may be padded with extra 0xFF fill bytes
+        while *data.get(pos + 1)? == 0xFF {
+            pos += 1;
+        }
+        let marker = data[pos + 1];
+        match marker {
+            // Start of scan: the rest is entropy-coded image data
+            0xDA => {
+                out.extend_from_slice(&data[pos..]);
+                return Some(out);
+            }
+            0xD9 => {
+                out.extend_from_slice(&data[pos..pos + 2]);
+                return Some(out);
+            }
+            0x01 | 0xD0..=0xD7 => {
+                out.extend_from_slice(&data[pos..pos + 2]);
+                pos += 2;
+            }
+            _ => {
+                let length = u16::from_be_bytes([*data.get(pos + 2)?, *data.get(pos + 3)?]) as usize;
+                let end = pos + 2 + length;
+                if length < 2 || end > data.len() {
+                    return None;
+                }
+                // APP1 (EXIF/XMP), APP13 (IPTC) and comments carry metadata; APP2 (ICC) is kept for color
+                if !matches!(marker, 0xE1 | 0xED | 0xFE) {
+                    out.extend_from_slice(&data[pos..end]);
+                }
+                pos = end;
+            }
+        }
+    }
+}
+
+fn strip_png(data: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(&data[..8]);
+    let mut pos = 8;
+    loop {
+        let length = u32::from_be_bytes(data.get(pos..pos + 4)?.try_into().ok()?) as usize;
+        let chunk_type = data.get(pos + 4..pos + 8)?;
+        let end = pos.checked_add(12)?.checked_add(length)?;
+        if end > data.len() {
+            return None;
+        }
+        if !matches!(chunk_type, b"eXIf" | b"tEXt" | b"zTXt" | b"iTXt" | b"tIME") {
+            out.extend_from_slice(&data[pos..end]);
+        }
+        if chunk_type == b"IEND" {
+            return Some(out);
+        }
+        pos = end;
+    }
+}
@@ -0,0 +1,29 @@
+use std::path::Path;
+use std::time::SystemTime;
+use serde::de::DeserializeOwned;
+
+/// Last modification time of a file the server polls for changes, `None` if it's missing or unreadable
+pub fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Parse a JSON file, starting empty when it's missing or unparseable
+pub fn read_json<T: DeserializeOwned + Default>(path: &Path) -> T {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+            tracing::error!("Failed to parse {}: {}", path.display(), e);
+            T::default()
+        }),
+        Err(_) => T::default(),
+    }
+}
+
+/// The file's contents and modification time, if it changed since `known`. Blocking, so watchers
+/// run it through `web::block` and only take their lock to swap the result in
+pub fn read_json_if_changed<T: DeserializeOwned + Default>(path: &Path, known: Option<SystemTime>) -> Option<(T, Option<SystemTime>)> {
+    let modified = modified_time(path);
+    if modified.is_none() || modified == known {
+        return None;
+    }
+    Some((read_json(path), modified))
+}
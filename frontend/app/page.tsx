@@ -195,6 +195,17 @@ interface PlayerData {
   facialFeatures: FacialFeatures;
 }
 
+// An active room from GET /api/rooms
+interface RoomListing {
+  roomId: string;
+  baseRoomId: string;
+  theme: string;
+  language: string;
+  playerCount: number;
+  capacity: number | null;
+  locked: boolean;
+}
+
 // Available facial feature options per character type
 const FACIAL_OPTIONS = {
   cat: {
@@ -752,6 +763,36 @@ export default function Home() {
     }
   }, []);
 
+  // Live occupancy of active rooms, pushed over the /lobby feed while the landing page is open
+  const [liveRooms, setLiveRooms] = useState<RoomListing[]>([]);
+  useEffect(() => {
+    const protocol = window.location.protocol === 'https:' ? 'wss:' : 'ws:';
+    // In dev mode (port 3000) the backend is on port 3001
+    const host = window.location.host.replace(':3000', ':3001');
+    const ws = new WebSocket(`${protocol}//${host}/lobby`);
+    ws.onmessage = (event) => {
+      const message = JSON.parse(event.data);
+      switch (message.action) {
+        case 'LobbySnapshot':
+          setLiveRooms(message.rooms);
+          break;
+        case 'RoomCreated':
+          setLiveRooms((prev) => [...prev, message.room].sort((a, b) => a.roomId.localeCompare(b.roomId)));
+          break;
+        case 'RoomUpdated':
+          setLiveRooms((prev) => prev.map((room) => (room.roomId === message.room.roomId ? message.room : room)));
+          break;
+        case 'RoomRemoved':
+          setLiveRooms((prev) => prev.filter((room) => room.roomId !== message.roomId));
+          break;
+        case 'PlayerCountChanged':
+          setLiveRooms((prev) => prev.map((room) => (room.roomId === message.roomId ? { ...room, playerCount: message.playerCount } : room)));
+          break;
+      }
+    };
+    return () => ws.close();
+  }, []);
+
   const handleJoin = () => {
     if (!playerData.name.trim() || !playerData.activity.trim()) {
       alert('Please enter your name and activity!');
@@ -954,6 +995,21 @@ export default function Home() {
           )}
         </div>
 
+        {/* Live rooms */}
+        {liveRooms.length > 0 && (
+          <div>
+            <label className="block text-xs font-medium text-black mb-1">Live now</label>
+            <div className="space-y-0.5 text-xs text-gray-800">
+              {liveRooms.map((room) => (
+                <div key={room.roomId} className="flex justify-between">
+                  <span>{room.theme}{room.locked ? ' 🔒' : ''}</span>
+                  <span>{room.capacity ? `${room.playerCount}/${room.capacity}` : room.playerCount}</span>
+                </div>
+              ))}
+            </div>
+          </div>
+        )}
+
         {/* Join Button */}
         <button
           onClick={handleJoin}
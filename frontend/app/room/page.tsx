@@ -42,6 +42,8 @@ interface PlayerData {
     position: Position;
     rotation: number;
     isMoving: boolean;
+    role?: 'player' | 'moderator';
+    status?: 'active' | 'idle';
 }
 
 interface RemoteStream {
@@ -54,6 +56,16 @@ interface RemoteStream {
 // Animation types that can be triggered
 type AnimationType = 'jump' | 'wave' | 'dance' | null;
 
+// Low/medium/high layers for screen share, matching the server's `q`/`h`/`f` RIDs
+const SIMULCAST_ENCODINGS: RTCRtpEncodingParameters[] = [
+    { rid: 'q', scaleResolutionDownBy: 4, maxBitrate: 150_000 },
+    { rid: 'h', scaleResolutionDownBy: 2, maxBitrate: 500_000 },
+    { rid: 'f', maxBitrate: 1_500_000 },
+];
+
+// Must match the server's reaction allowlist
+const REACTION_EMOJI = ['👍', '❤️', '😂', '😮', '😢', '🎉', '👏', '🔥'];
+
 // Streaming Screen component - displays video stream as a 3D texture
 function StreamingScreen({ videoStream, onClick, isCinemaOverride }: { videoStream: MediaStream; onClick?: () => void; isCinemaOverride?: boolean }) {
     const [videoTexture, setVideoTexture] = useState<THREE.VideoTexture | null>(null);
@@ -729,7 +741,7 @@ function PlayerAvatar({ player, animation, isTalking, audioStream, videoStream,
                     outlineWidth={0.012}
                     outlineColor="#000000"
                 >
-                    {player.name}
+                    {player.status === 'idle' ? `${player.name} (away)` : player.name}
                 </Text>
             </Billboard>
 
@@ -767,7 +779,8 @@ function LocalPlayer({
     isCinema,
     hideTablet,
     chatInputFocused,
-    chatBubble
+    chatBubble,
+    correction
 }: {
     player: PlayerData;
     onMove: (position: Position, rotation: number, isMoving: boolean) => void;
@@ -779,12 +792,20 @@ function LocalPlayer({
     hideTablet?: boolean;
     chatInputFocused?: boolean;
     chatBubble?: { message: string; timestamp: number } | null;
+    correction?: { position: Position; timestamp: number } | null;
 }) {
     const groupRef = useRef<THREE.Group>(null);
     const { camera, gl, scene } = useThree();
     const keysPressed = useRef<Set<string>>(new Set());
     const positionRef = useRef<Position>({ ...player.position });
     const rotationRef = useRef<number>(player.rotation);
+
+    // The server rejected or cut short a move; snap back to where it has us
+    useEffect(() => {
+        if (correction) {
+            positionRef.current = { ...correction.position };
+        }
+    }, [correction]);
     const { triggerAnimation, updateAnimation, cancelAnimation, currentAnimation } = usePlayerAnimation();
     const micTexture = useTexture('/assets/textures/mic-talking-indicator_sprite_sheet.png');
 
@@ -1434,26 +1455,40 @@ function RoomPage() {
     const [roomTheme, setRoomTheme] = useState('');
     const [localPlayer, setLocalPlayer] = useState<PlayerData | null>(null);
     const [remotePlayers, setRemotePlayers] = useState<PlayerData[]>([]);
+    const [positionCorrection, setPositionCorrection] = useState<{ position: Position; timestamp: number } | null>(null);
     const remotePlayersRef = useRef<PlayerData[]>([]);
-    const [chatMessages, setChatMessages] = useState<{ sender: string; message: string }[]>([]);
+    const [chatMessages, setChatMessages] = useState<{ sender: string; message: string; messageId?: string; uploadId?: string; edited?: boolean; preview?: { url: string; title?: string; description?: string } }[]>([]);
     const [playerChatBubbles, setPlayerChatBubbles] = useState<{ [playerId: string]: { message: string; timestamp: number } }>({});
     const [localPlayerChatBubble, setLocalPlayerChatBubble] = useState<{ message: string; timestamp: number } | null>(null);
     const [chatInputFocused, setChatInputFocused] = useState(false);
     const [chatInput, setChatInput] = useState('');
+    const [typingPlayers, setTypingPlayers] = useState<Set<string>>(new Set());
     const [remoteStreams, setRemoteStreams] = useState<RemoteStream[]>([]);
     const [playerAnimations, setPlayerAnimations] = useState<Record<string, AnimationType>>({});
     const [talkingPlayers, setTalkingPlayers] = useState<Set<string>>(new Set());
+    // Muted publisher ID -> owning player ID
+    const [mutedPublishers, setMutedPublishers] = useState<Map<string, string>>(new Map());
     const [screenSharingPlayers, setScreenSharingPlayers] = useState<Set<string>>(new Set());
     const [viewingStream, setViewingStream] = useState<{ stream: MediaStream; playerName: string } | null>(null);
     const [isPodiumActive, setIsPodiumActive] = useState(false); // Toggle with 'P' key
 
     const wsRef = useRef<WebSocket | null>(null);
+    // Last movement tick applied, so late batches don't pull avatars backwards
+    const lastMoveSeqRef = useRef(0);
+    // Server clock minus ours in ms, from TimeSync round trips
+    const serverClockOffsetRef = useRef(0);
+    // When StartTyping was last sent; the server expires it after 5s, so it's refreshed every 3s
+    const typingSentAtRef = useRef(0);
     const publishTransportRef = useRef<PublishTransport | null>(null);
     const subscribeTransportRef = useRef<SubscribeTransport | null>(null);
     const localVideoRef = useRef<HTMLVideoElement>(null);
     const localStreamRef = useRef<MediaStream | null>(null);
     const localAudioStreamRef = useRef<MediaStream | null>(null);
     const publisherIdsRef = useRef<string[]>([]);
+    // Whether we've already told the user their uplink looks like the problem
+    const uplinkWarnedRef = useRef(false);
+    // Features the server enabled for this session (RoomState)
+    const simulcastEnabledRef = useRef(true);
     const audioPublisherIdsRef = useRef<string[]>([]);
     const subscribedIdsRef = useRef<Set<string>>(new Set());
     const moveThrottleRef = useRef<number>(0);
@@ -1500,7 +1535,11 @@ function RoomPage() {
     const audioAnalyzersRef = useRef<Map<string, AnalyserNode>>(new Map());
     const audioContextRef = useRef<AudioContext | null>(null);
     const publisherToPlayerRef = useRef<Map<string, string>>(new Map()); // Maps publisherId → playerId
+    // Maps publisherId → what it carries, so screens aren't mistaken for faces
+    const publisherMetadataRef = useRef<Map<string, { source?: 'mic' | 'camera' | 'screen'; label?: string }>>(new Map());
     const lastTalkingTimeRef = useRef<Map<string, number>>(new Map()); // Track last time player was talking
+    // Set once the server sends SpeakingChanged; local analysis is only a fallback for servers without detection
+    const serverSpeakingRef = useRef(false);
 
     // Audio level detection helper
     const analyzeAudioLevel = (playerId: string, stream: MediaStream) => {
@@ -1522,6 +1561,7 @@ function RoomPage() {
         const STOP_TALKING_DELAY = 800; // Delay in ms before removing talking indicator
 
         const checkAudioLevel = () => {
+            if (serverSpeakingRef.current) return;
             analyser.getByteFrequencyData(dataArray);
             const average = dataArray.reduce((sum, value) => sum + value, 0) / dataArray.length;
 
@@ -1572,7 +1612,56 @@ function RoomPage() {
             noseStyle,
             mouthStyle,
             characterType,
+            // Match with speakers of the same language (backend falls back to English)
+            language: searchParams.get('language') || navigator.language,
         });
+        // Stable per-browser profile so time limits survive reconnects
+        let profileId = localStorage.getItem('webhanginProfileId');
+        if (!profileId) {
+            profileId = crypto.randomUUID();
+            localStorage.setItem('webhanginProfileId', profileId);
+        }
+        params.set('profileId', profileId);
+        // Per-tab key that survives reloads, so the server can tell us what a crash took down
+        let sessionKey = sessionStorage.getItem('webhanginSessionKey');
+        if (!sessionKey) {
+            sessionKey = crypto.randomUUID();
+            sessionStorage.setItem('webhanginSessionKey', sessionKey);
+        }
+        params.set('sessionKey', sessionKey);
+        // Continue a session handed off from another device
+        const transferCode = searchParams.get('transferCode');
+        if (transferCode) {
+            params.set('transferCode', transferCode);
+        }
+        // Sign-in token from the deployment's auth service, when it hands one over in the URL
+        const authToken = searchParams.get('token') || sessionStorage.getItem('webhanginAuthToken');
+        if (authToken) {
+            sessionStorage.setItem('webhanginAuthToken', authToken);
+            params.set('token', authToken);
+        }
+        // Moderator (or other role) token from the admin API; kept for the tab so reconnects keep the role
+        const roleToken = searchParams.get('roleToken') || sessionStorage.getItem('webhanginRoleToken');
+        if (roleToken) {
+            sessionStorage.setItem('webhanginRoleToken', roleToken);
+            params.set('roleToken', roleToken);
+        }
+        // Join a specific room (hub portals, leaving a waiting room); wait if it's locked
+        const room = searchParams.get('room');
+        if (room) {
+            params.set('room', room);
+        }
+        params.set('waitingRoom', 'true');
+        // Passphrase for a password-protected room; without it the server asks with PasswordRequired
+        const roomPassword = searchParams.get('password');
+        if (roomPassword) {
+            params.set('password', roomPassword);
+        }
+        // Return to the same room after a dropped connection or server restart
+        const reconnectToken = sessionStorage.getItem('webhanginReconnectToken');
+        if (reconnectToken) {
+            params.set('reconnectToken', reconnectToken);
+        }
 
         // Use current hostname for WebSocket connection (works with ngrok)
         const protocol = window.location.protocol === 'https:' ? 'wss:' : 'ws:';
@@ -1591,12 +1680,18 @@ function RoomPage() {
         ws.onopen = () => {
             console.log('Connected to server, waiting for ICE servers from RoomState...');
             setIsConnected(true);
+            // Optional features this client handles; the server answers with what it enabled in HelloAck
+            ws.send(JSON.stringify({ action: 'Hello', capabilities: ['simulcast'] }));
             // Don't start peers here - wait for ICE servers from RoomState
         };
 
-        ws.onclose = () => {
+        ws.onclose = (event) => {
             console.log('Disconnected from server');
             setIsConnected(false);
+            // 4003: turned away by the room password
+            if (event.code === 4003) {
+                setChatMessages((prev) => [...prev, { sender: 'System', message: `Couldn't join: ${event.reason}` }]);
+            }
         };
 
         ws.onerror = (error) => {
@@ -1615,8 +1710,18 @@ function RoomPage() {
             }
         }, 5000);
 
+        // Clock sync, repeated since clocks drift
+        const syncClock = () => {
+            if (ws.readyState === ws.OPEN) {
+                ws.send(JSON.stringify({ action: 'TimeSync', clientTime: Date.now() }));
+            }
+        };
+        ws.addEventListener('open', syncClock);
+        const timeSyncInterval = setInterval(syncClock, 30000);
+
         return () => {
             clearInterval(pingInterval);
+            clearInterval(timeSyncInterval);
             if (localStreamRef.current) {
                 localStreamRef.current.getTracks().forEach((track) => track.stop());
             }
@@ -1755,9 +1860,9 @@ function RoomPage() {
                 if (state === 'connected' || state === 'completed') {
                     console.log('[SUBSCRIBE] ✅ ICE connection established!');
                 } else if (state === 'failed') {
-                    console.error('[SUBSCRIBE] ❌ ICE connection FAILED! Consider reconnecting.');
-                    // Mark that we need to recreate subscribe transport on next subscription attempt
-                    subscribeTransportReady.current = false;
+                    console.error('[SUBSCRIBE] ❌ ICE connection FAILED! Asking the server for an ICE restart.');
+                    // The server answers with a fresh Offer carrying new ICE credentials
+                    wsRef.current?.send(JSON.stringify({ action: 'RestartIce', target: 'subscriber' }));
                 } else if (state === 'disconnected') {
                     console.warn('[SUBSCRIBE] ⚠️ ICE connection disconnected - may recover automatically');
                 }
@@ -1779,8 +1884,8 @@ function RoomPage() {
     };
 
     const handleMessage = (message: any) => {
-        // Log all messages except Pong and PlayerMoved
-        if (message.action !== 'Pong' && message.action !== 'PlayerMoved') {
+        // Log all messages except Pong and PlayersMoved
+        if (message.action !== 'Pong' && message.action !== 'PlayersMoved') {
             console.log('[WS IN]', message.action, message);
         }
 
@@ -1788,8 +1893,22 @@ function RoomPage() {
             case 'Pong':
                 break;
 
+            case 'HelloAck':
+                simulcastEnabledRef.current = message.features.simulcast;
+                break;
+
             case 'RoomState':
                 setRoomTheme(message.roomTheme);
+                lastMoveSeqRef.current = 0;
+                sessionStorage.setItem('webhanginReconnectToken', message.reconnectToken);
+                simulcastEnabledRef.current = message.features?.simulcast ?? true;
+                // Backfill the conversation so far
+                setChatMessages((message.chatHistory ?? []).map((record: { sender: string; message: string; messageId: string; uploadId?: string }) => ({
+                    sender: record.sender,
+                    message: record.message,
+                    messageId: record.messageId,
+                    uploadId: record.uploadId,
+                })));
                 // Use yourPlayerId to correctly identify which player is us
                 const allPlayers = message.players as PlayerData[];
                 const myId = message.yourPlayerId as string;
@@ -1814,6 +1933,11 @@ function RoomPage() {
 
             case 'PlayerLeft':
                 setRemotePlayers((prev) => prev.filter((p) => p.id !== message.playerId));
+                setTypingPlayers((prev) => {
+                    const next = new Set(prev);
+                    next.delete(message.playerId);
+                    return next;
+                });
                 // Clean up any streams from this player
                 const leavingPlayerId = message.playerId;
                 setRemoteStreams((prev) => {
@@ -1838,15 +1962,30 @@ function RoomPage() {
                 });
                 break;
 
-            case 'PlayerMoved':
+            // Batched moves from the server tick, and periodic corrections for players outside our interest radius
+            case 'TimeSync': {
+                const now = Date.now();
+                serverClockOffsetRef.current = message.serverTime - (message.clientTime + now) / 2;
+                break;
+            }
+
+            case 'PlayersMoved':
+            case 'PlayerPositions': {
+                if (message.action === 'PlayersMoved') {
+                    if (message.seq <= lastMoveSeqRef.current) break;
+                    lastMoveSeqRef.current = message.seq;
+                }
+                const updates = new Map<string, { position: PlayerData['position']; rotation: number; isMoving: boolean }>(
+                    message.players.map((u: { playerId: string; position: PlayerData['position']; rotation: number; isMoving: boolean }) => [u.playerId, u])
+                );
                 setRemotePlayers((prev) =>
-                    prev.map((p) =>
-                        p.id === message.playerId
-                            ? { ...p, position: message.position, rotation: message.rotation, isMoving: message.isMoving }
-                            : p
-                    )
+                    prev.map((p) => {
+                        const update = updates.get(p.id);
+                        return update ? { ...p, position: update.position, rotation: update.rotation, isMoving: update.isMoving } : p;
+                    })
                 );
                 break;
+            }
 
             case 'PlayerAnimation':
                 // Trigger animation for the player with the animation type
@@ -1855,8 +1994,303 @@ function RoomPage() {
                 setPlayerAnimations((prev) => ({ ...prev, [message.playerId]: animType }));
                 break;
 
+            case 'LinkPreview':
+                setChatMessages((prev) => prev.map((msg) =>
+                    msg.messageId === message.messageId
+                        ? { ...msg, preview: { url: message.url, title: message.title, description: message.description } }
+                        : msg
+                ));
+                break;
+
+            case 'TransferSession':
+                setChatMessages((prev) => [...prev, { sender: 'System', message: `Continue on another device with code ${message.transferCode} (valid ${Math.round(message.expiresInSecs / 60)} min)` }]);
+                break;
+
+            case 'ServerShutdown': {
+                sessionStorage.setItem('webhanginReconnectToken', message.reconnectToken);
+                // Jittered delay so every client doesn't reconnect at the same instant
+                const { initialDelayMs, jitter } = message.reconnect;
+                const delay = initialDelayMs * (1 - jitter / 2 + Math.random() * jitter);
+                setChatMessages((prev) => [...prev, { sender: 'System', message: `Server restarting, reconnecting in ${Math.round(delay / 1000)}s...` }]);
+                setTimeout(() => window.location.reload(), delay);
+                break;
+            }
+
+            case 'SessionExpired':
+                sessionStorage.setItem('webhanginReconnectToken', message.reconnectToken);
+                setChatMessages((prev) => [...prev, { sender: 'System', message: 'Session time is up, reload to join again' }]);
+                break;
+
+            case 'SessionTransferred':
+                setChatMessages((prev) => [...prev, { sender: 'System', message: 'This session moved to another device' }]);
+                break;
+
+            case 'RepublishRequired':
+                setChatMessages((prev) => [...prev, { sender: 'System', message: `The server restarted and ${message.publisherIds.length} of your streams stopped, turn your mic or screen share back on` }]);
+                break;
+
+            case 'SessionRevoked':
+                setChatMessages((prev) => [...prev, { sender: 'System', message: 'This account was signed out by an administrator' }]);
+                break;
+
+            case 'Kicked':
+                setChatMessages((prev) => [...prev, { sender: 'System', message: message.reason }]);
+                break;
+
+            case 'PlayerMuted': {
+                const minutes = Math.ceil(message.durationSecs / 60);
+                const mutedPlayer = remotePlayersRef.current.find(p => p.id === message.playerId);
+                const text = mutedPlayer
+                    ? `${message.mutedBy} muted ${mutedPlayer.name} for ${minutes} minute(s)`
+                    : `${message.mutedBy} muted you for ${minutes} minute(s)`;
+                setChatMessages((prev) => [...prev, { sender: 'System', message: text }]);
+                break;
+            }
+
+            case 'PlayerForceMuted': {
+                const mutedPlayer = remotePlayersRef.current.find(p => p.id === message.playerId);
+                if (!mutedPlayer && message.muted) {
+                    // The server already closed our mic publishers
+                    stopMicrophone();
+                }
+                const verb = message.muted ? 'muted' : 'unmuted';
+                const text = mutedPlayer
+                    ? `${message.mutedBy} ${verb} ${mutedPlayer.name}'s mic`
+                    : `${message.mutedBy} ${verb} your mic`;
+                setChatMessages((prev) => [...prev, { sender: 'System', message: text }]);
+                break;
+            }
+
+            case 'ModerationFailed':
+                setChatMessages((prev) => [...prev, { sender: 'System', message: message.reason }]);
+                break;
+
+            case 'PublisherMuted':
+                setMutedPublishers((prev) => {
+                    const next = new Map(prev);
+                    if (message.muted) next.set(message.publisherId, message.playerId);
+                    else next.delete(message.publisherId);
+                    return next;
+                });
+                break;
+
+            case 'SpeakingChanged':
+                serverSpeakingRef.current = true;
+                setTalkingPlayers((prev) => {
+                    const next = new Set(prev);
+                    if (message.speaking) next.add(message.playerId);
+                    else next.delete(message.playerId);
+                    return next;
+                });
+                break;
+
+            case 'PlayerStatus':
+                setRemotePlayers((prev) => prev.map((p) => p.id === message.playerId ? { ...p, status: message.status } : p));
+                break;
+
+            case 'RoleChanged':
+                setRemotePlayers((prev) => prev.map((p) => p.id === message.playerId ? { ...p, role: message.role } : p));
+                break;
+
+            case 'TimeLimitWarning':
+                setChatMessages((prev) => [...prev, { sender: 'System', message: `${message.minutesRemaining} minute(s) of hangout time left today` }]);
+                break;
+
+            case 'TimeLimitReached':
+                setChatMessages((prev) => [...prev, { sender: 'System', message: message.reason }]);
+                break;
+
+            case 'RateLimited':
+                setChatMessages((prev) => [...prev, { sender: 'System', message: `You're sending ${message.class} messages too fast, some were dropped` }]);
+                break;
+
+            case 'PlayerReaction': {
+                // Float the emoji over whoever reacted; the server echoes our own reactions back too
+                const reactionTimestamp = Date.now();
+                const reactingPlayer = remotePlayersRef.current.find(p => p.id === message.playerId);
+                if (reactingPlayer) {
+                    setPlayerChatBubbles(prev => ({
+                        ...prev,
+                        [reactingPlayer.id]: { message: message.emoji, timestamp: reactionTimestamp }
+                    }));
+                    setTimeout(() => {
+                        setPlayerChatBubbles(prev => {
+                            if (prev[reactingPlayer.id]?.timestamp === reactionTimestamp) {
+                                const copy = { ...prev };
+                                delete copy[reactingPlayer.id];
+                                return copy;
+                            }
+                            return prev;
+                        });
+                    }, 2000);
+                } else {
+                    setLocalPlayerChatBubble({ message: message.emoji, timestamp: reactionTimestamp });
+                    setTimeout(() => {
+                        setLocalPlayerChatBubble(prev => prev?.timestamp === reactionTimestamp ? null : prev);
+                    }, 2000);
+                }
+                break;
+            }
+
+            case 'PlayerTyping':
+                setTypingPlayers((prev) => {
+                    const next = new Set(prev);
+                    if (message.typing) {
+                        next.add(message.playerId);
+                    } else {
+                        next.delete(message.playerId);
+                    }
+                    return next;
+                });
+                break;
+
+            case 'MessageEdited':
+                setChatMessages((prev) => prev.map((chat) => chat.messageId === message.messageId ? { ...chat, message: message.message, edited: true } : chat));
+                break;
+
+            case 'MessageDeleted':
+                setChatMessages((prev) => prev.filter((chat) => chat.messageId !== message.messageId));
+                break;
+
+            case 'MessageActionFailed':
+                setChatMessages((prev) => [...prev, { sender: 'System', message: message.reason }]);
+                break;
+
+            case 'DirectMessage':
+                setChatMessages((prev) => [...prev, { sender: `From ${message.sender}`, message: message.message, messageId: message.messageId }]);
+                break;
+
+            case 'DirectMessageFailed':
+                setChatMessages((prev) => [...prev, { sender: 'System', message: message.reason }]);
+                break;
+
+            case 'ChatRejected':
+                setChatMessages((prev) => [...prev, { sender: 'System', message: message.retryAfterMs > 0 ? `${message.reason}, try again in ${Math.ceil(message.retryAfterMs / 1000)}s` : message.reason }]);
+                break;
+
+            case 'SlowModeActive':
+                setChatMessages((prev) => [...prev, { sender: 'System', message: `Slow mode is on, try again in ${message.retryAfter}s` }]);
+                break;
+
+            case 'PublishQuality':
+                if (message.uplinkSuspect && !uplinkWarnedRef.current) {
+                    setChatMessages((prev) => [...prev, { sender: 'System', message: `Everyone is losing ${Math.round(message.bestLoss * 100)}%+ of your stream, your upload connection may be struggling` }]);
+                }
+                uplinkWarnedRef.current = message.uplinkSuspect;
+                break;
+
+            case 'BitrateHint': {
+                // Cap the top encoding; lower simulcast layers are already well under any hint
+                const pc = (publishTransportRef.current as any)?.pc as RTCPeerConnection | undefined;
+                const sender = pc?.getSenders().find((s) => s.track?.id === message.publisherId);
+                if (sender) {
+                    const params = sender.getParameters();
+                    const top = params.encodings?.[params.encodings.length - 1];
+                    if (top) {
+                        top.maxBitrate = message.kbps * 1000;
+                        sender.setParameters(params).catch((e) => console.warn('[PUBLISH] Failed to apply bitrate hint:', e));
+                    }
+                }
+                break;
+            }
+
+            case 'PasswordRequired': {
+                const password = window.prompt('This room is password protected. Enter the password:');
+                if (password === null) {
+                    wsRef.current?.close();
+                } else {
+                    wsRef.current?.send(JSON.stringify({ action: 'Authenticate', password }));
+                }
+                break;
+            }
+
+            case 'RoomPasswordChanged':
+                setChatMessages((prev) => [...prev, { sender: 'System', message: message.passwordProtected ? 'The host set a room password' : 'The host removed the room password' }]);
+                break;
+
+            case 'RoomPasswordRejected':
+                setChatMessages((prev) => [...prev, { sender: 'System', message: message.reason }]);
+                break;
+
+            case 'RoomLockChanged':
+                setChatMessages((prev) => [...prev, { sender: 'System', message: message.locked ? 'The host locked the room' : 'The host unlocked the room' }]);
+                break;
+
+            case 'IceRestartFailed':
+                // Fall back to recreating the transport on the next subscription attempt
+                console.error(`[ICE] Restart of ${message.target} failed:`, message.reason);
+                if (message.target === 'subscriber') {
+                    subscribeTransportReady.current = false;
+                }
+                break;
+
+            case 'Mentioned':
+                setChatMessages((prev) => [...prev, { sender: 'System', message: `🔔 ${message.by} mentioned you: ${message.message}` }]);
+                break;
+
+            case 'CountdownStarted': {
+                const secondsLeft = Math.max(0, Math.round((message.countdown.endsAt - message.serverTime) / 1000));
+                setChatMessages((prev) => [...prev, { sender: 'System', message: `⏱️ ${message.countdown.label || 'Countdown'}: ${secondsLeft}s` }]);
+                break;
+            }
+
+            case 'CountdownFinished':
+                setChatMessages((prev) => [...prev, { sender: 'System', message: `⏰ ${message.label || 'Countdown'} finished` }]);
+                break;
+
+            case 'CountdownRejected':
+                setChatMessages((prev) => [...prev, { sender: 'System', message: `Countdown rejected: ${message.reason}` }]);
+                break;
+
+            case 'RoomUnlocked':
+                // We're in the waiting room; move into the room we were waiting for
+                wsRef.current?.send(JSON.stringify({ action: 'SwitchRoom', roomId: message.roomId }));
+                break;
+
+            case 'PositionCorrected':
+                setPositionCorrection({ position: message.position, timestamp: Date.now() });
+                break;
+
+            case 'RoomSwitched': {
+                // The server closed our transports and publishers; start over once the new RoomState arrives
+                localStreamRef.current?.getTracks().forEach((track) => track.stop());
+                localStreamRef.current = null;
+                localAudioStreamRef.current?.getTracks().forEach((track) => track.stop());
+                localAudioStreamRef.current = null;
+                publishTransportRef.current = null;
+                subscribeTransportRef.current = null;
+                subscribeTransportReady.current = false;
+                pendingSubscriptions.current = [];
+                publisherIdsRef.current = [];
+                audioPublisherIdsRef.current = [];
+                subscribedIdsRef.current.clear();
+                publisherToPlayerRef.current.clear();
+                setLocalVideoStream(undefined);
+                setIsScreenSharing(false);
+                setIsMicActive(false);
+                setIsMicMuted(false);
+                setRemoteStreams([]);
+                setRemotePlayers([]);
+                setTypingPlayers(new Set());
+                setScreenSharingPlayers(new Set());
+                setIsIceConfigLoaded(false);
+                // A reload should land in the new room
+                const url = new URL(window.location.href);
+                url.searchParams.set('room', message.roomId);
+                window.history.replaceState(null, '', url.toString());
+                break;
+            }
+
+            case 'SwitchRoomFailed':
+                setChatMessages((prev) => [...prev, { sender: 'System', message: `Couldn't move to that room: ${message.reason}` }]);
+                break;
+
+            case 'SystemMessage':
+                setChatMessages((prev) => [...prev, { sender: 'System', message: message.message }]);
+                break;
+
             case 'ChatMessage':
-                setChatMessages((prev) => [...prev, { sender: message.sender, message: message.message }]);
+                setChatMessages((prev) => [...prev, { sender: message.sender, message: message.message, messageId: message.messageId, uploadId: message.uploadId }]);
                 // Add chat bubble for this player (use ref to avoid stale closure)
                 const senderPlayer = remotePlayersRef.current.find(p => p.name === message.sender);
                 if (senderPlayer) {
@@ -1963,14 +2397,18 @@ function RoomPage() {
                 }
                 break;
 
-            case 'PublisherIce':
-                console.log('[PUBLISH] Adding ICE candidate from server');
-                publishTransportRef.current?.addIceCandidate(message.candidate);
+            case 'PublisherIceBatch':
+                console.log('[PUBLISH] Adding', message.candidates.length, 'ICE candidates from server', message.endOfCandidates ? '(end)' : '');
+                message.candidates.forEach((candidate: RTCIceCandidateInit) => {
+                    publishTransportRef.current?.addIceCandidate(candidate);
+                });
                 break;
 
-            case 'SubscriberIce':
-                console.log('[SUBSCRIBE] Adding ICE candidate from server');
-                subscribeTransportRef.current?.addIceCandidate(message.candidate);
+            case 'SubscriberIceBatch':
+                console.log('[SUBSCRIBE] Adding', message.candidates.length, 'ICE candidates from server', message.endOfCandidates ? '(end)' : '');
+                message.candidates.forEach((candidate: RTCIceCandidateInit) => {
+                    subscribeTransportRef.current?.addIceCandidate(candidate);
+                });
                 break;
 
             case 'Published':
@@ -1980,6 +2418,9 @@ function RoomPage() {
                     if (message.playerId && message.playerId !== '') {
                         publisherToPlayerRef.current.set(publisherId, message.playerId);
                     }
+                    if (message.metadata?.[publisherId]) {
+                        publisherMetadataRef.current.set(publisherId, message.metadata[publisherId]);
+                    }
 
                     // Only subscribe if this isn't our own publisher and we haven't subscribed yet
                     const isOurPublisher = publisherIdsRef.current.includes(publisherId);
@@ -2262,10 +2703,67 @@ function RoomPage() {
         wsRef.current?.send(JSON.stringify({ action: 'PlayAnimation', animation: payload }));
     };
 
+    const sendReaction = (emoji: string) => {
+        if (!wsRef.current) return;
+        wsRef.current.send(JSON.stringify({ action: 'Reaction', emoji }));
+    };
+
+    const updateTyping = (typing: boolean) => {
+        if (!wsRef.current) return;
+        const now = Date.now();
+        if (typing && now - typingSentAtRef.current > 3000) {
+            wsRef.current.send(JSON.stringify({ action: 'StartTyping' }));
+            typingSentAtRef.current = now;
+        } else if (!typing && typingSentAtRef.current) {
+            wsRef.current.send(JSON.stringify({ action: 'StopTyping' }));
+            typingSentAtRef.current = 0;
+        }
+    };
+
+    const handleChatInputChange = (value: string) => {
+        setChatInput(value);
+        updateTyping(value.trim().length > 0);
+    };
+
     const sendChat = () => {
         if (!wsRef.current || !chatInput.trim()) return;
+        updateTyping(false);
 
         const message = chatInput.trim();
+        // `/kick <name>`, `/mute <name>`, `/forcemute <name>` and `/unforcemute <name>` for moderators;
+        // the server checks the role
+        const moderation = message.match(/^\/(kick|mute|forcemute|unforcemute)\s+(\S+)$/);
+        if (moderation) {
+            const target = remotePlayersRef.current.find(p => p.name.toLowerCase() === moderation[2].toLowerCase());
+            if (target) {
+                const actions: Record<string, string> = { kick: 'Kick', mute: 'MutePlayer', forcemute: 'ForceMute', unforcemute: 'LiftForceMute' };
+                wsRef.current.send(JSON.stringify({ action: actions[moderation[1]], playerId: target.id }));
+            } else {
+                setChatMessages((prev) => [...prev, { sender: 'System', message: `Nobody named ${moderation[2]} is here` }]);
+            }
+            setChatInput('');
+            return;
+        }
+        // `/password <phrase>` sets the room password for the host, `/password` alone removes it
+        const password = message.match(/^\/password(?:\s+(.+))?$/);
+        if (password) {
+            wsRef.current.send(JSON.stringify({ action: 'SetRoomPassword', password: password[1] ?? null }));
+            setChatInput('');
+            return;
+        }
+        // `/w <name> <message>` whispers to one player
+        const whisper = message.match(/^\/w\s+(\S+)\s+(.+)$/);
+        if (whisper) {
+            const target = remotePlayersRef.current.find(p => p.name.toLowerCase() === whisper[1].toLowerCase());
+            if (target) {
+                wsRef.current.send(JSON.stringify({ action: 'DirectMessage', toPlayerId: target.id, message: whisper[2] }));
+                setChatMessages((prev) => [...prev, { sender: `To ${target.name}`, message: whisper[2] }]);
+            } else {
+                setChatMessages((prev) => [...prev, { sender: 'System', message: `Nobody named ${whisper[1]} is here` }]);
+            }
+            setChatInput('');
+            return;
+        }
         wsRef.current.send(JSON.stringify({ action: 'ChatMessage', message }));
 
         // Show chat bubble for local player
@@ -2316,9 +2814,12 @@ function RoomPage() {
             // Publish tracks
             if (publishTransportRef.current && wsRef.current) {
                 for (const track of stream.getTracks()) {
-                    const publisher = await publishTransportRef.current.publish(track);
+                    // When the server negotiated simulcast, video goes out as three layers and the SFU
+                    // forwards each viewer the highest one under the room's video cap
+                    const encodings = track.kind === 'video' && simulcastEnabledRef.current ? SIMULCAST_ENCODINGS : undefined;
+                    const publisher = await publishTransportRef.current.publish(track, encodings);
                     wsRef.current.send(JSON.stringify({ action: 'Offer', sdp: publisher.offer }));
-                    wsRef.current.send(JSON.stringify({ action: 'Publish', publisherId: publisher.id }));
+                    wsRef.current.send(JSON.stringify({ action: 'Publish', publisherId: publisher.id, source: 'screen', label: track.label }));
                     publisherIdsRef.current.push(publisher.id);
                 }
             }
@@ -2351,7 +2852,13 @@ function RoomPage() {
                 throw new Error('getUserMedia is not supported. Please use HTTPS or localhost.');
             }
 
-            const stream = await navigator.mediaDevices.getUserMedia({ audio: true });
+            // Voice processing mangles instruments; the Music Lounge sends raw stereo instead
+            const isMusicRoom = roomTheme === 'Music Lounge';
+            const stream = await navigator.mediaDevices.getUserMedia({
+                audio: isMusicRoom
+                    ? { channelCount: 2, echoCancellation: false, noiseSuppression: false, autoGainControl: false }
+                    : true,
+            });
             console.log('[MIC] Got media stream, tracks:', stream.getTracks().length);
             localAudioStreamRef.current = stream;
             setIsMicActive(true);
@@ -2400,7 +2907,7 @@ function RoomPage() {
                     wsRef.current!.send(JSON.stringify({ action: 'Offer', sdp: publisher.offer }));
 
                     console.log('[MIC] Sending Publish message for:', publisher.id);
-                    wsRef.current!.send(JSON.stringify({ action: 'Publish', publisherId: publisher.id }));
+                    wsRef.current!.send(JSON.stringify({ action: 'Publish', publisherId: publisher.id, source: 'mic' }));
 
                     // Monitor connection state - wait for peer connection to be established
                     const pc = (publishTransportRef.current as any)?.pc as RTCPeerConnection | undefined;
@@ -2522,6 +3029,10 @@ function RoomPage() {
             audioTracks.forEach((track) => {
                 track.enabled = !track.enabled;
             });
+            // Let the room know, rather than everyone inferring it from silence
+            audioPublisherIdsRef.current.forEach((publisherId) => {
+                wsRef.current?.send(JSON.stringify({ action: 'SetMuted', publisherId, muted: !isMicMuted }));
+            });
             setIsMicMuted(!isMicMuted);
         }
     };
@@ -2625,6 +3136,7 @@ function RoomPage() {
                                     hideTablet={!!podiumStream && localPlayer?.name === podiumStream.name}
                                     chatInputFocused={chatInputFocused}
                                     chatBubble={localPlayerChatBubble}
+                                    correction={positionCorrection}
                                 />
                             )}
 
@@ -2637,8 +3149,9 @@ function RoomPage() {
                             )}
 
                             {remotePlayers.map((player) => {
-                                // Check if this player is talking
-                                const isTalking = talkingPlayers.has(player.id);
+                                // Check if this player is talking; a muted mic can't be
+                                const isMuted = Array.from(mutedPublishers.values()).includes(player.id);
+                                const isTalking = talkingPlayers.has(player.id) && !isMuted;
 
                                 // Find audio stream for this player
                                 const audioStream = remoteStreams.find((stream) => {
@@ -2727,18 +3240,49 @@ function RoomPage() {
                                 <div key={i} className="text-xs">
                                     <span className="text-orange-400 font-medium">{msg.sender}:</span>{' '}
                                     <span className="text-gray-300">{msg.message}</span>
+                                    {msg.edited && <span className="text-gray-500"> (edited)</span>}
+                                    {msg.uploadId && (
+                                        <img src={`/api/uploads/${msg.uploadId}`} alt={`Image from ${msg.sender}`} className="block mt-1 max-h-32 rounded" />
+                                    )}
+                                    {msg.preview && (
+                                        <a href={msg.preview.url} target="_blank" rel="noopener noreferrer" className="block mt-1 p-2 bg-gray-800/80 rounded border-l-2 border-orange-400">
+                                            {msg.preview.title && <span className="block text-white font-medium">{msg.preview.title}</span>}
+                                            {msg.preview.description && <span className="block text-gray-400 line-clamp-2">{msg.preview.description}</span>}
+                                        </a>
+                                    )}
                                 </div>
                             ))
                         )}
                     </div>
+                    {typingPlayers.size > 0 && (
+                        <div className="text-gray-400 text-xs italic mt-1">
+                            {remotePlayers.filter((p) => typingPlayers.has(p.id)).map((p) => p.name).join(', ')}
+                            {typingPlayers.size === 1 ? ' is typing…' : ' are typing…'}
+                        </div>
+                    )}
+                    <div className="flex gap-1 mt-2">
+                        {REACTION_EMOJI.map((emoji) => (
+                            <button
+                                key={emoji}
+                                onClick={() => sendReaction(emoji)}
+                                disabled={!isConnected}
+                                className="px-1.5 py-1 bg-gray-700/80 hover:bg-gray-600 rounded text-sm disabled:opacity-50"
+                            >
+                                {emoji}
+                            </button>
+                        ))}
+                    </div>
                     <div className="flex gap-2 mt-2">
                         <input
                             type="text"
                             value={chatInput}
-                            onChange={(e) => setChatInput(e.target.value)}
+                            onChange={(e) => handleChatInputChange(e.target.value)}
                             onKeyDown={(e) => e.key === 'Enter' && sendChat()}
                             onFocus={() => setChatInputFocused(true)}
-                            onBlur={() => setChatInputFocused(false)}
+                            onBlur={() => {
+                                setChatInputFocused(false);
+                                updateTyping(false);
+                            }}
                             disabled={!isConnected}
                             placeholder="Type a message..."
                             className="flex-1 px-3 py-2 bg-gray-700/80 border border-gray-600 rounded text-white text-xs placeholder-gray-400 disabled:opacity-50"